@@ -0,0 +1,101 @@
+//! Plays arbitrary moves on arbitrary boards and checks the game holds together after each:
+//! no panics, counts and flags that agree with the board, and flagged cells that no reveal,
+//! chord or flood fill opens.
+//!
+//! Run with `cargo +nightly fuzz run moves` from the repository root.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use minesweeper::app::{Action, AppState, Cursor};
+use minesweeper::board::{Board, CellState, Layout, Topology};
+use minesweeper::difficulty::Difficulty;
+
+/// Largest side tried; larger boards only make each run slower.
+const MAX_SIDE: usize = 24;
+const LAYOUTS: [Layout; 4] = [
+    Layout::Uniform,
+    Layout::Center,
+    Layout::Edges,
+    Layout::Gradient,
+];
+
+#[derive(Debug, Arbitrary)]
+struct Game {
+    width: u8,
+    height: u8,
+    mines: u16,
+    seed: u64,
+    cylinder: bool,
+    layout: u8,
+    moves: Vec<Move>,
+}
+
+#[derive(Debug, Arbitrary)]
+enum Move {
+    Reveal(u8, u8),
+    Flag(u8, u8),
+    Chord(u8, u8),
+    OpenRemaining,
+    Hint,
+    Undo,
+}
+
+fuzz_target!(|game: Game| {
+    let width = usize::from(game.width) % MAX_SIDE + 1;
+    let height = usize::from(game.height) % MAX_SIDE + 1;
+    let mines = usize::from(game.mines) % (width * height);
+    let Ok(board) = Board::try_new(width, height, mines) else {
+        return;
+    };
+    let mut app = AppState::new(Difficulty::Easy);
+    // Every move names its cell, so a repeated reveal must not turn into a chord.
+    app.double_tap_timeout = None;
+    app.board = board
+        .with_seed(game.seed)
+        .with_topology(if game.cylinder {
+            Topology::Cylinder
+        } else {
+            Topology::Flat
+        })
+        .with_layout(LAYOUTS[usize::from(game.layout) % LAYOUTS.len()]);
+    for step in game.moves {
+        let flagged = flagged_cells(&app.board);
+        let action = match step {
+            Move::Reveal(x, y) => at(&mut app, x, y, Action::Reveal),
+            Move::Flag(x, y) => at(&mut app, x, y, Action::ToggleFlag),
+            Move::Chord(x, y) => at(&mut app, x, y, Action::Chord),
+            Move::OpenRemaining => Action::OpenRemaining,
+            Move::Hint => Action::Hint,
+            Move::Undo => Action::Undo,
+        };
+        app.handle_action(action);
+        if let Err(e) = app.check_invariants() {
+            panic!("{action:?} broke the game: {e}");
+        }
+        if matches!(action, Action::Reveal | Action::Chord | Action::OpenRemaining) {
+            for (x, y) in flagged {
+                assert_eq!(
+                    app.board.cell_at(x, y),
+                    CellState::Flagged,
+                    "{action:?} opened the flag at ({x}, {y})"
+                );
+            }
+        }
+    }
+});
+
+/// Point the cursor at (`x`, `y`), wrapped onto the board, for `action`.
+fn at(app: &mut AppState, x: u8, y: u8, action: Action) -> Action {
+    let x = usize::from(x) % app.board.width();
+    let y = usize::from(y) % app.board.height();
+    app.cursor = Cursor::new(x, y);
+    action
+}
+
+fn flagged_cells(board: &Board) -> Vec<(usize, usize)> {
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| board.cell_at(x, y) == CellState::Flagged)
+        .collect()
+}
@@ -0,0 +1,69 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+/// Finished games the win rate is taken over.
+const WINDOW: usize = 10;
+/// How far the mine count moves after a game, in percent of the board's cells.
+const STEP_PERCENT: usize = 1;
+/// Cells kept free of mines, so the first reveal can always open a 3x3 area.
+const SAFE_START: usize = 9;
+
+/// Adaptive difficulty (`AppState::adaptive`): after each finished game the mine count moves
+/// a step toward a density the player wins about half their games at.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Adaptive {
+    /// Results of the most recent games, oldest first; `true` for a win.
+    recent: VecDeque<bool>,
+}
+
+impl Adaptive {
+    /// Note a finished game and return the mine count for the next one on a board of `cells`
+    /// cells that had `mines` mines: more if the player won more than half of their recent
+    /// games, fewer if they won less.
+    pub fn next_mines(&mut self, won: bool, cells: usize, mines: usize) -> usize {
+        if self.recent.len() == WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(won);
+        let wins = self.recent.iter().filter(|&&won| won).count();
+        let step = (cells * STEP_PERCENT / 100).max(1);
+        let mines = match (wins * 2).cmp(&self.recent.len()) {
+            Ordering::Greater => mines.saturating_add(step),
+            Ordering::Less => mines.saturating_sub(step),
+            Ordering::Equal => mines,
+        };
+        let most = cells
+            .saturating_sub(SAFE_START)
+            .max(1)
+            .min(cells.saturating_sub(1));
+        mines.clamp(most.min(1), most)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mines_follow_the_win_rate() {
+        let mut adaptive = Adaptive::default();
+        assert_eq!(adaptive.next_mines(true, 81, 10), 11);
+        assert_eq!(adaptive.next_mines(false, 81, 11), 11, "one of two won");
+        assert_eq!(adaptive.next_mines(false, 81, 11), 10);
+        assert_eq!(adaptive.next_mines(true, 480, 99), 99);
+        assert_eq!(adaptive.next_mines(true, 480, 99), 103);
+    }
+
+    #[test]
+    fn mine_counts_stay_playable() {
+        let mut adaptive = Adaptive::default();
+        assert_eq!(adaptive.next_mines(false, 81, 1), 1);
+        let mut adaptive = Adaptive::default();
+        assert_eq!(adaptive.next_mines(true, 81, 72), 72);
+        assert_eq!(adaptive.next_mines(true, 2, 1), 1);
+        for _ in 0..2 * WINDOW {
+            adaptive.next_mines(false, 81, 10);
+        }
+        assert_eq!(adaptive.recent.len(), WINDOW);
+    }
+}
@@ -1,6 +1,22 @@
-use crate::board::Board;
+use crate::adaptive::Adaptive;
+use crate::board::{Board, CellState, Layout, Neighbors, Topology};
+use crate::camera::Camera;
+use crate::campaign::Campaign;
+use crate::code::BoardCode;
+use crate::defuse::{Arrow, Defusal, Outcome};
 use crate::difficulty::Difficulty;
-use std::time::Instant;
+use crate::error::{Error, Result};
+use crate::events::{Event, EventBus};
+use crate::hotseat::HotSeat;
+use crate::mask::Mask;
+use crate::movelog::{Entry, Move, MoveLog};
+use crate::profile::{Profile, Timing};
+use crate::puzzle::Pack;
+use crate::recording::Macro;
+use crate::score::Score;
+use crate::solver::{self, Deduction, Guess, Loss, Rating};
+use std::time::Duration;
+use web_time::Instant;
 
 /// High-level commands the UI can react to after handling an action.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,24 +29,140 @@ pub enum Command {
     GameWon,
     /// The player lost; end the game and show lose UI.
     GameLost,
+    /// The player confirmed quitting; the front end should exit.
+    Quit,
 }
 
 /// Runtime game status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Status {
+    /// The game is in progress.
     Playing,
+    /// Every safe cell has been revealed.
     Win,
+    /// A mine was revealed.
     Lose,
 }
 
+/// What the primary (Enter/Space) key does. The flag key always does the other one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    /// The primary key reveals; the flag key flags.
+    Reveal,
+    /// The primary key flags; the flag key reveals.
+    Flag,
+}
+
+impl InputMode {
+    /// Switch between reveal and flag mode.
+    pub const fn toggle(self) -> Self {
+        match self {
+            Self::Reveal => Self::Flag,
+            Self::Flag => Self::Reveal,
+        }
+    }
+}
+
+/// Optional screen elements the player can hide to make room for the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    /// The key legend below the board.
+    Footer,
+    /// Column letters and row numbers around the board.
+    Rulers,
+    /// Live statistics beside the board.
+    Stats,
+    /// The debug overlay: frame times, recent events and a summary of the game state.
+    Debug,
+}
+
+/// Which optional panels are currently shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // one switch per panel
+pub struct Panels {
+    /// Whether the key legend is shown.
+    pub footer: bool,
+    /// Whether the coordinate rulers are shown.
+    pub rulers: bool,
+    /// Whether the stats panel is shown, room permitting.
+    pub stats: bool,
+    /// Whether the debug overlay is shown.
+    pub debug: bool,
+}
+
+impl Default for Panels {
+    fn default() -> Self {
+        Self {
+            footer: true,
+            rulers: true,
+            stats: false,
+            debug: false,
+        }
+    }
+}
+
+impl Panels {
+    /// Flip the visibility of one panel.
+    pub fn toggle(&mut self, panel: Panel) {
+        match panel {
+            Panel::Footer => self.footer = !self.footer,
+            Panel::Rulers => self.rulers = !self.rulers,
+            Panel::Stats => self.stats = !self.stats,
+            Panel::Debug => self.debug = !self.debug,
+        }
+    }
+}
+
+/// A UI layer drawn over the board. Layers stack; Esc closes the topmost one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layer {
+    /// End-of-game message. Not modal: game keys like Restart still work.
+    GameOver,
+    /// Seed entry dialog holding the text typed so far.
+    SeedPrompt(String),
+    /// "Quit?" confirmation dialog.
+    ConfirmQuit,
+    /// Hot-seat turn change: asks the next player to take the keyboard.
+    Handoff,
+    /// Puzzle pack browser with the selected puzzle, counting through every pack in order.
+    Packs(usize),
+    /// A mine just went off and the player is racing to defuse it.
+    Defuse(Defusal),
+}
+
+/// Longest seed text the prompt accepts.
+pub const SEED_PROMPT_MAX_LEN: usize = 32;
+
+/// Turn seed prompt text into a seed. Numbers are used as-is; any other text is hashed
+/// (FNV-1a, stable across runs and platforms). Blank text means no seed.
+pub fn parse_seed(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Ok(n) = text.parse::<u64>() {
+        return Some(n);
+    }
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    Some(hash)
+}
+
+/// Default window in which two Reveals on a revealed number count as a chord.
+pub const DEFAULT_DOUBLE_TAP_TIMEOUT: Duration = Duration::from_millis(300);
+
 /// Logical cursor within the board grid.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Cursor {
+    /// Column, from the left.
     pub x: usize,
+    /// Row, from the top.
     pub y: usize,
 }
 
 impl Cursor {
+    /// Create a cursor at (x, y).
     pub const fn new(x: usize, y: usize) -> Self {
         Self { x, y }
     }
@@ -39,56 +171,613 @@ impl Cursor {
 /// Player input intents. The higher-level input layer should map keys/mouse to these.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Action {
+    /// Move the cursor one cell left.
     MoveLeft,
+    /// Move the cursor one cell right.
     MoveRight,
+    /// Move the cursor one cell up.
     MoveUp,
+    /// Move the cursor one cell down.
     MoveDown,
+    /// Move the cursor diagonally up and left.
+    MoveUpLeft,
+    /// Move the cursor diagonally up and right.
+    MoveUpRight,
+    /// Move the cursor diagonally down and left.
+    MoveDownLeft,
+    /// Move the cursor diagonally down and right.
+    MoveDownRight,
+    /// Reveal the cursor cell (placing mines on the first reveal).
     Reveal,
+    /// Flag or unflag the cursor cell.
     ToggleFlag,
+    /// Flag the cursor cell (if hidden), then step in the last movement direction.
+    FlagAndAdvance,
+    /// Reveal the neighbors of a revealed number whose flags are all placed.
     Chord,
+    /// Reveal every hidden cell left, once as many flags are placed as there are mines.
+    OpenRemaining,
+    /// Count the hidden, flagged and revealed neighbors of the cursor cell, for the header
+    /// and the move log.
+    Peek,
+    /// Start a new game at the current difficulty.
     Restart,
+    /// Start a new game at the given difficulty.
     SetDifficulty(Difficulty),
+    /// Ask the solver for a safe cell or certain mine and move the cursor to it.
+    Hint,
+    /// Step back to the board as it was before the last reveal/flag/chord.
+    Undo,
+    /// Switch the primary key between revealing and flagging.
+    ToggleInputMode,
+    /// Show or hide an optional panel.
+    TogglePanel(Panel),
+    /// Start recording a macro, or stop and keep it as the last macro.
+    ToggleRecording,
+    /// Replay the last recorded macro.
+    PlayMacro,
+    /// Open the seed prompt.
+    OpenSeedPrompt,
+    /// Open the puzzle pack browser.
+    OpenPacks,
+    /// Type a character into the open prompt.
+    PromptChar(char),
+    /// Delete the last character in the open prompt.
+    PromptBackspace,
+    /// Apply the prompt's seed and start a new game.
+    PromptSubmit,
+    /// Close the topmost UI layer; at the top level, ask to confirm quitting.
+    Back,
+    /// Confirm the open quit dialog.
+    ConfirmQuit,
+    /// Start dragging the viewport from a screen position (column, row).
+    BeginPan(u16, u16),
+    /// Continue a drag to a new screen position, panning the viewport.
+    PanTo(u16, u16),
+    /// Finish the current drag.
+    EndPan,
+}
+
+/// Board state saved before a move so it can be undone.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    board: Board,
+    flags_placed: usize,
+    status: Status,
+    penalty: Duration,
+    score: Option<Score>,
 }
 
-/// AppState encapsulates a single game session.
+/// `AppState` encapsulates a single game session.
+// The flags are independent game options, not states of one machine.
+#[allow(clippy::struct_excessive_bools)]
 pub struct AppState {
+    /// The minefield being played.
     pub board: Board,
+    /// The selected cell.
     pub cursor: Cursor,
+    /// Difficulty the current board was created with.
     pub difficulty: Difficulty,
+    /// The custom board last chosen, which Alt+4 goes back to; `None` until one is.
+    pub last_custom: Option<Difficulty>,
+    /// Whether mines are placed when the game starts rather than around the first reveal,
+    /// so that reveal can hit one. Takes effect from the next [`restart`](Self::restart);
+    /// code and hand-made boards are unaffected.
+    pub hardcore: bool,
+    /// Whether new games start with their largest opening already revealed, to practise
+    /// the rest of the board without the opening lottery. Takes effect from the next
+    /// [`restart`](Self::restart); code and hand-made boards are unaffected.
+    pub practice: bool,
+    /// How the edges of new boards meet, e.g. joined left to right on a cylinder. Takes
+    /// effect from the next [`restart`](Self::restart); code and hand-made boards stay flat.
+    pub topology: Topology,
+    /// How mines are spread over new boards, e.g. crowded toward the middle. Takes effect
+    /// from the next [`restart`](Self::restart); code and hand-made boards are unaffected.
+    pub layout: Layout,
+    /// Recent results in adaptive mode, which moves the mine count between games to keep the
+    /// player winning about half of them; `None` keeps the difficulty as chosen.
+    pub adaptive: Option<Adaptive>,
+    /// The roguelike run being played, which picks each board and keeps lives and hints
+    /// across them; `None` outside a campaign.
+    pub campaign: Option<Campaign>,
+    /// Whether the first reveal (which places the mines) has happened.
     pub first_click_done: bool,
+    /// When the first reveal happened; the timer runs from here.
     pub start_time: Option<Instant>,
+    /// When the game was won or lost; the timer stops here.
+    pub end_time: Option<Instant>,
+    /// Number of flags on the board, for the remaining-mines counter.
     pub flags_placed: usize,
+    /// Whether the game is still in progress, won or lost.
     pub status: Status,
+    /// What the primary key does.
+    pub input_mode: InputMode,
+    /// Which optional panels are shown.
+    pub panels: Panels,
+    /// Direction of the most recent cursor move, used by `FlagAndAdvance`.
+    pub last_move: (isize, isize),
+    /// Double-tap-to-chord window; `None` disables the gesture.
+    pub double_tap_timeout: Option<Duration>,
+    /// Cell and time of the last Reveal on a revealed number, for double-tap detection.
+    last_reveal: Option<(Cursor, Instant)>,
+    /// Visible part of the board.
+    pub camera: Camera,
+    /// Screen position the current viewport drag is anchored at, if dragging.
+    pub drag_anchor: Option<(u16, u16)>,
+    /// Undo stack, most recent move last.
+    history: Vec<Snapshot>,
+    /// Number of hints requested this game; a win with hints counts as assisted.
+    pub hints_used: usize,
+    /// Whether every revealed number whose flags are all placed is chorded at once, so the
+    /// last flag around a number opens the rest. Off by default; see
+    /// [`auto_moves`](Self::auto_moves).
+    pub auto_chord: bool,
+    /// Whether hidden cells that must be mines, next to a number with exactly that many hidden
+    /// neighbors, are flagged at once. Off by default; see [`auto_moves`](Self::auto_moves).
+    pub auto_flag: bool,
+    /// Moves the assists ([`auto_chord`](Self::auto_chord) and
+    /// [`auto_flag`](Self::auto_flag)) made for the player this game; a win with any counts
+    /// as assisted.
+    pub auto_moves: usize,
+    /// Reveals, flags and chords made this game, whether or not they changed anything.
+    pub clicks: usize,
+    /// Moves taken back this game; a win after any sets no best time.
+    pub undos: usize,
+    /// The deduction currently highlighted on the board, cleared by the next move.
+    pub hint: Option<Deduction>,
+    /// Whether the losing move was a forced guess or a misplay, once a move has lost the
+    /// game (or set off a mine that is being defused).
+    pub loss: Option<Loss>,
+    /// How hard the board was to clear, rated once the game is over.
+    pub rating: Option<Rating>,
+    /// Whether a hint with nothing certain to show points at the hidden cell least likely
+    /// to be a mine instead. Off by default: a guess is still a guess.
+    pub guess_hints: bool,
+    /// The best guess currently highlighted on the board, cleared by the next move.
+    pub guess: Option<Guess>,
+    /// The cell last peeked at and how its neighbors stood, cleared by the next move.
+    pub peek: Option<(Cursor, Neighbors)>,
+    /// Macro being recorded, if recording is on.
+    pub recording: Option<Macro>,
+    /// Most recently finished recording, replayed by `PlayMacro`.
+    pub last_macro: Option<Macro>,
+    /// Seed used for new games; `None` means a random board each time.
+    pub seed: Option<u64>,
+    /// Board code the game was started from. Restarting replays it until the difficulty or
+    /// seed changes.
+    pub code: Option<BoardCode>,
+    /// Puzzle packs offered by the pack browser.
+    pub packs: Vec<Pack>,
+    /// The pack and puzzle (indexes into [`packs`](Self::packs)) being played, while the game
+    /// is one of them.
+    pub pack_puzzle: Option<(usize, usize)>,
+    /// Hand-made board the game was started from, as first loaded. Restarting replays it
+    /// until the difficulty or seed changes.
+    pub puzzle: Option<Board>,
+    /// Shape new boards are cut to, while the game is a shaped one. Restarting deals a new
+    /// board of the shape until the difficulty changes.
+    pub mask: Option<Mask>,
+    /// Open UI layers, topmost last. Esc closes them one at a time.
+    pub layers: Vec<Layer>,
+    /// Timings of reveals and solver runs, collected while set.
+    pub profile: Option<Profile>,
+    /// Every reveal, flag, chord and undo of the current game, in plain words.
+    pub moves: MoveLog,
+    /// What happened to the game since a front end last took the events: reveals, flags,
+    /// undos, new games and the end of the game.
+    pub events: EventBus,
+    /// The two players of a hot-seat game, when playing one.
+    pub hotseat: Option<HotSeat>,
+    /// Points earned this game in scoring mode; `None` when not keeping score. Set to
+    /// `Some(Score::default())` to turn scoring on.
+    pub score: Option<Score>,
+    /// In memory mode, how long revealed numbers stay on screen before they fade to blank;
+    /// `None` shows them for good.
+    pub memory: Option<Duration>,
+    /// Most flags the board may hold at once, further flags being refused; at most the number
+    /// of mines. `None` allows a flag on every hidden cell.
+    pub flag_budget: Option<usize>,
+    /// Time added for each wrong flag: one taken back off a safe cell, or left on one when
+    /// the game ends. `None` leaves wrong flags unpunished.
+    pub flag_penalty: Option<Duration>,
+    /// Time added for defusing a mine. With a penalty set, a mine that goes off opens a
+    /// [`Defusal`] instead of ending the game (except in hot-seat games); `None` ends it.
+    pub defuse_penalty: Option<Duration>,
+    /// Time wrong flags and defused mines have added to the timer this game.
+    pub penalty: Duration,
+    /// In fog-of-war mode, how many cells around the cursor can be seen, counting a diagonal
+    /// step as one; `None` shows the whole board.
+    pub fog: Option<usize>,
+    /// Timer reading each cell was revealed at, row by row, kept in memory mode only.
+    reveal_times: Vec<Option<Duration>>,
 }
 
 impl AppState {
+    /// Start a fresh game at the given difficulty.
     pub fn new(difficulty: Difficulty) -> Self {
         let (w, h, m) = difficulty.parameters();
-        let board = Board::new(w, h, m);
         Self {
-            board,
+            board: Board::new(w, h, m),
             cursor: Cursor::new(0, 0),
             difficulty,
+            last_custom: matches!(difficulty, Difficulty::Custom { .. }).then_some(difficulty),
+            hardcore: false,
+            practice: false,
+            topology: Topology::Flat,
+            layout: Layout::Uniform,
+            adaptive: None,
+            campaign: None,
             first_click_done: false,
             start_time: None,
+            end_time: None,
             flags_placed: 0,
             status: Status::Playing,
+            input_mode: InputMode::Reveal,
+            panels: Panels::default(),
+            last_move: (1, 0),
+            double_tap_timeout: Some(DEFAULT_DOUBLE_TAP_TIMEOUT),
+            last_reveal: None,
+            camera: Camera::new(w, h),
+            drag_anchor: None,
+            history: Vec::new(),
+            hints_used: 0,
+            auto_chord: false,
+            auto_flag: false,
+            auto_moves: 0,
+            clicks: 0,
+            undos: 0,
+            hint: None,
+            loss: None,
+            rating: None,
+            guess_hints: false,
+            guess: None,
+            peek: None,
+            recording: None,
+            last_macro: None,
+            seed: None,
+            code: None,
+            packs: Vec::new(),
+            pack_puzzle: None,
+            puzzle: None,
+            mask: None,
+            layers: Vec::new(),
+            profile: None,
+            moves: MoveLog::default(),
+            events: EventBus::default(),
+            hotseat: None,
+            score: None,
+            memory: None,
+            flag_budget: None,
+            flag_penalty: None,
+            defuse_penalty: None,
+            penalty: Duration::ZERO,
+            fog: None,
+            reveal_times: Vec::new(),
+        }
+    }
+
+    /// Move the difficulty on before a new game: adaptive mode after a finished game, and
+    /// a campaign run to its next board.
+    fn advance_difficulty(&mut self) {
+        if self.status != Status::Playing
+            && self.code.is_none()
+            && self.puzzle.is_none()
+            && let Some(adaptive) = &mut self.adaptive
+        {
+            let (width, height, mines) = self.difficulty.parameters();
+            let won = self.status == Status::Win;
+            let mines = adaptive.next_mines(won, width * height, mines);
+            self.difficulty = Difficulty::Custom {
+                width,
+                height,
+                mines,
+            };
+        }
+        let given_up = self.status == Status::Playing && self.first_click_done;
+        let next = self.campaign_run().map(|run| {
+            if given_up {
+                run.finished(false);
+            }
+            if run.is_over() {
+                run.new_run();
+            }
+            run.next_board()
+        });
+        if let Some(next) = next {
+            self.difficulty = next;
         }
     }
 
     /// Reset the current game while keeping the current difficulty.
     pub fn restart(&mut self) {
+        self.advance_difficulty();
         let (w, h, m) = self.difficulty.parameters();
-        self.board = Board::new(w, h, m);
+        self.mask = self
+            .mask
+            .take()
+            .filter(|mask| (mask.width, mask.height) == (w, h) && m < mask.cells());
+        self.board = self
+            .mask
+            .as_ref()
+            .and_then(|mask| mask.board(m).ok())
+            .unwrap_or_else(|| Board::new(w, h, m));
+        if let Some(seed) = self.seed {
+            self.board = self.board.clone().with_seed(seed);
+        }
         self.cursor = Cursor::new(0, 0);
         self.first_click_done = false;
         self.start_time = None;
+        self.end_time = None;
         self.flags_placed = 0;
+        self.penalty = Duration::ZERO;
+        self.score = self.score.map(|_| Score::default());
         self.status = Status::Playing;
+        self.camera = Camera::new(w, h);
+        self.drag_anchor = None;
+        self.last_reveal = None;
+        self.history.clear();
+        self.hints_used = 0;
+        self.auto_moves = 0;
+        self.clicks = 0;
+        self.undos = 0;
+        self.hint = None;
+        self.loss = None;
+        self.rating = None;
+        self.guess = None;
+        self.peek = None;
+        self.layers.clear();
+        self.moves.clear();
+        self.events.emit(Event::NewGame);
+        self.reveal_times.clear();
+        self.puzzle = self.puzzle.take().filter(|puzzle| {
+            self.seed.is_none() && (puzzle.width(), puzzle.height(), puzzle.mines()) == (w, h, m)
+        });
+        if let Some(puzzle) = &self.puzzle {
+            self.board = puzzle.clone();
+        } else {
+            self.pack_puzzle = None;
+        }
+        self.code = self
+            .code
+            .filter(|code| code.difficulty == self.difficulty && Some(code.seed) == self.seed);
+        if self.code.is_none() && self.puzzle.is_none() {
+            self.board = self
+                .board
+                .clone()
+                .with_topology(self.topology)
+                .with_layout(self.layout);
+        }
+        if let Some(code) = self.code {
+            // Open the same cell for everyone, so the mines do not depend on the first click.
+            let (col, row) = code.start();
+            self.cursor = Cursor::new(col, row);
+            self.camera.follow(col, row);
+            let before = self.board.clone();
+            self.reveal_at_cursor();
+            self.log_move(&before);
+        } else if self.hardcore || self.practice {
+            // A no-op for hand-made boards, whose mines are already placed.
+            self.board.place_mines();
+            if self.practice
+                && self.puzzle.is_none()
+                && let Some((col, row)) = self.board.largest_opening()
+            {
+                self.cursor = Cursor::new(col, row);
+                self.camera.follow(col, row);
+                let before = self.board.clone();
+                self.reveal_at_cursor();
+                self.log_move(&before);
+            }
+        }
+        if let Some(seat) = &mut self.hotseat {
+            *seat = HotSeat::new(seat.scoring, self.cursor);
+        }
+    }
+
+    /// The custom board to select: the last one chosen, or else this board's size.
+    pub const fn custom_difficulty(&self) -> Difficulty {
+        if let Some(custom) = self.last_custom {
+            return custom;
+        }
+        let (width, height, mines) = self.difficulty.parameters();
+        Difficulty::Custom {
+            width,
+            height,
+            mines,
+        }
+    }
+
+    /// Start a new game at difficulty `d`, remembering it if it is a custom board.
+    fn set_difficulty(&mut self, d: Difficulty) -> Command {
+        if matches!(d, Difficulty::Custom { .. }) {
+            self.last_custom = Some(d);
+        }
+        self.difficulty = d;
+        self.restart();
+        Command::Redraw
+    }
+
+    /// Play a hand-made board, such as one read by [`puzzle::parse`](crate::puzzle::parse).
+    pub fn start_puzzle(&mut self, board: Board) {
+        self.difficulty = Difficulty::Custom {
+            width: board.width(),
+            height: board.height(),
+            mines: board.mines(),
+        };
+        self.seed = None;
+        self.puzzle = Some(board);
+        self.mask = None;
+        self.restart();
+    }
+
+    /// Play boards in the shape of `mask`, such as one read by
+    /// [`mask::parse`](crate::mask::parse), with `mines` mines each.
+    ///
+    /// # Errors
+    /// Fails if the mines leave no cell of the shape safe; the game is left as it was.
+    pub fn start_shape(&mut self, mask: Mask, mines: usize) -> Result<()> {
+        mask.board(mines)?;
+        self.difficulty = Difficulty::Custom {
+            width: mask.width,
+            height: mask.height,
+            mines,
+        };
+        self.puzzle = None;
+        self.mask = Some(mask);
+        self.restart();
+        Ok(())
+    }
+
+    /// Start the game named by a board code.
+    pub fn apply_code(&mut self, code: BoardCode) {
+        self.mask = None;
+        self.difficulty = code.difficulty;
+        self.seed = Some(code.seed);
+        self.code = Some(code);
+        self.restart();
+    }
+
+    /// Whether the current game was dealt in hardcore mode, i.e. its first reveal could hit
+    /// a mine.
+    pub const fn is_hardcore(&self) -> bool {
+        self.hardcore && self.code.is_none() && self.puzzle.is_none()
+    }
+
+    /// Whether the current game was dealt in practice mode, i.e. opened for the player.
+    pub const fn is_practice(&self) -> bool {
+        self.practice && self.code.is_none() && self.puzzle.is_none()
+    }
+
+    /// The campaign run the game counts toward: none for board codes and hand-made boards.
+    fn campaign_run(&mut self) -> Option<&mut Campaign> {
+        if self.code.is_some() || self.puzzle.is_some() {
+            return None;
+        }
+        self.campaign.as_mut()
+    }
+
+    /// Whether there is a move to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    /// Check the board's invariants (see [`Board::check_invariants`]) and that the game state
+    /// agrees with it: the flag counter matches the flagged cells, the cursor is on the board,
+    /// a won game has every safe cell revealed and only a finished game has an end time.
+    ///
+    /// # Errors
+    /// Describes the first inconsistency found.
+    pub fn check_invariants(&self) -> Result<()> {
+        self.board.check_invariants()?;
+        let broken = |message: String| Err(Error::InvalidState(message));
+        let (width, height) = (self.board.width(), self.board.height());
+        let flagged = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.board.cell_at(x, y) == CellState::Flagged)
+            .count();
+        if flagged != self.flags_placed {
+            return broken(format!(
+                "the counter shows {} flags but {flagged} cells are flagged",
+                self.flags_placed
+            ));
+        }
+        if self.cursor.x >= width || self.cursor.y >= height {
+            return broken(format!(
+                "the cursor at ({}, {}) is off the {width}x{height} board",
+                self.cursor.x, self.cursor.y
+            ));
+        }
+        if self.status == Status::Win && !self.board.is_win() {
+            return broken("the game is won with safe cells still hidden".to_string());
+        }
+        if self.end_time.is_some() != (self.status != Status::Playing) {
+            return broken(format!(
+                "a {:?} game has end time {:?}",
+                self.status, self.end_time
+            ));
+        }
+        Ok(())
+    }
+
+    /// Time from the first reveal to now, or to the end of a finished game, plus any
+    /// [`penalty`](Self::penalty); zero before the game has started.
+    pub fn elapsed(&self) -> Duration {
+        let played = match (self.start_time, self.end_time) {
+            (Some(start), Some(end)) => end.duration_since(start),
+            (Some(start), None) => start.elapsed(),
+            (None, _) => Duration::ZERO,
+        };
+        played + self.penalty
+    }
+
+    /// Whole seconds of [`elapsed`](Self::elapsed), as the timer shows them.
+    pub fn elapsed_secs(&self) -> u64 {
+        self.elapsed().as_secs()
+    }
+
+    /// Whether the number at (`x`, `y`) has faded in memory mode: it was revealed at least
+    /// [`memory`](Self::memory) ago by the timer. Times are taken in the timer's whole
+    /// seconds, so a screen redrawn as the timer ticks is never behind. Nothing fades once the
+    /// game is over.
+    pub fn is_faded(&self, x: usize, y: usize) -> bool {
+        let Some(fade) = self.memory else {
+            return false;
+        };
+        let number = matches!(self.board.cell_at(x, y), CellState::Revealed(n) if n > 0);
+        let shown = Duration::from_secs(self.elapsed_secs());
+        number
+            && self.status == Status::Playing
+            && self
+                .reveal_times
+                .get(y * self.board.width() + x)
+                .copied()
+                .flatten()
+                .is_some_and(|at| shown >= at + fade)
+    }
+
+    /// Flags that can still be placed under the [`flag_budget`](Self::flag_budget), if there
+    /// is one.
+    pub fn flags_left(&self) -> Option<usize> {
+        self.flag_budget.map(|budget| {
+            budget
+                .min(self.board.mines())
+                .saturating_sub(self.flags_placed)
+        })
+    }
+
+    /// Whether the cell at (`x`, `y`) can be seen: in fog-of-war mode, only cells within
+    /// [`fog`](Self::fog) of the cursor can until the game is over.
+    pub fn is_visible(&self, x: usize, y: usize) -> bool {
+        self.fog.is_none_or(|radius| {
+            self.status != Status::Playing
+                || (x.abs_diff(self.cursor.x) <= radius && y.abs_diff(self.cursor.y) <= radius)
+        })
+    }
+
+    /// Update the visible board area (in cells), keeping the cursor on screen.
+    pub fn resize_view(&mut self, cols: usize, rows: usize) {
+        self.camera.resize(cols, rows);
+        self.camera.follow(self.cursor.x, self.cursor.y);
     }
 
     /// Handle a high-level action and return a command the UI can respond to.
     pub fn handle_action(&mut self, action: Action) -> Command {
+        // Dialogs capture all input while they are on top.
+        match self.layers.last() {
+            Some(Layer::SeedPrompt(_)) => return self.handle_prompt(action),
+            Some(Layer::ConfirmQuit) => return self.handle_confirm_quit(action),
+            Some(Layer::Handoff) => return self.handle_handoff(action),
+            Some(&Layer::Packs(selected)) => return self.handle_packs(selected, action),
+            Some(Layer::Defuse(_)) => return self.handle_defusal(action),
+            Some(Layer::GameOver) | None => {}
+        }
+        if let Some(cmd) = self.handle_macro_action(action) {
+            return cmd;
+        }
+        if let Some(cmd) = self.handle_anytime_action(action) {
+            return cmd;
+        }
+
         // If game is over, only allow restart or difficulty change.
         if !matches!(self.status, Status::Playing) {
             return match action {
@@ -96,11 +785,7 @@ impl AppState {
                     self.restart();
                     Command::Redraw
                 }
-                Action::SetDifficulty(d) => {
-                    self.difficulty = d;
-                    self.restart();
-                    Command::Redraw
-                }
+                Action::SetDifficulty(d) => self.set_difficulty(d),
                 _ => Command::None,
             };
         }
@@ -110,42 +795,658 @@ impl AppState {
             Action::MoveRight => self.try_move(1, 0),
             Action::MoveUp => self.try_move(0, -1),
             Action::MoveDown => self.try_move(0, 1),
-            Action::Reveal => self.reveal_at_cursor(),
-            Action::ToggleFlag => {
-                // Adjust flags_placed based on current cell state before toggle
-                match self.board.cell_at(self.cursor.x, self.cursor.y) {
-                    crate::board::CellState::Hidden => {
-                        // Will become Flagged
-                        self.flags_placed = self.flags_placed.saturating_add(1);
-                    }
-                    crate::board::CellState::Flagged => {
-                        // Will become Hidden
-                        self.flags_placed = self.flags_placed.saturating_sub(1);
-                    }
-                    crate::board::CellState::Revealed(_) => {}
+            Action::MoveUpLeft => self.try_move(-1, -1),
+            Action::MoveUpRight => self.try_move(1, -1),
+            Action::MoveDownLeft => self.try_move(-1, 1),
+            Action::MoveDownRight => self.try_move(1, 1),
+            Action::Reveal => self.record(Self::reveal_at_cursor),
+            Action::ToggleFlag => self.record(Self::toggle_flag_at_cursor),
+            Action::FlagAndAdvance => {
+                if matches!(
+                    self.board.cell_at(self.cursor.x, self.cursor.y),
+                    CellState::Hidden
+                ) {
+                    self.record(Self::toggle_flag_at_cursor);
                 }
-                self.board.toggle_flag(self.cursor.x, self.cursor.y);
+                let (dx, dy) = self.last_move;
+                self.try_move(dx, dy);
                 Command::Redraw
             }
-            Action::Chord => self.chord_at_cursor(),
+            Action::Chord => self.record(Self::chord_at_cursor),
+            Action::OpenRemaining => self.record_as(Self::open_remaining, Entry::opened_rest),
+            Action::Peek => self.peek(),
+            Action::Hint => self.request_hint(),
             Action::Restart => {
                 self.restart();
                 Command::Redraw
             }
-            Action::SetDifficulty(d) => {
-                self.difficulty = d;
-                self.restart();
+            Action::SetDifficulty(d) => self.set_difficulty(d),
+            // Handled above or only meaningful inside a prompt
+            _ => Command::None,
+        }
+    }
+
+    /// Start/stop/replay macros, and record every other action while recording.
+    fn handle_macro_action(&mut self, action: Action) -> Option<Command> {
+        match action {
+            Action::ToggleRecording => {
+                match self.recording.take() {
+                    Some(recorded) => self.last_macro = Some(recorded),
+                    None => self.recording = Some(Macro::new()),
+                }
+                Some(Command::Redraw)
+            }
+            Action::PlayMacro => {
+                // Stop any recording first so the replay doesn't record itself.
+                if let Some(recorded) = self.recording.take() {
+                    self.last_macro = Some(recorded);
+                }
+                Some(match self.last_macro.clone() {
+                    Some(m) => m.play(self),
+                    None => Command::None,
+                })
+            }
+            _ => {
+                if let Some(recording) = &mut self.recording {
+                    recording.push(action);
+                }
+                None
+            }
+        }
+    }
+
+    /// Actions allowed at any time, including after the game ended.
+    fn handle_anytime_action(&mut self, action: Action) -> Option<Command> {
+        let cmd = match action {
+            Action::BeginPan(col, row) => {
+                self.drag_anchor = Some((col, row));
+                Command::None
+            }
+            Action::PanTo(col, row) => self.pan_to(col, row),
+            Action::EndPan => {
+                self.drag_anchor = None;
+                Command::None
+            }
+            Action::ToggleInputMode => {
+                self.input_mode = self.input_mode.toggle();
+                Command::Redraw
+            }
+            Action::TogglePanel(panel) => {
+                self.panels.toggle(panel);
                 Command::Redraw
             }
+            Action::OpenSeedPrompt => {
+                self.layers.push(Layer::SeedPrompt(String::new()));
+                Command::Redraw
+            }
+            Action::OpenPacks if !self.packs.is_empty() => {
+                let selected = self.pack_puzzle.map_or(0, |(pack, index)| {
+                    self.packs[..pack]
+                        .iter()
+                        .map(|pack| pack.puzzles.len())
+                        .sum::<usize>()
+                        + index
+                });
+                self.layers.push(Layer::Packs(selected));
+                Command::Redraw
+            }
+            Action::Back => {
+                if self.layers.pop().is_none() {
+                    self.layers.push(Layer::ConfirmQuit);
+                }
+                Command::Redraw
+            }
+            // Undo also works after the game ended, to step back and analyse the loss.
+            Action::Undo => self.undo(),
+            _ => return None,
+        };
+        Some(cmd)
+    }
+
+    /// Append pasted text to the open prompt as one edit. Pastes outside a prompt are
+    /// dropped rather than replayed as a burst of game keys.
+    pub fn paste(&mut self, text: &str) -> Command {
+        let Some(Layer::SeedPrompt(prompt)) = self.layers.last_mut() else {
+            return Command::None;
+        };
+        for c in text.chars().filter(|c| !c.is_control()) {
+            if prompt.chars().count() >= SEED_PROMPT_MAX_LEN {
+                break;
+            }
+            prompt.push(c);
+        }
+        Command::Redraw
+    }
+
+    /// Text typed into the seed prompt, if it is the topmost layer.
+    pub fn seed_prompt(&self) -> Option<&str> {
+        match self.layers.last() {
+            Some(Layer::SeedPrompt(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Whether the game-over message is showing (Esc hides it to inspect the board).
+    pub fn game_over_shown(&self) -> bool {
+        self.layers.contains(&Layer::GameOver)
+    }
+
+    fn handle_confirm_quit(&mut self, action: Action) -> Command {
+        match action {
+            Action::ConfirmQuit => Command::Quit,
+            Action::Back => {
+                self.layers.pop();
+                Command::Redraw
+            }
+            _ => Command::None,
+        }
+    }
+
+    /// Wait for the next hot-seat player: the primary key or Esc closes the message, and
+    /// everything else is ignored so nothing is played by accident.
+    fn handle_handoff(&mut self, action: Action) -> Command {
+        match action {
+            Action::Reveal | Action::ToggleFlag | Action::Back => {
+                self.layers.pop();
+                Command::Redraw
+            }
+            _ => Command::None,
+        }
+    }
+
+    /// Browse the puzzle packs: up and down pick a puzzle, the primary key plays it and Esc
+    /// goes back to the game.
+    fn handle_packs(&mut self, selected: usize, action: Action) -> Command {
+        let puzzles = self
+            .packs
+            .iter()
+            .map(|pack| pack.puzzles.len())
+            .sum::<usize>();
+        let selected = match action {
+            Action::MoveUp => selected.saturating_sub(1),
+            Action::MoveDown => (selected + 1).min(puzzles.saturating_sub(1)),
+            Action::Reveal | Action::ToggleFlag => {
+                self.play_pack_puzzle(selected);
+                return Command::Redraw;
+            }
+            Action::Back => {
+                self.layers.pop();
+                return Command::Redraw;
+            }
+            _ => return Command::None,
+        };
+        if let Some(layer) = self.layers.last_mut() {
+            *layer = Layer::Packs(selected);
+        }
+        Command::Redraw
+    }
+
+    /// Start the `selected` puzzle, counting through every pack in order.
+    fn play_pack_puzzle(&mut self, mut selected: usize) {
+        for (pack, puzzles) in self.packs.iter().map(|pack| &pack.puzzles).enumerate() {
+            if let Some(puzzle) = puzzles.get(selected) {
+                self.start_puzzle(puzzle.board.clone());
+                self.pack_puzzle = Some((pack, selected));
+                return;
+            }
+            selected -= puzzles.len();
+        }
+    }
+
+    /// Enter the defusal sequence with the cursor keys. Everything else is ignored, except Esc,
+    /// which gives up and lets the mine go off.
+    fn handle_defusal(&mut self, action: Action) -> Command {
+        let Some(Layer::Defuse(defusal)) = self.layers.last_mut() else {
+            return Command::None;
+        };
+        let arrow = match action {
+            Action::MoveUp => Arrow::Up,
+            Action::MoveDown => Arrow::Down,
+            Action::MoveLeft => Arrow::Left,
+            Action::MoveRight => Arrow::Right,
+            Action::Back => return self.finish_defusal(Outcome::Exploded),
+            _ => return Command::None,
+        };
+        let outcome = defusal.press(arrow, Instant::now());
+        self.finish_defusal(outcome)
+    }
+
+    /// Let time pass: a defusal nobody finishes in time goes off. Call regularly, such as on
+    /// every frame; returns [`Command::Redraw`] while a defusal is counting down.
+    pub fn tick(&mut self) -> Command {
+        let Some(Layer::Defuse(defusal)) = self.layers.last() else {
+            return Command::None;
+        };
+        let outcome = defusal.check(Instant::now());
+        self.finish_defusal(outcome)
+    }
+
+    /// Close the defusal once it has an outcome: a defused mine is flagged and costs the
+    /// [`defuse_penalty`](Self::defuse_penalty); one that went off ends the game.
+    fn finish_defusal(&mut self, outcome: Outcome) -> Command {
+        if outcome == Outcome::Pending {
+            return Command::Redraw;
+        }
+        let Some(Layer::Defuse(defusal)) = self.layers.pop() else {
+            return Command::None;
+        };
+        let snapshot = self.snapshot();
+        if outcome == Outcome::Exploded {
+            let cmd = self.conclude(false);
+            self.commit(snapshot, Self::move_at_cursor);
+            return cmd;
+        }
+        for &(x, y) in &defusal.tripped {
+            self.board.toggle_flag(x, y);
+            self.flags_placed += 1;
         }
+        self.loss = None;
+        let fine = self.defuse_penalty.unwrap_or_default();
+        self.penalty = self.penalty.saturating_add(fine);
+        if let Some(score) = &mut self.score {
+            score.break_streak();
+        }
+        // A chord can open the last safe cells next to the mine it set off.
+        let cmd = self.conclude(true);
+        self.push_move(Entry {
+            at: self.elapsed(),
+            x: self.cursor.x,
+            y: self.cursor.y,
+            action: Move::Defused,
+            ended: (self.status != Status::Playing).then_some(self.status),
+        });
+        self.history.push(snapshot);
+        cmd
+    }
+
+    fn handle_prompt(&mut self, action: Action) -> Command {
+        let Some(Layer::SeedPrompt(prompt)) = self.layers.last_mut() else {
+            return Command::None;
+        };
+        match action {
+            Action::PromptChar(c) => {
+                if !c.is_control() && prompt.chars().count() < SEED_PROMPT_MAX_LEN {
+                    prompt.push(c);
+                }
+            }
+            Action::PromptBackspace => {
+                prompt.pop();
+            }
+            Action::PromptSubmit => {
+                let code = prompt.parse::<BoardCode>().ok();
+                self.seed = parse_seed(prompt);
+                self.layers.pop();
+                match code {
+                    Some(code) => self.apply_code(code),
+                    None => self.restart(),
+                }
+            }
+            Action::Back => {
+                self.layers.pop();
+            }
+            _ => return Command::None,
+        }
+        Command::Redraw
+    }
+
+    /// Run a board-changing move, saving the prior state for undo if anything changed.
+    fn record(&mut self, apply: impl FnOnce(&mut Self) -> Command) -> Command {
+        self.record_as(apply, Self::move_at_cursor)
+    }
+
+    /// Run a board-changing move like [`record`](Self::record), with `describe` telling the
+    /// move log what it did.
+    fn record_as(
+        &mut self,
+        apply: impl FnOnce(&mut Self) -> Command,
+        describe: fn(&Self, &Board) -> Option<Entry>,
+    ) -> Command {
+        self.clicks += 1;
+        let snapshot = self.snapshot();
+        let cmd = apply(self);
+        if self.board == snapshot.board && self.status == snapshot.status {
+            return cmd;
+        }
+        self.commit(snapshot, describe);
+        // Undo takes back the assists' moves along with the move that set them off.
+        self.assist(cmd)
+    }
+
+    /// Let the assists play: with [`auto_flag`](Self::auto_flag) on, flag every certain mine
+    /// next to a number, and with [`auto_chord`](Self::auto_chord) on, chord every number whose
+    /// flags are all placed, until neither finds anything or the game ends. Each move is
+    /// logged. Returns what the last move (`cmd` if there was none) asks of the UI.
+    fn assist(&mut self, mut cmd: Command) -> Command {
+        while self.status == Status::Playing
+            && !matches!(self.layers.last(), Some(Layer::Defuse(_)))
+        {
+            let before = self.board.clone();
+            if self.auto_flag
+                && self.flags_left() != Some(0)
+                && let Some((x, y)) = self.certain_mine()
+            {
+                self.board.toggle_flag(x, y);
+                self.flags_placed += 1;
+                cmd = Command::Redraw;
+                if let Some(entry) = Entry::of(self, &before, x, y) {
+                    self.push_move(entry);
+                }
+            } else if self.auto_chord
+                && let Some((x, y)) = self.chordable()
+            {
+                // The chord settles as if made at the cursor, e.g. to find the mines it set off.
+                let cursor = self.cursor;
+                self.cursor = Cursor::new(x, y);
+                let safe = self.board.chord(x, y);
+                cmd = self.settle(safe);
+                self.log_move(&before);
+                self.cursor = cursor;
+            } else {
+                break;
+            }
+            self.auto_moves += 1;
+        }
+        cmd
+    }
+
+    /// The revealed numbers on the board, with their positions.
+    fn numbers(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        let board = &self.board;
+        (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+            .filter_map(|(x, y)| match board.cell_at(x, y) {
+                CellState::Revealed(n) if n > 0 => Some((x, y, usize::from(n))),
+                _ => None,
+            })
+    }
+
+    /// How many neighbors of (`x`, `y`) are in `state`.
+    fn around(&self, x: usize, y: usize, state: CellState) -> usize {
+        self.board
+            .neighbors(x, y)
+            .filter(|&(nx, ny)| self.board.cell_at(nx, ny) == state)
+            .count()
+    }
+
+    /// A revealed number with all its flags placed and hidden cells left around it.
+    fn chordable(&self) -> Option<(usize, usize)> {
+        self.numbers()
+            .find(|&(x, y, n)| {
+                self.around(x, y, CellState::Flagged) == n
+                    && self.around(x, y, CellState::Hidden) > 0
+            })
+            .map(|(x, y, _)| (x, y))
+    }
+
+    /// An unflagged hidden cell next to a number with exactly as many hidden (or flagged)
+    /// neighbors as it counts, so it must be a mine.
+    fn certain_mine(&self) -> Option<(usize, usize)> {
+        self.numbers()
+            .filter(|&(x, y, n)| {
+                self.around(x, y, CellState::Hidden) + self.around(x, y, CellState::Flagged) == n
+            })
+            .find_map(|(x, y, _)| {
+                self.board
+                    .neighbors(x, y)
+                    .find(|&(nx, ny)| self.board.cell_at(nx, ny) == CellState::Hidden)
+            })
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            board: self.board.clone(),
+            flags_placed: self.flags_placed,
+            status: self.status,
+            penalty: self.penalty,
+            score: self.score,
+        }
+    }
+
+    /// Log, score and keep for undo the move made since `snapshot`, if it changed anything.
+    fn commit(&mut self, snapshot: Snapshot, describe: fn(&Self, &Board) -> Option<Entry>) {
+        if self.board != snapshot.board || self.status != snapshot.status {
+            // A mine that goes off after a defusal was judged when it was set off.
+            let tripped =
+                self.status == Status::Lose || matches!(self.layers.last(), Some(Layer::Defuse(_)));
+            if tripped && self.loss.is_none() {
+                self.loss = Some(Loss::judge(&snapshot.board));
+            }
+            if let Some(entry) = describe(self, &snapshot.board) {
+                self.push_move(entry);
+                self.score_move(entry);
+            }
+            self.end_turn(&snapshot.board);
+            self.history.push(snapshot);
+            self.hint = None;
+            self.guess = None;
+            self.peek = None;
+        }
+    }
+
+    /// In a hot-seat game, credit the move that changed `before` to the current player and
+    /// hand over to the other one if it opened cells.
+    fn end_turn(&mut self, before: &Board) {
+        let Some(seat) = &mut self.hotseat else {
+            return;
+        };
+        let opened = self.board.revealed().saturating_sub(before.revealed());
+        let mine = self.status == Status::Lose;
+        if seat.end_turn(opened, mine, self.cursor) && self.status == Status::Playing {
+            self.cursor = seat.current().cursor;
+            self.camera.follow(self.cursor.x, self.cursor.y);
+            self.layers.push(Layer::Handoff);
+        }
+    }
+
+    /// In scoring mode, score the move `entry` describes.
+    fn score_move(&mut self, entry: Entry) {
+        let bbbv = self.board.bbbv();
+        let elapsed = self.elapsed();
+        let mine = self.board.is_mine(entry.x, entry.y);
+        let Some(score) = &mut self.score else {
+            return;
+        };
+        match entry.action {
+            Move::Revealed {
+                number: Some(_),
+                opened,
+            }
+            | Move::OpenedRest {
+                opened,
+                mine: false,
+            } => score.opened(opened, false),
+            Move::Chorded {
+                opened,
+                mine: false,
+            } => score.opened(opened, true),
+            Move::Flagged if mine => score.flagged_mine(),
+            _ => score.break_streak(),
+        }
+        if entry.ended == Some(Status::Win) {
+            score.won(bbbv, elapsed);
+        }
+    }
+
+    /// Add `entry` to the move log and tell the event bus what it did, and that it ended the
+    /// game if it did.
+    fn push_move(&mut self, entry: Entry) {
+        self.moves.push(entry);
+        if let Some(event) = Event::of_move(&entry) {
+            self.events.emit(event);
+        }
+        if let Some(status) = entry.ended {
+            self.events.emit(Event::GameEnded {
+                status,
+                time: self.elapsed(),
+            });
+        }
+    }
+
+    /// Add the move at the cursor that changed `before` to the move log.
+    fn log_move(&mut self, before: &Board) {
+        if let Some(entry) = self.move_at_cursor(before) {
+            self.push_move(entry);
+        }
+    }
+
+    /// The move at the cursor that changed `before`, as the move log tells it.
+    fn move_at_cursor(&self, before: &Board) -> Option<Entry> {
+        Entry::of(self, before, self.cursor.x, self.cursor.y)
+    }
+
+    fn request_hint(&mut self) -> Command {
+        if self.campaign_run().is_some_and(|run| run.hints == 0) {
+            return Command::None;
+        }
+        let deductions = self.timed(|p| &mut p.solver, |app| solver::deduce(&app.board));
+        let (x, y) = match deductions.hint() {
+            Some(hint) => {
+                self.hint = Some(hint);
+                let (Deduction::Safe(x, y) | Deduction::Mine(x, y)) = hint;
+                (x, y)
+            }
+            // Before the first reveal every cell is a guess, and the first one is safe anyway.
+            None if self.guess_hints && self.first_click_done => {
+                let guess = self.timed(|p| &mut p.solver, |app| solver::best_guess(&app.board));
+                let Some(guess) = guess else {
+                    return Command::None;
+                };
+                self.guess = Some(guess);
+                (guess.x, guess.y)
+            }
+            None => return Command::None,
+        };
+        self.hints_used += 1;
+        if let Some(run) = self.campaign_run() {
+            run.hints -= 1;
+        }
+        if let Some(score) = &mut self.score {
+            score.break_streak();
+        }
+        self.cursor = Cursor::new(x, y);
+        self.camera.follow(x, y);
+        Command::Redraw
+    }
+
+    /// Count the neighbors of the cursor cell for the header, and log the count for players
+    /// following the game with a screen reader.
+    fn peek(&mut self) -> Command {
+        let Cursor { x, y } = self.cursor;
+        let neighbors = self.board.neighbor_summary(x, y);
+        self.peek = Some((self.cursor, neighbors));
+        self.push_move(Entry {
+            at: self.elapsed(),
+            x,
+            y,
+            action: Move::Peeked(neighbors),
+            ended: None,
+        });
+        Command::Redraw
+    }
+
+    fn undo(&mut self) -> Command {
+        // Taking a move back would have to hand the turn back too, and in a campaign it would
+        // make lives meaningless.
+        if self.hotseat.is_some() || self.campaign_run().is_some() {
+            return Command::None;
+        }
+        let Some(snapshot) = self.history.pop() else {
+            return Command::None;
+        };
+        self.push_move(Entry {
+            at: self.elapsed(),
+            x: self.cursor.x,
+            y: self.cursor.y,
+            action: Move::Undone,
+            ended: None,
+        });
+        self.undos += 1;
+        self.board = snapshot.board;
+        self.flags_placed = snapshot.flags_placed;
+        self.status = snapshot.status;
+        self.loss = None;
+        self.rating = None;
+        self.penalty = snapshot.penalty;
+        self.score = snapshot.score;
+        if let Some(score) = &mut self.score {
+            score.break_streak();
+        }
+        if self.status == Status::Playing {
+            self.layers.retain(|layer| *layer != Layer::GameOver);
+            self.end_time = None;
+        }
+        self.last_reveal = None;
+        self.hint = None;
+        self.guess = None;
+        self.peek = None;
+        self.stamp_reveals();
+        Command::Redraw
+    }
+
+    /// Drag the viewport so the board follows the mouse. Each cell is two columns wide,
+    /// so horizontal motion is consumed in steps of two.
+    fn pan_to(&mut self, col: u16, row: u16) -> Command {
+        let Some((anchor_col, anchor_row)) = self.drag_anchor else {
+            return Command::None;
+        };
+        let dx = (i32::from(col) - i32::from(anchor_col)) / 2;
+        let dy = i32::from(row) - i32::from(anchor_row);
+        if dx == 0 && dy == 0 {
+            return Command::None;
+        }
+        self.camera.pan(
+            isize::try_from(-dx).unwrap_or_default(),
+            isize::try_from(-dy).unwrap_or_default(),
+        );
+        // Keep any odd leftover column so slow drags still add up to a full cell.
+        self.drag_anchor = Some((
+            u16::try_from(i32::from(anchor_col) + dx * 2).unwrap_or(col),
+            row,
+        ));
+        Command::Redraw
+    }
+
+    fn toggle_flag_at_cursor(&mut self) -> Command {
+        if !self.board.in_play(self.cursor.x, self.cursor.y) {
+            return Command::None;
+        }
+        // Adjust flags_placed based on current cell state before toggle
+        match self.board.cell_at(self.cursor.x, self.cursor.y) {
+            CellState::Hidden => {
+                if self.flags_left() == Some(0) {
+                    return Command::None;
+                }
+                // Will become Flagged
+                self.flags_placed = self.flags_placed.saturating_add(1);
+            }
+            CellState::Flagged => {
+                // Will become Hidden
+                self.flags_placed = self.flags_placed.saturating_sub(1);
+                // Before the first reveal no mines are placed, so no flag can be wrong yet.
+                if self.board.mines_placed() && !self.board.is_mine(self.cursor.x, self.cursor.y) {
+                    self.punish(1);
+                }
+            }
+            CellState::Revealed(_) => {}
+        }
+        self.board.toggle_flag(self.cursor.x, self.cursor.y);
+        Command::Redraw
     }
 
     fn try_move(&mut self, dx: isize, dy: isize) -> Command {
-        let nx = self.cursor.x as isize + dx;
-        let ny = self.cursor.y as isize + dy;
+        self.last_move = (dx, dy);
+        let (Ok(x), Ok(y)) = (
+            isize::try_from(self.cursor.x),
+            isize::try_from(self.cursor.y),
+        ) else {
+            return Command::None;
+        };
+        let (nx, ny) = (x + dx, y + dy);
         if self.board.in_bounds(nx, ny) {
-            self.cursor.x = nx as usize;
-            self.cursor.y = ny as usize;
+            // in_bounds guarantees both coordinates are non-negative
+            self.cursor.x = nx.unsigned_abs();
+            self.cursor.y = ny.unsigned_abs();
+            self.camera.follow(self.cursor.x, self.cursor.y);
             Command::Redraw
         } else {
             Command::None
@@ -160,32 +1461,865 @@ impl AppState {
     }
 
     fn reveal_at_cursor(&mut self) -> Command {
-        self.ensure_timer_started();
-        let safe = self.board.reveal(self.cursor.x, self.cursor.y);
-        if !safe {
-            self.status = Status::Lose;
-            return Command::GameLost;
+        // Cells cut out of a shaped board do not start the game either.
+        if !self.board.in_play(self.cursor.x, self.cursor.y) {
+            return Command::None;
         }
-        if self.board.is_win() {
-            self.status = Status::Win;
-            return Command::GameWon;
+        // A second Reveal on the same revealed number within the timeout acts as a chord.
+        if matches!(self.board.cell_at(self.cursor.x, self.cursor.y), CellState::Revealed(n) if n > 0)
+        {
+            let now = Instant::now();
+            let double_tap = self.double_tap_timeout.is_some_and(|timeout| {
+                self.last_reveal
+                    .is_some_and(|(at, t)| at == self.cursor && now.duration_since(t) <= timeout)
+            });
+            if double_tap {
+                self.last_reveal = None;
+                return self.chord_at_cursor();
+            }
+            self.last_reveal = Some((self.cursor, now));
+            return Command::None;
         }
-        Command::Redraw
+        self.last_reveal = None;
+        self.ensure_timer_started();
+        let Cursor { x, y } = self.cursor;
+        let safe = self.timed(|p| &mut p.reveal, |app| app.board.reveal(x, y));
+        self.settle(safe)
     }
 
     fn chord_at_cursor(&mut self) -> Command {
         self.ensure_timer_started();
-        let safe = self.board.chord(self.cursor.x, self.cursor.y);
-        if !safe {
-            self.status = Status::Lose;
-            return Command::GameLost;
+        let Cursor { x, y } = self.cursor;
+        let safe = self.timed(|p| &mut p.reveal, |app| app.board.chord(x, y));
+        self.settle(safe)
+    }
+
+    /// Reveal every cell left once every mine has a flag. A misplaced flag loses at once,
+    /// with no defusal: the mine it left open was ruled out rather than stepped on.
+    fn open_remaining(&mut self) -> Command {
+        if !self.first_click_done || self.flags_placed != self.board.mines() {
+            return Command::None;
         }
-        if self.board.is_win() {
-            self.status = Status::Win;
-            return Command::GameWon;
+        let safe = self.timed(|p| &mut p.reveal, |app| app.board.open_remaining());
+        self.stamp_reveals();
+        self.conclude(safe)
+    }
+
+    /// Run `work`, adding its time to the timing `pick` selects if profiling.
+    fn timed<T>(
+        &mut self,
+        pick: fn(&mut Profile) -> &mut Timing,
+        work: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let started = self.profile.is_some().then(Instant::now);
+        let result = work(self);
+        if let (Some(profile), Some(started)) = (&mut self.profile, started) {
+            pick(profile).add(started.elapsed());
+        }
+        result
+    }
+
+    /// In memory mode, note the game time of every newly revealed cell and forget the cells
+    /// that are hidden again, as after an undo.
+    fn stamp_reveals(&mut self) {
+        if self.memory.is_none() {
+            return;
+        }
+        let width = self.board.width();
+        self.reveal_times.resize(width * self.board.height(), None);
+        let now = Duration::from_secs(self.elapsed_secs());
+        for (i, time) in self.reveal_times.iter_mut().enumerate() {
+            if matches!(
+                self.board.cell_at(i % width, i / width),
+                CellState::Revealed(_)
+            ) {
+                time.get_or_insert(now);
+            } else {
+                *time = None;
+            }
+        }
+    }
+
+    /// Update the status after a reveal or chord, showing the game-over message if it ended
+    /// or the defusal if a mine went off and may be defused.
+    fn settle(&mut self, safe: bool) -> Command {
+        self.stamp_reveals();
+        if !safe && self.defuse_penalty.is_some() && self.hotseat.is_none() {
+            let defusal = Defusal::new(self.tripped_mines(), Instant::now());
+            self.layers.push(Layer::Defuse(defusal));
+            return Command::Redraw;
+        }
+        self.conclude(safe)
+    }
+
+    /// The mines set off by the reveal or chord at the cursor.
+    fn tripped_mines(&self) -> Vec<(usize, usize)> {
+        let Cursor { x, y } = self.cursor;
+        if self.board.cell_at(x, y) == CellState::Hidden {
+            return vec![(x, y)];
+        }
+        self.board
+            .neighbors(x, y)
+            .filter(|&(nx, ny)| {
+                self.board.cell_at(nx, ny) == CellState::Hidden && self.board.is_mine(nx, ny)
+            })
+            .collect()
+    }
+
+    /// End the game if the move was not `safe` or cleared the board.
+    fn conclude(&mut self, safe: bool) -> Command {
+        let (status, cmd) = if !safe {
+            (Status::Lose, Command::GameLost)
+        } else if self.board.is_win() {
+            (Status::Win, Command::GameWon)
+        } else {
+            return Command::Redraw;
+        };
+        self.status = status;
+        self.end_time = Some(Instant::now());
+        self.rating = solver::rate(&self.board);
+        self.layers.push(Layer::GameOver);
+        if let Some(run) = self.campaign_run() {
+            run.finished(status == Status::Win);
+        }
+        if status == Status::Win
+            && let Some((pack, index)) = self.pack_puzzle
+        {
+            let time = self.elapsed();
+            let best = &mut self.packs[pack].puzzles[index].best;
+            if best.is_none_or(|best| time < best) {
+                *best = Some(time);
+            }
+        }
+        let board = &self.board;
+        let wrong_flags = (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| board.cell_at(x, y) == CellState::Flagged && !board.is_mine(x, y))
+            .count();
+        self.punish(wrong_flags);
+        cmd
+    }
+
+    /// Add the [`flag_penalty`](Self::flag_penalty) for `wrong_flags` flags to the timer.
+    fn punish(&mut self, wrong_flags: usize) {
+        if let Some(fine) = self.flag_penalty {
+            let count = u32::try_from(wrong_flags).unwrap_or(u32::MAX);
+            self.penalty = self.penalty.saturating_add(fine.saturating_mul(count));
         }
-        Command::Redraw
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn flag_and_advance_follows_last_move() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.handle_action(Action::MoveDown);
+        app.handle_action(Action::FlagAndAdvance);
+        app.handle_action(Action::FlagAndAdvance);
+        assert_eq!(app.board.cell_at(0, 1), CellState::Flagged);
+        assert_eq!(app.board.cell_at(0, 2), CellState::Flagged);
+        assert_eq!(app.cursor, Cursor::new(0, 3));
+        assert_eq!(app.flags_placed, 2);
+    }
+
+    #[test]
+    fn flag_and_advance_keeps_existing_flags() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::FlagAndAdvance);
+        assert_eq!(app.board.cell_at(0, 0), CellState::Flagged);
+        assert_eq!(app.cursor, Cursor::new(1, 0));
+        assert_eq!(app.flags_placed, 1);
+    }
+
+    /// Build a 3x3 game with a single mine in the bottom-right corner.
+    fn app_with_corner_mine() -> AppState {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 3, 1);
+        app.board.place_mines_at(&[(2, 2)]);
+        app
+    }
+
+    #[test]
+    fn double_tap_reveal_chords() {
+        let mut app = app_with_corner_mine();
+        app.cursor = Cursor::new(1, 1);
+        app.handle_action(Action::Reveal);
+        app.board.toggle_flag(2, 2);
+        assert_eq!(app.handle_action(Action::Reveal), Command::None);
+        assert_eq!(app.handle_action(Action::Reveal), Command::GameWon);
+    }
+
+    #[test]
+    fn double_tap_can_be_disabled() {
+        let mut app = app_with_corner_mine();
+        app.double_tap_timeout = None;
+        app.cursor = Cursor::new(1, 1);
+        app.handle_action(Action::Reveal);
+        app.board.toggle_flag(2, 2);
+        app.handle_action(Action::Reveal);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.board.cell_at(0, 0), CellState::Hidden);
+    }
+
+    #[test]
+    fn undo_steps_back_through_moves_and_losses() {
+        let mut app = app_with_corner_mine();
+        app.handle_action(Action::ToggleFlag);
+        app.cursor = Cursor::new(2, 2);
+        assert_eq!(app.handle_action(Action::Reveal), Command::GameLost);
+
+        assert!(app.end_time.is_some());
+        let stopped = app.elapsed();
+        assert_eq!(app.elapsed(), stopped);
+
+        assert_eq!(app.handle_action(Action::Undo), Command::Redraw);
+        assert_eq!(app.status, Status::Playing);
+        assert_eq!(app.end_time, None);
+        assert_eq!(app.handle_action(Action::Undo), Command::Redraw);
+        assert_eq!(app.board.cell_at(0, 0), CellState::Hidden);
+        assert_eq!(app.flags_placed, 0);
+        assert!(!app.can_undo());
+        assert_eq!(app.handle_action(Action::Undo), Command::None);
+        assert_eq!(app.undos, 2);
+        app.restart();
+        assert_eq!(app.undos, 0);
+    }
+
+    #[test]
+    fn noop_moves_are_not_recorded() {
+        let mut app = app_with_corner_mine();
+        app.cursor = Cursor::new(1, 1);
+        app.handle_action(Action::Reveal);
+        app.handle_action(Action::Chord);
+        app.handle_action(Action::Undo);
+        assert!(!app.can_undo());
+    }
+
+    #[test]
+    fn clicks_count_board_moves_only() {
+        let mut app = app_with_corner_mine();
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::MoveRight);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::Undo);
+        assert_eq!(app.clicks, 2);
+        app.restart();
+        assert_eq!(app.clicks, 0);
+    }
+
+    #[test]
+    fn hint_moves_cursor_and_counts() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(5, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        assert_eq!(app.handle_action(Action::Hint), Command::None);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.handle_action(Action::Hint), Command::Redraw);
+        assert_eq!(app.hint, Some(Deduction::Mine(2, 0)));
+        assert_eq!(app.cursor, Cursor::new(2, 0));
+        assert_eq!(app.hints_used, 1);
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(app.hint, None);
+    }
+
+    #[test]
+    fn guess_hints_are_opt_in() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(6, 1, 1);
+        app.board.place_mines_at(&[(0, 0)]);
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.handle_action(Action::Hint), Command::None);
+        app.guess_hints = true;
+        assert_eq!(app.handle_action(Action::Hint), Command::Redraw);
+        assert_eq!(app.guess.map(|g| (g.x, g.y)), Some((3, 0)));
+        assert_eq!(
+            (app.cursor, app.hint, app.hints_used),
+            (Cursor::new(3, 0), None, 1)
+        );
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.guess, None);
+    }
+
+    #[test]
+    fn profiling_times_reveals_and_solver_runs() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.profile, None);
+        app.profile = Some(Profile::default());
+        app.restart();
+        app.handle_action(Action::Reveal);
+        app.handle_action(Action::Hint);
+        let profile = app.profile.expect("still profiling after a restart");
+        assert_eq!((profile.reveal.count, profile.solver.count), (1, 1));
+        assert_eq!(profile.render.count, 0);
+    }
+
+    #[test]
+    fn pasted_seed_is_applied_as_one_edit() {
+        let mut app = AppState::new(Difficulty::Easy);
+        assert_eq!(app.paste("12345"), Command::None);
+        app.handle_action(Action::OpenSeedPrompt);
+        app.paste("123\n45");
+        // Game keys are captured by the prompt instead of acting on the board.
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(app.seed_prompt(), Some("12345"));
+        app.handle_action(Action::PromptSubmit);
+        assert_eq!(app.seed, Some(12345));
+        assert_eq!(app.board.seed(), Some(12345));
+        assert_eq!(app.board.cell_at(0, 0), CellState::Hidden);
+    }
+
+    #[test]
+    fn codes_open_the_same_board_wherever_players_click() {
+        let code: BoardCode = "MS-easy-Friday".parse().expect("valid code");
+        let mut app = AppState::new(Difficulty::Hard);
+        app.handle_action(Action::OpenSeedPrompt);
+        app.paste("MS-easy-Friday");
+        app.handle_action(Action::PromptSubmit);
+        assert_eq!(app.code, Some(code));
+        assert_eq!(app.board.width(), 9);
+        assert_ne!(app.board.cell_at(4, 4), CellState::Hidden);
+
+        let mut other = AppState::new(Difficulty::Easy);
+        other.apply_code(code);
+        assert_eq!(other.board, app.board);
+        other.handle_action(Action::Restart);
+        assert_eq!(other.board, app.board);
+        other.handle_action(Action::SetDifficulty(Difficulty::Medium));
+        assert_eq!(other.code, None);
+    }
+
+    #[test]
+    fn puzzles_survive_restarts_until_the_board_changes() {
+        let puzzle = crate::puzzle::parse("#..\n.1.\n...").expect("valid puzzle");
+        let mut app = AppState::new(Difficulty::Hard);
+        app.start_puzzle(puzzle.clone());
+        assert_eq!(app.board, puzzle);
+        app.cursor = Cursor::new(2, 2);
+        assert_eq!(app.handle_action(Action::Reveal), Command::GameWon);
+        app.handle_action(Action::Restart);
+        assert_eq!(app.board, puzzle);
+        app.handle_action(Action::SetDifficulty(Difficulty::Easy));
+        assert_eq!(app.puzzle, None);
+    }
+
+    #[test]
+    fn the_last_custom_board_is_remembered() {
+        let mut app = AppState::new(Difficulty::Easy);
+        assert_eq!(
+            app.custom_difficulty(),
+            Difficulty::custom(9, 9, 10).expect("valid")
+        );
+        let custom = Difficulty::custom(12, 7, 20).expect("valid");
+        app.handle_action(Action::SetDifficulty(custom));
+        app.handle_action(Action::SetDifficulty(Difficulty::Hard));
+        assert_eq!(app.custom_difficulty(), custom);
+        assert_eq!(
+            AppState::new(custom).custom_difficulty(),
+            custom,
+            "from the command line"
+        );
+    }
+
+    #[test]
+    fn hardcore_games_can_be_lost_on_the_first_reveal() {
+        let mut app = AppState::new(Difficulty::Custom {
+            width: 2,
+            height: 1,
+            mines: 1,
+        });
+        app.hardcore = true;
+        app.seed = Some(5);
+        app.restart();
+        let mine = usize::from(app.board.is_mine(1, 0));
+        app.cursor = Cursor::new(mine, 0);
+        assert_eq!(app.handle_action(Action::Reveal), Command::GameLost);
+
+        app.apply_code("MS-easy-Friday".parse().expect("valid code"));
+        assert_eq!(app.status, Status::Playing, "codes keep their safe start");
+    }
+
+    #[test]
+    fn numbers_fade_in_memory_mode() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.memory = Some(Duration::from_secs(2));
+        app.board = Board::new(7, 1, 2);
+        app.board.place_mines_at(&[(2, 0), (5, 0)]);
+        let wait = |app: &mut AppState| {
+            app.start_time = app
+                .start_time
+                .and_then(|start| start.checked_sub(Duration::from_secs(2)));
+        };
+        app.handle_action(Action::Reveal);
+        assert!(!app.is_faded(1, 0));
+        wait(&mut app);
+        assert!(app.is_faded(1, 0));
+        assert!(!app.is_faded(0, 0), "blank cells stay as they are");
+
+        app.cursor = Cursor::new(3, 0);
+        app.handle_action(Action::Reveal);
+        app.handle_action(Action::Undo);
+        wait(&mut app);
+        app.handle_action(Action::Reveal);
+        assert!(!app.is_faded(3, 0), "a cell revealed again shows again");
+
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::Reveal);
+        assert!(!app.is_faded(1, 0), "the whole board shows at the end");
+    }
+
+    #[test]
+    fn fog_hides_cells_away_from_the_cursor() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.fog = Some(1);
+        app.cursor = Cursor::new(1, 1);
+        assert!(app.is_visible(0, 0) && app.is_visible(2, 2));
+        assert!(!app.is_visible(3, 1));
+        app.cursor = Cursor::new(2, 1);
+        assert!(app.is_visible(3, 1));
+        app.status = Status::Lose;
+        assert!(app.is_visible(0, 8), "the board shows in full at the end");
+    }
+
+    #[test]
+    fn wrong_flags_cost_time() {
+        let mut app = app_with_corner_mine();
+        app.flag_penalty = Some(Duration::from_secs(10));
+        app.cursor = Cursor::new(1, 2);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(app.penalty, Duration::from_secs(10));
+        assert!(app.elapsed() >= app.penalty, "the timer includes it");
+        app.cursor = Cursor::new(2, 2);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(app.penalty, Duration::from_secs(10), "mine flags are free");
+
+        app.cursor = Cursor::new(0, 2);
+        app.handle_action(Action::ToggleFlag);
+        app.cursor = Cursor::new(2, 2);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.penalty, Duration::from_secs(20), "left on a safe cell");
+        app.handle_action(Action::Undo);
+        assert_eq!(app.penalty, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn unflagging_before_the_first_reveal_is_free() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.flag_penalty = Some(Duration::from_secs(10));
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::ToggleFlag);
+        assert!(!app.board.mines_placed());
+        assert_eq!(app.penalty, Duration::ZERO);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.penalty, Duration::ZERO);
+    }
+
+    #[test]
+    fn flags_beyond_the_budget_are_refused() {
+        let mut app = AppState::new(Difficulty::Custom {
+            width: 3,
+            height: 1,
+            mines: 2,
+        });
+        app.flag_budget = Some(usize::MAX);
+        assert_eq!(app.flags_left(), Some(2), "capped at the number of mines");
+        app.flag_budget = Some(1);
+        app.handle_action(Action::ToggleFlag);
+        app.cursor = Cursor::new(1, 0);
+        assert_eq!(app.handle_action(Action::ToggleFlag), Command::None);
+        assert_eq!((app.flags_placed, app.flags_left()), (1, Some(0)));
+        app.cursor = Cursor::new(0, 0);
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(app.flags_left(), Some(1), "removing a flag gives it back");
+    }
+
+    #[test]
+    fn scoring_follows_the_moves() {
+        let mut app = app_with_corner_mine();
+        app.score = Some(Score::default());
+        app.cursor = Cursor::new(2, 2);
+        app.handle_action(Action::ToggleFlag);
+        app.cursor = Cursor::new(0, 0);
+        app.handle_action(Action::Reveal);
+        let score = app.score.expect("scoring is on");
+        assert_eq!(score.streak, 2);
+        assert!(score.points > 20 + 8 * 10, "a win adds a speed bonus");
+
+        app.handle_action(Action::Undo);
+        assert_eq!(app.score.map(|s| (s.points, s.streak)), Some((20, 0)));
+        app.restart();
+        assert_eq!(app.score, Some(Score::default()));
+    }
+
+    #[test]
+    fn adaptive_games_change_mines_after_a_result() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.adaptive = Some(Adaptive::default());
+        app.restart();
+        assert_eq!(app.difficulty, Difficulty::Easy, "no game was finished");
+        app.status = Status::Lose;
+        app.restart();
+        assert_eq!(app.difficulty.parameters(), (9, 9, 9));
+        assert_eq!(app.board.mines(), 9);
+    }
+
+    #[test]
+    fn pack_puzzles_are_picked_and_marked_solved() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.packs = crate::puzzle::builtin_packs();
+        app.packs.push(app.packs[0].clone());
+        let first = app.packs[0].puzzles.len();
+        app.handle_action(Action::OpenPacks);
+        for _ in 0..first {
+            app.handle_action(Action::MoveDown);
+        }
+        assert_eq!(app.layers.last(), Some(&Layer::Packs(first)));
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.pack_puzzle, Some((1, 0)));
+        assert!(app.layers.is_empty());
+        assert_eq!(app.board, app.packs[1].puzzles[0].board);
 
+        let safe = (0..app.board.height())
+            .flat_map(|y| (0..app.board.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| !app.board.is_mine(x, y))
+            .collect::<Vec<_>>();
+        for (x, y) in safe {
+            app.cursor = Cursor::new(x, y);
+            app.handle_action(Action::Reveal);
+        }
+        assert_eq!(app.status, Status::Win);
+        assert!(app.packs[1].puzzles[0].best.is_some());
+        assert_eq!(app.packs[0].puzzles[0].best, None);
+
+        app.handle_action(Action::OpenPacks);
+        assert_eq!(app.layers.last(), Some(&Layer::Packs(first)));
+        app.handle_action(Action::SetDifficulty(Difficulty::Easy));
+        app.handle_action(Action::Back);
+        app.handle_action(Action::SetDifficulty(Difficulty::Easy));
+        assert_eq!(app.pack_puzzle, None, "a new board leaves the pack");
+    }
+
+    #[test]
+    fn practice_games_start_opened() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.practice = true;
+        app.seed = Some(3);
+        app.restart();
+        let opened = app.board.revealed();
+        assert!(opened > 1, "a region of zeroes is open");
+        assert_eq!(
+            app.board.cell_at(app.cursor.x, app.cursor.y),
+            CellState::Revealed(0)
+        );
+        assert!(app.is_practice());
+        assert!(!app.can_undo(), "the opening is not the player's move");
+    }
+
+    #[test]
+    fn mines_can_be_defused_for_a_penalty() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.defuse_penalty = Some(Duration::from_secs(10));
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(1, 0)]);
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        let Some(Layer::Defuse(defusal)) = app.layers.last() else {
+            panic!("no defusal");
+        };
+        assert_eq!(app.status, Status::Playing);
+        for arrow in defusal.sequence.clone() {
+            app.handle_action(match arrow {
+                Arrow::Up => Action::MoveUp,
+                Arrow::Down => Action::MoveDown,
+                Arrow::Left => Action::MoveLeft,
+                Arrow::Right => Action::MoveRight,
+            });
+        }
+        assert_eq!(app.board.cell_at(1, 0), CellState::Flagged);
+        assert_eq!(
+            (app.status, app.penalty),
+            (Status::Playing, Duration::from_secs(10))
+        );
+        assert!(app.layers.is_empty());
+
+        app.handle_action(Action::Undo);
+        assert_eq!(app.board.cell_at(1, 0), CellState::Hidden);
+        app.handle_action(Action::Reveal);
+        if let Some(Layer::Defuse(defusal)) = app.layers.last_mut() {
+            defusal.deadline = Instant::now();
+        }
+        app.tick();
+        assert_eq!(app.status, Status::Lose);
+        assert_eq!(app.layers, vec![Layer::GameOver]);
+    }
+
+    #[test]
+    fn campaign_runs_share_lives_and_hints_across_boards() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.campaign = Some(Campaign::default());
+        app.restart();
+        assert_eq!(app.board.mines(), 9);
+        let run = |app: &AppState| app.campaign.clone().expect("in a campaign");
+
+        app.board = Board::new(4, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.handle_action(Action::Hint), Command::Redraw);
+        assert_eq!(
+            app.handle_action(Action::Hint),
+            Command::None,
+            "out of hints"
+        );
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.status, Status::Lose);
+        assert_eq!(app.handle_action(Action::Undo), Command::None);
+        assert_eq!((run(&app).lives, run(&app).hints), (2, 0));
+
+        app.restart();
+        app.board = Board::new(2, 1, 1);
+        app.board.place_mines_at(&[(1, 0)]);
+        app.handle_action(Action::Reveal);
+        assert_eq!((app.status, run(&app).depth), (Status::Win, 1));
+
+        app.restart();
+        app.handle_action(Action::Reveal);
+        app.restart();
+        assert_eq!(run(&app).lives, 1, "giving a board up costs a life");
+    }
+
+    #[test]
+    fn losses_are_judged_forced_or_avoidable() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 3, 2);
+        app.board.place_mines_at(&[(0, 0), (2, 0)]);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.status, Status::Lose);
+        assert_eq!(app.loss, Some(Loss::ForcedGuess), "nothing to go on");
+
+        app.restart();
+        app.board = Board::new(3, 3, 2);
+        app.board.place_mines_at(&[(0, 0), (2, 0)]);
+        app.cursor = Cursor::new(1, 2);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.loss, None);
+        app.cursor = Cursor::new(0, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.loss, Some(Loss::Misplay), "B1 was provably safe");
+        app.handle_action(Action::Undo);
+        assert_eq!((app.loss, app.rating), (None, None));
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!((app.status, app.loss), (Status::Win, None));
+        assert_eq!(app.rating, Some(Rating::Hard), "B1 takes the subset rule");
+    }
+
+    #[test]
+    fn open_remaining_needs_a_flag_per_mine() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(5, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.handle_action(Action::OpenRemaining), Command::None);
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(app.handle_action(Action::OpenRemaining), Command::GameWon);
+        assert_eq!(
+            app.moves
+                .entries()
+                .last()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("00:00 opened the remaining 2 cells; board cleared")
+        );
+        app.handle_action(Action::Undo);
+        assert_eq!((app.status, app.board.revealed()), (Status::Playing, 2));
+    }
+
+    #[test]
+    fn auto_chord_opens_around_the_last_flag() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.auto_chord = true;
+        app.board = Board::new(4, 1, 1);
+        app.board.place_mines_at(&[(0, 0)]);
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        app.cursor = Cursor::new(0, 0);
+        assert_eq!(app.handle_action(Action::ToggleFlag), Command::GameWon);
+        assert_eq!(app.auto_moves, 1);
+        assert_eq!(
+            app.moves
+                .entries()
+                .last()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("00:00 chorded B1, opened 2 cells; board cleared")
+        );
+        app.handle_action(Action::Undo);
+        assert_eq!(
+            (app.status, app.board.cell_at(0, 0)),
+            (Status::Playing, CellState::Hidden),
+            "the flag and its chord are one move"
+        );
+    }
+
+    #[test]
+    fn auto_flag_marks_certain_mines() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.auto_flag = true;
+        app.board = Board::new(4, 1, 1);
+        app.board.place_mines_at(&[(1, 0)]);
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.auto_moves, 0, "the mine could be either side of C1");
+        app.cursor = Cursor::new(0, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.board.cell_at(1, 0), CellState::Flagged);
+        assert_eq!((app.flags_placed, app.auto_moves), (1, 1));
+
+        app.handle_action(Action::Undo);
+        app.auto_chord = true;
+        assert_eq!(app.handle_action(Action::Reveal), Command::GameWon);
+        assert_eq!(
+            app.auto_moves, 3,
+            "undo keeps the count; a flag, then its chord"
+        );
+    }
+
+    #[test]
+    fn peeking_counts_the_cursor_neighbors() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 2, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::ToggleFlag);
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.handle_action(Action::Peek), Command::Redraw);
+        let (cell, neighbors) = app.peek.expect("peeked");
+        assert_eq!(cell, app.cursor);
+        assert_eq!(
+            app.moves
+                .entries()
+                .last()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("00:00 peeked at B1: 4 hidden, 1 flagged, 0 revealed")
+        );
+        assert_eq!(neighbors.hidden, 4);
+
+        app.cursor = Cursor::new(0, 1);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.peek, None, "a move makes the count stale");
+    }
+
+    #[test]
+    fn cylinder_games_wrap_but_codes_stay_flat() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.topology = Topology::Cylinder;
+        app.restart();
+        assert_eq!(app.board.topology(), Topology::Cylinder);
+        app.apply_code("MS-easy-Friday".parse().expect("valid code"));
+        assert_eq!(app.board.topology(), Topology::Flat);
+    }
+
+    #[test]
+    fn layouts_reach_new_boards_but_not_codes() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.layout = Layout::Edges;
+        app.restart();
+        assert_eq!(app.board.layout(), Layout::Edges);
+        app.apply_code("MS-easy-Friday".parse().expect("valid code"));
+        assert_eq!(app.board.layout(), Layout::Uniform);
+    }
+
+    #[test]
+    fn shaped_games_keep_their_shape_until_the_difficulty_changes() {
+        let mut app = AppState::new(Difficulty::Easy);
+        let mask = crate::mask::parse(b"###\n#.#\n###\n").expect("valid stencil");
+        assert!(app.start_shape(mask.clone(), 8).is_err());
+        app.start_shape(mask, 1).expect("fits");
+        app.cursor = Cursor::new(1, 1);
+        app.handle_action(Action::Reveal);
+        assert!(!app.first_click_done, "the hole cannot be played");
+        app.handle_action(Action::Restart);
+        assert_eq!(app.board.cells(), 8);
+        app.handle_action(Action::SetDifficulty(Difficulty::Easy));
+        assert_eq!((app.mask, app.board.cells()), (None, 81));
+    }
+
+    #[test]
+    fn parse_seed_accepts_numbers_and_text() {
+        assert_eq!(parse_seed(" 42 "), Some(42));
+        assert_eq!(parse_seed(""), None);
+        assert_eq!(parse_seed("hello"), parse_seed("hello"));
+        assert_ne!(parse_seed("hello"), parse_seed("world"));
+    }
+
+    #[test]
+    fn escape_closes_layers_before_quitting() {
+        let mut app = app_with_corner_mine();
+        app.cursor = Cursor::new(2, 2);
+        app.handle_action(Action::Reveal);
+        assert!(app.game_over_shown());
+
+        app.handle_action(Action::OpenSeedPrompt);
+        app.handle_action(Action::Back);
+        assert_eq!(app.seed_prompt(), None);
+        assert!(app.game_over_shown());
+        app.handle_action(Action::Back);
+        assert!(!app.game_over_shown());
+        assert_eq!(app.status, Status::Lose);
+
+        app.handle_action(Action::Back);
+        assert_eq!(app.layers, vec![Layer::ConfirmQuit]);
+        assert_eq!(app.handle_action(Action::Restart), Command::None);
+        app.handle_action(Action::Back);
+        assert!(app.layers.is_empty());
+        app.handle_action(Action::Back);
+        assert_eq!(app.handle_action(Action::ConfirmQuit), Command::Quit);
+    }
+
+    fn any_action() -> impl Strategy<Value = Action> {
+        prop_oneof![
+            Just(Action::MoveLeft),
+            Just(Action::MoveRight),
+            Just(Action::MoveUp),
+            Just(Action::MoveDown),
+            Just(Action::MoveDownRight),
+            Just(Action::Reveal),
+            Just(Action::ToggleFlag),
+            Just(Action::FlagAndAdvance),
+            Just(Action::Chord),
+            Just(Action::Hint),
+            Just(Action::Undo),
+            Just(Action::Restart),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn random_actions_keep_invariants(
+            (width, height, mines) in (2..8usize, 2..8usize)
+                .prop_flat_map(|(w, h)| (Just(w), Just(h), 1..w * h)),
+            seed: u64,
+            actions in prop::collection::vec(any_action(), 0..120),
+        ) {
+            let difficulty = Difficulty::custom(width, height, mines).expect("valid size");
+            let mut app = AppState::new(difficulty);
+            app.seed = Some(seed);
+            app.restart();
+            for action in actions {
+                app.handle_action(action);
+                prop_assert!(app.check_invariants().is_ok(), "{:?}", app.check_invariants());
+            }
+        }
+    }
+}
@@ -0,0 +1,599 @@
+use minesweeper::app::{AppState, Status};
+use minesweeper::board::{Board, CellState, Layout, Neighbors, Topology};
+use minesweeper::code::BoardCode;
+use minesweeper::difficulty::Difficulty;
+use minesweeper::error::{Error, Result};
+use minesweeper::mask::Mask;
+use minesweeper::movelog::{self, Move};
+use minesweeper::render::status_name;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use web_time::Instant;
+
+/// The default autosave file: `autosave.json` in the user data directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("minesweeper").join("autosave.json"))
+}
+
+/// A game in progress as written to the autosave file. Each row has a character per cell:
+/// a space for a cell cut out of a shaped board, `.` hidden, `*` a hidden mine, `F` a flag,
+/// `X` a flag on a mine and a digit for a revealed number.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Snapshot {
+    difficulty: String,
+    seed: Option<u64>,
+    #[serde(default)]
+    cylinder: bool,
+    #[serde(default)]
+    layout: Option<String>,
+    /// Seconds on the timer.
+    elapsed: f64,
+    rows: Vec<String>,
+    /// Whether the game was dealt in hardcore or practice mode. A recovered game carries
+    /// its modes on, as it does its difficulty.
+    #[serde(default)]
+    hardcore: bool,
+    #[serde(default)]
+    practice: bool,
+    /// The board code the game was started from.
+    #[serde(default)]
+    code: Option<String>,
+    /// The hand-made board the game was started from, as first loaded, in the rows' format,
+    /// and where it is in the puzzle packs if it is one of theirs.
+    #[serde(default)]
+    puzzle: Option<Vec<String>>,
+    #[serde(default)]
+    pack_puzzle: Option<(usize, usize)>,
+    #[serde(default)]
+    hints: usize,
+    #[serde(default)]
+    auto_moves: usize,
+    #[serde(default)]
+    clicks: usize,
+    #[serde(default)]
+    undos: usize,
+    #[serde(default)]
+    moves: Vec<LoggedMove>,
+}
+
+/// A move of the game's [`MoveLog`](movelog::MoveLog) as written to the autosave file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LoggedMove {
+    /// Seconds on the timer when the move was made.
+    at: f64,
+    x: usize,
+    y: usize,
+    #[serde(flatten)]
+    action: LoggedAction,
+    /// `"won"` or `"lost"` for a move that ended the game and was then undone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ended: Option<String>,
+}
+
+/// What a [`LoggedMove`] did, as [`Move`] has it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "move", rename_all = "kebab-case")]
+enum LoggedAction {
+    Revealed {
+        number: Option<u8>,
+        opened: usize,
+    },
+    Chorded {
+        opened: usize,
+        mine: bool,
+    },
+    Flagged,
+    Unflagged,
+    Undone,
+    OpenedRest {
+        opened: usize,
+        mine: bool,
+    },
+    Defused,
+    Peeked {
+        hidden: usize,
+        flagged: usize,
+        revealed: usize,
+    },
+}
+
+impl LoggedMove {
+    fn of(entry: &movelog::Entry) -> Self {
+        let action = match entry.action {
+            Move::Revealed { number, opened } => LoggedAction::Revealed { number, opened },
+            Move::Chorded { opened, mine } => LoggedAction::Chorded { opened, mine },
+            Move::Flagged => LoggedAction::Flagged,
+            Move::Unflagged => LoggedAction::Unflagged,
+            Move::Undone => LoggedAction::Undone,
+            Move::OpenedRest { opened, mine } => LoggedAction::OpenedRest { opened, mine },
+            Move::Defused => LoggedAction::Defused,
+            Move::Peeked(Neighbors {
+                hidden,
+                flagged,
+                revealed,
+            }) => LoggedAction::Peeked {
+                hidden,
+                flagged,
+                revealed,
+            },
+        };
+        Self {
+            at: entry.at.as_secs_f64(),
+            x: entry.x,
+            y: entry.y,
+            action,
+            ended: entry.ended.map(|status| status_name(status).to_string()),
+        }
+    }
+
+    /// The move as the log keeps it, or `None` if it was not written by [`of`](Self::of).
+    fn entry(&self) -> Option<movelog::Entry> {
+        let action = match self.action {
+            LoggedAction::Revealed { number, opened } => Move::Revealed { number, opened },
+            LoggedAction::Chorded { opened, mine } => Move::Chorded { opened, mine },
+            LoggedAction::Flagged => Move::Flagged,
+            LoggedAction::Unflagged => Move::Unflagged,
+            LoggedAction::Undone => Move::Undone,
+            LoggedAction::OpenedRest { opened, mine } => Move::OpenedRest { opened, mine },
+            LoggedAction::Defused => Move::Defused,
+            LoggedAction::Peeked {
+                hidden,
+                flagged,
+                revealed,
+            } => Move::Peeked(Neighbors {
+                hidden,
+                flagged,
+                revealed,
+            }),
+        };
+        let ended = match self.ended.as_deref() {
+            None => None,
+            Some("won") => Some(Status::Win),
+            Some("lost") => Some(Status::Lose),
+            Some(_) => return None,
+        };
+        Some(movelog::Entry {
+            at: Duration::try_from_secs_f64(self.at).ok()?,
+            x: self.x,
+            y: self.y,
+            action,
+            ended,
+        })
+    }
+}
+
+/// The rows of `board` in the autosave format.
+fn rows(board: &Board) -> Vec<String> {
+    (0..board.height())
+        .map(|y| {
+            (0..board.width())
+                .map(|x| match (board.in_play(x, y), board.is_mine(x, y)) {
+                    (false, _) => ' ',
+                    (true, mine) => match (board.cell_at(x, y), mine) {
+                        (CellState::Flagged, true) => 'X',
+                        (CellState::Flagged, false) => 'F',
+                        (_, true) => '*',
+                        (CellState::Revealed(n), false) => char::from(b'0' + n),
+                        (CellState::Hidden, false) => '.',
+                    },
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The board `rows` describe, with its mines, numbers and flags back in place. `setup` gives
+/// it its seed, topology and layout before the mines go in.
+fn read_board(rows: &[String], setup: impl FnOnce(Board) -> Board) -> Result<Board> {
+    let broken = || Error::Parse("the autosave does not hold a valid board".to_string());
+    let width = rows.first().map_or(0, |row| row.chars().count());
+    let grid: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+    if grid.iter().any(|row| row.len() != width) {
+        return Err(broken());
+    }
+    let cells = || {
+        grid.iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &c)| (x, y, c)))
+    };
+    let mines: Vec<(usize, usize)> = cells()
+        .filter(|&(_, _, c)| matches!(c, '*' | 'X'))
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    let playfield: Vec<bool> = cells().map(|(_, _, c)| c != ' ').collect();
+    let board = if playfield.iter().all(|&cell| cell) {
+        Board::try_new(width, grid.len(), mines.len())?
+    } else {
+        Board::try_shaped(width, grid.len(), mines.len(), &playfield)?
+    };
+    let mut board = setup(board);
+    board.place_mines_at(&mines);
+    for (x, y, c) in cells() {
+        match c {
+            '0'..='8' if board.cell_at(x, y) == CellState::Hidden => {
+                board.reveal(x, y);
+            }
+            'F' | 'X' => board.toggle_flag(x, y),
+            ' ' | '.' | '*' | '0'..='8' => {}
+            _ => return Err(broken()),
+        }
+    }
+    let numbers_match = cells().all(|(x, y, c)| match c.to_digit(10) {
+        Some(n) => {
+            matches!(board.cell_at(x, y), CellState::Revealed(shown) if u32::from(shown) == n)
+        }
+        None => !matches!(board.cell_at(x, y), CellState::Revealed(_)),
+    });
+    if !numbers_match {
+        return Err(broken());
+    }
+    Ok(board)
+}
+
+impl Snapshot {
+    fn of(app: &AppState) -> Self {
+        let board = &app.board;
+        Self {
+            difficulty: app.difficulty.name().to_string(),
+            seed: board.seed(),
+            cylinder: board.topology() == Topology::Cylinder,
+            layout: (board.layout() != Layout::Uniform).then(|| board.layout().name().to_string()),
+            elapsed: app.elapsed().as_secs_f64(),
+            rows: rows(board),
+            hardcore: app.hardcore,
+            practice: app.practice,
+            code: app.code.map(|code| code.to_string()),
+            puzzle: app.puzzle.as_ref().map(rows),
+            pack_puzzle: app.pack_puzzle,
+            hints: app.hints_used,
+            auto_moves: app.auto_moves,
+            clicks: app.clicks,
+            undos: app.undos,
+            moves: app.moves.entries().iter().map(LoggedMove::of).collect(),
+        }
+    }
+
+    /// The board as it stood, with its mines, numbers and flags back in place.
+    fn board(&self) -> Result<Board> {
+        read_board(&self.rows, |mut board| {
+            if let Some(seed) = self.seed {
+                board = board.with_seed(seed);
+            }
+            let layout = self.layout.as_deref().and_then(|name| name.parse().ok());
+            board
+                .with_topology(if self.cylinder {
+                    Topology::Cylinder
+                } else {
+                    Topology::Flat
+                })
+                .with_layout(layout.unwrap_or_default())
+        })
+    }
+
+    /// Put the game back into `app`: timer, counters, move log and where the board came from.
+    fn restore(&self, app: &mut AppState) -> Result<()> {
+        let board = self.board()?;
+        let puzzle = match &self.puzzle {
+            Some(rows) => Some(read_board(rows, |board| board)?),
+            None => None,
+        }
+        .filter(|puzzle| {
+            self.seed.is_none()
+                && (puzzle.width(), puzzle.height(), puzzle.mines())
+                    == (board.width(), board.height(), board.mines())
+        });
+        let moves = self
+            .moves
+            .iter()
+            .map(LoggedMove::entry)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Error::Parse("the autosave holds a broken move log".to_string()))?;
+        let (width, height, mines) = (board.width(), board.height(), board.mines());
+        app.difficulty = match self.difficulty.parse::<Difficulty>() {
+            Ok(preset) if preset.parameters() == (width, height, mines) => preset,
+            _ => Difficulty::Custom {
+                width,
+                height,
+                mines,
+            },
+        };
+        app.seed = self.seed;
+        // Off for the restart below, so it opens nothing on the board about to be replaced.
+        app.hardcore = false;
+        app.practice = false;
+        app.code = None;
+        app.puzzle = None;
+        app.mask = (board.cells() < width * height).then(|| Mask {
+            width,
+            height,
+            playfield: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| board.in_play(x, y))
+                .collect(),
+        });
+        app.restart();
+        // Set after the restart, which would deal the code's or puzzle's board afresh and
+        // clear the counters and the log.
+        app.hardcore = self.hardcore;
+        app.practice = self.practice;
+        app.code = self
+            .code
+            .as_deref()
+            .and_then(|code| code.parse::<BoardCode>().ok())
+            .filter(|code| {
+                code.difficulty.parameters() == (width, height, mines)
+                    && Some(code.seed) == self.seed
+            });
+        app.pack_puzzle = self.pack_puzzle.filter(|&(pack, index)| {
+            app.packs
+                .get(pack)
+                .and_then(|pack| pack.puzzles.get(index))
+                .is_some_and(|known| Some(&known.board) == puzzle.as_ref())
+        });
+        app.puzzle = puzzle;
+        app.hints_used = self.hints;
+        app.auto_moves = self.auto_moves;
+        app.clicks = self.clicks;
+        app.undos = self.undos;
+        for entry in moves {
+            app.moves.push(entry);
+        }
+        let elapsed = Duration::try_from_secs_f64(self.elapsed).unwrap_or_default();
+        app.flags_placed = self
+            .rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .filter(|&c| matches!(c, 'F' | 'X'))
+            .count();
+        app.board = board;
+        app.first_click_done = true;
+        app.start_time = Some(
+            Instant::now()
+                .checked_sub(elapsed)
+                .unwrap_or_else(Instant::now),
+        );
+        app.check_invariants()
+    }
+}
+
+/// Keeps the game in progress in a file, rewritten every few seconds, so a crash, a dropped
+/// SSH link or a killed terminal costs at most that much play: the next launch picks the game
+/// up again with [`recover`](Self::recover). The file goes once the game is over or a new
+/// one begins.
+pub struct Autosave {
+    path: PathBuf,
+    /// Time between snapshots.
+    every: Duration,
+    /// Whether the file holds the game being played.
+    saved: bool,
+    /// Whether the game changed since the file was last written.
+    changed: bool,
+}
+
+impl Autosave {
+    /// Save to `path` every `every` while a game is played.
+    pub const fn new(path: PathBuf, every: Duration) -> Self {
+        Self {
+            path,
+            every,
+            saved: false,
+            changed: false,
+        }
+    }
+
+    /// Time between snapshots, for the timer that calls [`tick`](Self::tick).
+    pub const fn every(&self) -> Duration {
+        self.every
+    }
+
+    /// Put the unfinished game kept at `path`, if there is one, into `app`. Returns whether
+    /// there was; a file that cannot be read back is removed.
+    pub fn recover(&mut self, app: &mut AppState) -> bool {
+        let Ok(text) = fs::read_to_string(&self.path) else {
+            return false;
+        };
+        let snapshot: Option<Snapshot> = serde_json::from_str(&text).ok();
+        if snapshot.is_some_and(|snapshot| snapshot.restore(app).is_ok()) {
+            self.saved = true;
+            true
+        } else {
+            let _ = fs::remove_file(&self.path);
+            app.restart();
+            false
+        }
+    }
+
+    /// Note a change to `app` for the next [`tick`](Self::tick), or remove the file once
+    /// there is no game in progress to keep. Call after every change to `app`.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be removed.
+    pub fn observe(&mut self, app: &AppState) -> io::Result<()> {
+        if !in_progress(app) {
+            return self.clear();
+        }
+        self.changed = true;
+        Ok(())
+    }
+
+    /// Write the game if it changed since the last snapshot. Call every
+    /// [`every`](Self::every).
+    ///
+    /// # Errors
+    /// Fails if the file cannot be written.
+    pub fn tick(&mut self, app: &AppState) -> io::Result<()> {
+        if self.changed && in_progress(app) {
+            self.write(app)?;
+        }
+        Ok(())
+    }
+
+    /// Write the game now if it is in progress, as when quitting, so none of it is lost.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be written or removed.
+    pub fn flush(&mut self, app: &AppState) -> io::Result<()> {
+        if in_progress(app) {
+            self.write(app)
+        } else {
+            self.clear()
+        }
+    }
+
+    fn write(&mut self, app: &AppState) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Written aside and renamed over the old file, so a crash mid-write keeps the last one.
+        let mut partial = self.path.clone().into_os_string();
+        partial.push(".tmp");
+        fs::write(&partial, serde_json::to_string(&Snapshot::of(app))?)?;
+        fs::rename(&partial, &self.path)?;
+        self.saved = true;
+        self.changed = false;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.changed = false;
+        if std::mem::take(&mut self.saved) {
+            match fs::remove_file(&self.path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `app` is a game worth keeping: started, not over, and not part of a campaign run
+/// or hot-seat match, whose state lives outside the board.
+fn in_progress(app: &AppState) -> bool {
+    app.status == Status::Playing
+        && app.board.revealed() > 0
+        && app.campaign.is_none()
+        && app.hotseat.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::{Action, Cursor};
+
+    #[test]
+    fn an_unfinished_game_survives_a_restart() {
+        let path =
+            std::env::temp_dir().join(format!("minesweeper-autosave-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(4, 3, 2).with_seed(11);
+        app.board.place_mines_at(&[(3, 0), (3, 2)]);
+        let mut autosave = Autosave::new(path.clone(), Duration::from_secs(30));
+        autosave.observe(&app).expect("nothing to write");
+        assert!(!path.exists(), "not started");
+
+        app.cursor = Cursor::new(0, 0);
+        app.handle_action(Action::Reveal);
+        app.cursor = Cursor::new(3, 0);
+        app.handle_action(Action::ToggleFlag);
+        autosave.observe(&app).expect("noted");
+        assert!(!path.exists(), "written on the next tick");
+        autosave.tick(&app).expect("written");
+        let mut resumed = AppState::new(Difficulty::Hard);
+        assert!(Autosave::new(path.clone(), Duration::ZERO).recover(&mut resumed));
+        assert_eq!(resumed.board.width(), 4);
+        assert_eq!(resumed.board.seed(), Some(11));
+        assert_eq!(resumed.board.cell_at(3, 0), CellState::Flagged);
+        assert_eq!(resumed.board.cell_at(2, 1), CellState::Revealed(2));
+        assert_eq!(resumed.board.revealed(), app.board.revealed());
+        assert_eq!(resumed.flags_placed, 1);
+        assert!(resumed.first_click_done && resumed.status == Status::Playing);
+
+        app.cursor = Cursor::new(3, 2);
+        app.handle_action(Action::Reveal);
+        autosave.observe(&app).expect("removed");
+        assert!(!path.exists(), "the game is over");
+        assert!(!Autosave::new(path, Duration::ZERO).recover(&mut resumed));
+    }
+
+    /// `app` as the next launch gets it back from its autosave.
+    fn recovered(app: &AppState) -> AppState {
+        let text = serde_json::to_string(&Snapshot::of(app)).expect("written");
+        let snapshot: Snapshot = serde_json::from_str(&text).expect("read back");
+        let mut resumed = AppState::new(Difficulty::Hard);
+        snapshot.restore(&mut resumed).expect("restored");
+        resumed
+    }
+
+    #[test]
+    fn counters_moves_and_board_codes_survive_a_restart() {
+        let mut app = AppState::new(Difficulty::Easy);
+        let code: BoardCode = "MS-easy-a".parse().expect("valid code");
+        app.apply_code(code);
+        let hidden = (0..app.board.width() * app.board.height())
+            .map(|i| (i % app.board.width(), i / app.board.width()))
+            .find(|&(x, y)| app.board.cell_at(x, y) == CellState::Hidden)
+            .expect("a hidden cell");
+        app.cursor = Cursor::new(hidden.0, hidden.1);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::Undo);
+        (app.hints_used, app.auto_moves) = (1, 2);
+        let resumed = recovered(&app);
+        assert_eq!(resumed.code, Some(code));
+        assert_eq!(resumed.hints_used, 1);
+        assert_eq!(
+            (resumed.auto_moves, resumed.clicks, resumed.undos),
+            (2, app.clicks, 1)
+        );
+        assert_eq!(resumed.moves, app.moves);
+        assert!(!resumed.is_practice());
+    }
+
+    #[test]
+    fn hand_made_boards_and_modes_survive_a_restart() {
+        let puzzle = Board::new(3, 1, 1);
+        let mut puzzle_app = AppState::new(Difficulty::Easy);
+        puzzle_app.start_puzzle({
+            let mut board = puzzle.clone();
+            board.place_mines_at(&[(2, 0)]);
+            board
+        });
+        puzzle_app.handle_action(Action::ToggleFlag);
+        puzzle_app.cursor = Cursor::new(1, 0);
+        puzzle_app.handle_action(Action::Reveal);
+        let resumed = recovered(&puzzle_app);
+        assert_eq!(resumed.puzzle, puzzle_app.puzzle);
+        assert_eq!(resumed.board.cell_at(0, 0), CellState::Flagged);
+
+        let mut practice = AppState::new(Difficulty::Easy);
+        practice.practice = true;
+        practice.hardcore = true;
+        practice.restart();
+        let resumed = recovered(&practice);
+        assert!(resumed.is_practice() && resumed.is_hardcore());
+        assert_eq!(resumed.moves, practice.moves, "the opening is logged once");
+    }
+
+    #[test]
+    fn boards_that_do_not_add_up_are_refused() {
+        let snapshot = Snapshot {
+            difficulty: "custom".to_string(),
+            seed: None,
+            cylinder: false,
+            layout: None,
+            elapsed: 3.0,
+            rows: vec!["1*".to_string(), "3.".to_string()],
+            ..Snapshot::default()
+        };
+        assert!(snapshot.board().is_err());
+        let fixed = Snapshot {
+            rows: vec!["1*".to_string(), "11".to_string()],
+            ..snapshot
+        };
+        let board = fixed.board().expect("consistent");
+        assert_eq!((board.mines(), board.revealed()), (1, 3));
+    }
+}
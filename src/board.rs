@@ -1,98 +1,466 @@
-use rand::rng;
+use crate::error::{Error, Result};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng, rng};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 
+/// Most cells a board may have, so a size from a board code, a network peer or a file cannot
+/// ask for more memory than a game could use.
+pub const MAX_CELLS: usize = 1_000_000;
+
+/// What the player can see of a cell.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CellState {
+    /// Not yet revealed.
     Hidden,
+    /// Revealed, showing the number of adjacent mines (a revealed mine shows as 0).
     Revealed(u8),
+    /// Marked by the player as a suspected mine.
     Flagged,
 }
 
-#[derive(Debug, Clone)]
+/// How the edges of a board meet, which decides what counts as a neighbor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// A plain rectangle: cells on an edge have fewer neighbors.
+    #[default]
+    Flat,
+    /// The left and right edges are joined, so the first and last columns are neighbors;
+    /// the top and bottom rows are still edges.
+    Cylinder,
+}
+
+/// How mines are spread over the board when they are placed at random.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Layout {
+    /// Every cell is as likely as any other.
+    #[default]
+    Uniform,
+    /// Mines crowd toward the middle, leaving the rim open.
+    Center,
+    /// Mines crowd toward the rim, leaving the middle open.
+    Edges,
+    /// Sparse on the left, dense on the right.
+    Gradient,
+}
+
+impl Layout {
+    /// How much likelier a mine is where a layout is densest than where it is sparsest.
+    const BIAS: f64 = 4.0;
+
+    /// `"uniform"`, `"center"`, `"edges"` or `"gradient"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Uniform => "uniform",
+            Self::Center => "center",
+            Self::Edges => "edges",
+            Self::Gradient => "gradient",
+        }
+    }
+
+    /// How likely a mine is at (x, y) of a `width` by `height` board, relative to the
+    /// sparsest cell.
+    fn weight(self, x: usize, y: usize, width: usize, height: usize) -> f64 {
+        // From 0 at the left or top edge to 1 at the right or bottom one.
+        #[allow(clippy::cast_precision_loss)] // board sides are far below 2^52
+        let across = |n: usize, of: usize| (n as f64 + 0.5) / of as f64;
+        let (sx, sy) = (across(x, width), across(y, height));
+        // From 0 in the middle to 1 at the rim.
+        let rim = (2.0 * sx - 1.0).abs().max((2.0 * sy - 1.0).abs());
+        let density = match self {
+            Self::Uniform => 0.0,
+            Self::Center => 1.0 - rim,
+            Self::Edges => rim,
+            Self::Gradient => sx,
+        };
+        1.0 + (Self::BIAS - 1.0) * density
+    }
+}
+
+impl FromStr for Layout {
+    type Err = Error;
+
+    /// Parse a layout name, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "uniform" => Ok(Self::Uniform),
+            "center" | "centre" => Ok(Self::Center),
+            "edges" => Ok(Self::Edges),
+            "gradient" => Ok(Self::Gradient),
+            _ => Err(Error::Parse(format!(
+                "unknown layout '{s}' (expected uniform, center, edges or gradient)"
+            ))),
+        }
+    }
+}
+
+/// How the neighbors of a cell stand, as [`Board::neighbor_summary`] counts them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Neighbors {
+    /// Neighbors not yet revealed or flagged.
+    pub hidden: usize,
+    /// Flagged neighbors.
+    pub flagged: usize,
+    /// Revealed neighbors.
+    pub revealed: usize,
+}
+
+impl Display for Neighbors {
+    /// Like `3 hidden, 1 flagged, 4 revealed`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hidden, {} flagged, {} revealed",
+            self.hidden, self.flagged, self.revealed
+        )
+    }
+}
+
+/// A minefield with per-cell visibility. Mines are placed lazily on the first reveal so
+/// the first click is always safe.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Board {
     width: usize,
     height: usize,
     mines: usize,
     mines_placed: bool,
+    /// Seed for mine placement when no generator is passed in; `None` uses the thread RNG.
+    seed: Option<u64>,
+    topology: Topology,
+    layout: Layout,
     minefield: Vec<bool>,
+    /// Mines around each cell, kept in step with `minefield` whenever mines are placed.
+    counts: Vec<u8>,
     state: Vec<CellState>,
+    /// Cells cut out of a shaped board (see [`try_shaped`](Self::try_shaped)): never mined,
+    /// revealed, flagged or counted as anyone's neighbor.
+    void: Vec<bool>,
 }
 
 impl Board {
     /// Create an empty board with all cells hidden and no mines placed yet.
+    ///
+    /// # Panics
+    /// Panics where [`try_new`](Self::try_new) would fail; use that for sizes that come from
+    /// players or files.
     pub fn new(width: usize, height: usize, mines: usize) -> Self {
-        assert!(width > 0 && height > 0, "Board dimensions must be > 0");
-        assert!(mines < width * height, "Mines must be less than cell count");
+        Self::try_new(width, height, mines).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Create an empty board with all cells hidden and no mines placed yet, if it can be
+    /// played (see [`check_size`](Self::check_size)).
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidBoard`] describing the problem.
+    pub fn try_new(width: usize, height: usize, mines: usize) -> Result<Self> {
+        Self::check_size(width, height, mines)?;
         let len = width * height;
-        Self {
+        Ok(Self {
             width,
             height,
             mines,
             mines_placed: false,
+            seed: None,
+            topology: Topology::Flat,
+            layout: Layout::Uniform,
             minefield: vec![false; len],
+            counts: vec![0; len],
             state: vec![CellState::Hidden; len],
+            void: vec![false; len],
+        })
+    }
+
+    /// Create an empty board in the shape of `playfield`, row by row: `true` for a cell of
+    /// the board, `false` for a hole or the space around the shape.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidBoard`] if `playfield` does not have `width * height` cells or
+    /// the mines leave none of its cells safe.
+    pub fn try_shaped(
+        width: usize,
+        height: usize,
+        mines: usize,
+        playfield: &[bool],
+    ) -> Result<Self> {
+        let mut board = Self::try_new(width, height, 0)?;
+        if playfield.len() != board.void.len() {
+            return Err(Error::InvalidBoard(format!(
+                "a {width}x{height} shape needs {} cells, got {}",
+                board.void.len(),
+                playfield.len()
+            )));
         }
+        let cells = playfield.iter().filter(|&&cell| cell).count();
+        if mines >= cells {
+            return Err(Error::InvalidBoard(format!(
+                "{mines} mines do not fit a shape of {cells} cells"
+            )));
+        }
+        board.mines = mines;
+        board.void = playfield.iter().map(|&cell| !cell).collect();
+        Ok(board)
+    }
+
+    /// Check that a board of this size can be played: both dimensions non-zero, at most
+    /// [`MAX_CELLS`] cells, and at least one safe cell.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidBoard`] describing the problem.
+    pub fn check_size(width: usize, height: usize, mines: usize) -> Result<()> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidBoard(format!(
+                "board size {width}x{height} must be at least 1x1"
+            )));
+        }
+        let Some(cells) = width
+            .checked_mul(height)
+            .filter(|&cells| cells <= MAX_CELLS)
+        else {
+            return Err(Error::InvalidBoard(format!(
+                "board size {width}x{height} is too large; boards have at most {MAX_CELLS} cells"
+            )));
+        };
+        if mines >= cells {
+            return Err(Error::InvalidBoard(format!(
+                "{mines} mines do not fit on a {width}x{height} board"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Use a fixed seed for mine placement, so the same seed and first click give the
+    /// same board.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Join the board's edges as `topology` says. Call before mines are placed, since it
+    /// changes which cells count each mine.
+    pub const fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// How the board's edges meet.
+    pub const fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Spread randomly placed mines as `layout` says. Boards with mines already placed keep
+    /// them.
+    pub const fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// How randomly placed mines are spread.
+    pub const fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// The seed mines are placed with, if any.
+    pub const fn seed(&self) -> Option<u64> {
+        self.seed
     }
 
     /// Board width in cells.
-    pub const fn width(&self) -> usize { self.width }
+    pub const fn width(&self) -> usize {
+        self.width
+    }
     /// Board height in cells.
-    pub const fn height(&self) -> usize { self.height }
+    pub const fn height(&self) -> usize {
+        self.height
+    }
     /// Total number of mines.
-    pub const fn mines(&self) -> usize { self.mines }
+    pub const fn mines(&self) -> usize {
+        self.mines
+    }
+    /// Whether the mines have been placed, which happens on the first reveal.
+    pub const fn mines_placed(&self) -> bool {
+        self.mines_placed
+    }
+
+    /// Cells that can be played: all of them, less any cut out of a shaped board.
+    pub fn cells(&self) -> usize {
+        self.void.iter().filter(|&&void| !void).count()
+    }
+
+    /// Whether (x, y) is on the board and not cut out of its shape.
+    pub fn in_play(&self, x: usize, y: usize) -> bool {
+        self.contains(x, y)
+    }
 
     /// Return the current state of a cell at (x, y).
     pub fn cell_at(&self, x: usize, y: usize) -> CellState {
         self.state[self.idx(x, y)]
     }
 
-
     #[inline]
     fn idx(&self, x: usize, y: usize) -> usize {
         y * self.width + x
     }
 
+    /// Whether signed coordinates (e.g. cursor position plus a step) lie on the board.
     pub fn in_bounds(&self, x: isize, y: isize) -> bool {
-        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+        usize::try_from(x).is_ok_and(|x| x < self.width)
+            && usize::try_from(y).is_ok_and(|y| y < self.height)
     }
 
+    /// Like `in_bounds`, but for unsigned coordinates, and leaving out cells cut out of a
+    /// shaped board.
+    #[inline]
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && !self.void[self.idx(x, y)]
+    }
+
+    /// The up-to-eight coordinates surrounding (x, y) that lie on the board, reaching across
+    /// the joined edges of its [`Topology`].
     pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        // Narrower boards would meet a column from both sides, counting it twice.
+        let wrap = self.topology == Topology::Cylinder && self.width >= 3;
         (-1isize..=1)
             .flat_map(move |dy| (-1isize..=1).map(move |dx| (dx, dy)))
             .filter(move |&(dx, dy)| !(dx == 0 && dy == 0))
-            .map(move |(dx, dy)| (x as isize + dx, y as isize + dy))
-            .filter(|&(nx, ny)| self.in_bounds(nx, ny))
-            .map(|(nx, ny)| (nx as usize, ny as usize))
+            .filter_map(move |(dx, dy)| {
+                let nx = if wrap {
+                    (x + self.width).checked_add_signed(dx)? % self.width
+                } else {
+                    x.checked_add_signed(dx)?
+                };
+                Some((nx, y.checked_add_signed(dy)?))
+            })
+            .filter(|&(nx, ny)| self.contains(nx, ny))
+    }
+
+    /// How many neighbors of (x, y) are hidden, flagged and revealed.
+    pub fn neighbor_summary(&self, x: usize, y: usize) -> Neighbors {
+        let mut summary = Neighbors {
+            hidden: 0,
+            flagged: 0,
+            revealed: 0,
+        };
+        for (nx, ny) in self.neighbors(x, y) {
+            match self.cell_at(nx, ny) {
+                CellState::Hidden => summary.hidden += 1,
+                CellState::Flagged => summary.flagged += 1,
+                CellState::Revealed(_) => summary.revealed += 1,
+            }
+        }
+        summary
     }
 
+    /// Number of mines around (x, y).
     pub fn adjacent_mine_count(&self, x: usize, y: usize) -> u8 {
+        self.counts[self.idx(x, y)]
+    }
+
+    /// Number of mines around (x, y), counted from the minefield rather than looked up.
+    #[allow(clippy::cast_possible_truncation)] // at most 8 neighbors
+    fn count_mines_around(&self, x: usize, y: usize) -> u8 {
         self.neighbors(x, y)
             .filter(|&(nx, ny)| self.minefield[self.idx(nx, ny)])
             .count() as u8
     }
 
+    /// Recount the mines around every cell, once per placement, so reveals and flood fills
+    /// only look counts up.
+    fn update_counts(&mut self) {
+        self.counts = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.count_mines_around(x, y))
+            .collect();
+    }
+
     /// Lazily place mines on the first reveal, excluding a specific coordinate.
     /// Ensures the excluded position is never mined.
     pub fn place_mines_excluding(&mut self, exclude: (usize, usize)) {
+        let mut rng = self.rng();
+        self.place_mines_excluding_with(exclude, &mut rng);
+    }
+
+    /// Place mines anywhere before the first reveal, so it can hit one (hardcore mode).
+    pub fn place_mines(&mut self) {
+        let mut rng = self.rng();
+        self.place_mines_with(&mut rng);
+    }
+
+    /// Like [`place_mines_excluding`](Self::place_mines_excluding), drawing from `rng`
+    /// instead of the board's seed, so callers that own the randomness (tests, simulations,
+    /// replays) decide exactly where the mines go.
+    pub fn place_mines_excluding_with<R: Rng + ?Sized>(
+        &mut self,
+        exclude: (usize, usize),
+        rng: &mut R,
+    ) {
+        let exclude_idx = self.idx(exclude.0, exclude.1);
+        self.place_random(Some(exclude_idx), rng);
+    }
+
+    /// Like [`place_mines`](Self::place_mines), drawing from `rng` instead of the board's
+    /// seed.
+    pub fn place_mines_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.place_random(None, rng);
+    }
+
+    /// The generator mines are placed with when none is passed in: seeded from
+    /// [`seed`](Self::seed) if there is one, else from the thread RNG.
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rng()),
+        }
+    }
+
+    fn place_random<R: Rng + ?Sized>(&mut self, exclude_idx: Option<usize>, rng: &mut R) {
         if self.mines_placed {
             return;
         }
         let total = self.width * self.height;
-        let exclude_idx = self.idx(exclude.0, exclude.1);
-        let mut candidates: Vec<usize> = (0..total).filter(|&i| i != exclude_idx).collect();
-        let mut rng = rng();
-        candidates.shuffle(&mut rng);
+        let mut candidates: Vec<usize> = (0..total)
+            .filter(|&i| Some(i) != exclude_idx && !self.void[i])
+            .collect();
+        if self.layout == Layout::Uniform {
+            candidates.shuffle(rng);
+        } else {
+            // Weighted sampling without replacement: each cell draws u^(1/weight) and the
+            // highest draws get the mines (compared as logarithms, which keep their order).
+            let mut draws: Vec<(f64, usize)> = candidates
+                .iter()
+                .map(|&i| {
+                    let weight =
+                        self.layout
+                            .weight(i % self.width, i / self.width, self.width, self.height);
+                    (rng.random::<f64>().ln() / weight, i)
+                })
+                .collect();
+            draws.sort_by(|a, b| b.0.total_cmp(&a.0));
+            candidates = draws.into_iter().map(|(_, i)| i).collect();
+        }
         for &i in candidates.iter().take(self.mines) {
             self.minefield[i] = true;
         }
         self.mines_placed = true;
+        self.update_counts();
+    }
 
-        // After placing mines, precompute numbers for any already revealed cells (none in lazy start)
+    /// Place mines at explicit coordinates instead of randomly.
+    /// The mine count becomes the number of coordinates given.
+    pub fn place_mines_at(&mut self, mines: &[(usize, usize)]) {
+        self.minefield.fill(false);
+        for &(x, y) in mines {
+            let i = self.idx(x, y);
+            self.minefield[i] = true;
+        }
+        self.mines = self.minefield.iter().filter(|&&m| m).count();
+        self.mines_placed = true;
+        self.update_counts();
     }
 
     /// Reveal a cell. Returns true if safe, false if a mine was revealed.
     pub fn reveal(&mut self, x: usize, y: usize) -> bool {
-        if !self.in_bounds(x as isize, y as isize) {
+        if !self.contains(x, y) {
             return true; // Out of bounds treated as no-op
         }
         if !self.mines_placed {
@@ -136,7 +504,7 @@ impl Board {
 
     /// Toggle flag on a cell. Hidden <-> Flagged. No-op if Revealed.
     pub fn toggle_flag(&mut self, x: usize, y: usize) {
-        if !self.in_bounds(x as isize, y as isize) {
+        if !self.contains(x, y) {
             return;
         }
         let i = self.idx(x, y);
@@ -150,7 +518,7 @@ impl Board {
     /// Chord a revealed numbered cell: if number equals adjacent flag count,
     /// reveal all unflagged hidden neighbors. Returns true if safe, false if a mine was revealed.
     pub fn chord(&mut self, x: usize, y: usize) -> bool {
-        if !self.in_bounds(x as isize, y as isize) {
+        if !self.contains(x, y) {
             return true;
         }
         let i = self.idx(x, y);
@@ -187,12 +555,34 @@ impl Board {
         safe
     }
 
+    /// Reveal every hidden cell that is not flagged, for the end of a game where every mine
+    /// has a flag. Returns true if safe, false if one of them was a mine.
+    pub fn open_remaining(&mut self) -> bool {
+        let mut safe = true;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.state[self.idx(x, y)] != CellState::Hidden {
+                    continue;
+                }
+                if self.minefield[self.idx(x, y)] {
+                    safe = false;
+                } else {
+                    self.reveal(x, y);
+                }
+            }
+        }
+        safe
+    }
+
     /// Check if all non-mine cells are revealed.
     pub fn is_win(&self) -> bool {
         for y in 0..self.height {
             for x in 0..self.width {
                 let i = self.idx(x, y);
-                if !self.minefield[i] && !matches!(self.state[i], CellState::Revealed(_)) {
+                if !self.minefield[i]
+                    && !self.void[i]
+                    && !matches!(self.state[i], CellState::Revealed(_))
+                {
                     return false;
                 }
             }
@@ -200,11 +590,191 @@ impl Board {
         true
     }
 
+    /// Number of revealed cells.
+    pub fn revealed(&self) -> usize {
+        self.state
+            .iter()
+            .filter(|state| matches!(state, CellState::Revealed(_)))
+            .count()
+    }
+
     /// Check if a coordinate contains a mine.
     pub fn is_mine(&self, x: usize, y: usize) -> bool {
         self.minefield[self.idx(x, y)]
     }
 
+    /// The board's 3BV (Bechtel's Board Benchmark Value): the fewest reveals that clear it,
+    /// counting each opening once plus every safe cell outside an opening. Meaningful once
+    /// the mines are placed.
+    pub fn bbbv(&self) -> usize {
+        self.bbbv_progress().1
+    }
+
+    /// How much of the board's 3BV has been cleared, and the 3BV itself: an opening counts
+    /// once it is open, and a cell outside the openings once it is revealed.
+    pub fn bbbv_progress(&self) -> (usize, usize) {
+        let revealed = |i: usize| matches!(self.state[i], CellState::Revealed(_));
+        let mut covered = vec![false; self.minefield.len()];
+        let (mut solved, mut total) = (0, 0);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = self.idx(x, y);
+                if covered[i]
+                    || self.minefield[i]
+                    || self.void[i]
+                    || self.adjacent_mine_count(x, y) != 0
+                {
+                    continue;
+                }
+                total += 1;
+                solved += usize::from(revealed(i));
+                self.cover_opening(x, y, &mut covered);
+            }
+        }
+        let singles = (0..self.minefield.len())
+            .filter(|&i| !covered[i] && !self.minefield[i] && !self.void[i]);
+        for i in singles {
+            total += 1;
+            solved += usize::from(revealed(i));
+        }
+        (solved, total)
+    }
+
+    /// The cell whose reveal opens the most cells: one in the largest region of zeroes,
+    /// counting the numbers around it. `None` before the mines are placed or when no safe
+    /// cell is a zero.
+    pub fn largest_opening(&self) -> Option<(usize, usize)> {
+        if !self.mines_placed {
+            return None;
+        }
+        let mut covered = vec![false; self.minefield.len()];
+        let mut best: Option<(usize, (usize, usize))> = None;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = self.idx(x, y);
+                if covered[i]
+                    || self.minefield[i]
+                    || self.void[i]
+                    || self.adjacent_mine_count(x, y) != 0
+                {
+                    continue;
+                }
+                let size = self.cover_opening(x, y, &mut covered);
+                if best.is_none_or(|(most, _)| size > most) {
+                    best = Some((size, (x, y)));
+                }
+            }
+        }
+        best.map(|(_, cell)| cell)
+    }
+
+    /// The board as it was before the first reveal, with the same mines but every cell
+    /// hidden and unflagged.
+    pub fn covered(&self) -> Self {
+        let mut board = self.clone();
+        board.state.fill(CellState::Hidden);
+        board
+    }
+
+    /// Mark in `covered` the cells a click on the zero at (`x`, `y`) opens: its whole region
+    /// of zeroes and the numbers around it. Returns how many were newly marked.
+    fn cover_opening(&self, x: usize, y: usize, covered: &mut [bool]) -> usize {
+        let mut count = 1;
+        covered[self.idx(x, y)] = true;
+        let mut stack = vec![(x, y)];
+        while let Some((cx, cy)) = stack.pop() {
+            for (nx, ny) in self.neighbors(cx, cy) {
+                let n = self.idx(nx, ny);
+                if !covered[n] {
+                    covered[n] = true;
+                    count += 1;
+                    if self.adjacent_mine_count(nx, ny) == 0 {
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Check that the board is internally consistent: cell storage matches the size, the
+    /// mine count matches the minefield (which stays empty until mines are placed), no
+    /// revealed cell is a mine and every revealed number counts its neighbors correctly.
+    ///
+    /// Cheap enough to run after every move, e.g. in `debug_assert!` from an embedding front
+    /// end.
+    ///
+    /// # Errors
+    /// Describes the first inconsistency found.
+    pub fn check_invariants(&self) -> Result<()> {
+        let broken = |message: String| Err(Error::InvalidBoard(message));
+        let cells = self.width * self.height;
+        if cells == 0
+            || self.minefield.len() != cells
+            || self.counts.len() != cells
+            || self.state.len() != cells
+            || self.void.len() != cells
+        {
+            return broken(format!(
+                "a {}x{} board stores {} mine, {} count, {} state and {} shape cells",
+                self.width,
+                self.height,
+                self.minefield.len(),
+                self.counts.len(),
+                self.state.len(),
+                self.void.len()
+            ));
+        }
+        if self.mines >= self.cells() {
+            return broken(format!(
+                "{} mines do not fit {} cells",
+                self.mines,
+                self.cells()
+            ));
+        }
+        if let Some(i) = (0..cells)
+            .find(|&i| self.void[i] && (self.minefield[i] || self.state[i] != CellState::Hidden))
+        {
+            return broken(format!(
+                "({}, {}) is cut out of the shape but is not empty",
+                i % self.width,
+                i / self.width
+            ));
+        }
+        let placed = self.minefield.iter().filter(|&&m| m).count();
+        let expected = if self.mines_placed { self.mines } else { 0 };
+        if placed != expected {
+            return broken(format!(
+                "{placed} mines are placed, expected {expected} of {}",
+                self.mines
+            ));
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = self.idx(x, y);
+                let actual = self.count_mines_around(x, y);
+                if self.counts[i] != actual {
+                    return broken(format!(
+                        "({x}, {y}) is counted {} but has {actual} mines around it",
+                        self.counts[i]
+                    ));
+                }
+                let CellState::Revealed(n) = self.state[i] else {
+                    continue;
+                };
+                if self.minefield[i] {
+                    return broken(format!("the mine at ({x}, {y}) is revealed"));
+                }
+                if n != actual {
+                    return broken(format!(
+                        "({x}, {y}) shows {n} but has {actual} mines around it"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Count the number of correctly flagged mines.
     pub fn count_correct_flags(&self) -> usize {
         let mut count = 0;
@@ -218,17 +788,41 @@ impl Board {
         }
         count
     }
-
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     fn board_with(width: usize, height: usize, mines: usize) -> Board {
         Board::new(width, height, mines)
     }
 
+    #[test]
+    fn unplayable_sizes_are_errors() {
+        assert!(Board::try_new(3, 3, 8).is_ok());
+        for (width, height, mines) in [(0, 3, 0), (3, 0, 0), (3, 3, 9), (usize::MAX, 2, 1)] {
+            assert!(
+                matches!(
+                    Board::try_new(width, height, mines),
+                    Err(Error::InvalidBoard(_))
+                ),
+                "{width}x{height} with {mines} mines"
+            );
+        }
+    }
+
+    #[test]
+    fn boards_are_capped_in_size() {
+        assert!(Board::check_size(1000, 1000, 1).is_ok());
+        assert!(matches!(
+            Board::check_size(100_000, 100_000, 1),
+            Err(Error::InvalidBoard(_))
+        ));
+        assert!(Board::check_size(MAX_CELLS + 1, 1, 0).is_err());
+    }
+
     #[test]
     fn in_bounds_works() {
         let b = board_with(3, 2, 1);
@@ -301,6 +895,7 @@ mod tests {
         b.mines_placed = true;
         let mine_idx = b.idx(2, 2);
         b.minefield[mine_idx] = true; // bottom-right is a mine
+        b.update_counts();
         // Reveal center (1,1) which should have 1 adjacent mine
         let safe = b.reveal(1, 1);
         assert!(safe);
@@ -324,6 +919,7 @@ mod tests {
         b.mines_placed = true;
         let mine_idx = b.idx(0, 0);
         b.minefield[mine_idx] = true;
+        b.update_counts();
         // Reveal (1,1) which should have 1 adjacent mine
         assert!(b.reveal(1, 1));
         assert!(matches!(b.state[b.idx(1, 1)], CellState::Revealed(1)));
@@ -340,7 +936,9 @@ mod tests {
         let mut b = board_with(3, 3, 1);
         // Place a mine at (2,2), reveal center shows 1
         b.mines_placed = true;
-        b.minefield[b.idx(2, 2)] = true;
+        let mine_idx = b.idx(2, 2);
+        b.minefield[mine_idx] = true;
+        b.update_counts();
         assert!(b.reveal(1, 1));
         assert!(matches!(b.state[b.idx(1, 1)], CellState::Revealed(1)));
         // Do NOT place any flags, chording should be a no-op
@@ -354,7 +952,26 @@ mod tests {
             .neighbors(1, 1)
             .filter(|&(nx, ny)| matches!(b.state[b.idx(nx, ny)], CellState::Hidden))
             .count();
-        assert_eq!(before_hidden, after_hidden, "chord should not reveal when flags don't match");
+        assert_eq!(
+            before_hidden, after_hidden,
+            "chord should not reveal when flags don't match"
+        );
+    }
+
+    #[test]
+    fn open_remaining_reveals_every_unflagged_cell() {
+        let mut b = Board::new(4, 1, 1);
+        b.place_mines_at(&[(3, 0)]);
+        b.toggle_flag(3, 0);
+        assert!(b.open_remaining());
+        assert!(b.is_win());
+        assert_eq!(b.cell_at(3, 0), CellState::Flagged);
+
+        let mut b = Board::new(4, 1, 1);
+        b.place_mines_at(&[(3, 0)]);
+        b.toggle_flag(0, 0);
+        assert!(!b.open_remaining(), "the wrong flag left the mine open");
+        assert_eq!(b.revealed(), 2);
     }
 
     #[test]
@@ -362,7 +979,9 @@ mod tests {
         let mut b = board_with(2, 2, 1);
         // Deterministic mine at (0,0)
         b.mines_placed = true;
-        b.minefield[b.idx(0, 0)] = true;
+        let mine_idx = b.idx(0, 0);
+        b.minefield[mine_idx] = true;
+        b.update_counts();
         // Reveal all safe cells
         assert!(b.reveal(1, 0));
         assert!(b.reveal(0, 1));
@@ -375,17 +994,206 @@ mod tests {
         // Place a single mine far from corner to create zeros near (0,0)
         let mut b = board_with(3, 3, 1);
         b.mines_placed = true;
-        b.minefield[b.idx(2, 2)] = true;
+        let mine_idx = b.idx(2, 2);
+        b.minefield[mine_idx] = true;
+        b.update_counts();
         // Revealing (0,0) should not panic and should reveal a region up to numbers at the boundary
         assert!(b.reveal(0, 0));
         // Ensure all non-mine cells except those adjacent to the mine are revealed
         for y in 0..3 {
             for x in 0..3 {
-                if (x, y) == (2, 2) { continue; }
+                if (x, y) == (2, 2) {
+                    continue;
+                }
                 assert!(matches!(b.state[b.idx(x, y)], CellState::Revealed(_)));
             }
         }
         assert!(!matches!(b.state[b.idx(2, 2)], CellState::Revealed(_)));
     }
-}
 
+    #[test]
+    fn same_seed_same_mines() {
+        let mut a = board_with(9, 9, 10).with_seed(42);
+        let mut b = board_with(9, 9, 10).with_seed(42);
+        a.reveal(4, 4);
+        b.reveal(4, 4);
+        assert_eq!(a.minefield, b.minefield);
+    }
+
+    #[test]
+    fn injected_generators_place_mines_reproducibly() {
+        let mut seeded = board_with(9, 9, 10).with_seed(42);
+        seeded.place_mines_excluding((4, 4));
+        let mut injected = board_with(9, 9, 10);
+        injected.place_mines_excluding_with((4, 4), &mut StdRng::seed_from_u64(42));
+        assert_eq!(
+            seeded.minefield, injected.minefield,
+            "the seed is just a default"
+        );
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut first = board_with(9, 9, 10);
+        first.place_mines_with(&mut rng);
+        let mut second = board_with(9, 9, 10);
+        second.place_mines_with(&mut rng);
+        assert_ne!(first.minefield, second.minefield, "the generator moves on");
+        assert_eq!(second.minefield.iter().filter(|&&m| m).count(), 10);
+    }
+
+    #[test]
+    fn bbbv_counts_openings_and_lone_numbers() {
+        let mut b = board_with(5, 1, 1);
+        b.place_mines_at(&[(2, 0)]);
+        assert_eq!(b.bbbv(), 2);
+        b.reveal(0, 0);
+        assert_eq!(b.bbbv_progress(), (1, 2));
+        let mut b = board_with(3, 3, 1);
+        b.place_mines_at(&[(1, 1)]);
+        assert_eq!(b.bbbv(), 8);
+        b.reveal(0, 0);
+        b.reveal(2, 2);
+        assert_eq!(b.bbbv_progress(), (2, 8));
+    }
+
+    #[test]
+    fn largest_opening_is_in_the_biggest_zero_region() {
+        let mut b = board_with(7, 1, 2);
+        assert_eq!(b.largest_opening(), None, "no mines placed yet");
+        b.place_mines_at(&[(1, 0), (3, 0)]);
+        assert_eq!(b.largest_opening(), Some((5, 0)));
+        b.place_mines_at(&[(2, 0), (6, 0)]);
+        assert_eq!(
+            b.largest_opening(),
+            Some((4, 0)),
+            "opens three cells, (0, 0) two"
+        );
+    }
+
+    #[test]
+    fn cylinders_wrap_columns_but_not_rows() {
+        let mut b = board_with(4, 3, 1).with_topology(Topology::Cylinder);
+        b.place_mines_at(&[(0, 1)]);
+        assert_eq!(b.adjacent_mine_count(3, 0), 1, "across the joined edge");
+        assert_eq!(b.neighbors(3, 0).count(), 5, "the top row is still an edge");
+        assert_eq!(b.neighbors(0, 1).count(), 8);
+        let narrow = board_with(2, 2, 1).with_topology(Topology::Cylinder);
+        assert_eq!(narrow.neighbors(0, 0).count(), 3, "each cell once");
+    }
+
+    #[test]
+    fn shaped_boards_leave_their_holes_alone() {
+        // A ring: the middle cell is cut out.
+        let playfield = [true, true, true, true, false, true, true, true, true];
+        assert!(Board::try_shaped(3, 3, 8, &playfield).is_err());
+        assert!(Board::try_shaped(3, 3, 1, &playfield[..8]).is_err());
+        let mut b = Board::try_shaped(3, 3, 7, &playfield).expect("valid shape");
+        assert_eq!(b.cells(), 8);
+        b.reveal(0, 0);
+        assert!(!b.is_mine(1, 1) && !b.in_play(1, 1));
+        assert_eq!(
+            b.cell_at(0, 0),
+            CellState::Revealed(2),
+            "the hole is no neighbor"
+        );
+        assert!(b.is_win());
+        b.reveal(1, 1);
+        b.toggle_flag(1, 1);
+        assert_eq!(b.cell_at(1, 1), CellState::Hidden);
+        assert!(b.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn layouts_crowd_mines_where_they_say() {
+        // Mines in the middle 10x10 of a 20x20 board, over a few seeds.
+        let middle = |layout: Layout| -> usize {
+            (0..10)
+                .map(|seed| {
+                    let mut b = board_with(20, 20, 80).with_seed(seed).with_layout(layout);
+                    b.place_mines();
+                    (5..15)
+                        .flat_map(|y| (5..15).map(move |x| (x, y)))
+                        .filter(|&(x, y)| b.is_mine(x, y))
+                        .count()
+                })
+                .sum()
+        };
+        // A quarter of the board, so 200 of the 800 mines when they are spread evenly.
+        assert!(middle(Layout::Center) > 250);
+        assert!(middle(Layout::Edges) < 160);
+        assert!((160..250).contains(&middle(Layout::Uniform)));
+        assert_eq!("Centre".parse::<Layout>().ok(), Some(Layout::Center));
+        assert!("spiral".parse::<Layout>().is_err());
+    }
+
+    #[test]
+    fn neighbor_summary_counts_each_state() {
+        let mut b = board_with(3, 3, 1);
+        b.place_mines_at(&[(2, 2)]);
+        b.toggle_flag(2, 2);
+        b.reveal(0, 0);
+        assert_eq!(
+            b.neighbor_summary(1, 1).to_string(),
+            "0 hidden, 1 flagged, 7 revealed"
+        );
+        assert_eq!(b.neighbor_summary(2, 2).revealed, 3, "a corner has three");
+    }
+
+    #[test]
+    fn check_invariants_catches_corrupted_boards() {
+        let mut b = board_with(3, 3, 1);
+        assert!(b.check_invariants().is_ok());
+        b.minefield[0] = true;
+        assert!(b.check_invariants().is_err());
+        b.place_mines_at(&[(2, 2)]);
+        b.reveal(0, 0);
+        assert!(b.check_invariants().is_ok());
+        let i = b.idx(1, 1);
+        b.state[i] = CellState::Revealed(3);
+        assert!(b.check_invariants().is_err());
+    }
+
+    #[test]
+    fn mines_placed_up_front_can_be_anywhere() {
+        let mut b = board_with(2, 1, 1).with_seed(3);
+        b.place_mines();
+        assert!(b.check_invariants().is_ok());
+        let mine = usize::from(b.is_mine(1, 0));
+        b.place_mines_excluding((mine, 0));
+        assert!(b.is_mine(mine, 0), "placed mines stay put");
+        assert!(!b.reveal(mine, 0));
+    }
+
+    #[test]
+    fn counts_are_computed_when_mines_are_placed() {
+        let mut b = board_with(3, 3, 2);
+        b.place_mines_at(&[(0, 0), (2, 0)]);
+        assert_eq!(b.adjacent_mine_count(1, 0), 2);
+        assert_eq!(b.adjacent_mine_count(1, 1), 2);
+        assert_eq!(b.adjacent_mine_count(0, 2), 0);
+        b.place_mines_at(&[(1, 1)]);
+        assert_eq!(b.adjacent_mine_count(0, 2), 1);
+        let i = b.idx(0, 2);
+        b.counts[i] = 0;
+        assert!(b.check_invariants().is_err(), "stale counts are caught");
+    }
+
+    proptest! {
+        #[test]
+        fn random_moves_keep_invariants(
+            (width, height, mines) in (1..8usize, 1..8usize)
+                .prop_flat_map(|(w, h)| (Just(w), Just(h), 0..w * h)),
+            seed: u64,
+            moves in prop::collection::vec((0..3u8, 0..8usize, 0..8usize), 0..40),
+        ) {
+            let mut b = board_with(width, height, mines).with_seed(seed);
+            for (kind, x, y) in moves {
+                match kind {
+                    0 => { b.reveal(x, y); }
+                    1 => b.toggle_flag(x, y),
+                    _ => { b.chord(x, y); }
+                }
+                prop_assert!(b.check_invariants().is_ok(), "{:?}", b.check_invariants());
+            }
+        }
+    }
+}
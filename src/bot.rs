@@ -0,0 +1,162 @@
+use crate::headless::{self, Reply};
+use anyhow::{Context, anyhow};
+use minesweeper::app::{AppState, Status};
+use minesweeper::render::{Renderer, TextRenderer, rows};
+use minesweeper::solver;
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Turns after which a bot that has not finished the game is stopped.
+pub const MAX_TURNS: usize = 100_000;
+
+/// Script operations allowed per turn, so a runaway loop in a script cannot hang the game.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// A Rhai bot script.
+///
+/// The script defines `fn turn(board)`, called once per turn with what a player can see,
+/// and returns a headless-mode command such as `"reveal 3 4"`, `"flag 0 1"` or `"chord 2 2"`.
+/// `board` is a map with:
+/// - `width`, `height`, `mines`, `flags`, `turn`
+/// - `rows`: the board as text rows (`#` hidden, `F` flagged, `.` empty, digits)
+/// - `safe`, `mines_known`: `[x, y]` cells the solver can prove safe or mined
+pub struct Bot {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+}
+
+impl Bot {
+    /// Compile the script at `path`.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read or does not compile.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let source =
+            fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        Self::new(&source).with_context(|| format!("in {}", path.display()))
+    }
+
+    /// Compile a script from source.
+    ///
+    /// # Errors
+    /// Fails if the script does not compile.
+    pub fn new(source: &str) -> anyhow::Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile(source).map_err(|e| anyhow!("{e}"))?;
+        Ok(Self {
+            engine,
+            ast,
+            scope: Scope::new(),
+        })
+    }
+
+    /// Ask the script for its next command.
+    ///
+    /// # Errors
+    /// Fails if the script errors or does not return a string.
+    pub fn next_command(&mut self, app: &AppState, turn: usize) -> anyhow::Result<String> {
+        let board = board_map(app, turn);
+        self.engine
+            .call_fn::<String>(&mut self.scope, &self.ast, "turn", (board,))
+            .map_err(|e| anyhow!("turn {turn}: {e}"))
+    }
+}
+
+/// Let `bot` play `app` to the end, writing each command and its reply, then the final
+/// board, to `output`.
+///
+/// Commands the game rejects are reported and play goes on; the bot is stopped after
+/// [`MAX_TURNS`] turns or when it returns `quit`.
+///
+/// # Errors
+/// Fails if the script errors or writing fails.
+pub fn run(app: &mut AppState, bot: &mut Bot, mut output: impl Write) -> anyhow::Result<()> {
+    // Every command names its cell, so a repeated reveal must not turn into a chord.
+    app.double_tap_timeout = None;
+    for turn in 1..=MAX_TURNS {
+        if app.status != Status::Playing {
+            break;
+        }
+        let command = bot.next_command(app, turn)?;
+        let reply = headless::execute(app, &command);
+        if reply == Ok(Reply::Quit) {
+            break;
+        }
+        writeln!(output, "{command} -> {}", headless::text(app, &reply))?;
+    }
+    TextRenderer::new(output).render(app)?;
+    Ok(())
+}
+
+/// What the script sees each turn.
+fn board_map(app: &AppState, turn: usize) -> Map {
+    let int = |n: usize| Dynamic::from(i64::try_from(n).unwrap_or(i64::MAX));
+    let cells = |cells: Vec<(usize, usize)>| -> Dynamic {
+        cells
+            .into_iter()
+            .map(|(x, y)| Dynamic::from(vec![int(x), int(y)]))
+            .collect::<Array>()
+            .into()
+    };
+    let deductions = solver::deduce(&app.board);
+    let mut map = Map::new();
+    map.insert("width".into(), int(app.board.width()));
+    map.insert("height".into(), int(app.board.height()));
+    map.insert("mines".into(), int(app.board.mines()));
+    map.insert("flags".into(), int(app.flags_placed));
+    map.insert("turn".into(), int(turn));
+    map.insert(
+        "rows".into(),
+        rows(app)
+            .into_iter()
+            .map(Dynamic::from)
+            .collect::<Array>()
+            .into(),
+    );
+    map.insert("safe".into(), cells(deductions.safe));
+    map.insert("mines_known".into(), cells(deductions.mines));
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+
+    #[test]
+    fn solver_bot_clears_an_easy_board() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 3, 1);
+        app.board.place_mines_at(&[(2, 2)]);
+        let mut bot = Bot::new(
+            r#"
+            fn turn(board) {
+                if board.safe.len() > 0 {
+                    let cell = board.safe[0];
+                    return `reveal ${cell[0]} ${cell[1]}`;
+                }
+                "reveal 0 0"
+            }
+            "#,
+        )
+        .expect("compiles");
+        let mut out = Vec::new();
+        run(&mut app, &mut bot, &mut out).expect("bot runs");
+        assert_eq!(app.status, Status::Win);
+        let out = String::from_utf8(out).expect("utf-8");
+        assert!(out.starts_with("reveal 0 0 -> won\nstatus won"));
+    }
+
+    #[test]
+    fn script_errors_are_reported() {
+        assert!(Bot::new("fn turn(board) {").is_err());
+        let mut bot = Bot::new("fn turn(board) { 42 }").expect("compiles");
+        let app = AppState::new(Difficulty::Easy);
+        assert!(bot.next_command(&app, 1).is_err());
+    }
+}
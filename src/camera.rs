@@ -0,0 +1,106 @@
+/// Viewport into the board, measured in cells.
+///
+/// The camera tracks which part of the board is visible when the board is larger than the
+/// terminal. `x`/`y` is the top-left visible cell and `cols`/`rows` the visible extent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Camera {
+    /// Leftmost visible column.
+    pub x: usize,
+    /// Topmost visible row.
+    pub y: usize,
+    /// Number of visible columns.
+    pub cols: usize,
+    /// Number of visible rows.
+    pub rows: usize,
+    board_width: usize,
+    board_height: usize,
+}
+
+impl Camera {
+    /// Create a camera showing the whole board until the first resize.
+    pub const fn new(board_width: usize, board_height: usize) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            cols: board_width,
+            rows: board_height,
+            board_width,
+            board_height,
+        }
+    }
+
+    /// Update the visible extent (e.g. after a terminal resize) and keep the offset in range.
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        self.cols = cols.clamp(1, self.board_width);
+        self.rows = rows.clamp(1, self.board_height);
+        self.clamp();
+    }
+
+    /// Shift the viewport by a number of cells, stopping at the board edges.
+    pub fn pan(&mut self, dx: isize, dy: isize) {
+        self.x = self.x.saturating_add_signed(dx);
+        self.y = self.y.saturating_add_signed(dy);
+        self.clamp();
+    }
+
+    /// Scroll the minimum amount needed for (x, y) to be visible.
+    pub fn follow(&mut self, x: usize, y: usize) {
+        if x < self.x {
+            self.x = x;
+        } else if x >= self.x + self.cols {
+            self.x = x + 1 - self.cols;
+        }
+        if y < self.y {
+            self.y = y;
+        } else if y >= self.y + self.rows {
+            self.y = y + 1 - self.rows;
+        }
+        self.clamp();
+    }
+
+    /// Whether part of the board is hidden off-screen.
+    pub const fn is_scrollable(&self) -> bool {
+        self.cols < self.board_width || self.rows < self.board_height
+    }
+
+    fn clamp(&mut self) {
+        self.x = self.x.min(self.board_width - self.cols);
+        self.y = self.y.min(self.board_height - self.rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Camera;
+
+    #[test]
+    fn resize_clamps_to_board() {
+        let mut c = Camera::new(30, 16);
+        c.resize(100, 100);
+        assert_eq!((c.cols, c.rows), (30, 16));
+        assert!(!c.is_scrollable());
+        c.resize(10, 5);
+        assert_eq!((c.cols, c.rows), (10, 5));
+        assert!(c.is_scrollable());
+    }
+
+    #[test]
+    fn pan_stops_at_edges() {
+        let mut c = Camera::new(30, 16);
+        c.resize(10, 5);
+        c.pan(-3, -3);
+        assert_eq!((c.x, c.y), (0, 0));
+        c.pan(100, 100);
+        assert_eq!((c.x, c.y), (20, 11));
+    }
+
+    #[test]
+    fn follow_scrolls_minimally() {
+        let mut c = Camera::new(30, 16);
+        c.resize(10, 5);
+        c.follow(12, 2);
+        assert_eq!((c.x, c.y), (3, 0));
+        c.follow(0, 7);
+        assert_eq!((c.x, c.y), (0, 3));
+    }
+}
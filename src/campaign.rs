@@ -0,0 +1,160 @@
+use crate::difficulty::Difficulty;
+
+/// Lives a run starts with.
+const LIVES: u32 = 3;
+/// Hints a run starts with.
+const HINTS: u32 = 1;
+/// Side of a campaign board, and of one shrunk by [`Perk::SmallerBoard`].
+const SIDE: usize = 9;
+const SMALL_SIDE: usize = 7;
+/// Mine density of the first board, the rise per board cleared and the cap, in percent.
+const DENSITY: usize = 12;
+const DENSITY_STEP: usize = 2;
+const MAX_DENSITY: usize = 30;
+/// Boards cleared between perks.
+const PERK_EVERY: usize = 3;
+
+/// A reward handed out every few boards of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perk {
+    /// One more hint for the rest of the run.
+    ExtraHint,
+    /// The next board is smaller, with fewer mines to find.
+    SmallerBoard,
+}
+
+impl Perk {
+    /// The perk in a few words, for the game-over message.
+    pub const fn describe(self) -> &'static str {
+        match self {
+            Self::ExtraHint => "an extra hint",
+            Self::SmallerBoard => "a smaller next board",
+        }
+    }
+}
+
+/// A roguelike run ([`AppState::campaign`]): boards of rising density, one after another,
+/// with lives and hints shared across the run. Each mine that goes off (or board given up)
+/// costs a life and the run ends when none are left; every few boards cleared earn a
+/// [`Perk`].
+///
+/// [`AppState::campaign`]: crate::app::AppState::campaign
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Campaign {
+    /// Boards cleared this run.
+    pub depth: usize,
+    /// Lives left.
+    pub lives: u32,
+    /// Hints left.
+    pub hints: u32,
+    /// The deepest any run has gone, kept across runs (and, by the front end, across
+    /// sessions).
+    pub best: usize,
+    /// The perk earned by the board just cleared, if it earned one.
+    pub perk: Option<Perk>,
+    /// Whether the next board is shrunk by [`Perk::SmallerBoard`].
+    shrink: bool,
+}
+
+impl Default for Campaign {
+    fn default() -> Self {
+        Self {
+            depth: 0,
+            lives: LIVES,
+            hints: HINTS,
+            best: 0,
+            perk: None,
+            shrink: false,
+        }
+    }
+}
+
+impl Campaign {
+    /// Whether the run has ended, with no lives left.
+    pub const fn is_over(&self) -> bool {
+        self.lives == 0
+    }
+
+    /// Note a finished board: a win goes a level deeper, perhaps with a perk, and anything
+    /// else costs a life.
+    pub fn finished(&mut self, won: bool) {
+        self.perk = None;
+        if !won {
+            self.lives = self.lives.saturating_sub(1);
+            return;
+        }
+        self.depth += 1;
+        self.best = self.best.max(self.depth);
+        if self.depth.is_multiple_of(PERK_EVERY) {
+            let perk = if (self.depth / PERK_EVERY) % 2 == 1 {
+                Perk::ExtraHint
+            } else {
+                Perk::SmallerBoard
+            };
+            match perk {
+                Perk::ExtraHint => self.hints += 1,
+                Perk::SmallerBoard => self.shrink = true,
+            }
+            self.perk = Some(perk);
+        }
+    }
+
+    /// Start over after the run ended, keeping the best depth.
+    pub fn new_run(&mut self) {
+        *self = Self {
+            best: self.best,
+            ..Self::default()
+        };
+    }
+
+    /// The next board of the run, using up a [`Perk::SmallerBoard`].
+    pub fn next_board(&mut self) -> Difficulty {
+        let side = if self.shrink { SMALL_SIDE } else { SIDE };
+        self.shrink = false;
+        let cells = side * side;
+        let density = (DENSITY + DENSITY_STEP * self.depth).min(MAX_DENSITY);
+        Difficulty::Custom {
+            width: side,
+            height: side,
+            mines: cells * density / 100,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boards_get_denser_and_perks_come_every_few() {
+        let mut run = Campaign::default();
+        assert_eq!(run.next_board().parameters(), (9, 9, 9));
+        for _ in 0..PERK_EVERY {
+            run.finished(true);
+        }
+        assert_eq!(
+            (run.depth, run.hints, run.perk),
+            (3, 2, Some(Perk::ExtraHint))
+        );
+        assert_eq!(run.next_board().parameters(), (9, 9, 14));
+        for _ in 0..PERK_EVERY {
+            run.finished(true);
+        }
+        assert_eq!(run.perk, Some(Perk::SmallerBoard));
+        assert_eq!(run.next_board().parameters(), (7, 7, 11));
+        assert_eq!(run.next_board().parameters(), (9, 9, 19));
+    }
+
+    #[test]
+    fn runs_end_when_the_lives_run_out() {
+        let mut run = Campaign::default();
+        run.finished(true);
+        for _ in 0..LIVES {
+            assert!(!run.is_over());
+            run.finished(false);
+        }
+        assert!(run.is_over());
+        run.new_run();
+        assert_eq!((run.depth, run.lives, run.best), (0, LIVES, 1));
+    }
+}
@@ -0,0 +1,148 @@
+use minesweeper::error::{self, Error};
+use serde_json::json;
+use std::fs::File;
+use std::io::{self, BufWriter, Stdout, Write, stdout};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Where the game is drawn: the terminal, and a cast file as well when recording (`--record`).
+pub struct Output {
+    stdout: Stdout,
+    cast: Option<Cast<BufWriter<File>>>,
+}
+
+impl Output {
+    /// Draw to the terminal only.
+    pub fn terminal() -> Self {
+        Self {
+            stdout: stdout(),
+            cast: None,
+        }
+    }
+
+    /// Draw to the terminal and record what is drawn to a cast file at `path`, sized like the
+    /// terminal is now.
+    ///
+    /// # Errors
+    /// Fails if the terminal size cannot be read or the file cannot be written.
+    pub fn recording(path: &Path) -> error::Result<Self> {
+        let (width, height) = crossterm::terminal::size().map_err(Error::TerminalInit)?;
+        let cast = File::create(path)
+            .and_then(|file| Cast::new(BufWriter::new(file), width, height))
+            .map_err(|error| Error::SaveIo {
+                path: path.to_path_buf(),
+                error,
+            })?;
+        Ok(Self {
+            stdout: stdout(),
+            cast: Some(cast),
+        })
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.stdout.write(buf)?;
+        if let Some(cast) = &mut self.cast {
+            cast.pending.extend_from_slice(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    /// Flush the terminal, and add what was written since the last flush (a frame, usually) to
+    /// the cast. A cast that cannot be written is given up rather than stopping the game.
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(cast) = &mut self.cast
+            && let Err(e) = cast.frame()
+        {
+            tracing::warn!("cannot write the cast, so recording stops: {e}");
+            self.cast = None;
+        }
+        self.stdout.flush()
+    }
+}
+
+/// An asciinema cast (format version 2) being written: a header line, then one line per
+/// frame, `[seconds, "o", "output"]`, with what the terminal was sent.
+struct Cast<W: Write> {
+    out: W,
+    started: Instant,
+    /// Output not written out yet, including the first bytes of a character split between
+    /// writes.
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Cast<W> {
+    /// Start a cast of a `width` by `height` terminal in `out`, with its header.
+    fn new(mut out: W, width: u16, height: u16) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+            "title": "Minesweeper",
+        });
+        writeln!(out, "{header}")?;
+        Ok(Self {
+            out,
+            started: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Write out what is pending as an output event, keeping back an unfinished character.
+    fn frame(&mut self) -> io::Result<()> {
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            // Not text at all: written as replacement characters rather than held forever.
+            Err(_) => self.pending.len(),
+        };
+        if complete == 0 {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&self.pending[..complete]).into_owned();
+        self.pending.drain(..complete);
+        let at = self.started.elapsed().as_secs_f64();
+        writeln!(self.out, "{}", json!([at, "o", text]))?;
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn frames_are_recorded_as_output_events() {
+        let mut cast = Cast::new(Vec::new(), 80, 24).expect("in memory");
+        cast.pending.extend_from_slice(b"\x1b[1;1Hflag \xe2\x9a");
+        cast.frame().expect("in memory");
+        cast.pending.extend_from_slice(b"\x91");
+        cast.frame().expect("in memory");
+        cast.frame().expect("nothing pending");
+
+        let text = String::from_utf8(cast.out).expect("utf-8");
+        let lines: Vec<Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json"))
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            (
+                &lines[0]["version"],
+                &lines[0]["width"],
+                &lines[0]["height"]
+            ),
+            (&json!(2), &json!(80), &json!(24))
+        );
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "\x1b[1;1Hflag ");
+        assert_eq!(lines[2][2], "\u{2691}", "the split character");
+        assert!(lines[1][0].as_f64() <= lines[2][0].as_f64());
+    }
+}
@@ -0,0 +1,443 @@
+use crate::commands;
+use crate::generate::GenerateArgs;
+use crate::headless::Format;
+use crate::net::DEFAULT_PORT;
+use crate::serve;
+use crate::simulate::SimulateArgs;
+use crate::ui::{Appearance, Theme};
+use clap::{Parser, Subcommand};
+use minesweeper::app::parse_seed;
+use minesweeper::board::Layout;
+use minesweeper::code::BoardCode;
+use minesweeper::difficulty::Difficulty;
+use minesweeper::hotseat::Scoring;
+use std::path::PathBuf;
+
+/// Minesweeper in the terminal.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+#[allow(clippy::struct_excessive_bools)] // independent command-line switches
+pub struct Cli {
+    /// Run a tool instead of playing.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Starting difficulty: easy, medium or hard [default: from the config, else easy].
+    #[arg(short, long, global = true)]
+    pub difficulty: Option<Difficulty>,
+
+    /// Board width for a custom game (other dimensions default to the difficulty's).
+    #[arg(long, global = true)]
+    pub width: Option<usize>,
+
+    /// Board height for a custom game.
+    #[arg(long, global = true)]
+    pub height: Option<usize>,
+
+    /// Number of mines for a custom game.
+    #[arg(short, long, global = true)]
+    pub mines: Option<usize>,
+
+    /// Seed for mine placement; numbers are used as-is, other text is hashed.
+    #[arg(short, long, global = true, value_parser = seed_arg)]
+    pub seed: Option<u64>,
+
+    /// How mines are spread: uniform, center (crowded in the middle), edges (crowded at the
+    /// rim) or gradient (sparse left, dense right). Best times are kept apart per layout.
+    #[arg(long, global = true)]
+    pub layout: Option<Layout>,
+
+    /// Play the exact game named by a board code, like MS-hard-9xQk2.
+    #[arg(long, conflicts_with_all = ["difficulty", "width", "height", "mines", "seed"])]
+    pub code: Option<BoardCode>,
+
+    /// Play a hand-made board from a text file: `#` mine, `.` hidden, digits shown at start.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["difficulty", "width", "height", "mines", "seed", "code"])]
+    pub board: Option<PathBuf>,
+
+    /// Play boards in the shape of a text stencil (`.` and spaces are holes) or a monochrome
+    /// PBM image (black pixels are cells); `--mines` sets the mines [default: 15% of cells].
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["difficulty", "width", "height", "code", "board"])]
+    pub mask: Option<PathBuf>,
+
+    /// Add a pack of puzzles to the browser (O); may be given more than once.
+    #[arg(long = "pack", value_name = "FILE")]
+    pub packs: Vec<PathBuf>,
+
+    /// Let the first reveal hit a mine: mines are placed before it rather than around it.
+    #[arg(long, conflicts_with_all = ["code", "board"])]
+    pub hardcore: bool,
+
+    /// Chord every number as soon as all its flags are placed. Games played with it count as
+    /// assisted.
+    #[arg(long)]
+    pub auto_chord: bool,
+
+    /// Flag the hidden cells around a number as soon as they are all it can be. Games played
+    /// with it count as assisted.
+    #[arg(long)]
+    pub auto_flag: bool,
+
+    /// Practice: every game starts with its largest opening already revealed. Practice games
+    /// do not set best times.
+    #[arg(long, conflicts_with_all = ["code", "board"])]
+    pub practice: bool,
+
+    /// Join the board's left and right edges, so cells in the first and last columns are
+    /// neighbors. Best times are kept apart from flat boards.
+    #[arg(long, conflicts_with_all = ["code", "board"])]
+    pub cylinder: bool,
+
+    /// Adaptive difficulty: after each finished game, add mines if you win more than half of
+    /// your recent games and take some away if you win fewer.
+    #[arg(long, conflicts_with_all = ["code", "board"])]
+    pub adaptive: bool,
+
+    /// Campaign: a run of ever denser boards with three lives and one hint shared between
+    /// them, and a perk every few boards cleared. Undo is off.
+    #[arg(long, conflicts_with_all = ["code", "board", "seed", "adaptive", "hotseat", "versus"])]
+    pub campaign: bool,
+
+    /// Keep score: points for reveals, chords and mine flags, multiplied by streaks of
+    /// correct moves, plus a speed bonus for a win.
+    #[arg(long)]
+    pub score: bool,
+
+    /// Memory mode: revealed numbers fade to blank this many seconds after they open.
+    #[arg(long, value_name = "SECS")]
+    pub memory: Option<u64>,
+
+    /// Allow at most this many flags on the board at once [default: the number of mines].
+    #[arg(long, value_name = "FLAGS", num_args = 0..=1)]
+    #[allow(clippy::option_option)] // absent, given bare, or given a value
+    pub flag_budget: Option<Option<usize>>,
+
+    /// Add this many seconds to the timer for each flag taken back off a safe cell or left on
+    /// one when the game ends.
+    #[arg(long, value_name = "SECS")]
+    pub flag_penalty: Option<u64>,
+
+    /// Second chance: a mine that goes off can be defused by pressing the arrows shown within
+    /// three seconds, flagging it and adding this many seconds to the timer.
+    #[arg(long, value_name = "SECS")]
+    pub defuse: Option<u64>,
+
+    /// Fog of war: only cells within this many steps of the cursor can be seen.
+    #[arg(long, value_name = "RADIUS")]
+    pub fog: Option<usize>,
+
+    /// Two players take turns on one board: whoever sets off a mine loses (`survival`), or
+    /// whoever opened more cells wins (`cells`).
+    #[arg(long, value_name = "SCORING", num_args = 0..=1, default_missing_value = "survival")]
+    pub hotseat: Option<Scoring>,
+
+    /// Two players race the same board side by side at one keyboard (WASD vs arrows).
+    #[arg(long, conflicts_with_all = ["hotseat", "headless", "msp", "code", "board"])]
+    pub versus: bool,
+
+    /// Color scheme [default: from the config, else classic].
+    #[arg(long, value_enum)]
+    pub theme: Option<Theme>,
+
+    /// Draw with plain ASCII characters only.
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// Draw below the shell prompt, in this many lines of the scrollback [default: enough for
+    /// the whole board], instead of on the alternate screen. The mouse is left to the terminal.
+    #[arg(long, value_name = "LINES", num_args = 0..=1, conflicts_with_all = ["versus", "headless", "msp"])]
+    #[allow(clippy::option_option)] // absent, given bare, or given a value
+    pub inline: Option<Option<u16>>,
+
+    /// Record what is drawn, with its timing, to an asciinema cast file (format version 2),
+    /// for playing back with `asciinema play` or sharing as a terminal recording.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "msp"])]
+    pub record: Option<PathBuf>,
+
+    /// Also take moves as text commands (`reveal C4`, `flag D2`, `chord B3`, `restart`), one
+    /// per line, from SOURCE: a TCP port to listen on from this machine, an address such as
+    /// `0.0.0.0:4000` to listen on more widely, or a named pipe made with `mkfifo`. For
+    /// crowd-played games fed from a chat, and for driving the game from other programs.
+    #[arg(long, value_name = "SOURCE", conflicts_with_all = ["versus", "headless", "msp"])]
+    pub commands: Option<commands::Source>,
+
+    /// Take moves only from `--commands`: the keyboard is left with the panels, suspending and
+    /// quitting.
+    #[arg(long, requires = "commands")]
+    pub commands_only: bool,
+
+    /// Play without the TUI: read text commands from stdin and print results to stdout.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Reply format in headless mode.
+    #[arg(long, value_enum, default_value_t, requires = "headless")]
+    pub format: Format,
+
+    /// Let another program play over stdin/stdout with the MSP protocol (see the README).
+    #[arg(long, conflicts_with = "headless")]
+    pub msp: bool,
+
+    /// Let a Rhai bot script play a game without the TUI, printing each move.
+    #[cfg(feature = "bot")]
+    #[arg(long, value_name = "SCRIPT")]
+    pub bot: Option<PathBuf>,
+
+    /// Let others watch the game live over WebSocket on this port.
+    #[cfg(feature = "spectate")]
+    #[arg(long, value_name = "PORT")]
+    pub spectate: Option<u16>,
+
+    /// Serve the game's state as JSON at `http://localhost:PORT/state`, for stream overlays.
+    #[arg(long, value_name = "PORT", conflicts_with_all = ["versus", "headless", "msp"])]
+    pub overlay: Option<u16>,
+
+    /// Measure render, reveal (flood fill) and solver times and print them on exit.
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Config file to use instead of the one in the user config directory.
+    #[arg(short, long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+}
+
+/// Non-interactive tools.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print or save a board without launching the UI.
+    Generate(GenerateArgs),
+    /// Let the solver play many random boards and report its win rate, the average 3BV and
+    /// how often it had to guess.
+    Simulate(SimulateArgs),
+    /// Race an opponent on the same board: wait for them to `join`, then play.
+    Host {
+        /// Port to listen on.
+        #[arg(short, long, default_value_t = DEFAULT_PORT)]
+        port: u16,
+
+        /// Play together on one shared board instead of racing.
+        #[arg(long)]
+        coop: bool,
+    },
+    /// Play with someone who is hosting at ADDRESS (`host:port`, or just `host`).
+    Join {
+        /// Where the host is listening.
+        address: String,
+    },
+    /// Run a private leaderboard: an HTTP API that verifies and lists best times.
+    Serve {
+        /// Port to listen on.
+        #[arg(short, long, default_value_t = serve::DEFAULT_PORT)]
+        port: u16,
+
+        /// File the scores are kept in [default: leaderboard.jsonl in the user data directory].
+        #[arg(long, value_name = "PATH")]
+        data: Option<PathBuf>,
+    },
+    /// Write the game log as CSV for a spreadsheet: games.csv, a row per game, and
+    /// summary.csv, the games, wins and times per board.
+    Export {
+        /// Directory to write the files to.
+        #[arg(short, long, value_name = "DIR", default_value = ".")]
+        output: PathBuf,
+    },
+    /// Move settings between machines as a single portable file.
+    Settings {
+        /// Export or import.
+        #[command(subcommand)]
+        action: SettingsAction,
+    },
+}
+
+/// What `settings` does.
+#[derive(Debug, Subcommand)]
+pub enum SettingsAction {
+    /// Write the config (keys, theme, gameplay and the rest) to a profile file.
+    Export {
+        /// Profile file to write.
+        file: PathBuf,
+    },
+    /// Replace the config with a profile's settings, keeping the old config as a backup.
+    Import {
+        /// Profile file to read.
+        file: PathBuf,
+    },
+}
+
+impl Cli {
+    /// The difficulty to start with: `--difficulty` (or `default`), with any custom size
+    /// or mine count applied on top.
+    ///
+    /// # Errors
+    /// Returns an error if the resulting board cannot be played.
+    pub fn difficulty(&self, default: Difficulty) -> minesweeper::error::Result<Difficulty> {
+        let base = self.difficulty.unwrap_or(default);
+        if self.width.is_none() && self.height.is_none() && self.mines.is_none() {
+            return Ok(base);
+        }
+        let (w, h, m) = base.parameters();
+        Difficulty::custom(
+            self.width.unwrap_or(w),
+            self.height.unwrap_or(h),
+            self.mines.unwrap_or(m),
+        )
+    }
+
+    /// Rendering options: command-line choices override `defaults` (from the config).
+    pub fn appearance(&self, defaults: Appearance) -> Appearance {
+        Appearance {
+            theme: self.theme.unwrap_or(defaults.theme),
+            ascii: self.ascii || defaults.ascii,
+            palette: defaults.palette,
+            glyphs: defaults.glyphs,
+        }
+    }
+}
+
+fn seed_arg(text: &str) -> Result<u64, String> {
+    parse_seed(text).ok_or_else(|| "seed must not be empty".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_difficulty_and_seed() {
+        let cli = Cli::try_parse_from(["minesweeper", "--difficulty", "hard", "--seed", "12345"])
+            .expect("valid arguments");
+        assert_eq!(
+            cli.difficulty(Difficulty::Easy).ok(),
+            Some(Difficulty::Hard)
+        );
+        assert_eq!(cli.seed, Some(12345));
+        assert_eq!(cli.appearance(Appearance::default()), Appearance::default());
+    }
+
+    #[test]
+    fn custom_size_overrides_the_preset() {
+        let cli = Cli::try_parse_from(["minesweeper", "-d", "hard", "--mines", "120"])
+            .expect("valid arguments");
+        assert_eq!(
+            cli.difficulty(Difficulty::Easy)
+                .ok()
+                .map(Difficulty::parameters),
+            Some((30, 16, 120))
+        );
+
+        let cli = Cli::try_parse_from(["minesweeper", "--width", "3", "--height", "3"])
+            .expect("valid arguments");
+        assert!(cli.difficulty(Difficulty::Easy).is_err());
+    }
+
+    #[test]
+    fn flag_budget_value_is_optional() {
+        let budget = |args: &[&str]| {
+            Cli::try_parse_from(["minesweeper"].iter().chain(args))
+                .expect("valid arguments")
+                .flag_budget
+        };
+        assert_eq!(budget(&[]), None);
+        assert_eq!(budget(&["--flag-budget"]), Some(None));
+        assert_eq!(budget(&["--flag-budget", "5"]), Some(Some(5)));
+    }
+
+    #[test]
+    fn generate_accepts_board_options() {
+        let cli = Cli::try_parse_from([
+            "minesweeper",
+            "generate",
+            "--width",
+            "30",
+            "--height",
+            "16",
+            "--mines",
+            "99",
+            "--no-guess",
+            "--seed",
+            "7",
+            "--layout",
+            "edges",
+        ])
+        .expect("valid arguments");
+        let Some(Command::Generate(args)) = &cli.command else {
+            panic!("expected the generate subcommand");
+        };
+        assert!(args.no_guess);
+        assert_eq!(cli.seed, Some(7));
+        assert_eq!(cli.layout, Some(Layout::Edges));
+        assert_eq!(
+            cli.difficulty(Difficulty::Easy)
+                .ok()
+                .map(Difficulty::parameters),
+            Some((30, 16, 99))
+        );
+    }
+
+    #[test]
+    fn host_and_join_take_addresses() {
+        let cli =
+            Cli::try_parse_from(["minesweeper", "host", "-d", "hard", "--coop"]).expect("valid");
+        assert!(matches!(
+            cli.command,
+            Some(Command::Host {
+                port: DEFAULT_PORT,
+                coop: true
+            })
+        ));
+        assert_eq!(cli.difficulty, Some(Difficulty::Hard));
+        let cli = Cli::try_parse_from(["minesweeper", "join", "10.0.0.2:9000"]).expect("valid");
+        assert!(
+            matches!(cli.command, Some(Command::Join { address }) if address == "10.0.0.2:9000")
+        );
+    }
+
+    #[test]
+    fn json_format_needs_headless() {
+        assert!(Cli::try_parse_from(["minesweeper", "--format", "json"]).is_err());
+        let cli = Cli::try_parse_from(["minesweeper", "--headless", "--format", "json"])
+            .expect("valid arguments");
+        assert_eq!(cli.format, Format::Json);
+    }
+
+    #[test]
+    fn code_replaces_board_options() {
+        let cli = Cli::try_parse_from(["minesweeper", "--code", "MS-hard-9xQk2"]).expect("valid");
+        assert_eq!(cli.code.map(|code| code.difficulty), Some(Difficulty::Hard));
+        assert!(
+            Cli::try_parse_from(["minesweeper", "--code", "MS-hard-9xQk2", "-s", "1"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["minesweeper", "--code", "hard"]).is_err());
+    }
+
+    #[test]
+    fn board_file_replaces_board_options() {
+        let cli = Cli::try_parse_from(["minesweeper", "--board", "puzzle.txt"]).expect("valid");
+        assert_eq!(cli.board, Some(PathBuf::from("puzzle.txt")));
+        assert!(
+            Cli::try_parse_from(["minesweeper", "--board", "puzzle.txt", "-d", "hard"]).is_err()
+        );
+    }
+
+    #[test]
+    fn unset_options_fall_back_to_config() {
+        let cli = Cli::try_parse_from(["minesweeper", "--ascii"]).expect("valid arguments");
+        assert_eq!(
+            cli.difficulty(Difficulty::Medium).ok(),
+            Some(Difficulty::Medium)
+        );
+        let config = Appearance {
+            theme: Theme::Mono,
+            ascii: false,
+            ..Appearance::default()
+        };
+        assert_eq!(
+            cli.appearance(config),
+            Appearance {
+                theme: Theme::Mono,
+                ascii: true,
+                ..Appearance::default()
+            }
+        );
+    }
+}
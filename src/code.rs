@@ -0,0 +1,137 @@
+use crate::difficulty::Difficulty;
+use crate::error::{Error, Result};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+const BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// A short code naming one exact game, such as `MS-hard-9xQk2`: the board size and a seed in
+/// base62.
+///
+/// Games started from a code open the center cell first, so the mines do not depend on
+/// where the player clicks and everyone with the code plays the same board. Custom sizes
+/// are written `WxHxM` (`MS-20x10x30-abc`). Any base62 text is a valid seed, so players can
+/// make up codes like `MS-easy-Friday`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardCode {
+    /// Board size and mine count.
+    pub difficulty: Difficulty,
+    /// Seed for mine placement.
+    pub seed: u64,
+}
+
+impl BoardCode {
+    /// The cell every game from this code opens first.
+    pub const fn start(self) -> (usize, usize) {
+        let (width, height, _) = self.difficulty.parameters();
+        (width / 2, height / 2)
+    }
+}
+
+impl Display for BoardCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.difficulty {
+            Difficulty::Custom {
+                width,
+                height,
+                mines,
+            } => write!(f, "MS-{width}x{height}x{mines}-")?,
+            preset => write!(f, "MS-{}-", preset.name())?,
+        }
+        let mut digits = Vec::new();
+        let mut n = self.seed;
+        loop {
+            digits.push(BASE62[usize::try_from(n % 62).unwrap_or_default()]);
+            n /= 62;
+            if n == 0 {
+                break;
+            }
+        }
+        digits.reverse();
+        f.write_str(&String::from_utf8_lossy(&digits))
+    }
+}
+
+impl FromStr for BoardCode {
+    type Err = Error;
+
+    /// Parse `MS-<difficulty>-<seed>`; the `MS` prefix and the difficulty ignore case, the
+    /// base62 seed does not.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Parse(format!("'{s}' is not a board code (like MS-hard-9xQk2)"));
+        let mut parts = s.trim().splitn(3, '-');
+        let (Some(prefix), Some(size), Some(seed)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if !prefix.eq_ignore_ascii_case("MS") || seed.is_empty() {
+            return Err(invalid());
+        }
+        let difficulty = match size.split('x').collect::<Vec<_>>()[..] {
+            [width, height, mines] => {
+                let number = |text: &str| text.parse::<usize>().map_err(|_| invalid());
+                Difficulty::custom(number(width)?, number(height)?, number(mines)?)?
+            }
+            _ => size.parse()?,
+        };
+        let seed = seed.bytes().try_fold(0u64, |n, digit| {
+            let value = BASE62
+                .iter()
+                .position(|&b| b == digit)
+                .ok_or_else(invalid)?;
+            n.checked_mul(62)
+                .and_then(|n| n.checked_add(u64::try_from(value).ok()?))
+                .ok_or_else(|| Error::Parse(format!("the seed in '{s}' is too long")))
+        })?;
+        Ok(Self { difficulty, seed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for code in [
+            BoardCode {
+                difficulty: Difficulty::Hard,
+                seed: 0,
+            },
+            BoardCode {
+                difficulty: Difficulty::Easy,
+                seed: u64::MAX,
+            },
+            BoardCode {
+                difficulty: Difficulty::Custom {
+                    width: 20,
+                    height: 10,
+                    mines: 30,
+                },
+                seed: 123_456,
+            },
+        ] {
+            assert_eq!(code.to_string().parse::<BoardCode>().ok(), Some(code));
+        }
+        assert_eq!(
+            BoardCode {
+                difficulty: Difficulty::Hard,
+                seed: 62,
+            }
+            .to_string(),
+            "MS-hard-10"
+        );
+    }
+
+    #[test]
+    fn parses_made_up_codes_and_rejects_others() {
+        let code: BoardCode = "ms-EASY-Friday".parse().expect("valid code");
+        assert_eq!(code.difficulty, Difficulty::Easy);
+        assert_eq!(code.to_string(), "MS-easy-Friday");
+        assert!("MS-hard".parse::<BoardCode>().is_err());
+        assert!("MS-hard-no_way".parse::<BoardCode>().is_err());
+        assert!("MS-2x2x4-a".parse::<BoardCode>().is_err());
+        assert!("MS-easy-zzzzzzzzzzzzzzzz".parse::<BoardCode>().is_err());
+        assert!("12345".parse::<BoardCode>().is_err());
+    }
+}
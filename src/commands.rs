@@ -0,0 +1,243 @@
+use crate::input::{InputAction, InputEvent};
+use crate::net::Session;
+use minesweeper::app::{Action, AppState, Command, Cursor, Layer, Status};
+use minesweeper::board::CellState;
+use minesweeper::error::{self, Error};
+use minesweeper::render::parse_cell_name;
+use std::convert::Infallible;
+use std::fs::OpenOptions;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Where `--commands` reads moves from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A TCP address to listen on, taking any number of connections at once.
+    Tcp(SocketAddr),
+    /// A named pipe (made with `mkfifo`), kept open across writers.
+    Fifo(PathBuf),
+}
+
+impl FromStr for Source {
+    type Err = Infallible;
+
+    /// A port number, listened on on this machine only; an address such as `0.0.0.0:4000` to
+    /// take commands from elsewhere too; or else the path of a pipe.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(port) = s.parse() {
+            return Ok(Self::Tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, port))));
+        }
+        Ok(s.parse()
+            .map_or_else(|_| Self::Fifo(PathBuf::from(s)), Self::Tcp))
+    }
+}
+
+/// A move a command asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Play {
+    /// Reveal, flag or chord the cell at (x, y).
+    At(Action, usize, usize),
+    /// Start the next game, once this one is over.
+    Restart,
+}
+
+/// Read one command: `reveal C4`, `flag D2` or `chord B3` (or just `r`, `f` and `c`) with the
+/// cell named as on the rulers, or `restart`. A leading `!`, as chat bots expect, is ignored.
+fn parse(line: &str, width: usize) -> Result<Play, String> {
+    let line = line.trim();
+    let words: Vec<&str> = line
+        .strip_prefix('!')
+        .unwrap_or(line)
+        .split_whitespace()
+        .collect();
+    let action = match words
+        .first()
+        .map(|word| word.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("restart") if words.len() == 1 => return Ok(Play::Restart),
+        Some("reveal" | "r") => Action::Reveal,
+        Some("flag" | "f") => Action::ToggleFlag,
+        Some("chord" | "c") => Action::Chord,
+        _ => return Err(format!("not a command: '{line}'")),
+    };
+    match words[1..] {
+        [cell] => parse_cell_name(cell, width)
+            .map(|(x, y)| Play::At(action, x, y))
+            .ok_or_else(|| format!("no cell called '{cell}'")),
+        _ => Err(format!("'{line}' names no cell")),
+    }
+}
+
+/// Carry out the command `line` on `app` as if it came from the keyboard (through the network
+/// `session`, if any), leaving the cursor where it was, and return what the game asks of the
+/// front end. A move that does not fit the board as it is by then, such as revealing a cell
+/// someone else already opened, is turned down with the reason.
+///
+/// # Errors
+/// Returns why the command was not played.
+pub fn play(
+    line: &str,
+    app: &mut AppState,
+    session: Option<&mut Session>,
+) -> Result<Command, String> {
+    if !matches!(app.layers.last(), None | Some(Layer::GameOver)) {
+        return Err("a dialog is open".to_string());
+    }
+    let (action, x, y) = match parse(line, app.board.width())? {
+        Play::Restart if app.status == Status::Playing => {
+            return Err("the game is still on".to_string());
+        }
+        Play::Restart => (Action::Restart, app.cursor.x, app.cursor.y),
+        Play::At(_, _, y) if y >= app.board.height() => {
+            return Err(format!("no row {} on the board", y + 1));
+        }
+        Play::At(_, _, _) if app.status != Status::Playing => {
+            return Err("the game is over".to_string());
+        }
+        Play::At(action, x, y) => {
+            let fits = match app.board.cell_at(x, y) {
+                CellState::Hidden => action != Action::Chord,
+                CellState::Flagged => action == Action::ToggleFlag,
+                CellState::Revealed(n) => action == Action::Chord && n > 0,
+            };
+            if !fits || !app.board.in_play(x, y) {
+                return Err(format!("'{}' does not fit that cell", line.trim()));
+            }
+            (action, x, y)
+        }
+    };
+    let cursor = app.cursor;
+    app.cursor = Cursor::new(x, y);
+    let command = match session {
+        Some(session) => session.handle_action(app, action),
+        None => app.handle_action(action),
+    };
+    if action != Action::Restart {
+        app.cursor = cursor;
+    }
+    Ok(command)
+}
+
+/// Whether `input` still counts when `--commands-only` hands the game to the command source:
+/// the commands themselves and network messages do, and of the keyboard just enough to look
+/// through the panels, suspend and quit.
+pub const fn counts_when_commands_only(input: &InputEvent) -> bool {
+    matches!(
+        input,
+        InputEvent::Command(_)
+            | InputEvent::Remote(_)
+            | InputEvent::Action(
+                InputAction::Quit
+                    | InputAction::ConfirmQuit
+                    | InputAction::Back
+                    | InputAction::Suspend
+                    | InputAction::Resume
+                    | InputAction::TogglePanel(_)
+            )
+    )
+}
+
+/// Read command lines from `source` in tasks on the current runtime and send them over `tx`,
+/// each to be [played](play) on the main loop. The tasks end once the receiver is dropped.
+///
+/// # Errors
+/// Fails if the address cannot be listened on or the pipe cannot be opened.
+pub fn spawn_reader(source: &Source, tx: UnboundedSender<InputEvent>) -> error::Result<()> {
+    match source {
+        Source::Tcp(address) => {
+            let listener = std::net::TcpListener::bind(address)
+                .and_then(|listener| {
+                    listener.set_nonblocking(true)?;
+                    TcpListener::from_std(listener)
+                })
+                .map_err(|e| Error::Network(format!("cannot listen on {address}: {e}")))?;
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(forward(stream, tx.clone()));
+                }
+            });
+        }
+        Source::Fifo(path) => {
+            // Opened for writing too, so the pipe never reads as ended when a writer closes
+            // it, and opening does not wait for the first writer.
+            let pipe = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .map_err(|error| Error::LoadIo {
+                    path: path.clone(),
+                    error,
+                })?;
+            tokio::spawn(forward(File::from_std(pipe), tx));
+        }
+    }
+    Ok(())
+}
+
+/// Send each line read from `input` over `tx`, until it ends or the receiver is dropped.
+async fn forward(input: impl AsyncRead + Unpin, tx: UnboundedSender<InputEvent>) {
+    let mut lines = BufReader::new(input).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if !line.trim().is_empty() && tx.send(InputEvent::Command(line)).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+
+    #[test]
+    fn commands_name_a_move_and_a_cell() {
+        assert_eq!(parse("reveal c4", 9), Ok(Play::At(Action::Reveal, 2, 3)));
+        assert_eq!(parse("!FLAG D2", 9), Ok(Play::At(Action::ToggleFlag, 3, 1)));
+        assert_eq!(parse("  c A1 ", 9), Ok(Play::At(Action::Chord, 0, 0)));
+        assert_eq!(parse("restart", 9), Ok(Play::Restart));
+        assert!(parse("reveal", 9).is_err());
+        assert!(parse("reveal c4 d5", 9).is_err());
+        assert!(parse("reveal z1", 9).is_err());
+        assert!(parse("hello chat", 9).is_err());
+        assert_eq!(
+            "4000".parse(),
+            Ok(Source::Tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, 4000))))
+        );
+        assert_eq!(
+            "0.0.0.0:4000".parse(),
+            Ok(Source::Tcp(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 4000))))
+        );
+        assert_eq!(
+            "/tmp/moves".parse(),
+            Ok(Source::Fifo(PathBuf::from("/tmp/moves")))
+        );
+    }
+
+    #[test]
+    fn commands_play_moves_that_fit_the_board() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 2, 1);
+        app.board.place_mines_at(&[(2, 1)]);
+        app.cursor = Cursor::new(1, 1);
+        assert!(play("restart", &mut app, None).is_err(), "still on");
+        assert!(play("reveal a3", &mut app, None).is_err(), "no third row");
+        assert!(play("flag c2", &mut app, None).is_ok());
+        assert_eq!(app.board.cell_at(2, 1), CellState::Flagged);
+        assert!(play("reveal c2", &mut app, None).is_err(), "flagged");
+        assert!(play("reveal a1", &mut app, None).is_ok());
+        assert!(play("reveal a1", &mut app, None).is_err(), "already open");
+        assert!(play("reveal C1", &mut app, None).is_ok());
+        assert_eq!(app.cursor, Cursor::new(1, 1), "left where it was");
+        assert_eq!(app.status, Status::Win);
+        assert!(play("chord b1", &mut app, None).is_err(), "over");
+        assert!(play("restart", &mut app, None).is_ok());
+        assert_eq!(app.status, Status::Playing);
+    }
+}
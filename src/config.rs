@@ -0,0 +1,755 @@
+use crate::input::Keymap;
+use crate::sound::Event;
+use crate::ui::{Appearance, Glyphs, Palette, Theme};
+use minesweeper::difficulty::Difficulty;
+use minesweeper::error::{self, Error};
+use ratatui::text::Span;
+use serde::{Deserialize, Deserializer, de};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::io::ErrorKind;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Written to the config path on first run, so every option is discoverable.
+pub const DEFAULT_CONFIG: &str = r##"# Minesweeper configuration. Command-line options override these settings.
+
+# Starting difficulty: "easy", "medium" or "hard"
+difficulty = "easy"
+
+# Color scheme: "classic" or "mono"
+theme = "classic"
+
+# Draw with plain ASCII characters only
+ascii = false
+
+# How often, in milliseconds, the game wakes without input to move the timer on and redraw
+# (10 to 1000): raise it over slow SSH links, lower it for snappier countdowns
+tick-ms = 50
+
+# Most frames a second drawn while something moves on screen, such as a defusal countdown or
+# a burst of input (1 to 120); an idle game is only redrawn when its timer ticks over
+max-fps = 30
+
+[gameplay]
+# Window in milliseconds for a second Reveal on a number to chord; 0 disables it
+double-tap-ms = 300
+# Panels shown at startup (F2 / F3 toggle them in game)
+show-footer = true
+show-rulers = true
+# Place the mines before the first reveal, so even it can hit one
+hardcore = false
+# When a hint finds no certain cell, point at the hidden cell least likely to be a mine
+best-guess = false
+# Chord a number as soon as all its flags are placed (games count as assisted)
+auto-chord = false
+# Flag hidden cells a number shows must be mines (games count as assisted)
+auto-flag = false
+# Seconds between snapshots of the game in progress, picked up again on the next launch after
+# a crash or a dropped connection; 0 turns autosave off
+autosave-secs = 5
+
+[log]
+# Append each finished game (seed, difficulty, result, time, 3BV, clicks) as a line of JSON
+enabled = true
+# Log file; defaults to games.jsonl in the platform data directory
+# path = "/home/me/minesweeper-games.jsonl"
+
+[sound]
+# Which events make a sound: the terminal bell, or synthesized effects when built with the
+# audio feature
+reveal = false
+flag = false
+explosion = true
+win = true
+
+[notifications]
+# Notify when a game that lasted at least after-secs ends, or a win sets a personal best
+# (desktop notifications when built with the notify feature, else the terminal's OSC 9)
+enabled = false
+after-secs = 120
+best = true
+
+[submit]
+# Send each win on a board code (--code) to a leaderboard server (`minesweeper serve`), which
+# replays the moves to verify it. Nothing is sent unless this is turned on
+enabled = false
+# Where to send wins: an http:// URL (https is not supported)
+url = "http://localhost:8080/scores"
+# Name on the leaderboard; without one, wins are sent as "anonymous"
+# name = "ann"
+# Tries after the first when the server cannot be reached or fails (0 to 10)
+retries = 3
+
+[colors]
+# Colors for the cells, replacing the classic ones for terminal color schemes they clash with;
+# the mono theme still draws without color. Numbers "1" to "8", "hidden", "flag" and "zero"
+# take a color name ("blue", "lightred", "darkgray"...), a 256-color index ("208") or "#rrggbb"
+# 1 = "lightblue"
+# hidden = "#3a3a3a"
+
+[glyphs]
+# Symbols for the cells, each a single character one column wide: "hidden", "flag", "zero"
+# (revealed cells with no mines around) and "mine" (shown when the game is over). ASCII mode
+# uses only those that are ASCII
+# hidden = "▒"
+# flag = "F"
+
+[keys]
+# Extra key bindings, checked before the built-in ones. Keys are a single character or
+# space, enter, tab, esc, backspace, left, right, up, down, home, end, pageup, pagedown, f1-f12,
+# optionally after shift+, ctrl+ or alt+ (like shift+space).
+# Actions: move-left, move-right, move-up, move-down, move-up-left, move-up-right,
+# move-down-left, move-down-right, reveal, flag, flag-advance, chord, open-remaining, peek,
+# restart, undo, hint, toggle-mode, toggle-footer, toggle-rulers, toggle-stats, toggle-debug, record,
+# play-macro, seed, packs, change-difficulty, export, share, moves, back, quit
+# x = "reveal"
+"##;
+
+/// Settings loaded from `config.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Starting difficulty when `--difficulty` is not given.
+    #[serde(deserialize_with = "from_str")]
+    pub difficulty: Difficulty,
+    /// Color scheme when `--theme` is not given.
+    pub theme: Theme,
+    /// Draw with ASCII only (`--ascii` also turns this on).
+    pub ascii: bool,
+    /// Main-loop tick in milliseconds, within [`TICK_MS`].
+    #[serde(deserialize_with = "tick_ms")]
+    pub tick_ms: u64,
+    /// Frame-rate cap, within [`MAX_FPS`].
+    #[serde(deserialize_with = "max_fps")]
+    pub max_fps: u32,
+    /// Gameplay options.
+    pub gameplay: Gameplay,
+    /// Game log options.
+    pub log: Log,
+    /// Sound effect switches.
+    pub sound: Sound,
+    /// Game-end notifications.
+    pub notifications: Notifications,
+    /// Sending wins to a leaderboard server.
+    pub submit: Submit,
+    /// Cell colors replacing the classic ones.
+    #[serde(deserialize_with = "palette")]
+    pub colors: Palette,
+    /// Cell symbols replacing the usual ones.
+    #[serde(deserialize_with = "glyphs")]
+    pub glyphs: Glyphs,
+    /// Extra key bindings: key name to action name.
+    pub keys: BTreeMap<String, String>,
+}
+
+/// The `[gameplay]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+#[allow(clippy::struct_excessive_bools)] // independent switches
+pub struct Gameplay {
+    /// Double-tap-to-chord window in milliseconds; 0 disables the gesture.
+    pub double_tap_ms: u64,
+    /// Show the key legend at startup.
+    pub show_footer: bool,
+    /// Show the coordinate rulers at startup.
+    pub show_rulers: bool,
+    /// Place mines before the first reveal instead of keeping it safe.
+    pub hardcore: bool,
+    /// Let hints fall back to the best guess when nothing is certain.
+    pub best_guess: bool,
+    /// Chord satisfied numbers automatically.
+    pub auto_chord: bool,
+    /// Flag certain mines automatically.
+    pub auto_flag: bool,
+    /// Seconds between autosaves of the game in progress; 0 turns autosave off.
+    pub autosave_secs: u64,
+}
+
+/// The `[log]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Log {
+    /// Write finished games to the log.
+    pub enabled: bool,
+    /// Log file, instead of the default location.
+    pub path: Option<PathBuf>,
+}
+
+/// The `[sound]` table: which events make a sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+#[allow(clippy::struct_excessive_bools)] // one switch per event
+pub struct Sound {
+    /// Revealing cells.
+    pub reveal: bool,
+    /// Placing or removing a flag.
+    pub flag: bool,
+    /// Hitting a mine.
+    pub explosion: bool,
+    /// Clearing the board.
+    pub win: bool,
+}
+
+/// The `[notifications]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Notifications {
+    /// Send notifications at all.
+    pub enabled: bool,
+    /// Notify when a game that lasted at least this many seconds ends.
+    pub after_secs: u64,
+    /// Notify when a win beats the best time for its board size.
+    pub best: bool,
+}
+
+/// The `[submit]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Submit {
+    /// Send wins at all.
+    pub enabled: bool,
+    /// Leaderboard endpoint, an `http://` URL.
+    pub url: String,
+    /// Player name; `anonymous` when not given.
+    pub name: Option<String>,
+    /// Tries after the first, within [`RETRIES`].
+    #[serde(deserialize_with = "retries")]
+    pub retries: u32,
+}
+
+impl Sound {
+    /// Whether `event` is switched on.
+    pub const fn plays(self, event: Event) -> bool {
+        match event {
+            Event::Reveal => self.reveal,
+            Event::Flag => self.flag,
+            Event::Explosion => self.explosion,
+            Event::Win => self.win,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Easy,
+            theme: Theme::Classic,
+            ascii: false,
+            tick_ms: 50,
+            max_fps: 30,
+            gameplay: Gameplay::default(),
+            log: Log::default(),
+            sound: Sound::default(),
+            notifications: Notifications::default(),
+            submit: Submit::default(),
+            colors: Palette::default(),
+            glyphs: Glyphs::default(),
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for Gameplay {
+    fn default() -> Self {
+        Self {
+            double_tap_ms: 300,
+            show_footer: true,
+            show_rulers: true,
+            hardcore: false,
+            best_guess: false,
+            auto_chord: false,
+            auto_flag: false,
+            autosave_secs: 5,
+        }
+    }
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+        }
+    }
+}
+
+impl Default for Sound {
+    fn default() -> Self {
+        Self {
+            reveal: false,
+            flag: false,
+            explosion: true,
+            win: true,
+        }
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            after_secs: 120,
+            best: true,
+        }
+    }
+}
+
+impl Default for Submit {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "http://localhost:8080/scores".to_string(),
+            name: None,
+            retries: 3,
+        }
+    }
+}
+
+impl Config {
+    /// `config.toml` in the platform config directory (`$XDG_CONFIG_HOME/minesweeper` on
+    /// Linux), if one can be determined.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("minesweeper").join("config.toml"))
+    }
+
+    /// Read the config at `path`. A missing file is created with the commented defaults
+    /// (best effort; a read-only location just means running with defaults).
+    ///
+    /// # Errors
+    /// Fails if the file exists but cannot be read or is not valid config.
+    pub fn load_or_create(path: &Path) -> error::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| Error::ConfigParse {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            }),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if let Some(dir) = path.parent() {
+                    let _ = fs::create_dir_all(dir);
+                }
+                let _ = fs::write(path, DEFAULT_CONFIG);
+                Ok(Self::default())
+            }
+            Err(error) => Err(Error::LoadIo {
+                path: path.to_path_buf(),
+                error,
+            }),
+        }
+    }
+
+    /// Where to log finished games: the configured path, or `games.jsonl` in the platform
+    /// data directory (`$XDG_DATA_HOME/minesweeper` on Linux). `None` if logging is off.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        if !self.log.enabled {
+            return None;
+        }
+        self.log
+            .path
+            .clone()
+            .or_else(|| dirs::data_dir().map(|dir| dir.join("minesweeper").join("games.jsonl")))
+    }
+
+    /// Rendering options from the config.
+    pub const fn appearance(&self) -> Appearance {
+        Appearance {
+            theme: self.theme,
+            ascii: self.ascii,
+            palette: self.colors,
+            glyphs: self.glyphs,
+        }
+    }
+
+    /// The double-tap-to-chord window, or `None` if disabled.
+    pub const fn double_tap_timeout(&self) -> Option<Duration> {
+        match self.gameplay.double_tap_ms {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    /// Time between autosaves of the game in progress, or `None` if autosave is off.
+    pub const fn autosave_every(&self) -> Option<Duration> {
+        match self.gameplay.autosave_secs {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// How long the main loop waits for input before checking the timers again.
+    pub const fn tick(&self) -> Duration {
+        Duration::from_millis(self.tick_ms)
+    }
+
+    /// Shortest time between two frames, from the `max-fps` cap.
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.max_fps
+    }
+
+    /// The `[keys]` table as a keymap.
+    ///
+    /// # Errors
+    /// Fails on the first unknown key or action name.
+    pub fn keymap(&self) -> anyhow::Result<Keymap> {
+        let mut keymap = Keymap::default();
+        for (key, action) in &self.keys {
+            keymap.bind(key, action)?;
+        }
+        Ok(keymap)
+    }
+}
+
+/// Version of the settings profile layout, bumped when a profile could no longer be read as
+/// it was written. Profiles from a newer format are refused rather than half understood.
+pub const PROFILE_FORMAT: i64 = 1;
+
+/// Write the config at `config` (the defaults if there is none yet) to `profile`, a portable
+/// settings file for [`import_profile`] on another machine. The log path is left out, as it
+/// names a place on this one; the file gains a `[profile]` table with its format and the
+/// version of the game that wrote it.
+///
+/// # Errors
+/// Fails if the config cannot be read or is invalid, or the profile cannot be written.
+pub fn export_profile(config: &Path, profile: &Path) -> anyhow::Result<()> {
+    let text = match fs::read_to_string(config) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => DEFAULT_CONFIG.to_string(),
+        Err(error) => {
+            return Err(Error::LoadIo {
+                path: config.to_path_buf(),
+                error,
+            })?;
+        }
+    };
+    let parse_error = |message: String| Error::ConfigParse {
+        path: config.to_path_buf(),
+        message,
+    };
+    let mut settings: toml::Table =
+        toml::from_str(&text).map_err(|e| parse_error(e.to_string()))?;
+    toml::Value::Table(settings.clone())
+        .try_into::<Config>()
+        .map_err(|e| parse_error(e.to_string()))?;
+    if let Some(toml::Value::Table(log)) = settings.get_mut("log") {
+        log.remove("path");
+        if log.is_empty() {
+            settings.remove("log");
+        }
+    }
+    let mut header = toml::Table::new();
+    header.insert("format".into(), PROFILE_FORMAT.into());
+    header.insert("version".into(), env!("CARGO_PKG_VERSION").into());
+    settings.insert("profile".into(), header.into());
+    let text = format!(
+        "# Minesweeper settings profile: load it with `minesweeper settings import`.\n\n{}",
+        toml::to_string(&settings)?
+    );
+    fs::write(profile, text).map_err(|error| Error::SaveIo {
+        path: profile.to_path_buf(),
+        error,
+    })?;
+    Ok(())
+}
+
+/// Replace the config at `config` with the settings in `profile`, written by
+/// [`export_profile`], after checking the profile's format and that every setting and key
+/// binding is understood. The config it replaces is kept beside it with `.bak` added to its
+/// name, and that path is returned if there was one.
+///
+/// # Errors
+/// Fails if the profile cannot be read, is not a profile, comes from a newer format or holds
+/// settings or bindings this version does not know; the config is left alone then.
+pub fn import_profile(profile: &Path, config: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let text = fs::read_to_string(profile).map_err(|error| Error::LoadIo {
+        path: profile.to_path_buf(),
+        error,
+    })?;
+    let parse_error = |message: String| Error::ConfigParse {
+        path: profile.to_path_buf(),
+        message,
+    };
+    let mut settings: toml::Table =
+        toml::from_str(&text).map_err(|e| parse_error(e.to_string()))?;
+    let Some(toml::Value::Table(header)) = settings.remove("profile") else {
+        return Err(parse_error(
+            "not a settings profile: no [profile] table".into(),
+        ))?;
+    };
+    let version = header
+        .get("version")
+        .and_then(toml::Value::as_str)
+        .unwrap_or("an unknown version");
+    match header.get("format").and_then(toml::Value::as_integer) {
+        Some(format) if format <= PROFILE_FORMAT => {}
+        Some(_) => Err(parse_error(format!(
+            "the profile was written by minesweeper {version} in a newer format; update to \
+             import it"
+        )))?,
+        None => Err(parse_error("the [profile] table has no format".into()))?,
+    }
+    let parsed: Config = toml::Value::Table(settings.clone())
+        .try_into()
+        .map_err(|e| {
+            parse_error(format!(
+                "{} (written by minesweeper {version}, this is {})",
+                e.to_string().trim_end(),
+                env!("CARGO_PKG_VERSION")
+            ))
+        })?;
+    parsed.keymap()?;
+
+    let mut backup = config.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    let backup = match fs::rename(config, &backup) {
+        Ok(()) => Some(backup),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(error) => Err(Error::SaveIo {
+            path: backup,
+            error,
+        })?,
+    };
+    if let Some(dir) = config.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = format!(
+        "# Minesweeper configuration, imported from a settings profile. Command-line options \
+         override these settings.\n\n{}",
+        toml::to_string(&settings)?
+    );
+    fs::write(config, text).map_err(|error| Error::SaveIo {
+        path: config.to_path_buf(),
+        error,
+    })?;
+    Ok(backup)
+}
+
+/// Allowed `tick-ms` values: faster only burns CPU, slower makes the timer visibly stutter.
+pub const TICK_MS: RangeInclusive<u64> = 10..=1000;
+
+fn tick_ms<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let ms = u64::deserialize(deserializer)?;
+    if TICK_MS.contains(&ms) {
+        Ok(ms)
+    } else {
+        Err(de::Error::custom(format!(
+            "tick-ms must be from {} to {}, not {ms}",
+            TICK_MS.start(),
+            TICK_MS.end()
+        )))
+    }
+}
+
+/// Allowed `max-fps` values: terminals gain nothing from more, and fewer makes motion stutter.
+pub const MAX_FPS: RangeInclusive<u32> = 1..=120;
+
+fn max_fps<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let fps = u32::deserialize(deserializer)?;
+    if MAX_FPS.contains(&fps) {
+        Ok(fps)
+    } else {
+        Err(de::Error::custom(format!(
+            "max-fps must be from {} to {}, not {fps}",
+            MAX_FPS.start(),
+            MAX_FPS.end()
+        )))
+    }
+}
+
+/// Allowed `retries` values: enough to ride out a restarting server, few enough to give up
+/// within a couple of minutes.
+pub const RETRIES: RangeInclusive<u32> = 0..=10;
+
+fn retries<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let retries = u32::deserialize(deserializer)?;
+    if RETRIES.contains(&retries) {
+        Ok(retries)
+    } else {
+        Err(de::Error::custom(format!(
+            "retries must be from {} to {}, not {retries}",
+            RETRIES.start(),
+            RETRIES.end()
+        )))
+    }
+}
+
+/// The `[colors]` table: the classic palette with the colors it names replaced, keyed `1` to
+/// `8` for the numbers, `hidden`, `flag` and `zero`.
+fn palette<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Palette, D::Error> {
+    let mut palette = Palette::default();
+    for (name, color) in BTreeMap::<String, String>::deserialize(deserializer)? {
+        let slot = match name.as_str() {
+            "hidden" => &mut palette.hidden,
+            "flag" => &mut palette.flag,
+            "zero" => &mut palette.zero,
+            digit => match digit.parse::<usize>() {
+                Ok(n @ 1..=8) => &mut palette.numbers[n - 1],
+                _ => {
+                    return Err(de::Error::custom(format!(
+                        "unknown color '{name}': expected 1 to 8, hidden, flag or zero"
+                    )));
+                }
+            },
+        };
+        *slot = color
+            .parse()
+            .map_err(|_| de::Error::custom(format!("'{color}' is not a color (for {name})")))?;
+    }
+    Ok(palette)
+}
+
+/// The `[glyphs]` table: the usual cell symbols with the ones it names replaced, each a single
+/// character one terminal column wide so the board stays aligned.
+fn glyphs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Glyphs, D::Error> {
+    let mut glyphs = Glyphs::default();
+    for (name, glyph) in BTreeMap::<String, String>::deserialize(deserializer)? {
+        let slot = match name.as_str() {
+            "hidden" => &mut glyphs.hidden,
+            "flag" => &mut glyphs.flag,
+            "zero" => &mut glyphs.zero,
+            "mine" => &mut glyphs.mine,
+            _ => {
+                return Err(de::Error::custom(format!(
+                    "unknown glyph '{name}': expected hidden, flag, zero or mine"
+                )));
+            }
+        };
+        let mut chars = glyph.chars();
+        *slot = match (chars.next(), chars.next()) {
+            (Some(c), None) if !c.is_control() && Span::raw(glyph.as_str()).width() == 1 => c,
+            _ => {
+                return Err(de::Error::custom(format!(
+                    "the {name} glyph must be one character one column wide, not '{glyph}'"
+                )));
+            }
+        };
+    }
+    Ok(glyphs)
+}
+
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_file_matches_defaults() {
+        let parsed: Config = toml::from_str(DEFAULT_CONFIG).expect("default config parses");
+        assert_eq!(parsed, Config::default());
+    }
+
+    #[test]
+    fn parses_settings_and_keys() {
+        let config: Config = toml::from_str(
+            r#"
+            difficulty = "hard"
+            theme = "mono"
+            tick-ms = 200
+            [gameplay]
+            double-tap-ms = 0
+            [keys]
+            x = "reveal"
+            "#,
+        )
+        .expect("valid config");
+        assert_eq!(config.difficulty, Difficulty::Hard);
+        assert_eq!(config.appearance().theme, Theme::Mono);
+        assert_eq!(config.double_tap_timeout(), None);
+        assert_eq!(config.tick(), Duration::from_millis(200));
+        assert!(config.gameplay.show_footer);
+        assert!(config.keymap().is_ok());
+
+        let config: Config = toml::from_str("[log]\npath = \"games.jsonl\"").expect("valid");
+        assert_eq!(config.log_path(), Some(PathBuf::from("games.jsonl")));
+        let config: Config = toml::from_str("[log]\nenabled = false").expect("valid");
+        assert_eq!(config.log_path(), None);
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        assert!(toml::from_str::<Config>("difficulty = \"insane\"").is_err());
+        assert!(toml::from_str::<Config>("colour = \"red\"").is_err());
+        assert!(toml::from_str::<Config>("tick-ms = 0").is_err());
+        assert!(toml::from_str::<Config>("tick-ms = 5000").is_err());
+        assert!(toml::from_str::<Config>("max-fps = 0").is_err());
+        assert!(toml::from_str::<Config>("[submit]\nretries = 11").is_err());
+        let config: Config = toml::from_str("[keys]\nx = \"explode\"").expect("valid toml");
+        assert!(config.keymap().is_err());
+    }
+
+    #[test]
+    fn colors_replace_the_classic_ones() {
+        use ratatui::style::Color;
+        let config: Config =
+            toml::from_str("[colors]\n1 = \"lightblue\"\nflag = \"#ff8000\"\nzero = \"240\"")
+                .expect("valid colors");
+        let palette = config.appearance().palette;
+        assert_eq!(palette.numbers[0], Color::LightBlue);
+        assert_eq!(palette.numbers[1], Palette::default().numbers[1]);
+        assert_eq!(palette.flag, Color::Rgb(255, 128, 0));
+        assert_eq!(palette.zero, Color::Indexed(240));
+        assert!(toml::from_str::<Config>("[colors]\n9 = \"red\"").is_err());
+        assert!(toml::from_str::<Config>("[colors]\nhidden = \"grey-ish\"").is_err());
+    }
+
+    #[test]
+    fn glyphs_must_take_one_column() {
+        let config: Config =
+            toml::from_str("[glyphs]\nhidden = \"▒\"\nmine = \"X\"").expect("valid glyphs");
+        let glyphs = config.appearance().glyphs;
+        assert_eq!((glyphs.hidden, glyphs.mine), ('▒', 'X'));
+        assert_eq!(glyphs.flag, Glyphs::default().flag);
+        for bad in ["\"\"", "\"##\"", "\"💣\"", "\"\\t\""] {
+            assert!(
+                toml::from_str::<Config>(&format!("[glyphs]\nflag = {bad}")).is_err(),
+                "{bad}"
+            );
+        }
+        assert!(toml::from_str::<Config>("[glyphs]\nquestioned = \"?\"").is_err());
+    }
+
+    #[test]
+    fn profiles_carry_settings_between_configs() {
+        let dir = std::env::temp_dir().join(format!("minesweeper-profile-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir");
+        let (source, profile, target) = (
+            dir.join("source.toml"),
+            dir.join("profile.toml"),
+            dir.join("target.toml"),
+        );
+        let settings = "theme = \"mono\"\n[log]\npath = \"/mine.jsonl\"\n[keys]\nx = \"reveal\"\n";
+        fs::write(&source, settings).expect("source written");
+        fs::write(&target, "ascii = true\n").expect("target written");
+        export_profile(&source, &profile).expect("exported");
+        let backup = import_profile(&profile, &target).expect("imported");
+
+        let imported = Config::load_or_create(&target).expect("valid config");
+        assert_eq!(imported.theme, Theme::Mono);
+        assert_eq!(imported.keys.get("x").map(String::as_str), Some("reveal"));
+        assert_eq!(imported.log.path, None, "paths stay on their machine");
+        assert!(!imported.ascii);
+        assert_eq!(backup, Some(dir.join("target.toml.bak")));
+
+        let text = fs::read_to_string(&profile).expect("profile exists");
+        fs::write(&profile, text.replace("format = 1", "format = 2")).expect("rewritten");
+        assert!(import_profile(&profile, &target).is_err(), "newer format");
+        fs::write(&profile, "[profile]\nformat = 1\n[keys]\nx = \"explode\"\n").expect("written");
+        assert!(import_profile(&profile, &target).is_err(), "unknown action");
+        assert!(import_profile(&source, &target).is_err(), "not a profile");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::net::{Message, Peer};
+use minesweeper::app::{Action, AppState, Command, Cursor, Status};
+use minesweeper::board::CellState;
+use serde::{Deserialize, Serialize};
+
+/// A change to the shared board. Flags are sent as set/clear rather than toggles so two
+/// players flagging the same cell at once agree on the result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Move {
+    /// Reveal a hidden cell.
+    Reveal,
+    /// Flag a hidden cell.
+    Flag,
+    /// Remove a flag.
+    Unflag,
+    /// Reveal around a satisfied number.
+    Chord,
+}
+
+impl Move {
+    /// Whether the move still makes sense on a cell in `state`. A move made against a cell
+    /// the other player changed in the meantime is dropped.
+    const fn fits(self, state: CellState) -> bool {
+        matches!(
+            (self, state),
+            (Self::Reveal | Self::Flag, CellState::Hidden)
+                | (Self::Unflag, CellState::Flagged)
+                | (Self::Chord, CellState::Revealed(1..))
+        )
+    }
+
+    const fn action(self) -> Action {
+        match self {
+            Self::Reveal => Action::Reveal,
+            Self::Flag | Self::Unflag => Action::ToggleFlag,
+            Self::Chord => Action::Chord,
+        }
+    }
+}
+
+/// What is known about the other player, for drawing their cursor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Partner {
+    /// Their selected cell, once they have sent it.
+    pub cursor: Option<Cursor>,
+    /// They quit or the connection dropped.
+    pub left: bool,
+}
+
+/// Two players on one board.
+///
+/// The host orders all moves: its own are applied at once and forwarded, the guest's are
+/// sent to the host, checked against the board as it is by then, and echoed back if they
+/// still apply. Both sides apply the same moves in the same order to the same seeded board,
+/// so they stay in step, and the mine counter is shared as a matter of course.
+pub struct Coop {
+    peer: Peer,
+    host: bool,
+    /// The other player's cursor.
+    pub partner: Partner,
+    /// Last cursor position sent, so only changes go over the wire.
+    sent: Option<Cursor>,
+}
+
+impl Coop {
+    /// Play together over an established connection; `host` decides who orders moves.
+    pub fn new(peer: Peer, host: bool) -> Self {
+        Self {
+            peer,
+            host,
+            partner: Partner::default(),
+            sent: None,
+        }
+    }
+
+    /// The connection, to read from in the background.
+    pub const fn peer(&self) -> &Peer {
+        &self.peer
+    }
+
+    /// Handle a local action: board changes go through the host, everything else (cursor
+    /// movement, panels, dialogs) stays local.
+    pub fn handle_action(&mut self, app: &mut AppState, action: Action) -> Command {
+        let Cursor { x, y } = app.cursor;
+        let state = app.board.cell_at(x, y);
+        let mv = match action {
+            Action::Reveal => Move::Reveal,
+            Action::ToggleFlag if state == CellState::Flagged => Move::Unflag,
+            Action::ToggleFlag => Move::Flag,
+            Action::Chord => Move::Chord,
+            Action::FlagAndAdvance => {
+                if state == CellState::Hidden {
+                    self.submit(app, Move::Flag, x, y);
+                }
+                return step(app);
+            }
+            // One move per cell would be needed for the host to confirm it.
+            Action::OpenRemaining => return Command::None,
+            _ => return app.handle_action(action),
+        };
+        self.submit(app, mv, x, y)
+    }
+
+    /// Record a message from the other player, applying any move it carries.
+    pub fn receive(&mut self, app: &mut AppState, message: &Message) {
+        match *message {
+            Message::Play { mv, x, y } => {
+                if self.host {
+                    // The guest's move: apply it if it still fits, then confirm it.
+                    self.submit(app, mv, x, y);
+                } else {
+                    apply(app, mv, x, y);
+                }
+            }
+            Message::Cursor { x, y } => self.partner.cursor = Some(Cursor::new(x, y)),
+            Message::Bye => self.partner.left = true,
+            Message::Hello { .. } | Message::Progress { .. } | Message::Finished { .. } => {}
+        }
+    }
+
+    /// Tell the other player where the cursor is, if it moved.
+    pub fn report(&mut self, app: &AppState) {
+        if self.sent != Some(app.cursor) {
+            self.sent = Some(app.cursor);
+            self.peer.send(&Message::Cursor {
+                x: app.cursor.x,
+                y: app.cursor.y,
+            });
+        }
+    }
+
+    /// Play a move: the host applies and forwards it, the guest asks the host.
+    fn submit(&mut self, app: &mut AppState, mv: Move, x: usize, y: usize) -> Command {
+        if !self.host {
+            self.peer.send(&Message::Play { mv, x, y });
+            return Command::None;
+        }
+        let cmd = apply(app, mv, x, y);
+        if cmd != Command::None {
+            self.peer.send(&Message::Play { mv, x, y });
+        }
+        cmd
+    }
+}
+
+impl Drop for Coop {
+    fn drop(&mut self) {
+        self.peer.send(&Message::Bye);
+    }
+}
+
+/// Apply a move at `(x, y)` if the game is on and the cell still fits it, leaving the
+/// local cursor where it was.
+fn apply(app: &mut AppState, mv: Move, x: usize, y: usize) -> Command {
+    if app.status != Status::Playing
+        || x >= app.board.width()
+        || y >= app.board.height()
+        || !mv.fits(app.board.cell_at(x, y))
+    {
+        return Command::None;
+    }
+    let cursor = app.cursor;
+    app.cursor = Cursor::new(x, y);
+    let cmd = app.handle_action(mv.action());
+    app.cursor = cursor;
+    match cmd {
+        Command::None => Command::Redraw,
+        cmd => cmd,
+    }
+}
+
+/// The cursor move `FlagAndAdvance` makes after flagging: one step the way it last moved.
+fn step(app: &mut AppState) -> Command {
+    let action = match app.last_move {
+        (-1, -1) => Action::MoveUpLeft,
+        (0, -1) => Action::MoveUp,
+        (1, -1) => Action::MoveUpRight,
+        (-1, 0) => Action::MoveLeft,
+        (-1, 1) => Action::MoveDownLeft,
+        (0, 1) => Action::MoveDown,
+        (1, 1) => Action::MoveDownRight,
+        _ => Action::MoveRight,
+    };
+    app.handle_action(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_moves_are_dropped() {
+        assert!(Move::Flag.fits(CellState::Hidden));
+        assert!(!Move::Flag.fits(CellState::Flagged));
+        assert!(!Move::Reveal.fits(CellState::Revealed(0)));
+        assert!(Move::Chord.fits(CellState::Revealed(2)));
+        assert!(!Move::Chord.fits(CellState::Revealed(0)));
+    }
+
+    #[test]
+    fn moves_apply_away_from_the_cursor() {
+        let mut app = AppState::new(minesweeper::difficulty::Difficulty::Easy);
+        app.board.place_mines_at(&[(8, 8)]);
+        assert_eq!(apply(&mut app, Move::Flag, 8, 8), Command::Redraw);
+        // Both players flagged the same mine: the second flag no longer fits.
+        assert_eq!(apply(&mut app, Move::Flag, 8, 8), Command::None);
+        assert_eq!(app.flags_placed, 1);
+        assert_eq!(app.cursor, Cursor::new(0, 0));
+        assert_eq!(apply(&mut app, Move::Reveal, 0, 0), Command::GameWon);
+    }
+}
@@ -0,0 +1,136 @@
+use rand::{Rng, rng};
+use std::time::Duration;
+use web_time::Instant;
+
+/// How long the player has to enter the whole sequence.
+pub const WINDOW: Duration = Duration::from_secs(3);
+/// Arrows in a sequence.
+const LENGTH: usize = 4;
+
+/// One step of a defusal sequence, entered with the cursor keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrow {
+    /// Up.
+    Up,
+    /// Down.
+    Down,
+    /// Left.
+    Left,
+    /// Right.
+    Right,
+}
+
+impl Arrow {
+    const ALL: [Self; 4] = [Self::Up, Self::Down, Self::Left, Self::Right];
+
+    /// The arrow as drawn in the defusal dialog.
+    pub const fn glyph(self) -> char {
+        match self {
+            Self::Up => '↑',
+            Self::Down => '↓',
+            Self::Left => '←',
+            Self::Right => '→',
+        }
+    }
+}
+
+/// Where a defusal stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// More arrows are needed and there is still time.
+    Pending,
+    /// The whole sequence was entered in time.
+    Defused,
+    /// A wrong arrow was entered or time ran out.
+    Exploded,
+}
+
+/// A second chance after hitting a mine ([`AppState::defuse_penalty`]): enter a short
+/// random arrow sequence within [`WINDOW`] and the mine is flagged instead of going off.
+///
+/// [`AppState::defuse_penalty`]: crate::app::AppState::defuse_penalty
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defusal {
+    /// The arrows to enter, in order.
+    pub sequence: Vec<Arrow>,
+    /// How many of them have been entered.
+    pub entered: usize,
+    /// When time runs out.
+    pub deadline: Instant,
+    /// The mines that went off, flagged if the defusal succeeds.
+    pub tripped: Vec<(usize, usize)>,
+}
+
+impl Defusal {
+    /// A defusal of the `tripped` mines with a random sequence, its time starting `now`.
+    pub fn new(tripped: Vec<(usize, usize)>, now: Instant) -> Self {
+        let mut rng = rng();
+        Self {
+            sequence: (0..LENGTH)
+                .map(|_| Arrow::ALL[rng.random_range(0..Arrow::ALL.len())])
+                .collect(),
+            entered: 0,
+            deadline: now + WINDOW,
+            tripped,
+        }
+    }
+
+    /// Enter `arrow` at `now`: the next one in the sequence moves on, anything else, or
+    /// anything after the deadline, sets the mine off.
+    pub fn press(&mut self, arrow: Arrow, now: Instant) -> Outcome {
+        if self.check(now) == Outcome::Exploded || self.sequence.get(self.entered) != Some(&arrow) {
+            return Outcome::Exploded;
+        }
+        self.entered += 1;
+        self.check(now)
+    }
+
+    /// Where the defusal stands at `now`, without entering anything.
+    pub fn check(&self, now: Instant) -> Outcome {
+        if self.entered == self.sequence.len() {
+            Outcome::Defused
+        } else if now >= self.deadline {
+            Outcome::Exploded
+        } else {
+            Outcome::Pending
+        }
+    }
+
+    /// Time left at `now`.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.deadline.saturating_duration_since(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_sequence_defuses_in_time() {
+        let now = Instant::now();
+        let mut defusal = Defusal::new(vec![(1, 2)], now);
+        assert_eq!(defusal.sequence.len(), LENGTH);
+        let sequence = defusal.sequence.clone();
+        let (last, first) = sequence.split_last().expect("not empty");
+        for &arrow in first {
+            assert_eq!(defusal.press(arrow, now), Outcome::Pending);
+        }
+        assert_eq!(defusal.press(*last, now), Outcome::Defused);
+        assert_eq!(defusal.check(now + WINDOW), Outcome::Defused);
+    }
+
+    #[test]
+    fn wrong_arrows_and_slow_hands_explode() {
+        let now = Instant::now();
+        let mut defusal = Defusal::new(Vec::new(), now);
+        let first = defusal.sequence[0];
+        let wrong = Arrow::ALL.into_iter().find(|&arrow| arrow != first);
+        assert_eq!(
+            defusal.press(wrong.expect("four arrows"), now),
+            Outcome::Exploded
+        );
+        assert_eq!(defusal.press(first, now + WINDOW), Outcome::Exploded);
+        assert_eq!(defusal.remaining(now + WINDOW), Duration::ZERO);
+    }
+}
@@ -1,8 +1,25 @@
+use crate::board::Board;
+use crate::error::Error;
+use std::str::FromStr;
+
+/// Preset board sizes and mine counts, or a custom board.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Difficulty {
+    /// 9x9 with 10 mines.
     Easy,
+    /// 16x16 with 40 mines.
     Medium,
+    /// 30x16 with 99 mines.
     Hard,
+    /// Any size and mine count, e.g. from the command line.
+    Custom {
+        /// Board width in cells.
+        width: usize,
+        /// Board height in cells.
+        height: usize,
+        /// Number of mines; must be less than `width * height`.
+        mines: usize,
+    },
 }
 
 impl Difficulty {
@@ -16,15 +33,61 @@ impl Difficulty {
             Self::Easy => (9, 9, 10),
             Self::Medium => (16, 16, 40),
             Self::Hard => (30, 16, 99),
+            Self::Custom {
+                width,
+                height,
+                mines,
+            } => (width, height, mines),
+        }
+    }
+
+    /// `"easy"`, `"medium"`, `"hard"` or `"custom"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+            Self::Custom { .. } => "custom",
         }
     }
 
-    /// Cycle to the next difficulty in order: Easy -> Medium -> Hard -> Easy
+    /// Cycle to the next difficulty in order: Easy -> Medium -> Hard -> Easy.
+    /// A custom board cycles back to Easy.
     pub const fn cycle(self) -> Self {
         match self {
             Self::Easy => Self::Medium,
             Self::Medium => Self::Hard,
-            Self::Hard => Self::Easy,
+            Self::Hard | Self::Custom { .. } => Self::Easy,
+        }
+    }
+
+    /// A custom board, checked with [`Board::check_size`] so it can be played.
+    ///
+    /// # Errors
+    /// Returns an error describing the problem if the board cannot be played.
+    pub fn custom(width: usize, height: usize, mines: usize) -> Result<Self, Error> {
+        Board::check_size(width, height, mines)?;
+        Ok(Self::Custom {
+            width,
+            height,
+            mines,
+        })
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = Error;
+
+    /// Parse a preset name: `easy`, `medium` or `hard` (also `beginner`, `intermediate`,
+    /// `expert`), ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" | "beginner" => Ok(Self::Easy),
+            "medium" | "intermediate" => Ok(Self::Medium),
+            "hard" | "expert" => Ok(Self::Hard),
+            _ => Err(Error::Parse(format!(
+                "unknown difficulty '{s}' (expected easy, medium or hard)"
+            ))),
         }
     }
 }
@@ -46,4 +109,24 @@ mod tests {
         assert_eq!(Difficulty::Medium.cycle(), Difficulty::Hard);
         assert_eq!(Difficulty::Hard.cycle(), Difficulty::Easy);
     }
+
+    #[test]
+    fn parses_preset_names() {
+        assert_eq!("Hard".parse::<Difficulty>().ok(), Some(Difficulty::Hard));
+        assert_eq!(
+            "beginner".parse::<Difficulty>().ok(),
+            Some(Difficulty::Easy)
+        );
+        assert!("insane".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn custom_rejects_unplayable_boards() {
+        assert_eq!(
+            Difficulty::custom(5, 4, 3).ok().map(Difficulty::parameters),
+            Some((5, 4, 3))
+        );
+        assert!(Difficulty::custom(0, 4, 0).is_err());
+        assert!(Difficulty::custom(2, 2, 4).is_err());
+    }
 }
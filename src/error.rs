@@ -1,21 +1,80 @@
-use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error as ThisError;
 
 type StdResult<T> = std::result::Result<T, Error>;
 
-#[derive(Debug)]
+/// Errors reported by the game.
+#[derive(Debug, ThisError)]
 pub enum Error {
-    Generic(String),
+    /// Raw mode, the alternate screen or the terminal backend could not be set up.
+    #[error("cannot set up the terminal: {0}")]
+    TerminalInit(io::Error),
+    /// A config file that is not valid TOML or has unknown settings.
+    #[error("invalid config {}: {message}", path.display())]
+    ConfigParse {
+        /// The config file.
+        path: PathBuf,
+        /// What is wrong with it.
+        message: String,
+    },
+    /// A file that exists could not be read.
+    #[error("cannot read {}: {error}", path.display())]
+    LoadIo {
+        /// The file.
+        path: PathBuf,
+        /// Why reading failed.
+        error: io::Error,
+    },
+    /// A file could not be written.
+    #[error("cannot save {}: {error}", path.display())]
+    SaveIo {
+        /// The file.
+        path: PathBuf,
+        /// Why writing failed.
+        error: io::Error,
+    },
+    /// A board that cannot be played or generated, or whose cells contradict each other.
+    #[error("{0}")]
+    InvalidBoard(String),
+    /// Game state that broke one of its invariants.
+    #[error("{0}")]
+    InvalidState(String),
+    /// Text that should name a board code, difficulty or move but does not.
+    #[error("{0}")]
+    Parse(String),
+    /// A move the game does not allow: on a cell off the board, or after the game ended.
+    #[error("{0}")]
+    IllegalMove(String),
+    /// A recorded game that does not replay to a win.
+    #[error("{0}")]
+    InvalidReplay(String),
+    /// The connection to the other player failed or they sent something unexpected.
+    #[error("{0}")]
+    Network(String),
+    /// Any other I/O failure.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// JSON that could not be read or written.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::Generic(msg) => write!(f, "{}", msg),
-        }
-    }
-}
-
-impl std::error::Error for Error {}
-
+/// Result type using the game's [`Error`].
 pub type Result<T> = StdResult<T>;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_name_the_file_and_cause() {
+        let error = Error::SaveIo {
+            path: PathBuf::from("out.svg"),
+            error: io::Error::new(io::ErrorKind::PermissionDenied, "read-only"),
+        };
+        assert_eq!(error.to_string(), "cannot save out.svg: read-only");
+        let json = serde_json::from_str::<u8>("x").map_err(Error::from);
+        assert!(matches!(json, Err(Error::Json(_))));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::app::Status;
+use crate::movelog::{Entry, Move};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Most events kept waiting; older ones are dropped for front ends that never take them.
+const CAPACITY: usize = 1024;
+
+/// Something that happened to the game, as [`AppState::events`] hands it out.
+///
+/// [`AppState::events`]: crate::app::AppState::events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A move opened cells: a reveal, chord or opening of the rest, at (`x`, `y`).
+    CellsRevealed {
+        /// Column of the cell the move was made on.
+        x: usize,
+        /// Row of the cell the move was made on.
+        y: usize,
+        /// How many cells opened.
+        opened: usize,
+    },
+    /// A flag was placed on or taken off (`x`, `y`).
+    FlagToggled {
+        /// Column of the cell.
+        x: usize,
+        /// Row of the cell.
+        y: usize,
+        /// Whether the cell is flagged now.
+        flagged: bool,
+    },
+    /// The game was won or lost.
+    GameEnded {
+        /// [`Status::Win`] or [`Status::Lose`].
+        status: Status,
+        /// The time on the timer.
+        time: Duration,
+    },
+    /// The last move was taken back.
+    Undone,
+    /// A new game was set up, with no move made yet.
+    NewGame,
+}
+
+impl Event {
+    /// The event a logged move amounts to, if it changed the board in a way followers care
+    /// about.
+    pub(crate) const fn of_move(entry: &Entry) -> Option<Self> {
+        let (x, y) = (entry.x, entry.y);
+        match entry.action {
+            Move::Revealed {
+                number: Some(_),
+                opened,
+            }
+            | Move::Chorded { opened, .. }
+            | Move::OpenedRest { opened, .. }
+                if opened > 0 =>
+            {
+                Some(Self::CellsRevealed { x, y, opened })
+            }
+            Move::Flagged => Some(Self::FlagToggled {
+                x,
+                y,
+                flagged: true,
+            }),
+            Move::Unflagged => Some(Self::FlagToggled {
+                x,
+                y,
+                flagged: false,
+            }),
+            Move::Undone => Some(Self::Undone),
+            _ => None,
+        }
+    }
+}
+
+/// The events of a game waiting to be taken, oldest first. The game emits onto it as it is
+/// played; a front end takes them all with [`drain`](Self::drain) after each change and
+/// hands them to whatever follows the game (sounds, saves, statistics, the network), so
+/// none of those has to be wired into the game itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventBus {
+    pending: VecDeque<Event>,
+}
+
+impl EventBus {
+    /// Add `event` to the waiting ones.
+    pub fn emit(&mut self, event: Event) {
+        if self.pending.len() == CAPACITY {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(event);
+    }
+
+    /// Take every waiting event, oldest first.
+    pub fn drain(&mut self) -> Vec<Event> {
+        self.pending.drain(..).collect()
+    }
+
+    /// Whether no event is waiting.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{Action, AppState, Cursor};
+    use crate::board::Board;
+    use crate::difficulty::Difficulty;
+
+    #[test]
+    fn moves_emit_events_in_order() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(1, 0)]);
+        app.events.drain();
+        app.handle_action(Action::Reveal);
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::Reveal);
+        let time = app.elapsed();
+        assert_eq!(
+            app.events.drain(),
+            [
+                Event::CellsRevealed {
+                    x: 0,
+                    y: 0,
+                    opened: 1
+                },
+                Event::FlagToggled {
+                    x: 1,
+                    y: 0,
+                    flagged: true
+                },
+                Event::FlagToggled {
+                    x: 1,
+                    y: 0,
+                    flagged: false
+                },
+                Event::GameEnded {
+                    status: Status::Lose,
+                    time
+                },
+            ]
+        );
+        assert!(app.events.is_empty());
+        app.handle_action(Action::Undo);
+        app.handle_action(Action::Restart);
+        assert_eq!(app.events.drain(), [Event::Undone, Event::NewGame]);
+    }
+
+    #[test]
+    fn unclaimed_events_are_dropped_oldest_first() {
+        let mut bus = EventBus::default();
+        bus.emit(Event::Undone);
+        for _ in 0..CAPACITY {
+            bus.emit(Event::NewGame);
+        }
+        let events = bus.drain();
+        assert_eq!(events.len(), CAPACITY);
+        assert!(!events.contains(&Event::Undone));
+    }
+}
@@ -0,0 +1,325 @@
+use crate::app::{AppState, Status};
+use crate::board::CellState;
+use std::fmt::Write;
+
+/// Size of one cell in exported images, in pixels.
+pub const CELL: usize = 24;
+
+type Rgb = [u8; 3];
+
+const HIDDEN: Rgb = [0xbd, 0xbd, 0xbd];
+const OPEN: Rgb = [0xe6, 0xe6, 0xe6];
+const GRID: Rgb = [0x7b, 0x7b, 0x7b];
+const FLAG: Rgb = [0xe0, 0x20, 0x20];
+const BLACK: Rgb = [0, 0, 0];
+/// Behind the cells cut out of a shaped board (left transparent in SVG).
+#[cfg(feature = "png")]
+const WHITE: Rgb = [0xff, 0xff, 0xff];
+
+/// Colors of the numbers 1-8, as in the classic game.
+const NUMBERS: [Rgb; 8] = [
+    [0x00, 0x00, 0xff],
+    [0x00, 0x80, 0x00],
+    [0xff, 0x00, 0x00],
+    [0x00, 0x00, 0x80],
+    [0x80, 0x00, 0x00],
+    [0x00, 0x80, 0x80],
+    [0x00, 0x00, 0x00],
+    [0x80, 0x80, 0x80],
+];
+
+/// How a cell is drawn. Mines and wrong flags only show once the game is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Look {
+    /// Cut out of a shaped board.
+    Void,
+    Hidden,
+    Flag,
+    WrongFlag,
+    Mine,
+    Open(u8),
+}
+
+fn look(app: &AppState, x: usize, y: usize) -> Look {
+    let over = app.status != Status::Playing;
+    let mine = app.board.is_mine(x, y);
+    match app.board.cell_at(x, y) {
+        _ if !app.board.in_play(x, y) => Look::Void,
+        CellState::Hidden if over && mine => Look::Mine,
+        CellState::Hidden => Look::Hidden,
+        CellState::Flagged if over && !mine => Look::WrongFlag,
+        CellState::Flagged => Look::Flag,
+        CellState::Revealed(n) => Look::Open(n),
+    }
+}
+
+fn cells(app: &AppState) -> impl Iterator<Item = (usize, usize, Look)> + '_ {
+    let board = &app.board;
+    (0..board.height()).flat_map(move |y| (0..board.width()).map(move |x| (x, y, look(app, x, y))))
+}
+
+fn hex([r, g, b]: Rgb) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// The board as an SVG image, [`CELL`] pixels per cell. Once the game is over it shows
+/// every mine and marks wrong flags with a cross.
+pub fn svg(app: &AppState) -> String {
+    let (w, h) = (app.board.width() * CELL, app.board.height() * CELL);
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" font-family=\"monospace\" font-weight=\"bold\" \
+         font-size=\"16\" text-anchor=\"middle\">\n"
+    );
+    // Writing to a String cannot fail.
+    for (x, y, look) in cells(app) {
+        if look == Look::Void {
+            continue;
+        }
+        let (left, top) = (x * CELL, y * CELL);
+        let fill = match look {
+            Look::Hidden | Look::Flag => HIDDEN,
+            _ => OPEN,
+        };
+        let _ = writeln!(
+            out,
+            "<rect x=\"{left}\" y=\"{top}\" width=\"{CELL}\" height=\"{CELL}\" fill=\"{}\" \
+             stroke=\"{}\"/>",
+            hex(fill),
+            hex(GRID)
+        );
+        match look {
+            Look::Void | Look::Hidden | Look::Open(0) => {}
+            Look::Open(n) => {
+                let _ = writeln!(
+                    out,
+                    "<text x=\"{}\" y=\"{}\" fill=\"{}\">{n}</text>",
+                    left + CELL / 2,
+                    top + 18,
+                    hex(NUMBERS[usize::from(n.clamp(1, 8)) - 1])
+                );
+            }
+            Look::Mine => {
+                let _ = writeln!(
+                    out,
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"6\" fill=\"{}\"/>",
+                    left + 12,
+                    top + 12,
+                    hex(BLACK)
+                );
+            }
+            Look::Flag | Look::WrongFlag => {
+                let _ = writeln!(
+                    out,
+                    "<rect x=\"{}\" y=\"{}\" width=\"2\" height=\"14\" fill=\"{black}\"/>\
+                     <polygon points=\"{},{} {},{} {},{}\" fill=\"{}\"/>",
+                    left + 12,
+                    top + 5,
+                    left + 13,
+                    top + 4,
+                    left + 13,
+                    top + 12,
+                    left + 6,
+                    top + 8,
+                    hex(FLAG),
+                    black = hex(BLACK)
+                );
+                if look == Look::WrongFlag {
+                    let _ = writeln!(
+                        out,
+                        "<path d=\"M{} {}l16 16m0 -16l-16 16\" stroke=\"{}\" \
+                         stroke-width=\"2\"/>",
+                        left + 4,
+                        top + 4,
+                        hex(BLACK)
+                    );
+                }
+            }
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// 3x5 pixel digits 1-8 for PNG export, one row per byte (high bit on the left).
+#[cfg(feature = "png")]
+const DIGITS: [[u8; 5]; 8] = [
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+];
+
+/// Whether pixel `(px, py)` of a cell (0 to [`CELL`] - 1 each way) is covered by `look`'s
+/// symbol, and in which color. Matches the shapes drawn by [`svg`].
+#[cfg(feature = "png")]
+fn symbol_pixel(look: Look, px: usize, py: usize) -> Option<Rgb> {
+    let (dx, dy) = (px.abs_diff(12), py.abs_diff(12));
+    let pole = (12..14).contains(&px) && (5..19).contains(&py);
+    let pennant =
+        (6..=13).contains(&px) && (4..=12).contains(&py) && 4 * (px - 6) >= 7 * py.abs_diff(8);
+    match look {
+        Look::Void | Look::Hidden | Look::Open(0) => None,
+        Look::Open(n) => {
+            // Digits are scaled 3x and centered: 9 by 15 pixels.
+            let digit = usize::from(n.clamp(1, 8)) - 1;
+            let (gx, gy) = (px.checked_sub(8)? / 3, py.checked_sub(5)? / 3);
+            let row = DIGITS[digit].get(gy)?;
+            (gx < 3 && row & (0b100 >> gx) != 0).then_some(NUMBERS[digit])
+        }
+        Look::Mine => (dx * dx + dy * dy <= 36).then_some(BLACK),
+        Look::Flag | Look::WrongFlag => {
+            let cross = look == Look::WrongFlag
+                && (4..=20).contains(&px)
+                && (4..=20).contains(&py)
+                && (px.abs_diff(py) <= 1 || (px + py).abs_diff(24) <= 1);
+            if cross || pole {
+                Some(BLACK)
+            } else {
+                pennant.then_some(FLAG)
+            }
+        }
+    }
+}
+
+/// The board as a PNG image, drawn like [`svg`].
+///
+/// # Errors
+/// Fails if the image is too large to encode.
+#[cfg(feature = "png")]
+pub fn png(app: &AppState) -> Result<Vec<u8>, png::EncodingError> {
+    let (w, h) = (app.board.width() * CELL, app.board.height() * CELL);
+    let mut pixels = vec![0; w * h * 3];
+    for (x, y, look) in cells(app) {
+        let fill = match look {
+            Look::Void => WHITE,
+            Look::Hidden | Look::Flag => HIDDEN,
+            _ => OPEN,
+        };
+        for py in 0..CELL {
+            for px in 0..CELL {
+                let edge =
+                    look != Look::Void && (px == 0 || py == 0 || px == CELL - 1 || py == CELL - 1);
+                let color = symbol_pixel(look, px, py).unwrap_or(if edge { GRID } else { fill });
+                let i = ((y * CELL + py) * w + x * CELL + px) * 3;
+                pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    let size = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, size(w), size(h));
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+/// A Wordle-style summary for sharing a result: a line with the board, outcome, time and
+/// board code (or seed), then the board as emoji. Numbers are not shown, so the grid does not give the
+/// board away to someone about to play the same seed.
+pub fn share_text(app: &AppState) -> String {
+    let board = &app.board;
+    let secs = app.elapsed_secs();
+    let outcome = match app.status {
+        Status::Win => "won in",
+        Status::Lose => "lost after",
+        Status::Playing => "playing for",
+    };
+    let mut out = format!(
+        "Minesweeper {} {}x{}: {outcome} {}:{:02}",
+        app.difficulty.name(),
+        board.width(),
+        board.height(),
+        secs / 60,
+        secs % 60
+    );
+    match (app.code, board.seed()) {
+        (Some(code), _) => {
+            let _ = write!(out, " ({code})");
+        }
+        (None, Some(seed)) => {
+            let _ = write!(out, " (seed {seed})");
+        }
+        (None, None) => {}
+    }
+    let mut row = usize::MAX;
+    for (_, y, look) in cells(app) {
+        if y != row {
+            row = y;
+            out.push('\n');
+        }
+        out.push_str(match look {
+            Look::Void => "  ",
+            Look::Hidden => "⬛",
+            Look::Flag => "🚩",
+            Look::WrongFlag => "❌",
+            Look::Mine => "💣",
+            Look::Open(0) => "⬜",
+            Look::Open(_) => "🟦",
+        });
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{Action, Cursor};
+    use crate::board::Board;
+    use crate::difficulty::Difficulty;
+
+    fn lost_game() -> AppState {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.handle_action(Action::ToggleFlag);
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::Reveal);
+        app
+    }
+
+    #[test]
+    fn svg_shows_mines_and_wrong_flags_after_the_game() {
+        let app = lost_game();
+        assert_eq!(look(&app, 0, 0), Look::WrongFlag);
+        assert_eq!(look(&app, 1, 0), Look::Open(1));
+        assert_eq!(look(&app, 2, 0), Look::Mine);
+        let svg = svg(&app);
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"72\" height=\"24\"")
+        );
+        assert!(svg.contains(">1</text>"));
+        assert!(svg.contains("<circle"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn share_text_hides_numbers() {
+        let mut app = lost_game();
+        app.board = app.board.clone().with_seed(9);
+        assert_eq!(
+            share_text(&app),
+            "Minesweeper easy 3x1: lost after 0:00 (seed 9)\n❌🟦💣\n"
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_has_the_board_size() {
+        let png = png(&lost_game()).expect("small image encodes");
+        assert_eq!(&png[1..4], b"PNG");
+        // The IHDR chunk starts with the width and height.
+        assert_eq!(&png[16..24], &[0, 0, 0, 72, 0, 0, 0, 24]);
+    }
+}
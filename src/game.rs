@@ -0,0 +1,321 @@
+use crate::app::{self, AppState, Cursor};
+use crate::board::CellState;
+use crate::difficulty::Difficulty;
+use crate::error::{Error, Result};
+use crate::events;
+use std::time::Duration;
+
+/// A move on a [`Game`]. Cells are given by 0-based column and row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Action {
+    /// Reveal a cell. The first reveal places the mines and is always safe.
+    Reveal {
+        /// Column.
+        x: usize,
+        /// Row.
+        y: usize,
+    },
+    /// Flag a hidden cell, or take its flag off.
+    ToggleFlag {
+        /// Column.
+        x: usize,
+        /// Row.
+        y: usize,
+    },
+    /// Reveal the neighbors of a number whose mines are all flagged.
+    Chord {
+        /// Column.
+        x: usize,
+        /// Row.
+        y: usize,
+    },
+    /// Take back the last move.
+    Undo,
+    /// Start a new game on a board of the same size.
+    Restart,
+}
+
+/// A cell as the player sees it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Cell {
+    /// Not revealed yet.
+    Hidden,
+    /// Flagged as a mine.
+    Flagged,
+    /// Revealed, with the number of mines around it.
+    Revealed(u8),
+    /// An unflagged mine, shown once the game is over.
+    Mine,
+}
+
+/// Where a [`Game`] stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Status {
+    /// The game is in progress.
+    Playing,
+    /// Every safe cell has been revealed.
+    Win,
+    /// A mine was revealed.
+    Lose,
+}
+
+impl From<app::Status> for Status {
+    fn from(status: app::Status) -> Self {
+        match status {
+            app::Status::Playing => Self::Playing,
+            app::Status::Win => Self::Win,
+            app::Status::Lose => Self::Lose,
+        }
+    }
+}
+
+/// Something that happened in a [`Game`], as taken by [`Game::events`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A move opened cells: a reveal, chord or opening of the rest, at (`x`, `y`).
+    CellsRevealed {
+        /// Column of the cell the move was made on.
+        x: usize,
+        /// Row of the cell the move was made on.
+        y: usize,
+        /// How many cells opened.
+        opened: usize,
+    },
+    /// A flag was placed on or taken off (`x`, `y`).
+    FlagToggled {
+        /// Column of the cell.
+        x: usize,
+        /// Row of the cell.
+        y: usize,
+        /// Whether the cell is flagged now.
+        flagged: bool,
+    },
+    /// The game was won or lost.
+    GameEnded {
+        /// [`Status::Win`] or [`Status::Lose`].
+        status: Status,
+        /// The time on the timer.
+        time: Duration,
+    },
+    /// The last move was taken back.
+    Undone,
+    /// A new game was set up, with no move made yet.
+    NewGame,
+}
+
+impl From<events::Event> for Event {
+    fn from(event: events::Event) -> Self {
+        match event {
+            events::Event::CellsRevealed { x, y, opened } => Self::CellsRevealed { x, y, opened },
+            events::Event::FlagToggled { x, y, flagged } => Self::FlagToggled { x, y, flagged },
+            events::Event::GameEnded { status, time } => Self::GameEnded {
+                status: status.into(),
+                time,
+            },
+            events::Event::Undone => Self::Undone,
+            events::Event::NewGame => Self::NewGame,
+        }
+    }
+}
+
+/// What a [`Game`] looks like at one moment, owned so it can be kept or sent elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Snapshot {
+    /// Board width in cells.
+    pub width: usize,
+    /// Board height in cells.
+    pub height: usize,
+    /// Number of mines.
+    pub mines: usize,
+    /// Number of flags placed.
+    pub flags: usize,
+    /// Whether the game is still being played, won or lost.
+    pub status: Status,
+    /// Time on the timer.
+    pub elapsed: Duration,
+    /// Every cell, row by row.
+    pub cells: Vec<Cell>,
+}
+
+impl Snapshot {
+    /// The cell at (`x`, `y`), if it is on the board.
+    pub fn cell(&self, x: usize, y: usize) -> Option<Cell> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
+    }
+}
+
+/// A game of Minesweeper behind a small API that is kept stable from release to release,
+/// for programs that embed the engine or play it (bots, scripts, servers). Moves name their
+/// cell, the state comes out as a [`Snapshot`] and what happened as [`Event`]s; how the
+/// game is kept inside ([`AppState`] and the modules around it) may change freely.
+///
+/// ```
+/// use minesweeper::difficulty::Difficulty;
+/// use minesweeper::game::{Action, Cell, Game, Status};
+///
+/// let mut game = Game::seeded(Difficulty::Easy, 7);
+/// let status = game.apply(Action::Reveal { x: 4, y: 4 })?;
+/// assert_eq!(status, Status::Playing); // the first reveal is always safe
+/// assert!(matches!(game.snapshot().cell(4, 4), Some(Cell::Revealed(_))));
+/// # Ok::<(), minesweeper::error::Error>(())
+/// ```
+pub struct Game {
+    app: AppState,
+}
+
+impl Game {
+    /// Start a game at `difficulty`, with mines placed at random on the first reveal.
+    pub fn new(difficulty: Difficulty) -> Self {
+        let mut app = AppState::new(difficulty);
+        // Every move names its cell, so revealing a number twice must not chord.
+        app.double_tap_timeout = None;
+        Self { app }
+    }
+
+    /// Start a game at `difficulty` whose mines follow from `seed` and the first reveal, so
+    /// the same moves always play out the same way.
+    pub fn seeded(difficulty: Difficulty, seed: u64) -> Self {
+        let mut game = Self::new(difficulty);
+        game.app.seed = Some(seed);
+        game.app.restart();
+        game
+    }
+
+    /// Make a move and return where the game stands after it.
+    ///
+    /// # Errors
+    /// Returns [`Error::IllegalMove`] for a cell off the board, or a move on a cell once the
+    /// game is over; the game is left as it was.
+    pub fn apply(&mut self, action: Action) -> Result<Status> {
+        let (x, y, action) = match action {
+            Action::Reveal { x, y } => (x, y, app::Action::Reveal),
+            Action::ToggleFlag { x, y } => (x, y, app::Action::ToggleFlag),
+            Action::Chord { x, y } => (x, y, app::Action::Chord),
+            Action::Undo => {
+                self.app.handle_action(app::Action::Undo);
+                return Ok(self.status());
+            }
+            Action::Restart => {
+                self.app.handle_action(app::Action::Restart);
+                return Ok(self.status());
+            }
+        };
+        let board = &self.app.board;
+        if x >= board.width() || y >= board.height() {
+            return Err(Error::IllegalMove(format!(
+                "({x}, {y}) is off the {}x{} board",
+                board.width(),
+                board.height()
+            )));
+        }
+        if self.app.status != app::Status::Playing {
+            return Err(Error::IllegalMove("the game is over".to_string()));
+        }
+        self.app.cursor = Cursor::new(x, y);
+        self.app.handle_action(action);
+        Ok(self.status())
+    }
+
+    /// Where the game stands.
+    pub fn status(&self) -> Status {
+        self.app.status.into()
+    }
+
+    /// The board, counters and timer as they are now.
+    pub fn snapshot(&self) -> Snapshot {
+        let board = &self.app.board;
+        let over = self.app.status != app::Status::Playing;
+        let cells = (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+            .map(|(x, y)| match board.cell_at(x, y) {
+                CellState::Hidden if over && board.is_mine(x, y) => Cell::Mine,
+                CellState::Hidden => Cell::Hidden,
+                CellState::Flagged => Cell::Flagged,
+                CellState::Revealed(n) => Cell::Revealed(n),
+            })
+            .collect();
+        Snapshot {
+            width: board.width(),
+            height: board.height(),
+            mines: board.mines(),
+            flags: self.app.flags_placed,
+            status: self.status(),
+            elapsed: self.app.elapsed(),
+            cells,
+        }
+    }
+
+    /// Take the events emitted since the last call, oldest first.
+    pub fn events(&mut self) -> Vec<Event> {
+        self.app
+            .events
+            .drain()
+            .into_iter()
+            .map(Event::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    /// A 3x1 game with the mine at the right end.
+    fn small_game() -> Game {
+        let mut game = Game::new(Difficulty::Easy);
+        game.app.board = Board::new(3, 1, 1);
+        game.app.board.place_mines_at(&[(2, 0)]);
+        game.events();
+        game
+    }
+
+    #[test]
+    fn moves_play_out_through_the_facade() {
+        let mut game = small_game();
+        assert_eq!(
+            game.apply(Action::ToggleFlag { x: 2, y: 0 }).ok(),
+            Some(Status::Playing)
+        );
+        assert_eq!(
+            game.apply(Action::Reveal { x: 0, y: 0 }).ok(),
+            Some(Status::Win)
+        );
+        let snapshot = game.snapshot();
+        assert_eq!(
+            snapshot.cells,
+            [Cell::Revealed(0), Cell::Revealed(1), Cell::Flagged]
+        );
+        assert_eq!((snapshot.flags, snapshot.cell(3, 0)), (1, None));
+        let events = game.events();
+        assert!(matches!(
+            events.last(),
+            Some(Event::GameEnded {
+                status: Status::Win,
+                ..
+            })
+        ));
+        assert!(game.events().is_empty(), "taken");
+        game.apply(Action::Restart).expect("always allowed");
+        assert_eq!(game.events(), [Event::NewGame]);
+    }
+
+    #[test]
+    fn illegal_moves_leave_the_game_alone() {
+        let mut game = small_game();
+        assert!(game.apply(Action::Reveal { x: 3, y: 0 }).is_err());
+        assert_eq!(game.snapshot().cells, [Cell::Hidden; 3]);
+        assert_eq!(
+            game.apply(Action::Reveal { x: 2, y: 0 }).ok(),
+            Some(Status::Lose)
+        );
+        assert_eq!(game.snapshot().cell(2, 0), Some(Cell::Mine));
+        assert!(game.apply(Action::Reveal { x: 0, y: 0 }).is_err(), "over");
+        assert_eq!(game.apply(Action::Undo).ok(), Some(Status::Playing));
+    }
+}
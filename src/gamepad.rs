@@ -0,0 +1,163 @@
+use crate::input::{Dir, InputAction, InputEvent};
+use gilrs::{Axis, Button, EventType, Gilrs};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// How far the left stick must be pushed before it counts as a movement.
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// Game controller input, translated into the same `InputAction`s as the keyboard.
+///
+/// Bindings:
+/// - Movement: d-pad or left stick (one cell per push; held d-pad buttons repeat)
+/// - Reveal: South (A / Cross)
+/// - Flag: East (B / Circle)
+/// - Chord: West (X / Square)
+/// - Hint: North (Y / Triangle)
+/// - Undo: left shoulder
+/// - `ToggleMode`: Select
+/// - Back: Start (closes the topmost overlay/dialog, or asks to quit)
+pub struct Gamepad {
+    gilrs: Gilrs,
+    stick: Stick,
+}
+
+impl Gamepad {
+    /// Open the platform gamepad backend, or `None` if it is unavailable.
+    pub fn new() -> Option<Self> {
+        Gilrs::new().ok().map(|gilrs| Self {
+            gilrs,
+            stick: Stick::default(),
+        })
+    }
+
+    /// The next pending action from any connected controller, without blocking.
+    pub fn next_action(&mut self) -> Option<InputAction> {
+        while let Some(ev) = self.gilrs.next_event() {
+            let action = match ev.event {
+                EventType::ButtonPressed(button, _) => button_action(button),
+                EventType::ButtonRepeated(button, _) => dpad_dir(button).map(InputAction::Move),
+                EventType::AxisChanged(axis, value, _) => self.stick.update(axis, value),
+                _ => None,
+            };
+            if action.is_some() {
+                return action;
+            }
+        }
+        None
+    }
+}
+
+/// Forward controller actions over `tx` from a dedicated thread, alongside the keyboard.
+///
+/// Does nothing if no gamepad backend is available; the thread ends once the receiver
+/// is dropped.
+pub fn spawn_reader(tx: UnboundedSender<InputEvent>) {
+    thread::spawn(move || {
+        let Some(mut pad) = Gamepad::new() else {
+            return;
+        };
+        loop {
+            while let Some(action) = pad.next_action() {
+                if tx.send(InputEvent::Action(action)).is_err() {
+                    return;
+                }
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    });
+}
+
+/// Map a pressed controller button to an action.
+pub fn button_action(button: Button) -> Option<InputAction> {
+    if let Some(dir) = dpad_dir(button) {
+        return Some(InputAction::Move(dir));
+    }
+    match button {
+        Button::South => Some(InputAction::Reveal),
+        Button::East => Some(InputAction::Flag),
+        Button::West => Some(InputAction::Chord),
+        Button::North => Some(InputAction::Hint),
+        Button::LeftTrigger => Some(InputAction::Undo),
+        Button::Select => Some(InputAction::ToggleMode),
+        Button::Start => Some(InputAction::Back),
+        _ => None,
+    }
+}
+
+fn dpad_dir(button: Button) -> Option<Dir> {
+    match button {
+        Button::DPadLeft => Some(Dir::Left),
+        Button::DPadRight => Some(Dir::Right),
+        Button::DPadUp => Some(Dir::Up),
+        Button::DPadDown => Some(Dir::Down),
+        _ => None,
+    }
+}
+
+/// Left stick position, so a push is reported once rather than on every axis update.
+#[derive(Debug, Default)]
+struct Stick {
+    x: i8,
+    y: i8,
+}
+
+impl Stick {
+    /// Record a new axis value, returning a move when the stick crosses the threshold.
+    fn update(&mut self, axis: Axis, value: f32) -> Option<InputAction> {
+        let zone = if value >= STICK_THRESHOLD {
+            1
+        } else if value <= -STICK_THRESHOLD {
+            -1
+        } else {
+            0
+        };
+        let (slot, dir) = match axis {
+            Axis::LeftStickX => (&mut self.x, [Dir::Left, Dir::Right]),
+            // gilrs reports up as positive Y
+            Axis::LeftStickY => (&mut self.y, [Dir::Down, Dir::Up]),
+            _ => return None,
+        };
+        if *slot == zone {
+            return None;
+        }
+        *slot = zone;
+        match zone {
+            -1 => Some(InputAction::Move(dir[0])),
+            1 => Some(InputAction::Move(dir[1])),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_buttons_map_to_actions() {
+        assert_eq!(button_action(Button::South), Some(InputAction::Reveal));
+        assert_eq!(button_action(Button::East), Some(InputAction::Flag));
+        assert_eq!(button_action(Button::West), Some(InputAction::Chord));
+        assert_eq!(
+            button_action(Button::DPadUp),
+            Some(InputAction::Move(Dir::Up))
+        );
+    }
+
+    #[test]
+    fn stick_moves_once_per_push() {
+        let mut stick = Stick::default();
+        assert_eq!(
+            stick.update(Axis::LeftStickX, 0.8),
+            Some(InputAction::Move(Dir::Right))
+        );
+        assert_eq!(stick.update(Axis::LeftStickX, 0.9), None);
+        assert_eq!(stick.update(Axis::LeftStickX, 0.1), None);
+        assert_eq!(
+            stick.update(Axis::LeftStickY, 0.7),
+            Some(InputAction::Move(Dir::Up))
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{Context, bail};
+use clap::{Args, ValueEnum};
+use minesweeper::board::{Board, Layout};
+use minesweeper::generator::{self, Spec};
+use minesweeper::solver::{self, Rating};
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Options for `minesweeper generate`. Size, mines and seed come from the global options.
+#[derive(Debug, Clone, Args)]
+pub struct GenerateArgs {
+    /// Only produce boards that can be cleared from the start cell without guessing.
+    #[arg(long)]
+    pub no_guess: bool,
+
+    /// Only produce boards the solver rates at least this hard from the start cell: trivial,
+    /// easy, hard or guessy.
+    #[arg(long, value_name = "RATING")]
+    pub min_rating: Option<Rating>,
+
+    /// Only produce boards the solver rates at most this hard from the start cell.
+    #[arg(long, value_name = "RATING")]
+    pub max_rating: Option<Rating>,
+
+    /// First cell revealed, as column and row [default: the center].
+    #[arg(long, num_args = 2, value_names = ["X", "Y"])]
+    pub start: Option<Vec<usize>>,
+
+    /// Output format.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: Format,
+
+    /// Write to a file instead of stdout.
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+/// Board file formats `generate` can write.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// One line per row, `*` for mines and `.` for safe cells, after a `#` header line.
+    #[default]
+    Text,
+    /// Minesweeper Board Format: width, height (one byte each), mine count (two bytes,
+    /// big-endian), then one x/y byte pair per mine.
+    Mbf,
+    /// A JSON object with the size, seed, start cell and rows.
+    Json,
+}
+
+/// The JSON document written by `--format json`.
+#[derive(Debug, Serialize)]
+struct JsonBoard {
+    width: usize,
+    height: usize,
+    mines: usize,
+    seed: Option<u64>,
+    start: (usize, usize),
+    no_guess: bool,
+    /// How hard the board is to clear, if it is small enough to rate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rating: Option<&'static str>,
+    rows: Vec<String>,
+}
+
+/// Generate a board and write it out.
+///
+/// # Errors
+/// Fails if no board can be generated, the format cannot hold it, or writing fails.
+pub fn run(
+    args: &GenerateArgs,
+    size: (usize, usize, usize),
+    seed: Option<u64>,
+    layout: Layout,
+) -> anyhow::Result<()> {
+    let (width, height, mines) = size;
+    let start = match args.start.as_deref() {
+        Some(&[x, y]) => (x, y),
+        _ => (width / 2, height / 2),
+    };
+    let spec = Spec {
+        width,
+        height,
+        mines,
+        start,
+        seed,
+        no_guess: args.no_guess,
+        min_rating: args.min_rating,
+        max_rating: args.max_rating,
+        layout,
+    };
+    let board = generator::generate(&spec)?;
+    let bytes = encode(&board, &spec, args.format)?;
+    match &args.output {
+        Some(path) => {
+            fs::write(path, bytes).with_context(|| format!("cannot write {}", path.display()))
+        }
+        None => io::stdout()
+            .write_all(&bytes)
+            .context("cannot write to stdout"),
+    }
+}
+
+fn rows(board: &Board) -> Vec<String> {
+    (0..board.height())
+        .map(|y| {
+            (0..board.width())
+                .map(|x| if board.is_mine(x, y) { '*' } else { '.' })
+                .collect()
+        })
+        .collect()
+}
+
+fn encode(board: &Board, spec: &Spec, format: Format) -> anyhow::Result<Vec<u8>> {
+    let (sx, sy) = spec.start;
+    match format {
+        Format::Text => {
+            let seed = board
+                .seed()
+                .map_or_else(String::new, |s| format!(", seed {s}"));
+            let rating = solver::rate_from(board, spec.start)
+                .map_or_else(String::new, |rating| format!(", {}", rating.name()));
+            let mut text = format!(
+                "# {}x{}, {} mines{seed}, start {sx} {sy}{rating}\n",
+                board.width(),
+                board.height(),
+                board.mines()
+            );
+            for row in rows(board) {
+                text.push_str(&row);
+                text.push('\n');
+            }
+            Ok(text.into_bytes())
+        }
+        Format::Mbf => {
+            let (Ok(w), Ok(h), Ok(m)) = (
+                u8::try_from(board.width()),
+                u8::try_from(board.height()),
+                u16::try_from(board.mines()),
+            ) else {
+                bail!("MBF holds at most 255x255 boards with 65535 mines");
+            };
+            let mut bytes = vec![w, h];
+            bytes.extend_from_slice(&m.to_be_bytes());
+            for y in 0..h {
+                for x in 0..w {
+                    if board.is_mine(usize::from(x), usize::from(y)) {
+                        bytes.extend_from_slice(&[x, y]);
+                    }
+                }
+            }
+            Ok(bytes)
+        }
+        Format::Json => {
+            let json = JsonBoard {
+                width: board.width(),
+                height: board.height(),
+                mines: board.mines(),
+                seed: board.seed(),
+                start: spec.start,
+                no_guess: spec.no_guess,
+                rating: solver::rate_from(board, spec.start).map(Rating::name),
+                rows: rows(board),
+            };
+            let mut bytes = serde_json::to_vec_pretty(&json)?;
+            bytes.push(b'\n');
+            Ok(bytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corner_mine() -> (Board, Spec) {
+        let mut board = Board::new(3, 2, 1);
+        board.place_mines_at(&[(2, 1)]);
+        let spec = Spec {
+            width: 3,
+            height: 2,
+            mines: 1,
+            start: (0, 0),
+            seed: None,
+            no_guess: false,
+            min_rating: None,
+            max_rating: None,
+            layout: Layout::Uniform,
+        };
+        (board, spec)
+    }
+
+    #[test]
+    fn encodes_text_and_mbf() {
+        let (board, spec) = corner_mine();
+        let text = encode(&board, &spec, Format::Text).expect("text");
+        assert_eq!(text, b"# 3x2, 1 mines, start 0 0, guessy\n...\n..*\n");
+        let mbf = encode(&board, &spec, Format::Mbf).expect("mbf");
+        assert_eq!(mbf, [3, 2, 0, 1, 2, 1]);
+    }
+
+    #[test]
+    fn encodes_json() {
+        let (board, spec) = corner_mine();
+        let json = encode(&board, &spec, Format::Json).expect("json");
+        let value: serde_json::Value = serde_json::from_slice(&json).expect("valid json");
+        assert_eq!(value["rows"][1], "..*");
+        assert_eq!(value["start"], serde_json::json!([0, 0]));
+        assert_eq!(value["rating"], "guessy");
+    }
+}
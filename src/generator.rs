@@ -0,0 +1,203 @@
+use crate::board::{Board, Layout};
+use crate::error::{Error, Result};
+use crate::solver::{self, Rating};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng, rng};
+
+/// How many boards `generate` tries before giving up on a no-guess layout.
+pub const MAX_ATTEMPTS: usize = 10_000;
+/// How many boards `generate` rates before giving up on one in the rating range; rating takes
+/// far longer than checking a board can be cleared without guessing.
+pub const MAX_RATED_ATTEMPTS: usize = 1_000;
+
+/// Settings for generating a board outside of a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spec {
+    /// Board width in cells.
+    pub width: usize,
+    /// Board height in cells.
+    pub height: usize,
+    /// Number of mines.
+    pub mines: usize,
+    /// First cell to reveal; it is never a mine.
+    pub start: (usize, usize),
+    /// Master seed; `None` picks a random one.
+    pub seed: Option<u64>,
+    /// Only accept boards the solver can clear from `start` without guessing.
+    pub no_guess: bool,
+    /// Only accept boards [rated](solver::rate_from) from `start` at least this hard.
+    pub min_rating: Option<Rating>,
+    /// Only accept boards [rated](solver::rate_from) from `start` at most this hard.
+    pub max_rating: Option<Rating>,
+    /// How the mines are spread.
+    pub layout: Layout,
+}
+
+/// Place mines for `spec`, returning the board with mines placed but nothing revealed.
+///
+/// The returned board carries the seed it was laid out with, so starting a game with that
+/// seed and first revealing `spec.start` reproduces it exactly.
+///
+/// # Errors
+/// Fails if the spec cannot describe a playable board, if no no-guess layout was found within
+/// [`MAX_ATTEMPTS`] tries, or if none rated in range was found within [`MAX_RATED_ATTEMPTS`].
+pub fn generate(spec: &Spec) -> Result<Board> {
+    let Spec {
+        width,
+        height,
+        mines,
+        start,
+        ..
+    } = *spec;
+    Board::check_size(width, height, mines)?;
+    if start.0 >= width || start.1 >= height {
+        return Err(Error::InvalidBoard(format!(
+            "start ({}, {}) is off the {width}x{height} board",
+            start.0, start.1
+        )));
+    }
+    let (lowest, highest) = (
+        spec.min_rating.unwrap_or(Rating::Trivial),
+        spec.max_rating.unwrap_or(Rating::Guessy),
+    );
+    let rated = spec.min_rating.is_some() || spec.max_rating.is_some();
+    if rated {
+        if width * height > solver::MAX_RATED_CELLS {
+            return Err(Error::InvalidBoard(format!(
+                "boards over {} cells cannot be rated",
+                solver::MAX_RATED_CELLS
+            )));
+        }
+        if lowest > highest || (spec.no_guess && lowest == Rating::Guessy) {
+            return Err(Error::InvalidBoard(format!(
+                "no board can be rated {}..{}{}",
+                lowest.name(),
+                highest.name(),
+                if spec.no_guess {
+                    " without guessing"
+                } else {
+                    ""
+                }
+            )));
+        }
+    }
+
+    let mut seeds = StdRng::seed_from_u64(spec.seed.unwrap_or_else(|| rng().random()));
+    let attempts = match (rated, spec.no_guess) {
+        (true, _) => MAX_RATED_ATTEMPTS,
+        (false, true) => MAX_ATTEMPTS,
+        (false, false) => 1,
+    };
+    for _ in 0..attempts {
+        let mut board = Board::new(width, height, mines)
+            .with_seed(seeds.random())
+            .with_layout(spec.layout);
+        board.place_mines_excluding(start);
+        if spec.no_guess && !solvable_without_guessing(&board, start) {
+            continue;
+        }
+        if !rated
+            || solver::rate_from(&board, start)
+                .is_some_and(|rating| (lowest..=highest).contains(&rating))
+        {
+            return Ok(board);
+        }
+    }
+    Err(Error::InvalidBoard(if rated {
+        format!(
+            "no board rated {}..{} found in {attempts} attempts; try another mine count",
+            lowest.name(),
+            highest.name()
+        )
+    } else {
+        format!("no board solvable without guessing found in {attempts} attempts; try fewer mines")
+    }))
+}
+
+/// Whether revealing `start` and then only cells the solver proves safe clears the board.
+pub fn solvable_without_guessing(board: &Board, start: (usize, usize)) -> bool {
+    let mut board = board.clone();
+    if !board.reveal(start.0, start.1) {
+        return false;
+    }
+    loop {
+        if board.is_win() {
+            return true;
+        }
+        let safe = solver::deduce(&board).safe;
+        if safe.is_empty() {
+            return false;
+        }
+        for (x, y) in safe {
+            board.reveal(x, y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(mines: usize, no_guess: bool) -> Spec {
+        Spec {
+            width: 9,
+            height: 9,
+            mines,
+            start: (4, 4),
+            seed: Some(42),
+            no_guess,
+            min_rating: None,
+            max_rating: None,
+            layout: Layout::Uniform,
+        }
+    }
+
+    #[test]
+    fn same_seed_same_board() {
+        let a = generate(&spec(10, false)).expect("valid spec");
+        let b = generate(&spec(10, false)).expect("valid spec");
+        assert_eq!(a, b);
+        assert!(!a.is_mine(4, 4));
+        assert_eq!((0..81).filter(|i| a.is_mine(i % 9, i / 9)).count(), 10);
+    }
+
+    #[test]
+    fn no_guess_boards_are_solvable() {
+        let board = generate(&spec(10, true)).expect("easy density is solvable");
+        assert!(solvable_without_guessing(&board, (4, 4)));
+    }
+
+    #[test]
+    fn boards_are_found_in_the_rating_range() {
+        let hard = Spec {
+            min_rating: Some(Rating::Hard),
+            max_rating: Some(Rating::Hard),
+            ..spec(10, true)
+        };
+        let board = generate(&hard).expect("hard no-guess boards are common");
+        assert_eq!(solver::rate_from(&board, (4, 4)), Some(Rating::Hard));
+        assert!(solvable_without_guessing(&board, (4, 4)));
+
+        let guessy = Spec {
+            min_rating: Some(Rating::Guessy),
+            ..spec(10, true)
+        };
+        assert!(generate(&guessy).is_err(), "guessy yet no-guess");
+        let backwards = Spec {
+            min_rating: Some(Rating::Hard),
+            max_rating: Some(Rating::Easy),
+            ..spec(10, false)
+        };
+        assert!(generate(&backwards).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_specs() {
+        assert!(generate(&spec(81, false)).is_err());
+        let off_board = Spec {
+            start: (9, 0),
+            ..spec(10, false)
+        };
+        assert!(generate(&off_board).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+use clap::ValueEnum;
+use minesweeper::app::{Action, AppState, Command, Cursor, Status};
+use minesweeper::render::{Renderer, TextRenderer, rows, status_name};
+use minesweeper::solver::Deduction;
+use serde_json::{Value, json};
+use std::io::{self, BufRead, Write};
+
+/// Help text printed by the `help` command.
+const HELP: &str = "\
+commands (coordinates are 0-based column and row):
+  reveal X Y   (r)   reveal a cell
+  flag X Y     (f)   flag or unflag a cell
+  chord X Y    (c)   reveal around a satisfied number
+  hint               suggest a safe cell or certain mine
+  undo               take back the last move
+  restart            start a new game
+  print        (p)   show the board
+  help               show this help
+  quit         (q)   exit";
+
+/// How replies are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Short human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line, each with a `type` field.
+    Json,
+}
+
+/// What a command produced, before it is written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    /// A reveal, flag or chord at a cell, with what it led to.
+    Move {
+        /// The command name, e.g. `reveal`.
+        command: &'static str,
+        /// Target column.
+        x: usize,
+        /// Target row.
+        y: usize,
+        /// `ok`, `won` or `lost`.
+        result: &'static str,
+    },
+    /// The solver's suggestion, if it found one.
+    Hint(Option<Deduction>),
+    /// Whether there was a move to undo.
+    Undo(bool),
+    /// A new game was started.
+    Restart,
+    /// Show the board.
+    Board,
+    /// Show the command list.
+    Help,
+    /// Stop reading commands.
+    Quit,
+}
+
+/// Play over a pipe: read one command per line from `input`, write replies to `output`.
+///
+/// Blank lines and lines starting with `#` are ignored, so scripts can be commented.
+/// Errors are reported (as `error: ...` lines, or `error` objects in JSON) and do not end
+/// the session.
+///
+/// # Errors
+/// Fails only if reading input or writing output fails.
+pub fn run(
+    app: &mut AppState,
+    format: Format,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    // Every reveal names its cell, so a repeated reveal must not turn into a chord.
+    app.double_tap_timeout = None;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let reply = execute(app, line);
+        if reply == Ok(Reply::Quit) {
+            break;
+        }
+        match format {
+            Format::Text => writeln!(output, "{}", text(app, &reply))?,
+            Format::Json => writeln!(output, "{}", to_json(app, &reply))?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Run a single text command against the game.
+///
+/// # Errors
+/// Returns a message for unknown commands, bad coordinates, or moves after the game ended.
+pub fn execute(app: &mut AppState, line: &str) -> Result<Reply, String> {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or_default().to_ascii_lowercase();
+    let args: Vec<&str> = words.collect();
+    let (name, action) = match command.as_str() {
+        "reveal" | "r" => ("reveal", Action::Reveal),
+        "flag" | "f" => ("flag", Action::ToggleFlag),
+        "chord" | "c" => ("chord", Action::Chord),
+        "hint" => {
+            app.handle_action(Action::Hint);
+            return Ok(Reply::Hint(app.hint));
+        }
+        "undo" => {
+            return Ok(Reply::Undo(
+                app.handle_action(Action::Undo) != Command::None,
+            ));
+        }
+        "restart" => {
+            app.handle_action(Action::Restart);
+            return Ok(Reply::Restart);
+        }
+        "print" | "p" => return Ok(Reply::Board),
+        "help" | "?" => return Ok(Reply::Help),
+        "quit" | "q" | "exit" => return Ok(Reply::Quit),
+        other => return Err(format!("unknown command '{other}' (try 'help')")),
+    };
+
+    let (x, y) = coordinates(app, &args)?;
+    if app.status != Status::Playing {
+        return Err("the game is over; use 'restart'".to_string());
+    }
+    app.cursor = Cursor::new(x, y);
+    let result = match app.handle_action(action) {
+        Command::GameWon => "won",
+        Command::GameLost => "lost",
+        _ => "ok",
+    };
+    Ok(Reply::Move {
+        command: name,
+        x,
+        y,
+        result,
+    })
+}
+
+fn coordinates(app: &AppState, args: &[&str]) -> Result<(usize, usize), String> {
+    let [x, y] = args else {
+        return Err("expected two coordinates: X Y".to_string());
+    };
+    let parse = |s: &str| {
+        s.parse::<usize>()
+            .map_err(|_| format!("'{s}' is not a coordinate"))
+    };
+    let (x, y) = (parse(x)?, parse(y)?);
+    if x >= app.board.width() || y >= app.board.height() {
+        return Err(format!(
+            "({x}, {y}) is off the {}x{} board",
+            app.board.width(),
+            app.board.height()
+        ));
+    }
+    Ok((x, y))
+}
+
+/// A reply as human-readable text.
+pub fn text(app: &AppState, reply: &Result<Reply, String>) -> String {
+    let reply = match reply {
+        Ok(reply) => reply,
+        Err(e) => return format!("error: {e}"),
+    };
+    match *reply {
+        Reply::Move { result, .. } => result.to_string(),
+        Reply::Hint(Some(Deduction::Safe(x, y))) => format!("safe {x} {y}"),
+        Reply::Hint(Some(Deduction::Mine(x, y))) => format!("mine {x} {y}"),
+        Reply::Hint(None) => "no deduction; a guess is needed".to_string(),
+        Reply::Undo(true) | Reply::Restart => "ok".to_string(),
+        Reply::Undo(false) => "nothing to undo".to_string(),
+        Reply::Board => {
+            let mut renderer = TextRenderer::new(Vec::new());
+            // Writing to memory cannot fail.
+            let _ = renderer.render(app);
+            String::from_utf8_lossy(&renderer.into_inner())
+                .trim_end()
+                .to_string()
+        }
+        Reply::Help => HELP.to_string(),
+        Reply::Quit => String::new(),
+    }
+}
+
+/// A reply as a JSON object with a `type` field, for bots and scripts.
+fn to_json(app: &AppState, reply: &Result<Reply, String>) -> Value {
+    let reply = match reply {
+        Ok(reply) => reply,
+        Err(e) => return json!({ "type": "error", "message": e }),
+    };
+    let status = status_name(app.status);
+    match *reply {
+        Reply::Move {
+            command,
+            x,
+            y,
+            result,
+        } => json!({
+            "type": "move",
+            "command": command,
+            "x": x,
+            "y": y,
+            "result": result,
+            "status": status,
+        }),
+        Reply::Hint(hint) => {
+            let (kind, cell) = match hint {
+                Some(Deduction::Safe(x, y)) => (Some("safe"), Some([x, y])),
+                Some(Deduction::Mine(x, y)) => (Some("mine"), Some([x, y])),
+                None => (None, None),
+            };
+            json!({ "type": "hint", "kind": kind, "cell": cell })
+        }
+        Reply::Undo(undone) => json!({ "type": "undo", "undone": undone, "status": status }),
+        Reply::Restart => json!({ "type": "restart", "status": status }),
+        Reply::Board => json!({
+            "type": "board",
+            "status": status,
+            "width": app.board.width(),
+            "height": app.board.height(),
+            "mines": app.board.mines(),
+            "flags": app.flags_placed,
+            "hints_used": app.hints_used,
+            "rows": rows(app),
+        }),
+        Reply::Help => json!({ "type": "help", "text": HELP }),
+        Reply::Quit => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+
+    /// A 3x3 game with its only mine in the bottom-right corner.
+    fn corner_mine_game() -> AppState {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 3, 1);
+        app.board.place_mines_at(&[(2, 2)]);
+        app
+    }
+
+    fn play(format: Format, script: &str) -> String {
+        let mut app = corner_mine_game();
+        let mut out = Vec::new();
+        run(&mut app, format, script.as_bytes(), &mut out).expect("in-memory io");
+        String::from_utf8(out).expect("utf-8")
+    }
+
+    #[test]
+    fn scripted_game_is_won() {
+        let script = "# open the corner\nflag 2 2\nreveal 0 0\nprint\nquit\nreveal 1 1\n";
+        assert_eq!(
+            play(Format::Text, script),
+            "ok\nwon\nstatus won mines 1 flags 1\n...\n.11\n.1F\n"
+        );
+    }
+
+    #[test]
+    fn json_replies_are_one_object_per_line() {
+        let out = play(Format::Json, "reveal 2 2\nprint\nreveal 9 9\n");
+        let replies: Vec<Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("valid json"))
+            .collect();
+        assert_eq!(replies[0]["type"], "move");
+        assert_eq!(replies[0]["result"], "lost");
+        assert_eq!(replies[1]["type"], "board");
+        assert_eq!(replies[1]["rows"][2], "##*");
+        assert_eq!(replies[2]["type"], "error");
+    }
+
+    #[test]
+    fn bad_commands_are_reported() {
+        let mut app = corner_mine_game();
+        assert!(execute(&mut app, "dance").is_err());
+        assert!(execute(&mut app, "reveal 1").is_err());
+        assert!(execute(&mut app, "reveal 3 0").is_err());
+        assert_eq!(
+            execute(&mut app, "reveal 2 2"),
+            Ok(Reply::Move {
+                command: "reveal",
+                x: 2,
+                y: 2,
+                result: "lost"
+            })
+        );
+        assert!(execute(&mut app, "reveal 0 0").is_err());
+    }
+}
@@ -0,0 +1,678 @@
+use minesweeper::app::{AppState, Status};
+use minesweeper::board::{Layout, Topology};
+use minesweeper::events::Event;
+use minesweeper::render::status_name;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One finished game, as a line of the game log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// When the game ended, in seconds since the Unix epoch.
+    pub finished: u64,
+    /// The board's seed, if it had one; `--seed` with it replays the board.
+    pub seed: Option<u64>,
+    /// `"easy"`, `"medium"`, `"hard"` or `"custom"`.
+    pub difficulty: String,
+    /// Board width in cells.
+    pub width: usize,
+    /// Board height in cells.
+    pub height: usize,
+    /// Number of mines.
+    pub mines: usize,
+    /// `"won"` or `"lost"`.
+    pub result: String,
+    /// Seconds from the first reveal to the end.
+    pub time: f64,
+    /// The board's 3BV: the fewest reveals that clear it.
+    #[serde(rename = "3bv")]
+    pub bbbv: usize,
+    /// Reveals, flags and chords made.
+    pub clicks: usize,
+    /// Hints requested.
+    pub hints: usize,
+    /// Moves made by assists such as auto-chord and auto-flag, which mark the game as
+    /// assisted. Left out of the line when there were none.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub auto_moves: usize,
+    /// Moves taken back, which keep a win out of the best times. Left out of the line when
+    /// there were none.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub undos: usize,
+    /// Whether mines were placed before the first reveal. Left out of the line when false.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hardcore: bool,
+    /// Whether the game started with its opening revealed (`--practice`). Left out of the
+    /// line when false.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub practice: bool,
+    /// Whether the board wrapped left to right (`--cylinder`). Left out of the line when
+    /// false.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub cylinder: bool,
+    /// How the mines were spread (`--layout`). Left out of the line when they were spread
+    /// evenly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    /// Points earned, for games played in scoring mode. Left out of the line otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<u64>,
+    /// For a loss, `"forced-guess"` if nothing could be deduced before the losing move or
+    /// `"misplay"` if a safe cell could. Left out of the line for wins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loss: Option<String>,
+    /// How hard the board was to clear: `"trivial"`, `"easy"`, `"hard"` or `"guessy"`. Left
+    /// out of the line for boards too big to rate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<String>,
+}
+
+/// What personal bests are kept per: the board size, whether the game was hardcore, since
+/// hardcore games are lost on the first reveal now and then, and whether the board wrapped,
+/// which changes every number along its sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Category {
+    /// Board width in cells.
+    pub width: usize,
+    /// Board height in cells.
+    pub height: usize,
+    /// Number of mines.
+    pub mines: usize,
+    /// Whether mines were placed before the first reveal.
+    pub hardcore: bool,
+    /// Whether the board wrapped left to right.
+    pub cylinder: bool,
+    /// How the mines were spread.
+    pub layout: Layout,
+}
+
+impl Category {
+    /// The category of the game `app` is playing.
+    pub fn of(app: &AppState) -> Self {
+        Self {
+            width: app.board.width(),
+            height: app.board.height(),
+            mines: app.board.mines(),
+            hardcore: app.is_hardcore(),
+            cylinder: app.board.topology() == Topology::Cylinder,
+            layout: app.board.layout(),
+        }
+    }
+
+    fn of_entry(entry: &Entry) -> Self {
+        Self {
+            width: entry.width,
+            height: entry.height,
+            mines: entry.mines,
+            hardcore: entry.hardcore,
+            cylinder: entry.cylinder,
+            layout: entry
+                .layout
+                .as_deref()
+                .and_then(|name| name.parse().ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde passes fields by reference
+const fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde passes fields by reference
+const fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+impl Entry {
+    /// The log entry for the game `app` just finished.
+    pub fn of(app: &AppState) -> Self {
+        let (width, height, mines) = (app.board.width(), app.board.height(), app.board.mines());
+        Self {
+            finished: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            seed: app.board.seed(),
+            difficulty: app.difficulty.name().to_string(),
+            width,
+            height,
+            mines,
+            result: status_name(app.status).to_string(),
+            time: app.elapsed().as_secs_f64(),
+            bbbv: app.board.bbbv(),
+            clicks: app.clicks,
+            hints: app.hints_used,
+            auto_moves: app.auto_moves,
+            undos: app.undos,
+            hardcore: app.is_hardcore(),
+            practice: app.is_practice(),
+            cylinder: app.board.topology() == Topology::Cylinder,
+            layout: (app.board.layout() != Layout::Uniform)
+                .then(|| app.board.layout().name().to_string()),
+            score: app.score.map(|score| score.points),
+            loss: app
+                .loss
+                .filter(|_| app.status == Status::Lose)
+                .map(|loss| loss.name().to_string()),
+            rating: app.rating.map(|rating| rating.name().to_string()),
+        }
+    }
+}
+
+/// Appends each finished game to a JSON Lines file, for analysis with external tools.
+pub struct GameLog {
+    path: PathBuf,
+}
+
+impl GameLog {
+    /// Log to `path`, creating it (and its directory) on the first finished game.
+    pub const fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Log the game if `events`, just taken from `app`, end it.
+    ///
+    /// # Errors
+    /// Fails if the log cannot be written.
+    pub fn observe(&self, app: &AppState, events: &[Event]) -> io::Result<()> {
+        if events
+            .iter()
+            .any(|event| matches!(event, Event::GameEnded { .. }))
+        {
+            self.append(&Entry::of(app))?;
+        }
+        Ok(())
+    }
+
+    fn append(&self, entry: &Entry) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)
+    }
+}
+
+/// The best winning time for each [`Category`] in the log at `path`. A missing log, and
+/// lines that are not entries, count as no games; practice games and games with an undo are
+/// left out.
+pub fn best_times(path: &Path) -> HashMap<Category, f64> {
+    let mut best = HashMap::new();
+    for (category, _, time) in wins(path) {
+        best.entry(category)
+            .and_modify(|best: &mut f64| *best = best.min(time))
+            .or_insert(time);
+    }
+    best
+}
+
+/// The category, board seed and time of every win in the log at `path`, practice games and
+/// games with an undo aside.
+fn wins(path: &Path) -> Vec<(Category, Option<u64>, f64)> {
+    entries(&fs::read_to_string(path).unwrap_or_default())
+        .into_iter()
+        .filter(|entry| entry.result == "won" && !entry.practice && entry.undos == 0)
+        .map(|entry| (Category::of_entry(&entry), entry.seed, entry.time))
+        .collect()
+}
+
+/// The entries of a game log's text, oldest first; lines that are not entries are skipped.
+fn entries(text: &str) -> Vec<Entry> {
+    text.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Write the log at `log` as CSV files in `dir` for a spreadsheet: `games.csv`, a row per
+/// game, and `summary.csv`, the games, wins and times per [`Category`] with practice games
+/// and games with an undo left out. Returns the paths written.
+///
+/// # Errors
+/// Fails if the log cannot be read or the files cannot be written.
+pub fn export_csv(log: &Path, dir: &Path) -> io::Result<[PathBuf; 2]> {
+    let entries = entries(&fs::read_to_string(log)?);
+    fs::create_dir_all(dir)?;
+    let games = dir.join("games.csv");
+    let summary = dir.join("summary.csv");
+    fs::write(&games, games_csv(&entries))?;
+    fs::write(&summary, summary_csv(&entries))?;
+    Ok([games, summary])
+}
+
+/// One row per game, with the columns of the log.
+fn games_csv(entries: &[Entry]) -> String {
+    let mut csv = String::from(
+        "finished,seed,difficulty,width,height,mines,result,time,3bv,clicks,hints,auto_moves,\
+         undos,hardcore,practice,cylinder,layout,score,loss,rating\n",
+    );
+    for entry in entries {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{:.3},{},{},{},{},{},{},{},{},{},{},{},{}",
+            entry.finished,
+            entry.seed.map(|seed| seed.to_string()).unwrap_or_default(),
+            csv_field(&entry.difficulty),
+            entry.width,
+            entry.height,
+            entry.mines,
+            csv_field(&entry.result),
+            entry.time,
+            entry.bbbv,
+            entry.clicks,
+            entry.hints,
+            entry.auto_moves,
+            entry.undos,
+            entry.hardcore,
+            entry.practice,
+            entry.cylinder,
+            csv_field(entry.layout.as_deref().unwrap_or(Layout::Uniform.name())),
+            entry
+                .score
+                .map(|score| score.to_string())
+                .unwrap_or_default(),
+            csv_field(entry.loss.as_deref().unwrap_or_default()),
+            csv_field(entry.rating.as_deref().unwrap_or_default()),
+        );
+    }
+    csv
+}
+
+/// One row per category, in order of board size: games played and won, the share won as a
+/// percentage, and the best, mean and total winning time in seconds (empty with no wins).
+fn summary_csv(entries: &[Entry]) -> String {
+    #[derive(Default)]
+    struct Totals {
+        games: usize,
+        wins: usize,
+        best: Option<f64>,
+        time: f64,
+    }
+    let mut categories = BTreeMap::new();
+    for entry in entries
+        .iter()
+        .filter(|entry| !entry.practice && entry.undos == 0)
+    {
+        let category = Category::of_entry(entry);
+        let key = (
+            category.width,
+            category.height,
+            category.mines,
+            category.hardcore,
+            category.cylinder,
+            category.layout.name(),
+        );
+        let totals: &mut Totals = categories.entry(key).or_default();
+        totals.games += 1;
+        if entry.result == "won" {
+            totals.wins += 1;
+            totals.time += entry.time;
+            totals.best = Some(totals.best.map_or(entry.time, |best| best.min(entry.time)));
+        }
+    }
+    let mut csv = String::from(
+        "width,height,mines,hardcore,cylinder,layout,games,wins,win_percent,best_time,\
+         mean_time,total_time\n",
+    );
+    for ((width, height, mines, hardcore, cylinder, layout), totals) in categories {
+        #[allow(clippy::cast_precision_loss)] // game counts are far below 2^52
+        let (games, wins) = (totals.games as f64, totals.wins as f64);
+        let (best, mean, total) = match totals.best {
+            Some(best) => (
+                format!("{best:.3}"),
+                format!("{:.3}", totals.time / wins),
+                format!("{:.3}", totals.time),
+            ),
+            None => Default::default(),
+        };
+        let _ = writeln!(
+            csv,
+            "{width},{height},{mines},{hardcore},{cylinder},{layout},{},{},{:.1},{best},{mean},\
+             {total}",
+            totals.games,
+            totals.wins,
+            100.0 * wins / games,
+        );
+    }
+    csv
+}
+
+/// `text` as a CSV field: quoted, with quotes doubled, if it holds a comma, quote or line
+/// break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// How many of the fastest times per category count as a place on the leaderboard.
+pub const LEADERBOARD_PLACES: usize = 10;
+
+/// How the game being played stands against earlier wins.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Standing {
+    /// The fastest earlier win on boards of the same [`Category`], in seconds.
+    pub best: Option<f64>,
+    /// The fastest earlier win on this exact board, for boards with a seed (including board
+    /// codes) to know it by.
+    pub board_best: Option<f64>,
+    /// Once the game is won: its place, from 1, among the [`LEADERBOARD_PLACES`] fastest
+    /// times for its category, if it made one.
+    pub place: Option<usize>,
+}
+
+/// The fastest winning times per [`Category`] and per seeded board, so the header can show
+/// the times to beat and a win can be told where it ranks on the game-over screen.
+pub struct Leaderboard {
+    /// Winning times per category, fastest first.
+    times: HashMap<Category, Vec<f64>>,
+    /// Best winning time per category and board seed.
+    boards: HashMap<(Category, u64), f64>,
+    /// Where the current game stands, kept as it was when the game ended.
+    standing: Standing,
+}
+
+impl Leaderboard {
+    /// A leaderboard of the wins in the game log at `log`; empty without one.
+    pub fn new(log: Option<&Path>) -> Self {
+        let mut leaderboard = Self {
+            times: HashMap::new(),
+            boards: HashMap::new(),
+            standing: Standing::default(),
+        };
+        for (category, seed, time) in log.map(wins).unwrap_or_default() {
+            leaderboard.add(category, seed, time);
+        }
+        leaderboard
+    }
+
+    /// Where the game in `app` stands, with `events` just taken from it. Call after every
+    /// change to `app`; wins are added to the board as they happen, and the times to beat
+    /// stay those from before the win.
+    pub fn observe(&mut self, app: &AppState, events: &[Event]) -> Standing {
+        let won = events.iter().find_map(|event| match *event {
+            Event::GameEnded {
+                status: Status::Win,
+                time,
+            } => Some(time),
+            _ => None,
+        });
+        let category = Category::of(app);
+        let seed = app.board.seed();
+        if app.status == Status::Playing {
+            self.standing = Standing {
+                best: self
+                    .times
+                    .get(&category)
+                    .and_then(|times| times.first().copied()),
+                board_best: seed.and_then(|seed| self.boards.get(&(category, seed)).copied()),
+                place: None,
+            };
+        } else if let Some(time) = won
+            && !app.is_practice()
+            && app.undos == 0
+        {
+            let place = self.add(category, seed, time.as_secs_f64());
+            self.standing.place = (place < LEADERBOARD_PLACES).then_some(place + 1);
+        }
+        self.standing
+    }
+
+    /// Add a winning time, returning how many earlier times are at least as fast.
+    fn add(&mut self, category: Category, seed: Option<u64>, time: f64) -> usize {
+        let times = self.times.entry(category).or_default();
+        let place = times.partition_point(|&other| other <= time);
+        times.insert(place, time);
+        if let Some(seed) = seed {
+            self.boards
+                .entry((category, seed))
+                .and_modify(|best| *best = best.min(time))
+                .or_insert(time);
+        }
+        place
+    }
+}
+
+/// The game-over line for a win in `place` on the leaderboard, like "New #3 best!".
+pub fn place_message(place: usize) -> String {
+    if place == 1 {
+        "New best time!".to_string()
+    } else {
+        format!("New #{place} best!")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::{Action, Cursor};
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+
+    #[test]
+    fn finished_games_are_logged_once() {
+        let path =
+            std::env::temp_dir().join(format!("minesweeper-log-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let log = GameLog::new(path.clone());
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 3, 1).with_seed(7);
+        app.board.place_mines_at(&[(2, 2)]);
+        log.observe(&app, &[]).expect("nothing to write");
+        app.cursor = Cursor::new(2, 2);
+        app.handle_action(Action::Reveal);
+        let events = app.events.drain();
+        log.observe(&app, &events).expect("log written");
+        log.observe(&app, &[]).expect("already logged");
+
+        let text = fs::read_to_string(&path).expect("log exists");
+        let _ = fs::remove_file(&path);
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let entry: Entry = serde_json::from_str(lines[0]).expect("valid entry");
+        assert_eq!(entry.seed, Some(7));
+        assert_eq!(entry.difficulty, "easy");
+        assert_eq!(entry.result, "lost");
+        assert_eq!((entry.bbbv, entry.clicks), (1, 1));
+        assert!(lines[0].contains("\"3bv\":1"));
+        assert!(!lines[0].contains("hardcore") && !lines[0].contains("score"));
+    }
+
+    #[test]
+    fn best_times_keep_the_fastest_win_per_size() {
+        let path =
+            std::env::temp_dir().join(format!("minesweeper-best-{}.jsonl", std::process::id()));
+        let entry = |result: &str, time: f64, hardcore: bool| Entry {
+            finished: 0,
+            seed: None,
+            difficulty: "easy".to_string(),
+            width: 9,
+            height: 9,
+            mines: 10,
+            result: result.to_string(),
+            time,
+            bbbv: 1,
+            clicks: 1,
+            hints: 0,
+            auto_moves: 0,
+            undos: 0,
+            hardcore,
+            practice: false,
+            cylinder: false,
+            layout: None,
+            score: None,
+            loss: None,
+            rating: None,
+        };
+        let log = GameLog::new(path.clone());
+        let games = [
+            ("won", 40.0, false),
+            ("lost", 3.0, false),
+            ("won", 25.5, false),
+            ("won", 12.0, true),
+        ];
+        for (result, time, hardcore) in games {
+            log.append(&entry(result, time, hardcore))
+                .expect("log written");
+        }
+        let practice = Entry {
+            practice: true,
+            ..entry("won", 1.0, false)
+        };
+        log.append(&practice).expect("log written");
+        let best = best_times(&path);
+        let _ = fs::remove_file(&path);
+        let easy = Category {
+            width: 9,
+            height: 9,
+            mines: 10,
+            hardcore: false,
+            cylinder: false,
+            layout: Layout::Uniform,
+        };
+        assert_eq!(best.get(&easy), Some(&25.5));
+        let hardcore = Category {
+            hardcore: true,
+            ..easy
+        };
+        assert_eq!(best.get(&hardcore), Some(&12.0));
+        assert_eq!(best.len(), 2);
+        assert!(best_times(&path).is_empty());
+    }
+
+    #[test]
+    fn wins_are_placed_among_the_fastest_times() {
+        let won = || {
+            let mut app = AppState::new(Difficulty::Easy);
+            app.board = Board::new(3, 1, 1);
+            app.board.place_mines_at(&[(2, 0)]);
+            app.handle_action(Action::Reveal);
+            let events = app.events.drain();
+            (app, events)
+        };
+        let (app, events) = won();
+        let category = Category::of(&app);
+        let mut leaderboard = Leaderboard::new(None);
+        leaderboard.times.insert(category, vec![0.0, 0.0, 60.0]);
+        assert_eq!(leaderboard.observe(&app, &events).place, Some(3));
+        assert_eq!(
+            leaderboard.observe(&app, &[]).place,
+            Some(3),
+            "until the next game"
+        );
+        assert_eq!(leaderboard.times[&category].len(), 4, "added once");
+        let next = leaderboard.observe(&AppState::new(Difficulty::Easy), &[]);
+        assert_eq!(next.place, None);
+        assert_eq!(place_message(3), "New #3 best!");
+
+        let mut full = Leaderboard::new(None);
+        full.times.insert(category, vec![0.0; LEADERBOARD_PLACES]);
+        let (app, events) = won();
+        assert_eq!(full.observe(&app, &events).place, None, "too slow to place");
+        assert_eq!(full.times[&category].len(), LEADERBOARD_PLACES + 1);
+    }
+
+    #[test]
+    fn wins_after_an_undo_set_no_best_time() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::Reveal);
+        app.handle_action(Action::Undo);
+        app.cursor = Cursor::new(0, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.status, Status::Win);
+        let events = app.events.drain();
+        let mut leaderboard = Leaderboard::new(None);
+        assert_eq!(leaderboard.observe(&app, &events).place, None);
+        assert!(leaderboard.times.is_empty());
+
+        let path =
+            std::env::temp_dir().join(format!("minesweeper-undone-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        GameLog::new(path.clone())
+            .observe(&app, &events)
+            .expect("log written");
+        let text = fs::read_to_string(&path).expect("log exists");
+        let best = best_times(&path);
+        let _ = fs::remove_file(&path);
+        assert!(text.contains(r#""undos":1"#), "{text}");
+        assert!(best.is_empty());
+    }
+
+    #[test]
+    fn seeded_boards_keep_their_own_best() {
+        let game = |seed: u64| {
+            let mut app = AppState::new(Difficulty::Easy);
+            app.board = Board::new(3, 1, 1).with_seed(seed);
+            app
+        };
+        let category = Category::of(&game(7));
+        let mut leaderboard = Leaderboard::new(None);
+        leaderboard.add(category, Some(7), 30.0);
+        leaderboard.add(category, Some(8), 20.0);
+        leaderboard.add(category, None, 10.0);
+        let standing = leaderboard.observe(&game(7), &[]);
+        assert_eq!(
+            (standing.best, standing.board_best),
+            (Some(10.0), Some(30.0))
+        );
+        assert_eq!(
+            leaderboard.observe(&game(9), &[]).board_best,
+            None,
+            "never played"
+        );
+
+        let mut won = game(9);
+        won.board.place_mines_at(&[(2, 0)]);
+        won.handle_action(Action::Reveal);
+        let events = won.events.drain();
+        let standing = leaderboard.observe(&won, &events);
+        assert_eq!(standing.board_best, None, "the time to beat was none");
+        assert!(leaderboard.boards.contains_key(&(category, 9)));
+    }
+
+    #[test]
+    fn the_log_exports_as_csv() {
+        let entries = entries(concat!(
+            r#"{"finished":1,"seed":4,"difficulty":"easy","width":9,"height":9,"mines":10,"result":"won","time":30.0,"3bv":17,"clicks":20,"hints":0}"#,
+            "\nnot an entry\n",
+            r#"{"finished":2,"seed":null,"difficulty":"easy","width":9,"height":9,"mines":10,"result":"won","time":20.0,"3bv":12,"clicks":15,"hints":1}"#,
+            "\n",
+            r#"{"finished":3,"seed":null,"difficulty":"easy","width":9,"height":9,"mines":10,"result":"lost","time":5.5,"3bv":9,"clicks":3,"hints":0,"loss":"misplay","rating":"hard"}"#,
+            "\n",
+            r#"{"finished":4,"seed":null,"difficulty":"custom","width":5,"height":5,"mines":3,"result":"lost","time":2.0,"3bv":4,"clicks":1,"hints":0,"layout":"center"}"#,
+        ));
+        let games = games_csv(&entries);
+        let rows: Vec<&str> = games.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(
+            rows[1],
+            "1,4,easy,9,9,10,won,30.000,17,20,0,0,0,false,false,false,uniform,,,"
+        );
+        assert!(rows[4].contains(",center,"));
+        assert!(rows[3].ends_with(",misplay,hard"));
+
+        let summary = summary_csv(&entries);
+        let rows: Vec<&str> = summary.lines().collect();
+        assert_eq!(
+            &rows[1..],
+            [
+                "5,5,3,false,false,center,1,0,0.0,,,",
+                "9,9,10,false,false,uniform,3,2,66.7,20.000,25.000,50.000",
+            ]
+        );
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
@@ -0,0 +1,206 @@
+use crate::app::{Cursor, Status};
+use crate::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// How a hot-seat game is decided. Either way, a cleared board goes to whoever opened more
+/// cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scoring {
+    /// Whoever sets off a mine loses.
+    #[default]
+    Survival,
+    /// A mine ends the game, but only the cells each player opened count.
+    Cells,
+}
+
+impl FromStr for Scoring {
+    type Err = Error;
+
+    /// Parse `survival` or `cells`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "survival" => Ok(Self::Survival),
+            "cells" => Ok(Self::Cells),
+            _ => Err(Error::Parse(format!(
+                "unknown scoring '{s}' (expected survival or cells)"
+            ))),
+        }
+    }
+}
+
+/// One of the two players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Player {
+    /// Cells opened by this player's reveals and chords.
+    pub cells: usize,
+    /// Where this player left the cursor, restored when their turn comes round.
+    pub cursor: Cursor,
+}
+
+/// How a finished hot-seat game turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The player at this index (0 or 1) won.
+    Won(usize),
+    /// Both opened as many cells.
+    Draw,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Won(player) => write!(f, "Player {} wins", player + 1),
+            Self::Draw => write!(f, "It's a draw"),
+        }
+    }
+}
+
+/// Two players taking turns on one board and one keyboard.
+///
+/// A turn ends with a reveal or chord that opens cells; flags are free. The board, flags
+/// and timer are shared, while each player keeps a score and a cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotSeat {
+    /// How the game is decided.
+    pub scoring: Scoring,
+    /// Both players, first player first.
+    pub players: [Player; 2],
+    /// Index of the player whose turn it is.
+    pub turn: usize,
+    /// Index of the player who set off a mine, once one has.
+    pub tripped: Option<usize>,
+}
+
+impl HotSeat {
+    /// A new game, first player to move, both cursors at `cursor`.
+    pub const fn new(scoring: Scoring, cursor: Cursor) -> Self {
+        let player = Player { cells: 0, cursor };
+        Self {
+            scoring,
+            players: [player; 2],
+            turn: 0,
+            tripped: None,
+        }
+    }
+
+    /// Credit the current player with a move that opened `opened` cells, `mine` if it set
+    /// one off, made with the cursor at `cursor`. Returns whether the turn passed, in which
+    /// case the cursor should move to the next player's.
+    pub fn end_turn(&mut self, opened: usize, mine: bool, cursor: Cursor) -> bool {
+        if opened == 0 && !mine {
+            return false;
+        }
+        let player = &mut self.players[self.turn];
+        player.cells += opened;
+        player.cursor = cursor;
+        if mine {
+            self.tripped = Some(self.turn);
+            return false;
+        }
+        self.turn = 1 - self.turn;
+        true
+    }
+
+    /// The player whose turn it is.
+    pub const fn current(&self) -> &Player {
+        &self.players[self.turn]
+    }
+
+    /// Who won a game that ended with `status`, or `None` while it is still being played.
+    pub fn outcome(&self, status: Status) -> Option<Outcome> {
+        if status == Status::Playing {
+            return None;
+        }
+        if let (Scoring::Survival, Some(tripped)) = (self.scoring, self.tripped) {
+            return Some(Outcome::Won(1 - tripped));
+        }
+        let [first, second] = self.players;
+        Some(match first.cells.cmp(&second.cells) {
+            std::cmp::Ordering::Greater => Outcome::Won(0),
+            std::cmp::Ordering::Less => Outcome::Won(1),
+            std::cmp::Ordering::Equal => Outcome::Draw,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{Action, AppState, Command, Layer};
+    use crate::board::Board;
+    use crate::difficulty::Difficulty;
+
+    /// A hot-seat game on a 6x1 board with the mine at (2, 0).
+    fn game(scoring: Scoring) -> AppState {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.hotseat = Some(HotSeat::new(scoring, Cursor::new(0, 0)));
+        app.board = Board::new(6, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app
+    }
+
+    fn seat(app: &AppState) -> &HotSeat {
+        app.hotseat.as_ref().expect("hot-seat game")
+    }
+
+    /// Play `cells` in turn, closing the handoff message after each.
+    fn play(app: &mut AppState, cells: &[usize]) {
+        for &x in cells {
+            app.cursor = Cursor::new(x, 0);
+            app.handle_action(Action::Reveal);
+            app.handle_action(Action::Back);
+        }
+    }
+
+    #[test]
+    fn turns_pass_after_opening_cells() {
+        let mut app = game(Scoring::Survival);
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(seat(&app).turn, 0, "flags are free");
+        app.cursor = Cursor::new(0, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!((seat(&app).turn, seat(&app).players[0].cells), (1, 2));
+        assert_eq!(app.layers.last(), Some(&Layer::Handoff));
+        app.cursor = Cursor::new(5, 0);
+        assert_eq!(
+            app.handle_action(Action::Chord),
+            Command::None,
+            "waits for Enter"
+        );
+        app.handle_action(Action::Reveal);
+        assert_eq!(app.layers.last(), None);
+        assert_eq!(app.handle_action(Action::Undo), Command::None);
+    }
+
+    #[test]
+    fn cursors_are_kept_per_player() {
+        let mut app = game(Scoring::Survival);
+        play(&mut app, &[0]);
+        assert_eq!(
+            app.cursor,
+            Cursor::new(0, 0),
+            "the second player's own cursor"
+        );
+        play(&mut app, &[3]);
+        assert_eq!(app.cursor, Cursor::new(0, 0));
+        app.handle_action(Action::Restart);
+        assert_eq!(seat(&app).players[0].cells, 0);
+    }
+
+    #[test]
+    fn outcomes_follow_the_scoring() {
+        // The first player opens two cells, the second one, then the first sets off the mine.
+        let mut app = game(Scoring::Survival);
+        play(&mut app, &[0, 3, 2]);
+        assert_eq!(app.status, Status::Lose);
+        assert_eq!(seat(&app).outcome(app.status), Some(Outcome::Won(1)));
+
+        let mut app = game(Scoring::Cells);
+        play(&mut app, &[0, 3, 2]);
+        assert_eq!(seat(&app).outcome(app.status), Some(Outcome::Won(0)));
+        assert_eq!(Outcome::Won(1).to_string(), "Player 2 wins");
+        assert_eq!("CELLS".parse::<Scoring>().ok(), Some(Scoring::Cells));
+    }
+}
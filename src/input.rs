@@ -1,4 +1,11 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crate::net::Message;
+use anyhow::{Context, bail};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use minesweeper::app::{AppState, Layer, Panel};
+use minesweeper::difficulty::Difficulty;
+use std::collections::HashMap;
 
 /// Direction for cursor movement.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -7,75 +14,747 @@ pub enum Dir {
     Right,
     Up,
     Down,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
 /// High-level input actions translated from terminal events.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputAction {
     Move(Dir),
+    /// Move several cells at once, from a count typed before the movement key.
+    MoveBy(Dir, usize),
     Reveal,
     Flag,
+    FlagAdvance,
     Chord,
+    OpenRemaining,
+    Peek,
     Restart,
+    Undo,
+    Hint,
+    TogglePanel(Panel),
+    ToggleRecording,
+    PlayMacro,
+    OpenSeedPrompt,
+    OpenPacks,
+    PromptChar(char),
+    PromptBackspace,
+    PromptSubmit,
+    Back,
+    ConfirmQuit,
     ChangeDifficulty,
+    SelectDifficulty(Difficulty),
+    /// Switch to the last custom board (or the size given on the command line).
+    SelectCustom,
+    ToggleMode,
+    /// Save the finished board as an image.
+    Export,
+    /// Copy an emoji summary of the finished game.
+    Share,
+    /// Save the finished game's move log as text.
+    SaveMoves,
+    /// Hand the terminal back to the shell and stop the process (Ctrl+Z, SIGTSTP).
+    Suspend,
+    /// Retake the terminal after the process was continued (SIGCONT).
+    Resume,
     Quit,
+    /// Mouse button pressed at (column, row); starts a viewport drag.
+    DragStart(u16, u16),
+    /// Mouse dragged to (column, row).
+    Drag(u16, u16),
+    /// Mouse button released.
+    DragEnd,
 }
 
-/// Translate a crossterm Event into an optional InputAction.
+/// What the main loop handles: translated keys and what the other input sources send it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputEvent {
+    Action(InputAction),
+    /// Text pasted in one go (bracketed paste), meant for an open prompt.
+    Paste(String),
+    /// A message from the other player in a network game.
+    Remote(Message),
+    /// A line from the `--commands` source, like `reveal C4`.
+    Command(String),
+}
+
+/// Which translator applies to keys, decided by the layer on top of the app.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyContext {
+    #[default]
+    Game,
+    Prompt,
+    Confirm,
+}
+
+impl KeyContext {
+    /// The context for the app's current topmost layer.
+    pub fn of(app: &AppState) -> Self {
+        match app.layers.last() {
+            Some(Layer::SeedPrompt(_)) => Self::Prompt,
+            Some(Layer::ConfirmQuit) => Self::Confirm,
+            Some(Layer::GameOver | Layer::Handoff | Layer::Packs(_) | Layer::Defuse(_)) | None => {
+                Self::Game
+            }
+        }
+    }
+}
+
+/// Turns terminal events into input for whichever layer is on top, with the configured
+/// bindings and any count typed before a movement key. The main loop reads the terminal
+/// itself and translates each event as it gets to it, so keys typed right after a prompt
+/// opens already go to the prompt.
+pub struct Translator {
+    keymap: Keymap,
+    counts: CountPrefix,
+}
+
+impl Translator {
+    /// Translate with `keymap` on top of the built-in bindings.
+    pub fn new(keymap: Keymap) -> Self {
+        Self {
+            keymap,
+            counts: CountPrefix::default(),
+        }
+    }
+
+    /// What `event` means in `context`, if anything.
+    pub fn translate(&mut self, event: Event, context: KeyContext) -> Option<InputEvent> {
+        if let Event::Paste(text) = event {
+            return Some(InputEvent::Paste(text));
+        }
+        match context {
+            KeyContext::Game => self.counts.translate(&self.keymap, &event),
+            KeyContext::Prompt => translate_prompt_event(&event),
+            KeyContext::Confirm => translate_confirm_event(&event),
+        }
+        .map(InputEvent::Action)
+    }
+}
+
+/// Translate a crossterm `Event` into an optional `InputAction`.
 ///
 /// Supported bindings:
-/// - Movement: Arrow keys, WASD (W/A/S/d). Note: uppercase 'D' is reserved for ChangeDifficulty.
+/// - Movement: Arrow keys, WASD (W/A/S/d), or vim-style h/j/k/l. Note: uppercase 'D' is reserved
+///   for `ChangeDifficulty`.
+/// - Diagonal movement: y/U/b/n (roguelike; lowercase 'u' is Undo), or numpad 7/9/1/3 with
+///   Num Lock off (Home/PgUp/End/PgDn); with Num Lock on they are digits, i.e. counts
 /// - Reveal: Enter or Space
-/// - Flag: F/f
+/// - Flag: F/f, or Shift+Space (in terminals that report it)
+/// - Flag and advance: G/g (flag, then step in the last movement direction)
+/// - `ToggleMode`: Tab (swap what Reveal and Flag do)
 /// - Chord: C/c
 /// - Restart: R/r
-/// - ChangeDifficulty: D (uppercase)
+/// - Hint: H (uppercase; lowercase 'h' moves left)
+/// - Undo: u or Ctrl-Y (held keys repeat, walking back several moves; Ctrl-Z suspends)
+/// - `ChangeDifficulty`: D (uppercase)
+/// - `SelectDifficulty`: Alt+1/2/3 for Easy/Medium/Hard; `SelectCustom`: Alt+4
+/// - Show/hide panels: F2 (key legend), F3 (coordinate rulers), F4 (stats), F12 (debug overlay)
+/// - Macros: F5 starts/stops recording, F6 replays the last recording
+/// - Seed prompt: E/e
+/// - Save the finished board as an image: I/i
+/// - Copy an emoji summary of the finished game: P/p
+/// - Back: Esc closes the topmost overlay/dialog; at the top level it asks to quit
+/// - Suspend to the shell: Ctrl-Z (Unix)
 /// - Quit: Q/q or Ctrl-C
-pub fn translate_event(ev: Event) -> Option<InputAction> {
+/// - Pan viewport: click and drag with the left mouse button
+pub fn translate_event(ev: &Event) -> Option<InputAction> {
     match ev {
-        Event::Key(KeyEvent { code, modifiers, .. }) => {
+        // Presses and auto-repeats (held keys) are handled; releases are ignored.
+        Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        }) if *kind != KeyEventKind::Release => {
+            // The bindings below go by a letter's case. Terminals speaking the kitty keyboard
+            // protocol may send Shift+d as 'd' with Shift held, so the case comes from Shift.
+            let code = &match *code {
+                KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                    KeyCode::Char(c.to_ascii_uppercase())
+                }
+                code => code,
+            };
             // Handle Ctrl-C as Quit regardless of code case
-            if modifiers.contains(KeyModifiers::CONTROL) {
-                if matches!(code, KeyCode::Char('c') | KeyCode::Char('C')) {
-                    return Some(InputAction::Quit);
+            if modifiers.contains(KeyModifiers::CONTROL) && matches!(code, KeyCode::Char('c' | 'C'))
+            {
+                return Some(InputAction::Quit);
+            }
+            if modifiers.contains(KeyModifiers::CONTROL) && matches!(code, KeyCode::Char('z' | 'Z'))
+            {
+                return Some(InputAction::Suspend);
+            }
+            if modifiers.contains(KeyModifiers::CONTROL) && matches!(code, KeyCode::Char('y' | 'Y'))
+            {
+                return Some(InputAction::Undo);
+            }
+
+            // Alt+digit jumps straight to a difficulty instead of cycling
+            if modifiers.contains(KeyModifiers::ALT) {
+                let difficulty = match code {
+                    KeyCode::Char('1') => Some(Difficulty::Easy),
+                    KeyCode::Char('2') => Some(Difficulty::Medium),
+                    KeyCode::Char('3') => Some(Difficulty::Hard),
+                    _ => None,
+                };
+                if let Some(d) = difficulty {
+                    return Some(InputAction::SelectDifficulty(d));
+                }
+                if *code == KeyCode::Char('4') {
+                    return Some(InputAction::SelectCustom);
                 }
             }
 
             match code {
-                // Movement via arrows
-                KeyCode::Left => Some(InputAction::Move(Dir::Left)),
-                KeyCode::Right => Some(InputAction::Move(Dir::Right)),
-                KeyCode::Up => Some(InputAction::Move(Dir::Up)),
-                KeyCode::Down => Some(InputAction::Move(Dir::Down)),
+                // Movement via arrows, WASD (lowercase/uppercase except 'D' uppercase) or vim
+                // keys (lowercase 'h' only; 'H' is Hint)
+                KeyCode::Left | KeyCode::Char('a' | 'A' | 'h') => {
+                    Some(InputAction::Move(Dir::Left))
+                }
+                KeyCode::Right | KeyCode::Char('d' | 'l' | 'L') => {
+                    Some(InputAction::Move(Dir::Right))
+                }
+                KeyCode::Up | KeyCode::Char('w' | 'W' | 'k' | 'K') => {
+                    Some(InputAction::Move(Dir::Up))
+                }
+                KeyCode::Down | KeyCode::Char('s' | 'S' | 'j' | 'J') => {
+                    Some(InputAction::Move(Dir::Down))
+                }
+
+                // Diagonal movement via roguelike keys or the numpad with NumLock off
+                KeyCode::Home | KeyCode::Char('y' | 'Y') => Some(InputAction::Move(Dir::UpLeft)),
+                KeyCode::PageUp | KeyCode::Char('U') => Some(InputAction::Move(Dir::UpRight)),
+                KeyCode::End | KeyCode::Char('b' | 'B') => Some(InputAction::Move(Dir::DownLeft)),
+                KeyCode::PageDown | KeyCode::Char('n' | 'N') => {
+                    Some(InputAction::Move(Dir::DownRight))
+                }
 
                 // Reveal via Enter/Space
+                // Shift+Space flags, so the hand can stay on the reveal key
+                KeyCode::Char(' ') if modifiers.contains(KeyModifiers::SHIFT) => {
+                    Some(InputAction::Flag)
+                }
                 KeyCode::Enter | KeyCode::Char(' ') => Some(InputAction::Reveal),
 
                 // Chord
-                KeyCode::Char('c') | KeyCode::Char('C') => Some(InputAction::Chord),
+                KeyCode::Char('c' | 'C') => Some(InputAction::Chord),
+
+                // Open every cell left once all mines are flagged
+                KeyCode::Char('v' | 'V') => Some(InputAction::OpenRemaining),
+
+                // Count the cursor cell's neighbors
+                KeyCode::Char('t' | 'T') => Some(InputAction::Peek),
 
                 // Flag
-                KeyCode::Char('f') | KeyCode::Char('F') => Some(InputAction::Flag),
+                KeyCode::Char('f' | 'F') => Some(InputAction::Flag),
+                KeyCode::Char('g' | 'G') => Some(InputAction::FlagAdvance),
+
+                // Swap the primary action between reveal and flag
+                KeyCode::Tab => Some(InputAction::ToggleMode),
 
                 // Restart
-                KeyCode::Char('r') | KeyCode::Char('R') => Some(InputAction::Restart),
+                KeyCode::Char('r' | 'R') => Some(InputAction::Restart),
+
+                // Panel visibility
+                KeyCode::F(2) => Some(InputAction::TogglePanel(Panel::Footer)),
+                KeyCode::F(3) => Some(InputAction::TogglePanel(Panel::Rulers)),
+                KeyCode::F(4) => Some(InputAction::TogglePanel(Panel::Stats)),
+                KeyCode::F(12) => Some(InputAction::TogglePanel(Panel::Debug)),
+
+                // Seed prompt
+                KeyCode::Char('e' | 'E') => Some(InputAction::OpenSeedPrompt),
+
+                // Puzzle pack browser
+                KeyCode::Char('o' | 'O') => Some(InputAction::OpenPacks),
+
+                // Image, summary and move-log export of the finished game
+                KeyCode::Char('i' | 'I') => Some(InputAction::Export),
+                KeyCode::Char('p' | 'P') => Some(InputAction::Share),
+                KeyCode::Char('m' | 'M') => Some(InputAction::SaveMoves),
+
+                // Macro recording and playback
+                KeyCode::F(5) => Some(InputAction::ToggleRecording),
+                KeyCode::F(6) => Some(InputAction::PlayMacro),
+
+                // Hint (upper-case H; lowercase 'h' moves left)
+                KeyCode::Char('H') => Some(InputAction::Hint),
+
+                // Undo (lowercase only; uppercase 'U' moves diagonally)
+                KeyCode::Char('u') => Some(InputAction::Undo),
 
                 // Change difficulty (upper-case D)
                 KeyCode::Char('D') => Some(InputAction::ChangeDifficulty),
 
-                // Quit
-                KeyCode::Char('q') | KeyCode::Char('Q') => Some(InputAction::Quit),
+                // Close the topmost layer, or ask to quit
+                KeyCode::Esc => Some(InputAction::Back),
 
-                // Movement via WASD (lowercase/uppercase except 'D' uppercase)
-                KeyCode::Char('a') | KeyCode::Char('A') => Some(InputAction::Move(Dir::Left)),
-                KeyCode::Char('d') /* lowercase only */ => Some(InputAction::Move(Dir::Right)),
-                KeyCode::Char('w') | KeyCode::Char('W') => Some(InputAction::Move(Dir::Up)),
-                KeyCode::Char('s') | KeyCode::Char('S') => Some(InputAction::Move(Dir::Down)),
+                // Quit
+                KeyCode::Char('q' | 'Q') => Some(InputAction::Quit),
 
                 _ => None,
             }
         }
+        &Event::Mouse(MouseEvent {
+            kind, column, row, ..
+        }) => match kind {
+            MouseEventKind::Down(MouseButton::Left) => Some(InputAction::DragStart(column, row)),
+            MouseEventKind::Drag(MouseButton::Left) => Some(InputAction::Drag(column, row)),
+            MouseEventKind::Up(MouseButton::Left) => Some(InputAction::DragEnd),
+            _ => None,
+        },
         _ => None,
     }
 }
 
+/// Extra key bindings (from the config file), checked before the built-in ones.
+///
+/// A binding may name modifiers, like `shift+space` or `ctrl+f`. A plain binding also
+/// applies with Shift held, but Ctrl and Alt combinations keep their built-in meaning unless
+/// bound themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: HashMap<(KeyCode, KeyModifiers), InputAction>,
+}
+
+impl Keymap {
+    /// Bind a key name (a single character, `space`, `enter`, `tab`, `esc`, `backspace`,
+    /// an arrow, `home`/`end`/`pageup`/`pagedown`, or `f1`-`f12`), optionally after
+    /// `shift+`, `ctrl+` or `alt+`, to an action name such as `reveal` or `move-left`.
+    ///
+    /// # Errors
+    /// Fails if either name is not recognised, or for Ctrl+C and Ctrl+Z, which always quit
+    /// and suspend.
+    pub fn bind(&mut self, key: &str, action: &str) -> anyhow::Result<()> {
+        let chord = parse_chord(key).with_context(|| format!("unknown key '{key}'"))?;
+        if chord.1.contains(KeyModifiers::CONTROL)
+            && matches!(chord.0, KeyCode::Char('c' | 'C' | 'z' | 'Z'))
+        {
+            bail!("'{key}' is reserved");
+        }
+        let Some(action) = parse_action(action) else {
+            bail!("unknown action '{action}' for key '{key}'");
+        };
+        self.bindings.insert(chord, action);
+        Ok(())
+    }
+
+    /// Translate an event like [`translate_event`], preferring the custom bindings.
+    pub fn translate(&self, ev: &Event) -> Option<InputAction> {
+        self.lookup(ev).or_else(|| translate_event(ev))
+    }
+
+    fn lookup(&self, ev: &Event) -> Option<InputAction> {
+        match ev {
+            Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind,
+                ..
+            }) if *kind != KeyEventKind::Release => {
+                let chord = normalize(*code, *modifiers);
+                let plain = (chord.0, KeyModifiers::NONE);
+                self.bindings
+                    .get(&chord)
+                    .or_else(|| {
+                        let held = KeyModifiers::CONTROL | KeyModifiers::ALT;
+                        (!chord.1.intersects(held))
+                            .then(|| self.bindings.get(&plain))
+                            .flatten()
+                    })
+                    .copied()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A key name with any `shift+`, `ctrl+` and `alt+` prefixes, like `shift+space`.
+fn parse_chord(name: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut key = name;
+    while let Some((modifier, rest)) = key.split_once('+').filter(|(_, rest)| !rest.is_empty()) {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "shift" => KeyModifiers::SHIFT,
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            _ => return None,
+        };
+        key = rest;
+    }
+    Some(normalize(parse_key(key)?, modifiers))
+}
+
+/// A key and the modifiers that matter for bindings. Shift is carried by a letter's case
+/// rather than kept as a modifier, so `shift+a` and `A` are the same key.
+fn normalize(code: KeyCode, modifiers: KeyModifiers) -> (KeyCode, KeyModifiers) {
+    let modifiers = modifiers & (KeyModifiers::SHIFT | KeyModifiers::CONTROL | KeyModifiers::ALT);
+    match code {
+        KeyCode::Char(c) if c != ' ' && modifiers.contains(KeyModifiers::SHIFT) => (
+            KeyCode::Char(c.to_ascii_uppercase()),
+            modifiers.difference(KeyModifiers::SHIFT),
+        ),
+        _ => (code, modifiers),
+    }
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let lower = name.to_ascii_lowercase();
+    let code = match lower.as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "esc" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => {
+            let n = lower.strip_prefix('f')?.parse().ok()?;
+            return (1..=12).contains(&n).then_some(KeyCode::F(n));
+        }
+    };
+    Some(code)
+}
+
+fn parse_action(name: &str) -> Option<InputAction> {
+    let action = match name {
+        "move-left" => InputAction::Move(Dir::Left),
+        "move-right" => InputAction::Move(Dir::Right),
+        "move-up" => InputAction::Move(Dir::Up),
+        "move-down" => InputAction::Move(Dir::Down),
+        "move-up-left" => InputAction::Move(Dir::UpLeft),
+        "move-up-right" => InputAction::Move(Dir::UpRight),
+        "move-down-left" => InputAction::Move(Dir::DownLeft),
+        "move-down-right" => InputAction::Move(Dir::DownRight),
+        "reveal" => InputAction::Reveal,
+        "flag" => InputAction::Flag,
+        "flag-advance" => InputAction::FlagAdvance,
+        "chord" => InputAction::Chord,
+        "open-remaining" => InputAction::OpenRemaining,
+        "peek" => InputAction::Peek,
+        "restart" => InputAction::Restart,
+        "undo" => InputAction::Undo,
+        "hint" => InputAction::Hint,
+        "toggle-mode" => InputAction::ToggleMode,
+        "toggle-footer" => InputAction::TogglePanel(Panel::Footer),
+        "toggle-rulers" => InputAction::TogglePanel(Panel::Rulers),
+        "toggle-stats" => InputAction::TogglePanel(Panel::Stats),
+        "toggle-debug" => InputAction::TogglePanel(Panel::Debug),
+        "record" => InputAction::ToggleRecording,
+        "play-macro" => InputAction::PlayMacro,
+        "seed" => InputAction::OpenSeedPrompt,
+        "packs" => InputAction::OpenPacks,
+        "change-difficulty" => InputAction::ChangeDifficulty,
+        "export" => InputAction::Export,
+        "share" => InputAction::Share,
+        "moves" => InputAction::SaveMoves,
+        "back" => InputAction::Back,
+        "quit" => InputAction::Quit,
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Largest count accepted as a movement prefix; further digits are ignored.
+pub const MAX_COUNT: usize = 999;
+
+/// Pending vim-style count typed before a movement key (e.g. `7l` moves seven cells right).
+///
+/// Plain digits accumulate into the count (a leading `0` is ignored). The next key press
+/// consumes it: movement keys become [`InputAction::MoveBy`], Esc just cancels the count, and
+/// any other key drops it and keeps its usual meaning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CountPrefix {
+    pending: Option<usize>,
+}
+
+impl CountPrefix {
+    /// Translate an event through `keymap`, applying and updating the pending count.
+    /// Digits bound in the keymap keep their binding instead of starting a count.
+    pub fn translate(&mut self, keymap: &Keymap, ev: &Event) -> Option<InputAction> {
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        }) = ev
+        else {
+            return keymap.translate(ev);
+        };
+        if *kind == KeyEventKind::Release {
+            return None;
+        }
+
+        if keymap.lookup(ev).is_none()
+            && let KeyCode::Char(c) = code
+            && let Some(digit) = c.to_digit(10)
+            && !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            && (digit != 0 || self.pending.is_some())
+        {
+            let count = self.pending.unwrap_or(0) * 10 + digit as usize;
+            self.pending = Some(count.min(MAX_COUNT));
+            return None;
+        }
+
+        let action = keymap.translate(ev);
+        match (action, self.pending.take()) {
+            (Some(InputAction::Move(dir)), Some(count)) => Some(InputAction::MoveBy(dir, count)),
+            (Some(InputAction::Back), Some(_)) => None,
+            _ => action,
+        }
+    }
+}
+
+/// Translate events while a text prompt is open: printable keys are typed into the
+/// prompt, Enter submits, Esc cancels, and Ctrl-C still quits.
+pub fn translate_prompt_event(ev: &Event) -> Option<InputAction> {
+    let Event::Key(KeyEvent {
+        code,
+        modifiers,
+        kind,
+        ..
+    }) = ev
+    else {
+        return None;
+    };
+    if *kind == KeyEventKind::Release {
+        return None;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        return matches!(code, KeyCode::Char('c' | 'C')).then_some(InputAction::Quit);
+    }
+    match code {
+        KeyCode::Char(c) => Some(InputAction::PromptChar(*c)),
+        KeyCode::Backspace => Some(InputAction::PromptBackspace),
+        KeyCode::Enter => Some(InputAction::PromptSubmit),
+        KeyCode::Esc => Some(InputAction::Back),
+        _ => None,
+    }
+}
+
+/// Translate events while the quit confirmation is open: Y/Enter confirms, N/Esc goes back.
+pub fn translate_confirm_event(ev: &Event) -> Option<InputAction> {
+    match ev {
+        Event::Key(KeyEvent { code, kind, .. }) if *kind != KeyEventKind::Release => match code {
+            KeyCode::Char('y' | 'Y' | 'q' | 'Q') | KeyCode::Enter => Some(InputAction::ConfirmQuit),
+            KeyCode::Char('n' | 'N') | KeyCode::Esc => Some(InputAction::Back),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn undo_stays_on_the_keyboard_beside_suspend() {
+        for (code, modifiers) in [
+            (KeyCode::Char('u'), KeyModifiers::NONE),
+            (KeyCode::Char('y'), KeyModifiers::CONTROL),
+        ] {
+            assert_eq!(
+                translate_event(&key(code, modifiers)),
+                Some(InputAction::Undo)
+            );
+        }
+        assert_eq!(
+            translate_event(&key(KeyCode::Char('z'), KeyModifiers::CONTROL)),
+            Some(InputAction::Suspend)
+        );
+    }
+
+    #[test]
+    fn alt_digits_select_difficulty() {
+        assert_eq!(
+            translate_event(&key(KeyCode::Char('3'), KeyModifiers::ALT)),
+            Some(InputAction::SelectDifficulty(Difficulty::Hard))
+        );
+        assert_eq!(
+            translate_event(&key(KeyCode::Char('4'), KeyModifiers::ALT)),
+            Some(InputAction::SelectCustom)
+        );
+        assert_eq!(
+            translate_event(&key(KeyCode::Char('3'), KeyModifiers::NONE)),
+            None
+        );
+    }
+
+    #[test]
+    fn count_prefix_repeats_movement() {
+        let keymap = Keymap::default();
+        let mut counts = CountPrefix::default();
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('1'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('2'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(counts.pending, Some(12));
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('l'), KeyModifiers::NONE)),
+            Some(InputAction::MoveBy(Dir::Right, 12))
+        );
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('l'), KeyModifiers::NONE)),
+            Some(InputAction::Move(Dir::Right))
+        );
+    }
+
+    #[test]
+    fn count_prefix_is_dropped_by_other_keys() {
+        let keymap = Keymap::default();
+        let mut counts = CountPrefix::default();
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('0'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(counts.pending, None);
+        counts.translate(&keymap, &key(KeyCode::Char('7'), KeyModifiers::NONE));
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Esc, KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(counts.pending, None);
+        counts.translate(&keymap, &key(KeyCode::Char('7'), KeyModifiers::NONE));
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('f'), KeyModifiers::NONE)),
+            Some(InputAction::Flag)
+        );
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Some(InputAction::Move(Dir::Down))
+        );
+    }
+
+    #[test]
+    fn keymap_overrides_defaults_and_counts() {
+        let mut keymap = Keymap::default();
+        keymap.bind("x", "reveal").expect("valid binding");
+        keymap.bind("5", "hint").expect("valid binding");
+        assert!(keymap.bind("f13", "reveal").is_err());
+        assert!(keymap.bind("x", "explode").is_err());
+
+        let mut counts = CountPrefix::default();
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('x'), KeyModifiers::NONE)),
+            Some(InputAction::Reveal)
+        );
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('5'), KeyModifiers::NONE)),
+            Some(InputAction::Hint)
+        );
+        assert_eq!(
+            counts.translate(&keymap, &key(KeyCode::Char('x'), KeyModifiers::CONTROL)),
+            None
+        );
+    }
+
+    #[test]
+    fn shift_sets_the_case_of_letters() {
+        assert_eq!(
+            translate_event(&key(KeyCode::Char('d'), KeyModifiers::SHIFT)),
+            Some(InputAction::ChangeDifficulty)
+        );
+        assert_eq!(
+            translate_event(&key(KeyCode::Char('d'), KeyModifiers::NONE)),
+            Some(InputAction::Move(Dir::Right))
+        );
+        let release = Event::Key(KeyEvent::new_with_kind(
+            KeyCode::Char('u'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        ));
+        assert_eq!(translate_event(&release), None);
+    }
+
+    #[test]
+    fn modifier_chords_bind_and_shift_space_flags() {
+        let space = |modifiers| key(KeyCode::Char(' '), modifiers);
+        assert_eq!(
+            translate_event(&space(KeyModifiers::SHIFT)),
+            Some(InputAction::Flag)
+        );
+        assert_eq!(
+            translate_event(&space(KeyModifiers::NONE)),
+            Some(InputAction::Reveal)
+        );
+
+        let mut keymap = Keymap::default();
+        keymap.bind("ctrl+space", "chord").expect("valid binding");
+        keymap.bind("shift+x", "hint").expect("valid binding");
+        assert!(keymap.bind("ctrl+c", "reveal").is_err(), "reserved");
+        assert!(keymap.bind("meta+x", "reveal").is_err());
+        assert_eq!(
+            keymap.translate(&space(KeyModifiers::CONTROL)),
+            Some(InputAction::Chord)
+        );
+        assert_eq!(
+            keymap.translate(&key(KeyCode::Char('X'), KeyModifiers::SHIFT)),
+            Some(InputAction::Hint)
+        );
+        assert_eq!(
+            keymap.translate(&space(KeyModifiers::SHIFT)),
+            Some(InputAction::Flag),
+            "unbound chords keep their built-in meaning"
+        );
+    }
+
+    #[test]
+    fn key_context_follows_top_layer() {
+        use minesweeper::app::Action;
+
+        let mut app = AppState::new(Difficulty::Easy);
+        assert_eq!(KeyContext::of(&app), KeyContext::Game);
+        app.handle_action(Action::OpenSeedPrompt);
+        assert_eq!(KeyContext::of(&app), KeyContext::Prompt);
+        app.handle_action(Action::Back);
+        app.handle_action(Action::Back);
+        assert_eq!(KeyContext::of(&app), KeyContext::Confirm);
+    }
+
+    #[test]
+    fn keys_are_translated_for_the_layer_on_top_when_handled() {
+        let mut translator = Translator::new(Keymap::default());
+        let typed = |c| key(KeyCode::Char(c), KeyModifiers::NONE);
+        assert_eq!(
+            translator.translate(typed('e'), KeyContext::Game),
+            Some(InputEvent::Action(InputAction::OpenSeedPrompt))
+        );
+        assert_eq!(
+            translator.translate(typed('r'), KeyContext::Prompt),
+            Some(InputEvent::Action(InputAction::PromptChar('r')))
+        );
+        assert_eq!(
+            translator.translate(Event::Paste("42".to_string()), KeyContext::Prompt),
+            Some(InputEvent::Paste("42".to_string()))
+        );
+    }
+
+    #[test]
+    fn alt_letters_keep_their_binding() {
+        assert_eq!(
+            translate_event(&key(KeyCode::Char('f'), KeyModifiers::ALT)),
+            Some(InputAction::Flag)
+        );
+    }
+}
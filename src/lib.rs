@@ -1,6 +1,86 @@
+//! Minesweeper game engine.
+//!
+//! The engine has no terminal dependencies: the `minesweeper` binary is one front end that
+//! translates keys into [`app::Action`]s and draws [`app::AppState`]. Other programs (GUIs,
+//! bots, tests) can drive a game the same way:
+//!
+//! ```
+//! use minesweeper::app::{Action, AppState, Status};
+//! use minesweeper::difficulty::Difficulty;
+//! use minesweeper::solver;
+//!
+//! let mut game = AppState::new(Difficulty::Easy);
+//! game.handle_action(Action::Reveal);
+//! assert_ne!(game.status, Status::Lose); // the first reveal is always safe
+//!
+//! // The solver only looks at what a player could see.
+//! let deductions = solver::deduce(&game.board);
+//! # let _ = deductions;
+//! ```
+//!
+//! [`game::Game`] wraps a game in a smaller API that is kept stable across releases: moves
+//! that name their cell, owned [`game::Snapshot`]s and the events since the last look. Prefer
+//! it where the rest of the engine's internals are not needed.
+//!
+//! To show a game, implement [`render::Renderer`]; [`render::TextRenderer`] prints the board
+//! as plain text.
+//!
+//! To follow a game without polling it (for sounds, statistics or a network peer), take the
+//! [`events::Event`]s it emits with `game.events.drain()` after each action.
+//!
+//! With the `wasm` feature the engine also builds for `wasm32-unknown-unknown`, and the
+//! `wasm` module wraps a game in a JavaScript-friendly `Game` class.
+#![deny(clippy::all, clippy::pedantic)]
+#![warn(missing_docs)]
+// Library accessors are plain getters; `#[must_use]` on each would only be noise.
+#![allow(clippy::must_use_candidate, clippy::return_self_not_must_use)]
+
+/// Mine counts that follow the player's win rate.
+pub mod adaptive;
+/// Game session state and the actions that drive it.
 pub mod app;
+/// The minefield and its rules.
 pub mod board;
+/// Viewport onto boards larger than the screen.
+pub mod camera;
+/// Roguelike runs of boards with lives and perks.
+pub mod campaign;
+/// Shareable board codes like `MS-hard-9xQk2`.
+pub mod code;
+/// The second-chance minigame for mines that go off.
+pub mod defuse;
+/// Preset difficulty levels.
 pub mod difficulty;
+/// Error and result types.
 pub mod error;
-pub mod input;
-pub mod ui;
+/// Typed events a game emits as it is played, for front ends to follow.
+pub mod events;
+/// Finished boards as SVG (and, with the `png` feature, PNG) images.
+pub mod export;
+/// A game behind a small, stable API for embedders and bots.
+pub mod game;
+/// Board generation outside of a game, including no-guess layouts.
+pub mod generator;
+/// Two players taking turns on one board.
+pub mod hotseat;
+/// Board shapes read from text stencils and monochrome images.
+pub mod mask;
+/// Human-readable logs of the moves in a game.
+pub mod movelog;
+/// Timings collected with `--profile`.
+pub mod profile;
+/// Hand-made boards in a simple text format.
+pub mod puzzle;
+/// Recorded action macros.
+pub mod recording;
+/// The `Renderer` extension point for front ends, a plain-text renderer and cell names.
+pub mod render;
+/// Verifying recorded games by replaying them.
+pub mod replay;
+/// Points for play in scoring mode.
+pub mod score;
+/// Deductions from the visible board, used for hints.
+pub mod solver;
+/// JavaScript bindings for browser front ends (`wasm` feature).
+#[cfg(feature = "wasm")]
+pub mod wasm;
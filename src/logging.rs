@@ -0,0 +1,41 @@
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tracing_subscriber::filter::LevelFilter;
+
+/// Environment variable naming the most detailed level logged: `error`, `warn` (the
+/// default), `info`, `debug`, `trace` or `off`.
+pub const LEVEL_VAR: &str = "MINESWEEPER_LOG";
+
+/// The default log file: `minesweeper.log` in the user data directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("minesweeper").join("minesweeper.log"))
+}
+
+/// Send `tracing` output to the end of the file at `path`. While the game has the terminal,
+/// anything written to stderr would land on top of the board, so problems are logged here
+/// instead. Nothing is set up when the level is `off`.
+///
+/// # Errors
+/// Fails if the file cannot be opened or a subscriber is already set.
+pub fn init(path: &Path) -> io::Result<()> {
+    let level = std::env::var(LEVEL_VAR)
+        .ok()
+        .and_then(|level| LevelFilter::from_str(&level).ok())
+        .unwrap_or(LevelFilter::WARN);
+    if level == LevelFilter::OFF {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_ansi(false)
+        .with_writer(Mutex::new(file))
+        .try_init()
+        .map_err(io::Error::other)
+}
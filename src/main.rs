@@ -1,142 +1,957 @@
+//! Terminal front end: key/mouse translation and ratatui rendering on top of the
+//! `minesweeper` engine crate.
 #![deny(clippy::all, clippy::pedantic)]
 
-mod app;
-mod board;
-mod difficulty;
+mod autosave;
+#[cfg(feature = "bot")]
+mod bot;
+mod cast;
+mod cli;
+mod clipboard;
+mod commands;
+mod config;
+mod coop;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod generate;
+mod headless;
+mod history;
 mod input;
+mod logging;
+mod msp;
+mod net;
+mod notify;
+mod overlay;
+mod progress;
+mod race;
+mod runs;
+mod serve;
+#[cfg(unix)]
+mod signals;
+mod simulate;
+mod sound;
+#[cfg(feature = "spectate")]
+mod spectate;
+mod submit;
+mod terminal;
 mod ui;
+mod versus;
 
-use crate::app::{Action, AppState, Command};
-use crate::difficulty::Difficulty;
-use crate::input::{translate_event, Dir, InputAction};
-use crate::ui::draw_app;
-use crossterm::event::{poll, read};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
-use crossterm::ExecutableCommand;
-use ratatui::prelude::CrosstermBackend;
-use ratatui::Terminal;
-use std::io::{stdout, Stdout};
-use std::time::Duration;
+use anyhow::Context;
+use cast::Output;
+use clap::{CommandFactory, Parser, error::ErrorKind};
+use cli::{Cli, Command as CliCommand, SettingsAction};
+use config::Config;
+use crossterm::event::EventStream;
+use futures::StreamExt;
+use input::{Dir, InputAction, InputEvent, KeyContext, Keymap, Translator};
+use minesweeper::adaptive::Adaptive;
+use minesweeper::app::{Action, AppState, Command, InputMode, Status};
+use minesweeper::board::{Board, Layout, Topology};
+use minesweeper::campaign::Campaign;
+use minesweeper::difficulty::Difficulty;
+use minesweeper::error::{self, Error};
+use minesweeper::events::Event;
+use minesweeper::export;
+use minesweeper::hotseat::HotSeat;
+use minesweeper::mask::{self, Mask};
+use minesweeper::movelog;
+use minesweeper::profile::Profile;
+use minesweeper::puzzle;
+use minesweeper::render::Renderer;
+use minesweeper::score::Score;
+use net::Session;
+use ratatui::backend::{Backend, CrosstermBackend};
+use std::fs;
+use std::io::stdout;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use terminal::TerminalGuard;
+use tokio::runtime::{self, Runtime};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::{self, Interval};
+use ui::{Appearance, TuiRenderer};
 
 fn main() {
-    // 1) Initialize terminal backend and enable raw mode
-    let mut stdout = stdout();
-    if let Err(e) = enable_raw_mode() {
-        eprintln!("Failed to enable raw mode: {e}");
+    // 0) Parse arguments before touching the terminal, so errors and --help print normally
+    let cli = Cli::parse();
+    if run_tool(&cli) {
         return;
     }
-    if let Err(e) = stdout.execute(EnterAlternateScreen) {
-        eprintln!("Failed to enter alternate screen: {e}");
-        let _ = disable_raw_mode();
+    let (config, keymap) = exit_on_error(load_config(&cli));
+    let difficulty = board_difficulty(&cli, config.difficulty);
+    let appearance = cli.appearance(config.appearance());
+
+    if play_without_tui(&cli, &config, difficulty) {
+        return;
+    }
+    // The main loop runs on a single-threaded runtime: terminal input, the network reader,
+    // the command readers, signals and timers are its tasks and futures. Work that blocks
+    // with no async form, like polling a gamepad or serving spectators, keeps threads of its
+    // own.
+    let runtime = exit_on_error(runtime());
+    let entered = runtime.enter();
+    if cli.versus {
+        let setup = |app: &mut AppState| {
+            app.double_tap_timeout = config.double_tap_timeout();
+            apply_modes(app, &cli);
+        };
+        exit_on_error(output(&cli).and_then(|output| {
+            runtime.block_on(versus::run(
+                output,
+                difficulty,
+                cli.seed,
+                appearance,
+                config.tick(),
+                config.frame_interval(),
+                setup,
+            ))
+        }));
         return;
     }
 
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = match Terminal::new(backend) {
-        Ok(t) => t,
-        Err(e) => {
-            let mut s = std::io::stdout();
-            let _ = s.execute(LeaveAlternateScreen);
-            let _ = disable_raw_mode();
-            eprintln!("Failed to create terminal: {e}");
-            return;
-        }
+    // 1) Instantiate AppState with the requested difficulty, settings and seed; a network
+    //    race replaces the board with the shared one once the other player is connected
+    let mut app = new_app(&cli, &config, difficulty);
+    let (tx, rx) = mpsc::unbounded_channel();
+    let session = exit_on_error(connect(&cli, &mut app, difficulty, tx.clone()));
+    #[cfg(feature = "spectate")]
+    let spectators = cli
+        .spectate
+        .map(|port| exit_on_error(spectate::Server::listen(port)));
+
+    let subscribers = Subscribers::new(&config, &mut app, &cli);
+
+    // 2) Log to a file from here on, then enable raw mode and the alternate screen until
+    //    the guard is dropped
+    let _ = logging::default_path().map(|path| logging::init(&path));
+    let (guard, renderer) = exit_on_error(open_screen(&cli, &config, &app, appearance));
+
+    // 3) Event loop: the terminal, the other input sources and the timers are awaited
+    //    together, so rendering never waits on input
+    #[cfg(feature = "gamepad")]
+    gamepad::spawn_reader(tx.clone());
+    // Without handlers the signals still end the game, just without restoring the terminal.
+    #[cfg(unix)]
+    let _ = signals::spawn_forwarder(tx);
+    let mut front = Front {
+        cli: &cli,
+        app,
+        session,
+        subscribers,
+        #[cfg(feature = "spectate")]
+        spectators,
+        renderer,
+        translator: Translator::new(keymap),
     };
+    let played = runtime.block_on(front.run(rx, config.tick()));
 
-    // 2) Instantiate AppState with default difficulty
-    let mut app = AppState::new(Difficulty::Easy);
-
-    // 3) Event loop
-    let tick = Duration::from_millis(50);
-    let mut running = true;
-    while running {
-        // Redraw UI each tick
-        let elapsed_secs = app
-            .start_time
-            .map(|t| t.elapsed().as_secs())
-            .unwrap_or(0);
-        let width = app.board.width();
-        let height = app.board.height();
-        let mines_total = app.board.mines();
-        let flags = app.flags_placed;
-        let cursor = Some((app.cursor.x, app.cursor.y));
-        let status = app.status;
-
-        let correct_flags = app.board.count_correct_flags();
-        if let Err(e) = terminal.draw(|f| {
-            draw_app(
-                f,
-                mines_total,
-                flags,
-                elapsed_secs,
-                width,
-                height,
-                |x, y| app.board.cell_at(x, y),
-                |x, y| app.board.is_mine(x, y),
-                cursor,
-                status,
-                correct_flags,
-            );
-        }) {
-            eprintln!("UI draw error: {e}");
-            break;
-        }
-
-        // Poll for events, handle inputs, and update app state
-        if let Ok(true) = poll(tick) {
-            if let Ok(event) = read() {
-                if let Some(input_action) = translate_event(event) {
-                    match input_action_to_action(input_action, &app) {
-                        Some(AppOrSys::Action(a)) => {
-                            let cmd = app.handle_action(a);
-                            match cmd {
-                                Command::GameWon | Command::GameLost => {
-                                    // Status already updated inside handle_action; we just redraw next tick
-                                }
-                                Command::Redraw | Command::None => {}
-                            }
-                        }
-                        Some(AppOrSys::Quit) => {
-                            running = false;
-                        }
-                        None => {}
-                    }
+    // 4) Restore terminal on exit
+    let Front {
+        app,
+        mut subscribers,
+        renderer,
+        ..
+    } = front;
+    subscribers.saves.close(&app);
+    // Drop the renderer first to release the backend writer
+    drop(renderer);
+    drop(guard);
+    if let Err(e) = played {
+        eprintln!("UI draw error: {e}");
+    }
+    if let Some(profile) = &app.profile {
+        eprint!("{profile}");
+    }
+    // Readers blocked on the network or a pipe are left behind rather than waited for.
+    drop(entered);
+    runtime.shutdown_background();
+}
+
+/// The game as the main loop plays it, with the network session, everything that follows the
+/// game, the screen and the translation of keys.
+struct Front<'a, B: Backend> {
+    cli: &'a Cli,
+    app: AppState,
+    session: Option<Session>,
+    subscribers: Subscribers,
+    #[cfg(feature = "spectate")]
+    spectators: Option<spectate::Server>,
+    renderer: TuiRenderer<B>,
+    translator: Translator,
+}
+
+impl<B: Backend> Front<'_, B> {
+    /// Play until the player quits or the terminal can no longer be read, handling keys as
+    /// they are typed and `inputs` from the other sources as they come, letting the game's
+    /// timers run every `tick` and autosaving as often as the config asks.
+    ///
+    /// # Errors
+    /// Fails if a frame cannot be drawn.
+    async fn run(
+        &mut self,
+        mut inputs: UnboundedReceiver<InputEvent>,
+        tick: Duration,
+    ) -> std::io::Result<()> {
+        let mut terminal = EventStream::new();
+        let mut ticks = time::interval(tick);
+        let mut autosaves = self.subscribers.saves.autosave_every().map(time::interval);
+        loop {
+            self.refresh()?;
+            let due = self.renderer.frame_due(&self.app);
+            let input = tokio::select! {
+                event = terminal.next() => match event {
+                    // Keys are translated only now, for the layer the input before them left
+                    // on top.
+                    Some(Ok(event)) => self.translator.translate(event, KeyContext::of(&self.app)),
+                    None | Some(Err(_)) => return Ok(()),
+                },
+                Some(input) = inputs.recv() => Some(input),
+                _ = ticks.tick() => None,
+                () = ui::next_frame(due) => None,
+                () = next_tick(autosaves.as_mut()) => {
+                    self.subscribers.saves.tick(&self.app);
+                    None
                 }
+            };
+            let Some(input) = input else {
+                continue;
+            };
+            if self.cli.commands_only && !commands::counts_when_commands_only(&input) {
+                continue;
+            }
+            let running = handle_input(
+                input,
+                &mut self.app,
+                &mut self.renderer,
+                self.session.as_mut(),
+            );
+            self.subscribers
+                .dispatch(&mut self.app, self.session.as_mut(), &mut self.renderer);
+            if !running {
+                return Ok(());
             }
         }
     }
 
-    // 4) Restore terminal on exit
-    // Drop terminal first to release the backend writer
-    drop(terminal);
-    let mut s: Stdout = std::io::stdout();
-    let _ = s.execute(LeaveAlternateScreen);
-    let _ = disable_raw_mode();
+    /// Let the game's timers run, fit the camera to the terminal and draw a frame if one is
+    /// due.
+    ///
+    /// # Errors
+    /// Fails if the frame cannot be drawn.
+    fn refresh(&mut self) -> std::io::Result<()> {
+        let (app, renderer) = (&mut self.app, &mut self.renderer);
+        renderer.fit(app);
+        if app.tick() != Command::None {
+            renderer.invalidate();
+        }
+        renderer.opponent = self.session.as_ref().and_then(Session::opponent).copied();
+        renderer.partner = self.session.as_ref().and_then(Session::partner).copied();
+        self.subscribers
+            .dispatch(app, self.session.as_mut(), renderer);
+        if app.status == Status::Playing {
+            renderer.notice = None;
+        }
+        #[cfg(feature = "spectate")]
+        if let Some(server) = &mut self.spectators {
+            let _ = server.render(app);
+        }
+        if renderer.frame_due(app) == Some(Duration::ZERO) {
+            draw(renderer, app)?;
+        }
+        Ok(())
+    }
+}
+
+/// Wait for the next tick of `interval`, or forever without one.
+async fn next_tick(interval: Option<&mut Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Everything in the front end that follows the game through the events it emits: what is
+/// kept on disk, sounds, notifications, the leaderboard, wins sent to a server and stream
+/// overlays.
+struct Subscribers {
+    saves: Saves,
+    sounds: sound::Sounds,
+    notifier: notify::Notifier,
+    leaderboard: history::Leaderboard,
+    submitter: submit::Submitter,
+    overlay: Option<overlay::Overlay>,
+}
+
+impl Subscribers {
+    /// Follow the game as `config` says, reading what is kept on disk into `app`.
+    fn new(config: &Config, app: &mut AppState, cli: &Cli) -> Self {
+        Self {
+            saves: Saves::open(config, app, cli),
+            sounds: sound::Sounds::new(config.sound),
+            notifier: notify::Notifier::new(config.notifications, config.log_path().as_deref()),
+            leaderboard: history::Leaderboard::new(config.log_path().as_deref()),
+            submitter: submit::Submitter::new(config.submit.clone()),
+            overlay: cli
+                .overlay
+                .map(|port| exit_on_error(overlay::Overlay::listen(port))),
+        }
+    }
+
+    /// Take the events `app` emitted since the last call and hand them to each subscriber,
+    /// to the network `session` and to the `renderer`, for where the game stands on the
+    /// leaderboard and the debug overlay. Call after every change to `app`, so each sees the
+    /// game as the events left it.
+    fn dispatch<B: Backend>(
+        &mut self,
+        app: &mut AppState,
+        session: Option<&mut Session>,
+        renderer: &mut TuiRenderer<B>,
+    ) {
+        let events = app.events.drain();
+        for event in &events {
+            tracing::debug!(?event, "game event");
+        }
+        if let Some(session) = session {
+            session.report(app, &events);
+        }
+        self.saves.observe(app, &events);
+        if let Err(e) = self.sounds.observe(&events, &mut stdout()) {
+            tracing::warn!("cannot play a sound: {e}");
+        }
+        if let Err(e) = self.notifier.observe(app, &events, &mut stdout()) {
+            tracing::warn!("cannot send a notification: {e}");
+        }
+        renderer.standing = self.leaderboard.observe(app, &events);
+        if let Some(notice) = self.submitter.observe(app, &events) {
+            tracing::info!("{notice}");
+            // A result that comes in after the next game began is only logged.
+            if app.status != Status::Playing {
+                renderer.notice = Some(notice);
+                renderer.invalidate();
+            }
+        }
+        renderer.debug.record(&events);
+        if let Some(overlay) = &self.overlay {
+            overlay.update(app);
+        }
+    }
+}
+
+/// What is kept on disk as the game is played: the game log, puzzle pack progress, the
+/// best campaign run and the game in progress.
+struct Saves {
+    game_log: Option<history::GameLog>,
+    progress: Option<progress::Progress>,
+    best_run: Option<runs::BestRun>,
+    autosave: Option<autosave::Autosave>,
+}
+
+impl Saves {
+    /// Open the files `config` allows, reading puzzle progress and the best run into `app`.
+    /// Solo games are autosaved, and a launch that does not pick a board of its own resumes
+    /// the game an earlier one left unfinished.
+    fn open(config: &Config, app: &mut AppState, cli: &Cli) -> Self {
+        let mut autosave = config
+            .autosave_every()
+            .filter(|_| cli.command.is_none())
+            .zip(autosave::default_path())
+            .map(|(every, path)| autosave::Autosave::new(path, every));
+        if let Some(autosave) = &mut autosave
+            && !picks_board(cli)
+        {
+            autosave.recover(app);
+        }
+        Self {
+            game_log: config.log_path().map(history::GameLog::new),
+            progress: progress::default_path()
+                .map(|path| progress::Progress::load(path, &mut app.packs)),
+            best_run: app
+                .campaign
+                .as_mut()
+                .zip(runs::default_path())
+                .map(|(campaign, path)| runs::BestRun::load(path, campaign)),
+            autosave,
+        }
+    }
+
+    /// Save what changed in `app` since the last call, `events` being the ones just taken
+    /// from it. Saving is best effort: a read-only location must not end the game.
+    fn observe(&mut self, app: &AppState, events: &[Event]) {
+        let failed = |what: &str, e: std::io::Error| tracing::warn!("cannot save {what}: {e}");
+        if let Some(log) = &self.game_log
+            && let Err(e) = log.observe(app, events)
+        {
+            failed("the game log", e);
+        }
+        if let Some(progress) = &mut self.progress
+            && let Err(e) = progress.observe(&app.packs)
+        {
+            failed("puzzle progress", e);
+        }
+        if let (Some(best_run), Some(campaign)) = (&mut self.best_run, &app.campaign)
+            && let Err(e) = best_run.observe(campaign)
+        {
+            failed("the best run", e);
+        }
+        if let Some(autosave) = &mut self.autosave
+            && let Err(e) = autosave.observe(app)
+        {
+            failed("the autosave", e);
+        }
+    }
+
+    /// How often the game in progress is autosaved, if it is.
+    fn autosave_every(&self) -> Option<Duration> {
+        self.autosave.as_ref().map(autosave::Autosave::every)
+    }
+
+    /// Write the game in progress to the autosave if it changed. Call every
+    /// [`autosave_every`](Self::autosave_every).
+    fn tick(&mut self, app: &AppState) {
+        if let Some(autosave) = &mut self.autosave
+            && let Err(e) = autosave.tick(app)
+        {
+            tracing::warn!("cannot save the autosave: {e}");
+        }
+    }
+
+    /// Save the game in progress on the way out, however the game was left.
+    fn close(&mut self, app: &AppState) {
+        if let Some(autosave) = &mut self.autosave
+            && let Err(e) = autosave.flush(app)
+        {
+            tracing::warn!("cannot save the autosave: {e}");
+        }
+    }
+}
+
+/// The single-threaded runtime the front end runs on.
+fn runtime() -> std::io::Result<Runtime> {
+    runtime::Builder::new_current_thread().enable_all().build()
+}
+
+/// Whether the command line asks for a board or mode of its own rather than the usual game.
+fn picks_board(cli: &Cli) -> bool {
+    cli.difficulty.is_some()
+        || cli.width.is_some()
+        || cli.height.is_some()
+        || cli.mines.is_some()
+        || cli.seed.is_some()
+        || cli.code.is_some()
+        || cli.board.is_some()
+        || cli.mask.is_some()
+        || cli.campaign
+        || cli.hotseat.is_some()
+}
+
+/// Handle one input event and mark the screen for redrawing, returning whether the game
+/// should keep running.
+fn handle_input<B: Backend>(
+    input: InputEvent,
+    app: &mut AppState,
+    renderer: &mut TuiRenderer<B>,
+    session: Option<&mut Session>,
+) -> bool {
+    renderer.invalidate();
+    match input {
+        // Bracketed paste delivers pasted text as a single event for the prompt
+        InputEvent::Paste(text) => {
+            let _ = app.paste(&text);
+        }
+        InputEvent::Action(action)
+            if matches!(input_action_to_action(action, app), AppOrSys::Outside) =>
+        {
+            outside(action, app, renderer);
+        }
+        InputEvent::Action(input_action) => return apply_input(input_action, app, session),
+        InputEvent::Remote(message) => {
+            if let Some(session) = session {
+                session.receive(app, &message);
+            }
+        }
+        InputEvent::Command(line) => match commands::play(&line, app, session) {
+            Ok(command) => return command != Command::Quit,
+            Err(reason) => tracing::debug!("command not played: {reason}"),
+        },
+    }
+    true
+}
+
+/// Carry out an action the front end handles itself: saving or sharing a finished game, or
+/// suspending to the shell.
+fn outside<B: Backend>(action: InputAction, app: &AppState, renderer: &mut TuiRenderer<B>) {
+    match action {
+        InputAction::Suspend | InputAction::Resume => {
+            // A terminal that cannot be retaken shows up as a draw error next frame.
+            #[cfg(unix)]
+            let _ = suspend(renderer, action);
+        }
+        _ if app.status != Status::Playing => renderer.notice = Some(save_result(action, app)),
+        _ => {}
+    }
+}
+
+/// Give the terminal back to the shell and stop (for `Suspend`), then retake it once the
+/// process continues and redraw the whole screen.
+#[cfg(unix)]
+fn suspend<B: Backend>(renderer: &mut TuiRenderer<B>, action: InputAction) -> error::Result<()> {
+    if action == InputAction::Suspend {
+        terminal::suspend();
+        signals::stop();
+    }
+    terminal::resume()?;
+    renderer.clear()?;
+    Ok(())
+}
+
+/// Take the terminal over, below the prompt with `--inline` or else on the alternate screen,
+/// and open a renderer on it, recording to a cast file with `--record`.
+fn open_screen(
+    cli: &Cli,
+    config: &Config,
+    app: &AppState,
+    appearance: Appearance,
+) -> error::Result<(TerminalGuard, TuiRenderer<CrosstermBackend<Output>>)> {
+    let output = output(cli)?;
+    let (guard, terminal, inline) = match cli.inline {
+        None => {
+            let (guard, terminal) = TerminalGuard::enter(output)?;
+            (guard, terminal, None)
+        }
+        Some(lines) => {
+            let (width, height) = crossterm::terminal::size().map_err(Error::TerminalInit)?;
+            let lines = lines
+                .unwrap_or_else(|| ui::inline_height(app, width, height))
+                .min(height);
+            let (guard, terminal) = TerminalGuard::enter_inline(lines, output)?;
+            (guard, terminal, Some(lines))
+        }
+    };
+    let mut renderer = TuiRenderer::new(terminal, appearance);
+    renderer.frame_interval = config.frame_interval();
+    renderer.inline = inline;
+    Ok((guard, renderer))
+}
+
+/// Where to draw: the terminal, and the `--record` cast file if one was asked for.
+fn output(cli: &Cli) -> error::Result<Output> {
+    cli.record
+        .as_deref()
+        .map_or_else(|| Ok(Output::terminal()), Output::recording)
+}
+
+/// Draw a frame, clearing the screen and trying again if drawing fails (see
+/// [`ui::retry_draw`]).
+fn draw<B: Backend>(renderer: &mut TuiRenderer<B>, app: &mut AppState) -> std::io::Result<()> {
+    ui::retry_draw(|recovering| {
+        if recovering {
+            renderer.clear()?;
+        }
+        render_timed(renderer, app)
+    })
+}
+
+/// Draw a frame, adding its time to the profile when profiling.
+fn render_timed<R: Renderer>(renderer: &mut R, app: &mut AppState) -> Result<(), R::Error> {
+    let started = Instant::now();
+    let drawn = renderer.render(app);
+    if let Some(profile) = &mut app.profile {
+        profile.render.add(started.elapsed());
+    }
+    drawn
+}
+
+/// Print an error (with its causes) and exit with status 1.
+fn exit_on_error<T, E: Into<anyhow::Error>>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|e| {
+        let e: anyhow::Error = e.into();
+        eprintln!("Error: {e:#}");
+        std::process::exit(1)
+    })
+}
+
+/// The board to play or generate, exiting with a usage error if it is unplayable.
+fn board_difficulty(cli: &Cli, default: Difficulty) -> Difficulty {
+    cli.difficulty(default)
+        .unwrap_or_else(|e| Cli::command().error(ErrorKind::ValueValidation, e).exit())
+}
+
+/// Load `--config` (or the default config file) and its key bindings.
+fn load_config(cli: &Cli) -> anyhow::Result<(Config, Keymap)> {
+    let config = match cli.config.clone().or_else(Config::default_path) {
+        Some(path) => Config::load_or_create(&path)?,
+        None => Config::default(),
+    };
+    let keymap = config.keymap()?;
+    Ok((config, keymap))
+}
+
+/// Run a mode that plays over stdin/stdout instead of the TUI (bot, MSP or headless), if one
+/// was requested. Returns whether it did.
+fn play_without_tui(cli: &Cli, config: &Config, difficulty: Difficulty) -> bool {
+    #[cfg(feature = "bot")]
+    if let Some(script) = &cli.bot {
+        let mut app = new_app(cli, config, difficulty);
+        exit_on_error(
+            bot::Bot::load(script).and_then(|mut bot| bot::run(&mut app, &mut bot, stdout())),
+        );
+        return true;
+    }
+
+    if cli.msp {
+        let mut app = new_app(cli, config, difficulty);
+        let stdin = std::io::stdin().lock();
+        exit_on_error(msp::run(&mut app, stdin, std::io::stdout()));
+        return true;
+    }
+
+    if cli.headless {
+        let mut app = new_app(cli, config, difficulty);
+        let stdin = std::io::stdin().lock();
+        exit_on_error(headless::run(
+            &mut app,
+            cli.format,
+            stdin,
+            std::io::stdout(),
+        ));
+        return true;
+    }
+    false
+}
+
+/// Create the game with the requested difficulty, config settings and seed.
+fn new_app(cli: &Cli, config: &Config, difficulty: Difficulty) -> AppState {
+    let mut app = AppState::new(difficulty);
+    app.double_tap_timeout = config.double_tap_timeout();
+    app.profile = cli.profile.then(Profile::default);
+    app.panels.footer = config.gameplay.show_footer;
+    app.panels.rulers = config.gameplay.show_rulers;
+    app.guess_hints = config.gameplay.best_guess;
+    app.auto_chord = cli.auto_chord || config.gameplay.auto_chord;
+    app.auto_flag = cli.auto_flag || config.gameplay.auto_flag;
+    app.hardcore = cli.hardcore || config.gameplay.hardcore;
+    app.adaptive = cli.adaptive.then(Adaptive::default);
+    app.campaign = cli.campaign.then(Campaign::default);
+    apply_modes(&mut app, cli);
+    app.seed = cli.seed;
+    app.hotseat = cli.hotseat.map(|scoring| HotSeat::new(scoring, app.cursor));
+    app.restart();
+    if let Some(code) = cli.code {
+        app.apply_code(code);
+    }
+    app.packs = puzzle::builtin_packs();
+    for path in &cli.packs {
+        app.packs.push(exit_on_error(load_pack(path)));
+    }
+    if let Some(path) = &cli.board {
+        app.start_puzzle(exit_on_error(load_puzzle(path)));
+    }
+    if let Some(path) = &cli.mask {
+        let mask = exit_on_error(load_mask(path));
+        let mines = cli.mines.unwrap_or_else(|| mask.default_mines());
+        exit_on_error(app.start_shape(mask, mines));
+    }
+    app
+}
+
+/// Run the subcommand, if it is a tool that needs neither the config nor the terminal;
+/// returns whether one ran.
+fn run_tool(cli: &Cli) -> bool {
+    match &cli.command {
+        Some(CliCommand::Generate(args)) => {
+            let difficulty = board_difficulty(cli, Difficulty::Easy);
+            exit_on_error(generate::run(
+                args,
+                difficulty.parameters(),
+                cli.seed,
+                cli.layout.unwrap_or(Layout::Uniform),
+            ));
+            true
+        }
+        Some(CliCommand::Simulate(args)) => {
+            let difficulty = board_difficulty(cli, Difficulty::Easy);
+            exit_on_error(simulate::run(
+                args,
+                difficulty.parameters(),
+                cli.seed,
+                cli.layout.unwrap_or(Layout::Uniform),
+            ));
+            true
+        }
+        Some(CliCommand::Serve { port, data }) => {
+            exit_on_error(serve::run(*port, data.clone().or_else(serve::default_path)));
+            true
+        }
+        Some(CliCommand::Export { output }) => {
+            exit_on_error(export_log(cli, output));
+            true
+        }
+        Some(CliCommand::Settings { action }) => {
+            exit_on_error(transfer_settings(cli, action));
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Write the game log as CSV files in `dir`, for `export`.
+fn export_log(cli: &Cli, dir: &Path) -> anyhow::Result<()> {
+    let (config, _) = load_config(cli)?;
+    let log = config
+        .log_path()
+        .context("the game log is turned off in the config")?;
+    let written = history::export_csv(&log, dir)
+        .with_context(|| format!("exporting the game log at {}", log.display()))?;
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Export or import a settings profile, for `settings`.
+fn transfer_settings(cli: &Cli, action: &SettingsAction) -> anyhow::Result<()> {
+    let path = cli
+        .config
+        .clone()
+        .or_else(Config::default_path)
+        .context("no config directory on this system; name a config with --config")?;
+    match action {
+        SettingsAction::Export { file } => {
+            config::export_profile(&path, file)?;
+            println!("Wrote {}", file.display());
+        }
+        SettingsAction::Import { file } => {
+            let backup = config::import_profile(file, &path)?;
+            println!("Imported the settings into {}", path.display());
+            if let Some(backup) = backup {
+                println!("The old settings are in {}", backup.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Turn on the game modes chosen on the command line that solo and versus games share.
+fn apply_modes(app: &mut AppState, cli: &Cli) {
+    app.practice = cli.practice;
+    if cli.cylinder {
+        app.topology = Topology::Cylinder;
+    }
+    if let Some(layout) = cli.layout {
+        app.layout = layout;
+    }
+    app.score = cli.score.then(Score::default);
+    app.memory = cli.memory.map(Duration::from_secs);
+    app.fog = cli.fog;
+    app.flag_penalty = cli.flag_penalty.map(Duration::from_secs);
+    app.flag_budget = cli.flag_budget.map(|budget| budget.unwrap_or(usize::MAX));
+    app.defuse_penalty = cli.defuse.map(Duration::from_secs);
+}
+
+/// Read a hand-made board for `--board`.
+fn load_puzzle(path: &Path) -> anyhow::Result<Board> {
+    let text = fs::read_to_string(path).map_err(|error| Error::LoadIo {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    puzzle::parse(&text).with_context(|| format!("invalid board {}", path.display()))
+}
+
+/// Read a board shape for `--mask`.
+fn load_mask(path: &Path) -> anyhow::Result<Mask> {
+    let bytes = fs::read(path).map_err(|error| Error::LoadIo {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    mask::parse(&bytes).with_context(|| format!("invalid shape {}", path.display()))
+}
+
+/// Read a puzzle pack for `--pack`.
+fn load_pack(path: &Path) -> anyhow::Result<puzzle::Pack> {
+    let text = fs::read_to_string(path).map_err(|error| Error::LoadIo {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    puzzle::parse_pack(&text).with_context(|| format!("invalid pack {}", path.display()))
+}
+
+/// Save the finished board as `minesweeper-<unix time>.svg` (and `.png` with the `png`
+/// feature) in the current directory, returning what to tell the player.
+fn save_image(app: &AppState) -> error::Result<String> {
+    let stamp = unix_time();
+    let svg = format!("minesweeper-{stamp}.svg");
+    save(&svg, export::svg(app))?;
+    #[cfg(feature = "png")]
+    let svg = {
+        let png = format!("minesweeper-{stamp}.png");
+        let image = export::png(app).map_err(|e| Error::SaveIo {
+            path: PathBuf::from(&png),
+            error: std::io::Error::other(e),
+        })?;
+        save(&png, image)?;
+        format!("{svg} and {png}")
+    };
+    Ok(format!("Saved {svg}"))
+}
+
+/// Copy the emoji share text to the clipboard, and save it as `minesweeper-<unix time>.txt`
+/// for terminals that do not support clipboard access.
+fn share_result(app: &AppState) -> error::Result<String> {
+    let text = export::share_text(app);
+    clipboard::copy(&mut stdout(), &text)?;
+    let path = format!("minesweeper-{}.txt", unix_time());
+    save(&path, text)?;
+    Ok(format!(
+        "Copied the result to the clipboard and saved {path}"
+    ))
+}
+
+/// Save or share the finished game as `action` asks, returning the notice to show.
+fn save_result(action: InputAction, app: &AppState) -> String {
+    let saved = match action {
+        InputAction::Export => save_image(app),
+        InputAction::SaveMoves => save_moves(app),
+        _ => share_result(app),
+    };
+    saved.unwrap_or_else(|e| e.to_string())
+}
+
+/// Save the move log, headed by what is needed to replay the board, as
+/// `minesweeper-<unix time>-moves.txt`.
+fn save_moves(app: &AppState) -> error::Result<String> {
+    let path = format!("minesweeper-{}-moves.txt", unix_time());
+    save(&path, movelog::transcript(app))?;
+    Ok(format!("Saved the move log to {path}"))
+}
+
+/// Write `contents` to `path` in the current directory.
+fn save(path: &str, contents: impl AsRef<[u8]>) -> error::Result<()> {
+    fs::write(path, contents).map_err(|error| Error::SaveIo {
+        path: PathBuf::from(path),
+        error,
+    })
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Start forwarding `--commands` over `tx`, and for `host` and `join` connect to the other
+/// player, set up the shared board and start forwarding their messages too.
+fn connect(
+    cli: &Cli,
+    app: &mut AppState,
+    difficulty: Difficulty,
+    tx: UnboundedSender<InputEvent>,
+) -> anyhow::Result<Option<Session>> {
+    if let Some(source) = &cli.commands {
+        commands::spawn_reader(source, tx.clone())?;
+    }
+    let (session, hello) = match &cli.command {
+        Some(CliCommand::Host { port, coop }) => Session::host(*port, difficulty, cli.seed, *coop)?,
+        Some(CliCommand::Join { address }) => Session::join(address)?,
+        _ => return Ok(None),
+    };
+    Session::start(app, &hello)?;
+    session.peer().spawn_reader(tx)?;
+    Ok(Some(session))
+}
+
+/// Apply one input action to the app (through the network session, if any), returning
+/// whether the game should keep running.
+fn apply_input(
+    input_action: InputAction,
+    app: &mut AppState,
+    mut session: Option<&mut Session>,
+) -> bool {
+    let mut handle = |app: &mut AppState, action| match session.as_deref_mut() {
+        Some(session) => session.handle_action(app, action),
+        None => app.handle_action(action),
+    };
+    match input_action_to_action(input_action, app) {
+        // Status is updated inside handle_action; we just redraw next tick
+        AppOrSys::Action(a) => handle(app, a) != Command::Quit,
+        AppOrSys::Repeat(a, count) => (0..count).all(|_| handle(app, a) != Command::Quit),
+        AppOrSys::Quit => false,
+        AppOrSys::Outside => true,
+    }
 }
 
 /// Represents either an app action to be handled or a request to quit the app
 enum AppOrSys {
     Action(Action),
+    /// The same action applied several times (from a count prefix like `7l`).
+    Repeat(Action, usize),
     Quit,
+    /// Handled by the front end itself rather than the game, like saving an image.
+    Outside,
 }
 
-/// Map high-level InputAction (from crossterm) into App Action or Quit.
-fn input_action_to_action(input: InputAction, app: &AppState) -> Option<AppOrSys> {
+/// Map high-level `InputAction` (from crossterm) into App Action or Quit.
+fn input_action_to_action(input: InputAction, app: &AppState) -> AppOrSys {
     match input {
-        InputAction::Move(dir) => Some(AppOrSys::Action(match dir {
-            Dir::Left => Action::MoveLeft,
-            Dir::Right => Action::MoveRight,
-            Dir::Up => Action::MoveUp,
-            Dir::Down => Action::MoveDown,
-        })),
-        InputAction::Reveal => Some(AppOrSys::Action(Action::Reveal)),
-        InputAction::Flag => Some(AppOrSys::Action(Action::ToggleFlag)),
-        InputAction::Chord => Some(AppOrSys::Action(Action::Chord)),
-        InputAction::Restart => Some(AppOrSys::Action(Action::Restart)),
+        InputAction::Move(dir) => AppOrSys::Action(move_action(dir)),
+        InputAction::MoveBy(dir, count) => AppOrSys::Repeat(move_action(dir), count),
+        InputAction::Reveal => AppOrSys::Action(match app.input_mode {
+            InputMode::Reveal => Action::Reveal,
+            InputMode::Flag => Action::ToggleFlag,
+        }),
+        InputAction::Flag => AppOrSys::Action(match app.input_mode {
+            InputMode::Reveal => Action::ToggleFlag,
+            InputMode::Flag => Action::Reveal,
+        }),
+        InputAction::FlagAdvance => AppOrSys::Action(Action::FlagAndAdvance),
+        InputAction::ToggleMode => AppOrSys::Action(Action::ToggleInputMode),
+        InputAction::Chord => AppOrSys::Action(Action::Chord),
+        InputAction::OpenRemaining => AppOrSys::Action(Action::OpenRemaining),
+        InputAction::Peek => AppOrSys::Action(Action::Peek),
+        InputAction::Restart => AppOrSys::Action(Action::Restart),
+        InputAction::Undo => AppOrSys::Action(Action::Undo),
+        InputAction::TogglePanel(panel) => AppOrSys::Action(Action::TogglePanel(panel)),
+        InputAction::OpenSeedPrompt => AppOrSys::Action(Action::OpenSeedPrompt),
+        InputAction::OpenPacks => AppOrSys::Action(Action::OpenPacks),
+        InputAction::PromptChar(c) => AppOrSys::Action(Action::PromptChar(c)),
+        InputAction::PromptBackspace => AppOrSys::Action(Action::PromptBackspace),
+        InputAction::PromptSubmit => AppOrSys::Action(Action::PromptSubmit),
+        InputAction::Back => AppOrSys::Action(Action::Back),
+        InputAction::ConfirmQuit => AppOrSys::Action(Action::ConfirmQuit),
+        InputAction::ToggleRecording => AppOrSys::Action(Action::ToggleRecording),
+        InputAction::PlayMacro => AppOrSys::Action(Action::PlayMacro),
+        InputAction::Hint => AppOrSys::Action(Action::Hint),
         InputAction::ChangeDifficulty => {
             let next = app.difficulty.cycle();
-            Some(AppOrSys::Action(Action::SetDifficulty(next)))
+            AppOrSys::Action(Action::SetDifficulty(next))
+        }
+        InputAction::SelectDifficulty(d) => AppOrSys::Action(Action::SetDifficulty(d)),
+        InputAction::SelectCustom => {
+            AppOrSys::Action(Action::SetDifficulty(app.custom_difficulty()))
         }
-        InputAction::Quit => Some(AppOrSys::Quit),
+        InputAction::DragStart(col, row) => AppOrSys::Action(Action::BeginPan(col, row)),
+        InputAction::Drag(col, row) => AppOrSys::Action(Action::PanTo(col, row)),
+        InputAction::DragEnd => AppOrSys::Action(Action::EndPan),
+        InputAction::Quit => AppOrSys::Quit,
+        InputAction::Export
+        | InputAction::Share
+        | InputAction::SaveMoves
+        | InputAction::Suspend
+        | InputAction::Resume => AppOrSys::Outside,
+    }
+}
+
+fn move_action(dir: Dir) -> Action {
+    match dir {
+        Dir::Left => Action::MoveLeft,
+        Dir::Right => Action::MoveRight,
+        Dir::Up => Action::MoveUp,
+        Dir::Down => Action::MoveDown,
+        Dir::UpLeft => Action::MoveUpLeft,
+        Dir::UpRight => Action::MoveUpRight,
+        Dir::DownLeft => Action::MoveDownLeft,
+        Dir::DownRight => Action::MoveDownRight,
     }
 }
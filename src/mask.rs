@@ -0,0 +1,251 @@
+use crate::board::Board;
+use crate::error::{Error, Result};
+
+/// Largest side a shape may have, in cells; larger images should be scaled down first.
+pub const MAX_SIDE: usize = 128;
+/// Share of a shape's cells that are mines unless told otherwise, in percent.
+const DENSITY: usize = 15;
+
+/// The outline of a shaped board: which cells of its bounding box are played on. Read from
+/// a text stencil or a monochrome PBM image with [`parse`], and dealt as a board with
+/// [`board`](Self::board).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    /// Width of the bounding box, in cells.
+    pub width: usize,
+    /// Height of the bounding box, in cells.
+    pub height: usize,
+    /// Row by row, whether each cell is part of the board.
+    pub playfield: Vec<bool>,
+}
+
+impl Mask {
+    /// How many cells the shape has.
+    pub fn cells(&self) -> usize {
+        self.playfield.iter().filter(|&&cell| cell).count()
+    }
+
+    /// The number of mines a shape gets when none is asked for: about 15% of its cells.
+    pub fn default_mines(&self) -> usize {
+        (self.cells() * DENSITY / 100).max(1)
+    }
+
+    /// An empty board of this shape with `mines` mines, placed on the first reveal as usual.
+    ///
+    /// # Errors
+    /// Fails if the mines leave no cell of the shape safe.
+    pub fn board(&self, mines: usize) -> Result<Board> {
+        Board::try_shaped(self.width, self.height, mines, &self.playfield)
+    }
+
+    /// The shape cropped to the rows and columns that have cells in them.
+    fn cropped(self) -> Result<Self> {
+        let rows: Vec<usize> = (0..self.height)
+            .filter(|&y| (0..self.width).any(|x| self.playfield[y * self.width + x]))
+            .collect();
+        let columns: Vec<usize> = (0..self.width)
+            .filter(|&x| (0..self.height).any(|y| self.playfield[y * self.width + x]))
+            .collect();
+        let (Some(&top), Some(&bottom)) = (rows.first(), rows.last()) else {
+            return Err(Error::Parse("the shape has no cells".to_string()));
+        };
+        let (left, right) = (columns[0], columns[columns.len() - 1]);
+        let (width, height) = (right - left + 1, bottom - top + 1);
+        if width > MAX_SIDE || height > MAX_SIDE {
+            return Err(Error::Parse(format!(
+                "the shape is {width}x{height} cells; scale it to at most {MAX_SIDE} a side"
+            )));
+        }
+        let playfield = (top..=bottom)
+            .flat_map(|y| (left..=right).map(move |x| (x, y)))
+            .map(|(x, y)| self.playfield[y * self.width + x])
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            playfield,
+        })
+    }
+}
+
+/// Read a board shape from a file's contents: a PBM image (plain `P1` or raw `P4`), whose
+/// black pixels become cells, or else a text stencil, where `.` and spaces are holes and any
+/// other character is a cell. Each pixel or character is one cell, and the shape is cropped
+/// to the rows and columns it uses.
+///
+/// ```text
+///   ###
+///  #####
+/// ###.###
+///  #####
+///   ###
+/// ```
+///
+/// # Errors
+/// Fails on a malformed image, text that is not UTF-8, a shape with no cells or one larger
+/// than [`MAX_SIDE`] a side.
+pub fn parse(bytes: &[u8]) -> Result<Mask> {
+    match bytes.get(..2) {
+        Some(b"P1") => parse_pbm(&bytes[2..], false),
+        Some(b"P4") => parse_pbm(&bytes[2..], true),
+        _ => {
+            let text = std::str::from_utf8(bytes)
+                .map_err(|_| Error::Parse("the shape is neither a PBM image nor text".into()))?;
+            parse_stencil(text)
+        }
+    }
+}
+
+/// Read a text stencil; ragged rows are padded with holes.
+fn parse_stencil(text: &str) -> Result<Mask> {
+    let rows: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let width = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    let playfield = rows
+        .iter()
+        .flat_map(|row| {
+            let cells = row.chars().map(|c| c != '.' && c != ' ');
+            cells.chain(std::iter::repeat(false)).take(width)
+        })
+        .collect();
+    Mask {
+        width,
+        height: rows.len(),
+        playfield,
+    }
+    .cropped()
+}
+
+/// Read the PBM image after its magic number: the width and height, then one bit per pixel,
+/// as `0`/`1` digits in the plain format or packed into bytes (rows padded to whole bytes)
+/// in the raw one.
+fn parse_pbm(bytes: &[u8], raw: bool) -> Result<Mask> {
+    let mut rest = bytes;
+    let mut header = [0; 2];
+    for value in &mut header {
+        let (number, after) = pbm_number(rest)?;
+        *value = number;
+        rest = after;
+    }
+    let [width, height] = header;
+    // Margins are cropped away later, but an image this large is not a board shape.
+    if width > MAX_SIDE * 8 || height > MAX_SIDE * 8 {
+        return Err(Error::Parse(format!(
+            "the image is {width}x{height} pixels; scale it to at most {MAX_SIDE} a side"
+        )));
+    }
+    let playfield: Vec<bool> = if raw {
+        // A single whitespace byte separates the header from the pixels.
+        let pixels = rest.get(1..).unwrap_or_default();
+        let stride = width.div_ceil(8);
+        if pixels.len() < stride * height {
+            return Err(Error::Parse(
+                "the image ends before its last row".to_string(),
+            ));
+        }
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| pixels[y * stride + x / 8] & (0x80 >> (x % 8)) != 0)
+            .collect()
+    } else {
+        let bits: Vec<bool> = strip_comments(rest)
+            .filter(|b| !b.is_ascii_whitespace())
+            .map(|b| match b {
+                b'0' => Ok(false),
+                b'1' => Ok(true),
+                _ => Err(Error::Parse(format!(
+                    "unexpected '{}' in the image",
+                    char::from(b)
+                ))),
+            })
+            .take(width * height)
+            .collect::<Result<_>>()?;
+        if bits.len() < width * height {
+            return Err(Error::Parse(
+                "the image ends before its last row".to_string(),
+            ));
+        }
+        bits
+    };
+    Mask {
+        width,
+        height,
+        playfield,
+    }
+    .cropped()
+}
+
+/// The next number of a PBM header, skipping whitespace and `#` comments, and what follows
+/// it.
+fn pbm_number(bytes: &[u8]) -> Result<(usize, &[u8])> {
+    let mut rest = bytes;
+    loop {
+        match rest.first() {
+            Some(b) if b.is_ascii_whitespace() => rest = &rest[1..],
+            Some(b'#') => {
+                let end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+                rest = &rest[end..];
+            }
+            _ => break,
+        }
+    }
+    let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+    let number = std::str::from_utf8(&rest[..digits])
+        .ok()
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| Error::Parse("the image header is missing its size".to_string()))?;
+    Ok((number, &rest[digits..]))
+}
+
+/// The bytes of a plain PBM body without its `#` comments.
+fn strip_comments(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    let mut comment = false;
+    bytes.iter().copied().filter(move |&b| {
+        match b {
+            b'#' => comment = true,
+            b'\n' => comment = false,
+            _ => {}
+        }
+        !comment
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stencils_are_cropped_and_padded() {
+        let mask = parse(b"\n   ##\n  #.#\n   #\n\n").expect("valid stencil");
+        assert_eq!((mask.width, mask.height, mask.cells()), (3, 3, 5));
+        assert_eq!(
+            mask.playfield,
+            [false, true, true, true, false, true, false, true, false]
+        );
+        assert!(parse(b"...\n   \n").is_err(), "no cells");
+        assert!(parse(&[b'#'; MAX_SIDE + 1]).is_err(), "too wide");
+    }
+
+    #[test]
+    fn plain_and_raw_pbm_images_read_alike() {
+        let plain = parse(b"P1\n# a plus\n3 3\n010\n111\n010\n").expect("plain image");
+        let raw = parse(b"P4 3 3\n\x40\xe0\x40").expect("raw image");
+        assert_eq!(plain, raw);
+        assert_eq!(plain.cells(), 5);
+        assert!(parse(b"P1 3 3 010 111").is_err(), "short image");
+        assert!(parse(b"P4 3 3\n\x40").is_err(), "short image");
+    }
+
+    #[test]
+    fn masks_deal_shaped_boards() {
+        let mask = parse(b"###\n#.#\n###\n").expect("valid stencil");
+        assert_eq!(mask.default_mines(), 1);
+        let board = mask.board(2).expect("fits");
+        assert_eq!((board.cells(), board.mines()), (8, 2));
+        assert!(!board.in_play(1, 1));
+        assert!(mask.board(8).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+use crate::app::{AppState, Status};
+use crate::board::{Board, CellState, Neighbors};
+use crate::render::cell_name;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// What a logged move did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    /// A hidden cell was revealed: its number, or `None` for a mine, and how many cells
+    /// opened in all (more than one when an empty region flooded open).
+    Revealed {
+        /// The number shown, or `None` if it was a mine.
+        number: Option<u8>,
+        /// Cells opened by the move.
+        opened: usize,
+    },
+    /// The neighbors of a revealed number were revealed.
+    Chorded {
+        /// Cells opened by the move.
+        opened: usize,
+        /// Whether one of them was a mine.
+        mine: bool,
+    },
+    /// A flag was placed.
+    Flagged,
+    /// A flag was removed.
+    Unflagged,
+    /// The previous move was taken back.
+    Undone,
+    /// Every hidden cell left was revealed at once, all mines being flagged.
+    OpenedRest {
+        /// Cells opened by the move.
+        opened: usize,
+        /// Whether one of them was a mine, left unflagged because a flag was misplaced.
+        mine: bool,
+    },
+    /// A mine that went off was defused and flagged.
+    Defused,
+    /// The neighbors of the cell were counted, changing nothing.
+    Peeked(Neighbors),
+}
+
+/// One line of a [`MoveLog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// Game time when the move was made.
+    pub at: Duration,
+    /// Column of the target cell.
+    pub x: usize,
+    /// Row of the target cell.
+    pub y: usize,
+    /// What the move did.
+    pub action: Move,
+    /// How the game ended, if this move ended it.
+    pub ended: Option<Status>,
+}
+
+impl Entry {
+    /// The entry for the move at (`x`, `y`) that turned `before` into the game as `app` has
+    /// it now, or `None` if the move changed nothing.
+    pub fn of(app: &AppState, before: &Board, x: usize, y: usize) -> Option<Self> {
+        let opened = app.board.revealed().saturating_sub(before.revealed());
+        let lost = app.status == Status::Lose;
+        let action = match (before.cell_at(x, y), app.board.cell_at(x, y)) {
+            (CellState::Hidden, CellState::Flagged) => Move::Flagged,
+            (CellState::Flagged, CellState::Hidden) => Move::Unflagged,
+            // A mine that goes off stays hidden on the board.
+            (CellState::Hidden, CellState::Hidden) if lost => Move::Revealed {
+                number: None,
+                opened,
+            },
+            (CellState::Hidden, CellState::Revealed(n)) => Move::Revealed {
+                number: Some(n),
+                opened,
+            },
+            (CellState::Revealed(_), _) if opened > 0 || lost => {
+                Move::Chorded { opened, mine: lost }
+            }
+            _ => return None,
+        };
+        Some(Self {
+            at: app.elapsed(),
+            x,
+            y,
+            action,
+            ended: (app.status != Status::Playing).then_some(app.status),
+        })
+    }
+
+    /// The entry for opening the remaining cells, which turned `before` into the game as
+    /// `app` has it now, or `None` if nothing opened.
+    pub fn opened_rest(app: &AppState, before: &Board) -> Option<Self> {
+        let opened = app.board.revealed().saturating_sub(before.revealed());
+        let mine = app.status == Status::Lose;
+        (opened > 0 || mine).then(|| Self {
+            at: app.elapsed(),
+            x: app.cursor.x,
+            y: app.cursor.y,
+            action: Move::OpenedRest { opened, mine },
+            ended: (app.status != Status::Playing).then_some(app.status),
+        })
+    }
+}
+
+impl Display for Entry {
+    /// Like `00:12 revealed D5 → 2`, with cells named as the rulers show them.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let secs = self.at.as_secs();
+        let cell = cell_name(self.x, self.y);
+        write!(f, "{:02}:{:02} ", secs / 60, secs % 60)?;
+        match self.action {
+            Move::Revealed { number: None, .. } => write!(f, "revealed {cell} → mine")?,
+            Move::Revealed {
+                number: Some(n),
+                opened,
+            } => {
+                write!(f, "revealed {cell} → {n}")?;
+                if opened > 1 {
+                    write!(f, ", opened {opened} cells")?;
+                }
+            }
+            Move::Chorded { mine: true, .. } => write!(f, "chorded {cell} → mine")?,
+            Move::Chorded { opened, .. } => write!(f, "chorded {cell}, opened {opened} cells")?,
+            Move::Flagged => write!(f, "flagged {cell}")?,
+            Move::Unflagged => write!(f, "unflagged {cell}")?,
+            Move::Undone => write!(f, "undid the last move")?,
+            Move::OpenedRest { mine: true, .. } => write!(f, "opened the remaining cells → mine")?,
+            Move::OpenedRest { opened, .. } => {
+                write!(f, "opened the remaining {opened} cells")?;
+            }
+            Move::Defused => write!(f, "defused a mine at {cell}")?,
+            Move::Peeked(neighbors) => write!(f, "peeked at {cell}: {neighbors}")?,
+        }
+        match self.ended {
+            Some(Status::Win) => write!(f, "; board cleared"),
+            Some(Status::Lose) => write!(f, "; game lost"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The moves of the current game in plain words, for players reviewing a run with a screen
+/// reader and for bug reports. Kept in [`AppState::moves`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveLog {
+    entries: Vec<Entry>,
+}
+
+impl MoveLog {
+    /// The logged moves, oldest first.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Forget every move, for a new game.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Add an entry at the end.
+    pub fn push(&mut self, entry: Entry) {
+        self.entries.push(entry);
+    }
+}
+
+impl Display for MoveLog {
+    /// One entry per line.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.entries
+            .iter()
+            .try_for_each(|entry| writeln!(f, "{entry}"))
+    }
+}
+
+/// What the last move did, for a front end's status line: like "Chord revealed 6 cells" or
+/// "Flag placed (12 left)". `None` before the first move of a game.
+pub fn status_line(app: &AppState) -> Option<String> {
+    let entry = app.moves.entries().last()?;
+    let cells = |n: usize| {
+        if n == 1 {
+            "1 cell".to_string()
+        } else {
+            format!("{n} cells")
+        }
+    };
+    let flags_left = app.board.mines().saturating_sub(app.flags_placed);
+    let what = match entry.action {
+        Move::Revealed { number: None, .. } => "Revealed a mine".to_string(),
+        Move::Revealed { opened, .. } => format!("Revealed {}", cells(opened)),
+        Move::Chorded { mine: true, .. } => "Chord hit a mine".to_string(),
+        Move::Chorded { opened, .. } => format!("Chord revealed {}", cells(opened)),
+        Move::Flagged => format!("Flag placed ({flags_left} left)"),
+        Move::Unflagged => format!("Flag removed ({flags_left} left)"),
+        Move::Undone => "Last move undone".to_string(),
+        Move::OpenedRest { mine: true, .. } => "Opened the rest and hit a mine".to_string(),
+        Move::OpenedRest { opened, .. } => format!("Opened the last {}", cells(opened)),
+        Move::Defused => "Mine defused and flagged".to_string(),
+        Move::Peeked(neighbors) => format!("{}: {neighbors}", cell_name(entry.x, entry.y)),
+    };
+    Some(match entry.ended {
+        Some(Status::Win) => format!("{what}; board cleared"),
+        Some(Status::Lose) => format!("{what}; game lost"),
+        _ => what,
+    })
+}
+
+/// The game's move log under a header naming the board, so the run can be reproduced: the
+/// difficulty and size, then the board code or seed when there is one.
+pub fn transcript(app: &AppState) -> String {
+    let board = &app.board;
+    let replay = match (app.code, board.seed()) {
+        (Some(code), _) => format!("Board code {code}\n"),
+        (None, Some(seed)) => format!("Seed {seed}\n"),
+        (None, None) => String::new(),
+    };
+    format!(
+        "Minesweeper {} {}x{}, {} mines\n{replay}\n{}",
+        app.difficulty.name(),
+        board.width(),
+        board.height(),
+        board.mines(),
+        app.moves
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{Action, Cursor};
+    use crate::difficulty::Difficulty;
+
+    #[test]
+    fn moves_read_as_plain_words() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(4, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.handle_action(Action::Reveal);
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::Reveal);
+        app.handle_action(Action::Undo);
+        app.cursor = Cursor::new(3, 0);
+        app.handle_action(Action::Reveal);
+        let lines: Vec<String> = app
+            .moves
+            .entries()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "00:00 revealed A1 → 0, opened 2 cells",
+                "00:00 flagged C1",
+                "00:00 unflagged C1",
+                "00:00 revealed C1 → mine; game lost",
+                "00:00 undid the last move",
+                "00:00 revealed D1 → 1; board cleared",
+            ]
+        );
+    }
+
+    #[test]
+    fn the_status_line_tells_the_last_move() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(4, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        assert_eq!(status_line(&app), None);
+        app.handle_action(Action::Reveal);
+        assert_eq!(status_line(&app).as_deref(), Some("Revealed 2 cells"));
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(status_line(&app).as_deref(), Some("Flag placed (0 left)"));
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(status_line(&app).as_deref(), Some("Flag removed (1 left)"));
+        app.cursor = Cursor::new(3, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!(
+            status_line(&app).as_deref(),
+            Some("Revealed 1 cell; board cleared")
+        );
+    }
+
+    #[test]
+    fn transcripts_name_the_board() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.apply_code("MS-easy-Friday".parse().expect("valid code"));
+        let text = transcript(&app);
+        assert!(text.starts_with("Minesweeper easy 9x9, 10 mines\nBoard code MS-easy-"));
+        assert!(text.contains("\n\n00:00 revealed "));
+    }
+}
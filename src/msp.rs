@@ -0,0 +1,144 @@
+use crate::headless;
+use minesweeper::app::{Action, AppState, Status};
+use minesweeper::render::{rows, status_name};
+use std::io::{self, BufRead, Write};
+
+/// Protocol version sent in the handshake.
+pub const VERSION: u32 = 1;
+
+/// Play over the Minesweeper Protocol (MSP): a line-based stdio protocol in the style of UCI,
+/// for programs that play the game.
+///
+/// The client opens with `msp`; the game answers `id name ...`, `id version N` and `mspok`,
+/// then describes the board with a state block:
+///
+/// ```text
+/// state <width> <height> <mines> <flags> <playing|won|lost>
+/// <one row per line, in headless notation>
+/// ```
+///
+/// Each move (`reveal X Y`, `flag X Y`, `chord X Y`, `hint`, `undo`) is answered with
+/// `result <reply>` and the new state block, followed by `gameover <won|lost> <seconds>` if
+/// the move ended the game. `newgame` starts another game with the same settings, `state`
+/// repeats the state block and `quit` ends the session. Anything else, or a move the game
+/// rejects, is answered with a single `error <message>` line.
+///
+/// # Errors
+/// Fails only if reading input or writing output fails.
+pub fn run(app: &mut AppState, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    // Every move names its cell, so a repeated reveal must not turn into a chord.
+    app.double_tap_timeout = None;
+    let mut ready = false;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        let command = match line.split_whitespace().next() {
+            Some(word) => word.to_ascii_lowercase(),
+            None => continue,
+        };
+        match command.as_str() {
+            "msp" => {
+                writeln!(output, "id name minesweeper {}", env!("CARGO_PKG_VERSION"))?;
+                writeln!(output, "id version {VERSION}")?;
+                writeln!(output, "mspok")?;
+                write_state(app, &mut output)?;
+                ready = true;
+            }
+            _ if !ready => writeln!(output, "error send 'msp' first")?,
+            "quit" => break,
+            "state" => write_state(app, &mut output)?,
+            "newgame" => {
+                app.handle_action(Action::Restart);
+                write_state(app, &mut output)?;
+            }
+            "reveal" | "flag" | "chord" | "hint" | "undo" => {
+                let before = app.status;
+                match headless::execute(app, line) {
+                    Err(e) => writeln!(output, "error {e}")?,
+                    reply => {
+                        writeln!(output, "result {}", headless::text(app, &reply))?;
+                        write_state(app, &mut output)?;
+                        if before == Status::Playing && app.status != Status::Playing {
+                            writeln!(
+                                output,
+                                "gameover {} {:.3}",
+                                status_name(app.status),
+                                app.elapsed().as_secs_f64()
+                            )?;
+                        }
+                    }
+                }
+            }
+            other => writeln!(output, "error unknown command '{other}'")?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+fn write_state(app: &AppState, output: &mut impl Write) -> io::Result<()> {
+    let board = &app.board;
+    writeln!(
+        output,
+        "state {} {} {} {} {}",
+        board.width(),
+        board.height(),
+        board.mines(),
+        app.flags_placed,
+        status_name(app.status)
+    )?;
+    for row in rows(app) {
+        writeln!(output, "{row}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+
+    fn session(app: &mut AppState, input: &str) -> String {
+        let mut out = Vec::new();
+        run(app, input.as_bytes(), &mut out).expect("in-memory session");
+        String::from_utf8(out).expect("utf-8")
+    }
+
+    #[test]
+    fn moves_stream_results_and_state() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(1, 0)]);
+        let out = session(
+            &mut app,
+            "msp\nreveal 0 0\nflag 9 9\nreveal 1 0\nquit\nstate\n",
+        );
+        let lines: Vec<_> = out.lines().collect();
+        assert!(lines[0].starts_with("id name minesweeper"));
+        assert!(lines[lines.len() - 1].starts_with("gameover lost "));
+        assert_eq!(
+            lines[1..lines.len() - 1],
+            [
+                "id version 1",
+                "mspok",
+                "state 3 1 1 0 playing",
+                "###",
+                "result ok",
+                "state 3 1 1 0 playing",
+                "1##",
+                "error (9, 9) is off the 3x1 board",
+                "result lost",
+                "state 3 1 1 0 lost",
+                "1*#",
+            ]
+        );
+    }
+
+    #[test]
+    fn handshake_comes_first() {
+        let mut app = AppState::new(Difficulty::Easy);
+        let out = session(&mut app, "reveal 0 0\nstate\n");
+        assert_eq!(out, "error send 'msp' first\nerror send 'msp' first\n");
+    }
+}
@@ -0,0 +1,437 @@
+use crate::coop::{Coop, Move, Partner};
+use crate::input::InputEvent;
+use crate::race::{Opponent, Race};
+use minesweeper::app::{Action, AppState, Command, Cursor};
+use minesweeper::board::{Board, Layout, Topology};
+use minesweeper::difficulty::Difficulty;
+use minesweeper::error::{Error, Result};
+use minesweeper::events::Event;
+use minesweeper::generator::{self, Spec};
+use rand::random;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Port `host` listens on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Everything sent between two players, one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Message {
+    /// First message from the host: the board both players get.
+    Hello {
+        /// Board width in cells.
+        width: usize,
+        /// Board height in cells.
+        height: usize,
+        /// Number of mines.
+        mines: usize,
+        /// Seed the mines are laid out with.
+        seed: u64,
+        /// Play together on one board instead of racing.
+        #[serde(default)]
+        coop: bool,
+    },
+    /// Share of the safe cells revealed so far, 0 to 100.
+    Progress {
+        /// Percent cleared.
+        percent: u8,
+    },
+    /// The sender's game ended.
+    Finished {
+        /// Whether the board was cleared (rather than a mine hit).
+        won: bool,
+        /// Time taken in seconds.
+        secs: u64,
+    },
+    /// A co-op move: a request from the guest, or a move the host has applied.
+    Play {
+        /// What to do.
+        #[serde(rename = "move")]
+        mv: Move,
+        /// Target column.
+        x: usize,
+        /// Target row.
+        y: usize,
+    },
+    /// Where the sender's cursor is, in co-op.
+    Cursor {
+        /// Column.
+        x: usize,
+        /// Row.
+        y: usize,
+    },
+    /// The sender quit or the connection dropped.
+    Bye,
+}
+
+/// A network game in progress.
+pub enum Session {
+    /// Both players clear copies of the same board.
+    Race(Race),
+    /// Both players work on one board.
+    Coop(Coop),
+}
+
+impl Session {
+    /// Wait for the other player on `port`, then send them the board: `difficulty`'s size
+    /// laid out with `seed` (random if `None`).
+    ///
+    /// Returns the session and the `Hello` that was sent, to be passed to [`Session::start`].
+    ///
+    /// # Errors
+    /// Fails if no connection can be made.
+    pub fn host(
+        port: u16,
+        difficulty: Difficulty,
+        seed: Option<u64>,
+        coop: bool,
+    ) -> Result<(Self, Message)> {
+        let mut peer = Peer::host(port)?;
+        let (width, height, mines) = difficulty.parameters();
+        let hello = Message::Hello {
+            width,
+            height,
+            mines,
+            seed: seed.unwrap_or_else(random),
+            coop,
+        };
+        peer.send(&hello);
+        let session = if coop {
+            Self::Coop(Coop::new(peer, true))
+        } else {
+            Self::Race(Race::new(peer))
+        };
+        Ok((session, hello))
+    }
+
+    /// Connect to a host at `address` and wait for the board; the host picks the mode.
+    ///
+    /// Returns the session and the host's `Hello`, to be passed to [`Session::start`].
+    ///
+    /// # Errors
+    /// Fails if the connection cannot be made or the host does not send a board.
+    pub fn join(address: &str) -> Result<(Self, Message)> {
+        let mut peer = Peer::join(address)?;
+        match peer.receive()? {
+            hello @ Message::Hello { coop: true, .. } => {
+                Ok((Self::Coop(Coop::new(peer, false)), hello))
+            }
+            hello @ Message::Hello { .. } => Ok((Self::Race(Race::new(peer)), hello)),
+            other => Err(Error::Network(format!(
+                "expected a board from the host, got {other:?}"
+            ))),
+        }
+    }
+
+    /// Set up `app` with the shared board from `hello` and open its center cell, so both
+    /// players start from the same opening at the same moment.
+    ///
+    /// # Errors
+    /// Fails if `hello` is not a `Hello` or describes an unplayable board, including one
+    /// larger than [`MAX_CELLS`](minesweeper::board::MAX_CELLS).
+    pub fn start(app: &mut AppState, hello: &Message) -> Result<()> {
+        let &Message::Hello {
+            width,
+            height,
+            mines,
+            seed,
+            coop,
+        } = hello
+        else {
+            return Err(Error::Network(format!("expected a board, got {hello:?}")));
+        };
+        // The size comes from the other side, so it is checked before anything is built
+        // for it, even when it matches the board already being played.
+        Board::check_size(width, height, mines)?;
+        if app.difficulty.parameters() != (width, height, mines) {
+            app.difficulty = Difficulty::Custom {
+                width,
+                height,
+                mines,
+            };
+        }
+        app.seed = Some(seed);
+        // Both players start from the same safe opening on a flat, evenly mined board, one
+        // player per side.
+        app.hardcore = false;
+        app.topology = Topology::Flat;
+        app.layout = Layout::Uniform;
+        app.hotseat = None;
+        app.campaign = None;
+        // The opening below is the only reveal made for the player; practice mode would log
+        // one of its own, and a shaped board would not match the other side's.
+        app.practice = false;
+        app.mask = None;
+        app.restart();
+        if coop {
+            // A reveal on a number could be either player's second tap; keep chords explicit.
+            app.double_tap_timeout = None;
+            // Only the player who set a mine off could defuse it, and assists could be set
+            // differently on each side; either would leave the boards apart.
+            app.defuse_penalty = None;
+            app.auto_chord = false;
+            app.auto_flag = false;
+        }
+        let start = (width / 2, height / 2);
+        app.board = generator::generate(&Spec {
+            width,
+            height,
+            mines,
+            start,
+            seed: Some(seed),
+            no_guess: false,
+            min_rating: None,
+            max_rating: None,
+            layout: app.layout,
+        })?;
+        app.cursor = Cursor::new(start.0, start.1);
+        app.handle_action(Action::Reveal);
+        Ok(())
+    }
+
+    /// The connection, to read from in the background.
+    pub const fn peer(&self) -> &Peer {
+        match self {
+            Self::Race(race) => race.peer(),
+            Self::Coop(coop) => coop.peer(),
+        }
+    }
+
+    /// The race opponent's progress, in a race.
+    pub const fn opponent(&self) -> Option<&Opponent> {
+        match self {
+            Self::Race(race) => Some(&race.opponent),
+            Self::Coop(_) => None,
+        }
+    }
+
+    /// The co-op partner's cursor, in co-op.
+    pub const fn partner(&self) -> Option<&Partner> {
+        match self {
+            Self::Race(_) => None,
+            Self::Coop(coop) => Some(&coop.partner),
+        }
+    }
+
+    /// Handle a local action. Anything that would swap the board, take back a move or ask
+    /// the solver is ignored online.
+    pub fn handle_action(&mut self, app: &mut AppState, action: Action) -> Command {
+        if matches!(
+            action,
+            Action::Restart
+                | Action::Undo
+                | Action::Hint
+                | Action::OpenSeedPrompt
+                | Action::OpenPacks
+                | Action::SetDifficulty(_)
+                | Action::PlayMacro
+        ) {
+            return Command::None;
+        }
+        match self {
+            Self::Race(_) => app.handle_action(action),
+            Self::Coop(coop) => coop.handle_action(app, action),
+        }
+    }
+
+    /// Record a message from the other player.
+    pub fn receive(&mut self, app: &mut AppState, message: &Message) {
+        match self {
+            Self::Race(race) => race.receive(message),
+            Self::Coop(coop) => coop.receive(app, message),
+        }
+    }
+
+    /// Send the other player whatever changed locally since last time, `events` being the
+    /// ones just taken from `app`.
+    pub fn report(&mut self, app: &AppState, events: &[Event]) {
+        match self {
+            Self::Race(race) => race.report(app, events),
+            Self::Coop(coop) => coop.report(app),
+        }
+    }
+}
+
+/// One end of a two-player connection.
+pub struct Peer {
+    stream: TcpStream,
+}
+
+impl Peer {
+    /// Wait on `port` for the other player to connect.
+    ///
+    /// # Errors
+    /// Fails if the port cannot be bound or accepting fails.
+    pub fn host(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .map_err(|e| Error::Network(format!("cannot listen on port {port}: {e}")))?;
+        eprintln!("Waiting for an opponent on port {port}...");
+        let (stream, addr) = listener
+            .accept()
+            .map_err(|e| Error::Network(format!("cannot accept a connection: {e}")))?;
+        eprintln!("{addr} joined");
+        Ok(Self { stream })
+    }
+
+    /// Connect to a player hosting at `address` (`host:port`, or just `host` for the
+    /// default port).
+    ///
+    /// # Errors
+    /// Fails if the address does not resolve or the connection is refused.
+    pub fn join(address: &str) -> Result<Self> {
+        let stream = if address.contains(':') {
+            TcpStream::connect(address)
+        } else {
+            TcpStream::connect((address, DEFAULT_PORT))
+        }
+        .map_err(|e| Error::Network(format!("cannot connect to {address}: {e}")))?;
+        Ok(Self { stream })
+    }
+
+    /// Send a message, ignoring failures: a vanished peer shows up as `Bye` on the reader.
+    pub fn send(&mut self, message: &Message) {
+        if let Ok(mut line) = serde_json::to_vec(message) {
+            line.push(b'\n');
+            let _ = self.stream.write_all(&line);
+        }
+    }
+
+    /// Block until the next message arrives.
+    ///
+    /// Reads byte by byte rather than through a buffer, so nothing after this message is
+    /// consumed before `spawn_reader` takes over.
+    ///
+    /// # Errors
+    /// Fails if the connection closes or the peer sends something unreadable.
+    pub fn receive(&mut self) -> Result<Message> {
+        let mut line = Vec::new();
+        let mut byte = [0];
+        while (&self.stream).read(&mut byte)? == 1 {
+            if byte[0] == b'\n' {
+                return serde_json::from_slice(&line).map_err(|e| {
+                    Error::Network(format!("unexpected message from the other player: {e}"))
+                });
+            }
+            line.push(byte[0]);
+        }
+        Err(Error::Network("the other player disconnected".to_string()))
+    }
+
+    /// Forward incoming messages to the main loop from a blocking task on the running
+    /// runtime, ending with `Bye` once the connection closes. The connection stays blocking,
+    /// as [`send`](Self::send) writes to it straight from the game.
+    ///
+    /// # Errors
+    /// Fails if the connection cannot be shared with the task.
+    pub fn spawn_reader(&self, tx: UnboundedSender<InputEvent>) -> Result<()> {
+        let stream = self.stream.try_clone()?;
+        tokio::task::spawn_blocking(move || {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                let Ok(message) = serde_json::from_str(&line) else {
+                    continue;
+                };
+                if tx.send(InputEvent::Remote(message)).is_err() {
+                    return;
+                }
+            }
+            let _ = tx.send(InputEvent::Remote(Message::Bye));
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn messages_are_tagged_json() {
+        let json = serde_json::to_string(&Message::Progress { percent: 42 }).expect("serialize");
+        assert_eq!(json, r#"{"type":"progress","percent":42}"#);
+        let back: Message = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(back, Message::Progress { percent: 42 });
+    }
+
+    #[test]
+    fn both_players_get_the_same_opening() {
+        let hello = Message::Hello {
+            width: 16,
+            height: 16,
+            mines: 40,
+            seed: 99,
+            coop: false,
+        };
+        let mut host = AppState::new(Difficulty::Medium);
+        let mut guest = AppState::new(Difficulty::Easy);
+        Session::start(&mut host, &hello).expect("valid board");
+        Session::start(&mut guest, &hello).expect("valid board");
+        assert_eq!(host.board, guest.board);
+        assert_eq!(guest.difficulty.parameters(), (16, 16, 40));
+        assert!(matches!(
+            guest.board.cell_at(8, 8),
+            minesweeper::board::CellState::Revealed(_)
+        ));
+    }
+
+    #[test]
+    fn an_oversized_board_from_the_host_is_refused() {
+        let hello = Message::Hello {
+            width: 100_000,
+            height: 100_000,
+            mines: 1,
+            seed: 1,
+            coop: false,
+        };
+        let mut app = AppState::new(Difficulty::Easy);
+        assert!(matches!(
+            Session::start(&mut app, &hello),
+            Err(Error::InvalidBoard(_))
+        ));
+        assert_eq!(app.difficulty, Difficulty::Easy, "left as it was");
+    }
+
+    #[test]
+    fn practice_mode_does_not_open_a_second_time() {
+        let hello = Message::Hello {
+            width: 9,
+            height: 9,
+            mines: 10,
+            seed: 3,
+            coop: false,
+        };
+        let mut app = AppState::new(Difficulty::Easy);
+        app.practice = true;
+        Session::start(&mut app, &hello).expect("valid board");
+        let reveals = app
+            .moves
+            .entries()
+            .iter()
+            .filter(|entry| matches!(entry.action, minesweeper::movelog::Move::Revealed { .. }))
+            .count();
+        assert_eq!(reveals, 1);
+    }
+
+    #[test]
+    fn peers_exchange_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = listener.local_addr().expect("address").to_string();
+        let client = thread::spawn(move || {
+            let mut peer = Peer::join(&address).expect("connect");
+            peer.send(&Message::Bye);
+            peer.receive().expect("reply")
+        });
+        let (stream, _) = listener.accept().expect("accept");
+        let mut host = Peer { stream };
+        assert_eq!(host.receive().expect("message"), Message::Bye);
+        host.send(&Message::Progress { percent: 7 });
+        assert_eq!(
+            client.join().expect("client thread"),
+            Message::Progress { percent: 7 }
+        );
+    }
+}
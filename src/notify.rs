@@ -0,0 +1,194 @@
+use crate::config;
+use crate::history::{self, Category};
+use minesweeper::app::{AppState, Status};
+use minesweeper::board::Layout;
+use minesweeper::events::Event;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Tells the player a game ended while they were looking elsewhere: after a long game, or
+/// when a win sets a personal best (`[notifications]` settings).
+///
+/// With the `notify` feature this is a desktop notification; otherwise it is sent to the
+/// terminal with OSC 9, which terminals such as iTerm2, kitty and Windows Terminal show as a
+/// system notification (others ignore it).
+pub struct Notifier {
+    settings: config::Notifications,
+    /// Best winning time per category.
+    bests: HashMap<Category, f64>,
+}
+
+impl Notifier {
+    /// Notify as `settings` say, with personal bests read from the game log at `log`.
+    pub fn new(settings: config::Notifications, log: Option<&Path>) -> Self {
+        Self {
+            settings,
+            bests: log.map(history::best_times).unwrap_or_default(),
+        }
+    }
+
+    /// Send a notification if `events`, just taken from `app`, end the game and it deserves
+    /// one; OSC 9 notifications are written to `out`.
+    ///
+    /// # Errors
+    /// Fails if the notification cannot be sent.
+    pub fn observe(
+        &mut self,
+        app: &AppState,
+        events: &[Event],
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        match self.message(app, events) {
+            Some(text) => send(&text, out),
+            None => Ok(()),
+        }
+    }
+
+    /// What to say about the game, if `events` end it.
+    fn message(&mut self, app: &AppState, events: &[Event]) -> Option<String> {
+        let (status, elapsed) = events.iter().find_map(|event| match *event {
+            Event::GameEnded { status, time } => Some((status, time)),
+            _ => None,
+        })?;
+        let category = Category::of(app);
+        let time = elapsed.as_secs_f64();
+        let best = status == Status::Win
+            && !app.is_practice()
+            && app.undos == 0
+            && self
+                .bests
+                .get(&category)
+                .is_none_or(|&previous| time < previous);
+        if best {
+            self.bests.insert(category, time);
+        }
+        let secs = elapsed.as_secs();
+        let clock = format!("{}:{:02}", secs / 60, secs % 60);
+        let name = app.difficulty.name();
+        if !self.settings.enabled {
+            None
+        } else if best && self.settings.best {
+            let hardcore = if category.hardcore { " hardcore" } else { "" };
+            let cylinder = if category.cylinder { " cylinder" } else { "" };
+            let layout = match category.layout {
+                Layout::Uniform => String::new(),
+                layout => format!(" {}", layout.name()),
+            };
+            Some(format!(
+                "New best on {name} {}x{}{hardcore}{cylinder}{layout}: {clock}",
+                category.width, category.height
+            ))
+        } else if secs >= self.settings.after_secs {
+            Some(match status {
+                Status::Win => format!("Won {name} in {clock}"),
+                _ => format!("Lost {name} after {clock}"),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "notify")]
+fn send(text: &str, _out: &mut impl Write) -> io::Result<()> {
+    notify_rust::Notification::new()
+        .summary("Minesweeper")
+        .body(text)
+        .show()
+        .map(drop)
+        .map_err(io::Error::other)
+}
+
+#[cfg(not(feature = "notify"))]
+fn send(text: &str, out: &mut impl Write) -> io::Result<()> {
+    write!(out, "\x1b]9;Minesweeper: {text}\x07")?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::{Action, Cursor};
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+
+    const EASY_3X1: Category = Category {
+        width: 3,
+        height: 1,
+        mines: 1,
+        hardcore: false,
+        cylinder: false,
+        layout: Layout::Uniform,
+    };
+
+    /// A 3x1 game ended by revealing `x`, with the mine at the right end, and the events it
+    /// emitted.
+    fn ended_game(x: usize) -> (AppState, Vec<Event>) {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.cursor = Cursor::new(x, 0);
+        app.handle_action(Action::Reveal);
+        let events = app.events.drain();
+        (app, events)
+    }
+
+    fn notifier(after_secs: u64, best: Option<f64>) -> Notifier {
+        let settings = config::Notifications {
+            enabled: true,
+            after_secs,
+            best: true,
+        };
+        let mut notifier = Notifier::new(settings, None);
+        notifier.bests.extend(best.map(|time| (EASY_3X1, time)));
+        notifier
+    }
+
+    #[test]
+    fn bests_and_long_games_get_a_message() {
+        let (won, events) = ended_game(0);
+        assert_eq!(
+            notifier(60, Some(0.0)).message(&won, &events),
+            None,
+            "short, not a best"
+        );
+        assert_eq!(
+            notifier(60, Some(100.0)).message(&won, &events).as_deref(),
+            Some("New best on easy 3x1: 0:00")
+        );
+        assert_eq!(
+            notifier(0, Some(0.0)).message(&won, &events).as_deref(),
+            Some("Won easy in 0:00")
+        );
+        let (lost, events) = ended_game(2);
+        assert_eq!(
+            notifier(0, None).message(&lost, &events).as_deref(),
+            Some("Lost easy after 0:00")
+        );
+    }
+
+    #[test]
+    fn each_game_end_is_told_once() {
+        let mut notifier = notifier(0, None);
+        let (won, events) = ended_game(0);
+        assert!(notifier.message(&won, &events).is_some());
+        assert_eq!(notifier.message(&won, &[]), None, "the events were taken");
+        assert!(notifier.bests.contains_key(&EASY_3X1));
+    }
+
+    #[test]
+    fn nothing_is_sent_when_disabled() {
+        let mut notifier = Notifier::new(config::Notifications::default(), None);
+        let mut out = Vec::new();
+        let (won, events) = ended_game(0);
+        notifier
+            .observe(&won, &events, &mut out)
+            .expect("in memory");
+        assert!(out.is_empty());
+        assert!(
+            notifier.bests.contains_key(&EASY_3X1),
+            "bests are still tracked"
+        );
+    }
+}
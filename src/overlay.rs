@@ -0,0 +1,126 @@
+use crate::race;
+use crate::serve::{read_request, write_response};
+use minesweeper::app::AppState;
+use minesweeper::error::{self, Error};
+use minesweeper::render::status_name;
+use serde_json::{Value, json};
+use std::io::{self, BufReader};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+/// How long an overlay may take to send its request, so a stalled one cannot block the rest.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The game as stream overlays see it: `GET /state` on a localhost port answers with the
+/// status, the timer, the mines left and how much of the board is cleared, as JSON that a
+/// browser source in OBS can poll.
+pub struct Overlay {
+    state: Arc<Mutex<Value>>,
+}
+
+impl Overlay {
+    /// Answer overlays on `port`, on this machine only.
+    ///
+    /// # Errors
+    /// Fails if the port cannot be bound.
+    pub fn listen(port: u16) -> error::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).map_err(|e| {
+            Error::Network(format!("cannot listen for overlays on port {port}: {e}"))
+        })?;
+        Ok(Self::new(listener))
+    }
+
+    /// Answer overlays from `listener` on a background thread, one request at a time.
+    fn new(listener: TcpListener) -> Self {
+        let state = Arc::new(Mutex::new(Value::Null));
+        let served = Arc::clone(&state);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // One bad client must not stop the others.
+                let _ = serve_one(&served, stream);
+            }
+        });
+        Self { state }
+    }
+
+    /// Show overlays `app` as it is now.
+    pub fn update(&self, app: &AppState) {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = state(app);
+    }
+}
+
+/// What `GET /state` answers for `app`.
+fn state(app: &AppState) -> Value {
+    json!({
+        "status": status_name(app.status),
+        "time": app.elapsed_secs(),
+        "mines": app.board.mines(),
+        "mines_left": app.board.mines().saturating_sub(app.flags_placed),
+        "progress": race::progress(&app.board),
+        "width": app.board.width(),
+        "height": app.board.height(),
+    })
+}
+
+fn serve_one(state: &Mutex<Value>, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let (status, body) = match read_request(BufReader::new(&stream)) {
+        Ok(request) if request.path() != "/state" => (404, json!({ "error": "not found" })),
+        Ok(request) if request.method() != "GET" => {
+            (405, json!({ "error": "only GET is allowed" }))
+        }
+        Ok(_) => (
+            200,
+            state.lock().unwrap_or_else(PoisonError::into_inner).clone(),
+        ),
+        Err(e) => (400, json!({ "error": e })),
+    };
+    write_response(&mut stream, status, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::{Action, Cursor};
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+    use std::io::{Read, Write};
+
+    fn get(port: u16, path: &str) -> String {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).expect("listening");
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").expect("request");
+        let mut response = String::new();
+        stream.read_to_string(&mut response).expect("response");
+        response
+    }
+
+    #[test]
+    fn overlays_get_the_game_as_it_is() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("free port");
+        let port = listener.local_addr().expect("bound").port();
+        let overlay = Overlay::new(listener);
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(4, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::ToggleFlag);
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        overlay.update(&app);
+
+        let response = get(port, "/state");
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        let (_, body) = response.split_once("\r\n\r\n").expect("a body");
+        let body: Value = serde_json::from_str(body).expect("json");
+        assert_eq!(body["status"], "playing");
+        assert_eq!(
+            (&body["mines"], &body["mines_left"]),
+            (&json!(1), &json!(0))
+        );
+        assert_eq!(body["progress"], 33);
+        assert!(get(port, "/scores").starts_with("HTTP/1.1 404"));
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// Running totals for one kind of work.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timing {
+    /// How many times the work was done.
+    pub count: u32,
+    /// Time spent in all of them.
+    pub total: Duration,
+    /// The slowest one.
+    pub max: Duration,
+}
+
+impl Timing {
+    /// Record one run that took `time`.
+    pub fn add(&mut self, time: Duration) {
+        self.count = self.count.saturating_add(1);
+        self.total += time;
+        self.max = self.max.max(time);
+    }
+
+    /// Average time per run; zero if nothing was recorded.
+    pub fn mean(&self) -> Duration {
+        self.total / self.count.max(1)
+    }
+}
+
+/// Where a game spends its time, collected while [`AppState::profile`] is set (`--profile`).
+///
+/// The engine fills in `reveal` and `solver`; front ends add `render`.
+///
+/// [`AppState::profile`]: crate::app::AppState::profile
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Drawing one frame.
+    pub render: Timing,
+    /// Reveals and chords, including the flood fill of empty regions.
+    pub reveal: Timing,
+    /// Solver runs for hints.
+    pub solver: Timing,
+}
+
+impl Display for Profile {
+    /// One line per kind of work, with times in milliseconds.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+        for (name, timing) in [
+            ("render", self.render),
+            ("reveal", self.reveal),
+            ("solver", self.solver),
+        ] {
+            writeln!(
+                f,
+                "{name:<8}{:>8} runs  mean {:>8.3} ms  max {:>8.3} ms  total {:>10.3} ms",
+                timing.count,
+                ms(timing.mean()),
+                ms(timing.max),
+                ms(timing.total)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_track_count_mean_and_max() {
+        let mut timing = Timing::default();
+        assert_eq!(timing.mean(), Duration::ZERO);
+        timing.add(Duration::from_millis(1));
+        timing.add(Duration::from_millis(3));
+        assert_eq!(timing.count, 2);
+        assert_eq!(timing.mean(), Duration::from_millis(2));
+        assert_eq!(timing.max, Duration::from_millis(3));
+        let profile = Profile {
+            render: timing,
+            ..Profile::default()
+        };
+        assert!(profile.to_string().starts_with(
+            "render         2 runs  mean    2.000 ms  max    3.000 ms  total      4.000 ms\n"
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+use minesweeper::puzzle::{Pack, Puzzle};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The default progress file: `puzzles.json` in the user data directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("minesweeper").join("puzzles.json"))
+}
+
+/// Best times for pack puzzles, kept in a JSON file so solved puzzles stay ticked in the
+/// pack browser. Puzzles are named `pack/puzzle`, so editing a pack keeps the times of the
+/// puzzles it still has.
+pub struct Progress {
+    path: PathBuf,
+    /// Best time in seconds per puzzle, as last read or written.
+    saved: BTreeMap<String, f64>,
+}
+
+impl Progress {
+    /// Read the progress kept at `path` into the puzzles of `packs`. A missing or unreadable
+    /// file counts as nothing solved.
+    pub fn load(path: PathBuf, packs: &mut [Pack]) -> Self {
+        let saved: BTreeMap<String, f64> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        for pack in packs {
+            for puzzle in &mut pack.puzzles {
+                if let Some(&secs) = saved.get(&key(&pack.name, puzzle)) {
+                    puzzle.best = Duration::try_from_secs_f64(secs).ok();
+                }
+            }
+        }
+        Self { path, saved }
+    }
+
+    /// Write the file if a puzzle of `packs` got a new best time since the last call. Call
+    /// after every change to the game.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be written.
+    pub fn observe(&mut self, packs: &[Pack]) -> io::Result<()> {
+        let mut bests = self.saved.clone();
+        for pack in packs {
+            for puzzle in &pack.puzzles {
+                if let Some(best) = puzzle.best {
+                    bests.insert(key(&pack.name, puzzle), best.as_secs_f64());
+                }
+            }
+        }
+        if bests == self.saved {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&bests)?)?;
+        self.saved = bests;
+        Ok(())
+    }
+}
+
+fn key(pack: &str, puzzle: &Puzzle) -> String {
+    format!("{pack}/{}", puzzle.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::puzzle::builtin_packs;
+
+    #[test]
+    fn best_times_survive_a_restart() {
+        let path =
+            std::env::temp_dir().join(format!("minesweeper-puzzles-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut packs = builtin_packs();
+        let mut progress = Progress::load(path.clone(), &mut packs);
+        progress.observe(&packs).expect("nothing to write");
+        assert!(!path.exists());
+
+        packs[0].puzzles[1].best = Some(Duration::from_secs(12));
+        progress.observe(&packs).expect("progress written");
+        let mut reloaded = builtin_packs();
+        Progress::load(path.clone(), &mut reloaded);
+        let _ = fs::remove_file(&path);
+        assert_eq!(reloaded[0].puzzles[1].best, Some(Duration::from_secs(12)));
+        assert_eq!(reloaded[0].puzzles[0].best, None);
+    }
+}
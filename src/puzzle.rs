@@ -0,0 +1,236 @@
+use crate::board::Board;
+use crate::error::{Error, Result};
+use std::time::Duration;
+
+/// The pack of puzzles that ships with the game, in the format [`parse_pack`] reads.
+pub const STARTER_PACK: &str = include_str!("../packs/starter.txt");
+
+/// A named set of hand-made boards, as read by [`parse_pack`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pack {
+    /// The name shown in the pack browser.
+    pub name: String,
+    /// The boards, in the order they are meant to be played.
+    pub puzzles: Vec<Puzzle>,
+}
+
+/// One board of a [`Pack`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    /// The name shown in the pack browser.
+    pub name: String,
+    /// A good time to beat, if the pack sets one.
+    pub par: Option<Duration>,
+    /// The board as it starts, like [`parse`] returns it.
+    pub board: Board,
+    /// The player's best winning time, if they have solved it. Front ends fill this in from
+    /// wherever they keep progress; [`AppState`](crate::app::AppState) updates it on a win.
+    pub best: Option<Duration>,
+}
+
+/// Read a hand-made board from text: one line per row, `#` for a mine, `.` for a hidden safe
+/// cell and a digit for a safe cell shown from the start. Blank lines and trailing spaces
+/// are ignored.
+///
+/// ```text
+/// ..#..
+/// .1...
+/// ...#.
+/// ```
+///
+/// The returned board has its mines placed and the digit cells revealed (a `0` opens its
+/// whole empty region, as a click would).
+///
+/// # Errors
+/// Fails on an unknown character, rows of different lengths, a board with no safe cell,
+/// or a digit that does not match the mines around it.
+pub fn parse(text: &str) -> Result<Board> {
+    let rows: Vec<&str> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let width = rows.first().map_or(0, |row| row.chars().count());
+    let mut mines = Vec::new();
+    let mut shown = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        let len = row.chars().count();
+        if len != width {
+            return Err(Error::Parse(format!(
+                "row {} has {len} cells, expected {width}",
+                y + 1
+            )));
+        }
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                '#' => mines.push((x, y)),
+                '.' => {}
+                '0'..='8' => shown.push((x, y, c)),
+                _ => {
+                    return Err(Error::Parse(format!(
+                        "unexpected '{c}' at row {}, column {} (use #, . or a digit)",
+                        y + 1,
+                        x + 1
+                    )));
+                }
+            }
+        }
+    }
+    let mut board = Board::try_new(width, rows.len(), mines.len())?;
+    board.place_mines_at(&mines);
+    for (x, y, digit) in shown {
+        let actual = board.adjacent_mine_count(x, y);
+        if digit != char::from(b'0' + actual) {
+            return Err(Error::InvalidBoard(format!(
+                "row {}, column {} shows {digit} but has {actual} mines around it",
+                y + 1,
+                x + 1
+            )));
+        }
+        board.reveal(x, y);
+    }
+    Ok(board)
+}
+
+/// Read a pack of puzzles: a `pack:` line naming it, then for each puzzle a `puzzle:` line
+/// naming it, an optional `par:` line with a time to beat in seconds, and the board in the
+/// format [`parse`] reads. Lines starting with `;` are comments.
+///
+/// ```text
+/// pack: Starter
+///
+/// puzzle: First steps
+/// par: 20
+/// 0....
+/// ...#.
+/// ```
+///
+/// # Errors
+/// Fails if the pack has no name or no puzzles, on an unknown `key:` line or a bad par time,
+/// or if a board does not parse (the message names the puzzle).
+pub fn parse_pack(text: &str) -> Result<Pack> {
+    let mut name = None;
+    let mut puzzles = Vec::new();
+    // The puzzle being read: its name, par time and board rows.
+    let mut current: Option<(String, Option<Duration>, String)> = None;
+    let mut finish = |current: Option<(String, Option<Duration>, String)>| -> Result<()> {
+        if let Some((name, par, rows)) = current {
+            let board =
+                parse(&rows).map_err(|e| Error::Parse(format!("puzzle \"{name}\": {e}")))?;
+            puzzles.push(Puzzle {
+                name,
+                par,
+                board,
+                best: None,
+            });
+        }
+        Ok(())
+    };
+    for line in text.lines().map(str::trim_end) {
+        if line.starts_with(';') {
+            continue;
+        }
+        match line.split_once(':').map(|(key, value)| (key, value.trim())) {
+            Some(("pack", value)) => name = Some(value.to_string()),
+            Some(("puzzle", value)) => {
+                finish(current.take())?;
+                current = Some((value.to_string(), None, String::new()));
+            }
+            Some(("par", value)) => {
+                let secs = value
+                    .parse()
+                    .map_err(|_| Error::Parse(format!("par \"{value}\" is not whole seconds")))?;
+                match &mut current {
+                    Some((_, par, _)) => *par = Some(Duration::from_secs(secs)),
+                    None => return Err(Error::Parse("par comes before any puzzle".to_string())),
+                }
+            }
+            Some((key, _)) => return Err(Error::Parse(format!("unknown key \"{key}\""))),
+            None => match &mut current {
+                Some((_, _, rows)) => {
+                    rows.push_str(line);
+                    rows.push('\n');
+                }
+                None if line.is_empty() => {}
+                None => {
+                    return Err(Error::Parse(
+                        "board rows come before any puzzle".to_string(),
+                    ));
+                }
+            },
+        }
+    }
+    finish(current)?;
+    let Some(name) = name else {
+        return Err(Error::Parse("the pack has no \"pack:\" line".to_string()));
+    };
+    if puzzles.is_empty() {
+        return Err(Error::Parse(format!("pack \"{name}\" has no puzzles")));
+    }
+    Ok(Pack { name, puzzles })
+}
+
+/// The packs that ship with the game.
+///
+/// # Panics
+/// If a built-in pack does not parse, which the tests rule out.
+pub fn builtin_packs() -> Vec<Pack> {
+    vec![parse_pack(STARTER_PACK).expect("the starter pack is valid")]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::CellState;
+    use crate::solver;
+
+    #[test]
+    fn parses_mines_and_shown_digits() {
+        let board = parse("..#..\n.1...  \n\n...#.\n").expect("valid board");
+        assert_eq!((board.width(), board.height(), board.mines()), (5, 3, 2));
+        assert!(board.is_mine(2, 0) && board.is_mine(3, 2));
+        assert_eq!(board.cell_at(1, 1), CellState::Revealed(1));
+        assert_eq!(board.cell_at(0, 0), CellState::Hidden);
+    }
+
+    #[test]
+    fn rejects_bad_boards() {
+        assert!(parse("").is_err());
+        assert!(parse("..\n...").is_err());
+        assert!(parse(".x").is_err());
+        assert!(parse("##\n##").is_err());
+        assert!(parse("#2").is_err());
+    }
+
+    #[test]
+    fn packs_name_their_puzzles() {
+        let pack =
+            parse_pack("; comment\npack: Mini\n\npuzzle: One\npar: 15\n#.\n..\npuzzle: Two\n.#1\n")
+                .expect("valid pack");
+        assert_eq!(pack.name, "Mini");
+        let names: Vec<_> = pack.puzzles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["One", "Two"]);
+        assert_eq!(pack.puzzles[0].par, Some(Duration::from_secs(15)));
+        assert_eq!(pack.puzzles[1].board.cell_at(2, 0), CellState::Revealed(1));
+
+        assert!(parse_pack("puzzle: No pack\n#.\n").is_err());
+        assert!(parse_pack("pack: Empty\n").is_err());
+        assert!(parse_pack("pack: Bad\npuzzle: X\npar: soon\n#.\n").is_err());
+        let error = parse_pack("pack: Bad\npuzzle: X\n#x\n").expect_err("bad cell");
+        assert!(error.to_string().starts_with("puzzle \"X\": "));
+    }
+
+    #[test]
+    fn builtin_puzzles_need_no_guessing() {
+        for puzzle in builtin_packs().iter().flat_map(|pack| &pack.puzzles) {
+            let mut board = puzzle.board.clone();
+            while !board.is_win() {
+                let safe = solver::deduce(&board).safe;
+                assert!(!safe.is_empty(), "\"{}\" needs a guess", puzzle.name);
+                for (x, y) in safe {
+                    assert!(board.reveal(x, y));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::net::{Message, Peer};
+use minesweeper::app::{AppState, Status};
+use minesweeper::board::{Board, CellState};
+use minesweeper::events::Event;
+
+/// What is known about the other player, for the header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Opponent {
+    /// Share of their safe cells revealed, 0 to 100.
+    pub percent: u8,
+    /// Whether they won, and in how many seconds, once their game is over.
+    pub finished: Option<(bool, u64)>,
+    /// They quit or the connection dropped.
+    pub left: bool,
+}
+
+/// A 1v1 race: both players clear the same board and see each other's progress.
+pub struct Race {
+    peer: Peer,
+    /// The other player's latest progress.
+    pub opponent: Opponent,
+    /// Last progress sent, so only changes go over the wire.
+    sent: Option<u8>,
+    finished_sent: bool,
+}
+
+impl Race {
+    /// Start racing over an established connection.
+    pub fn new(peer: Peer) -> Self {
+        Self {
+            peer,
+            opponent: Opponent::default(),
+            sent: None,
+            finished_sent: false,
+        }
+    }
+
+    /// The connection, to read from in the background.
+    pub const fn peer(&self) -> &Peer {
+        &self.peer
+    }
+
+    /// Record a message from the other player.
+    pub fn receive(&mut self, message: &Message) {
+        match *message {
+            Message::Progress { percent } => self.opponent.percent = percent,
+            Message::Finished { won, secs } => self.opponent.finished = Some((won, secs)),
+            Message::Bye => self.opponent.left = true,
+            Message::Hello { .. } | Message::Play { .. } | Message::Cursor { .. } => {}
+        }
+    }
+
+    /// Tell the other player about any progress since last time, and the end of the game
+    /// if `events` (just taken from `app`) end it.
+    pub fn report(&mut self, app: &AppState, events: &[Event]) {
+        let percent = progress(&app.board);
+        if self.sent != Some(percent) {
+            self.sent = Some(percent);
+            self.peer.send(&Message::Progress { percent });
+        }
+        for event in events {
+            if let Event::GameEnded { status, time } = *event
+                && !self.finished_sent
+            {
+                self.finished_sent = true;
+                self.peer.send(&Message::Finished {
+                    won: status == Status::Win,
+                    secs: time.as_secs(),
+                });
+            }
+        }
+    }
+}
+
+impl Drop for Race {
+    fn drop(&mut self) {
+        self.peer.send(&Message::Bye);
+    }
+}
+
+/// Share of the safe cells that are revealed, 0 to 100.
+pub fn progress(board: &Board) -> u8 {
+    let safe = board.cells() - board.mines();
+    let revealed = (0..board.width() * board.height())
+        .filter(|i| {
+            let (x, y) = (i % board.width(), i / board.width());
+            matches!(board.cell_at(x, y), CellState::Revealed(_)) && !board.is_mine(x, y)
+        })
+        .count();
+    u8::try_from(revealed * 100 / safe.max(1)).unwrap_or(100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_counts_safe_cells() {
+        let mut board = Board::new(3, 1, 1);
+        board.place_mines_at(&[(1, 0)]);
+        assert_eq!(progress(&board), 0);
+        board.reveal(0, 0);
+        assert_eq!(progress(&board), 50);
+        board.reveal(2, 0);
+        assert_eq!(progress(&board), 100);
+    }
+}
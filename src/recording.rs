@@ -0,0 +1,99 @@
+use crate::app::{Action, AppState, Command};
+
+/// A recorded sequence of actions that can be replayed through [`AppState::handle_action`].
+///
+/// Useful for tests, demos, and accessibility tooling that want to drive the game with a
+/// fixed script of inputs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Macro {
+    actions: Vec<Action>,
+}
+
+impl Macro {
+    /// An empty macro.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an action to the end of the macro.
+    pub fn push(&mut self, action: Action) {
+        self.actions.push(action);
+    }
+
+    /// The recorded actions, in order.
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Number of recorded actions.
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Whether nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Feed every action through `handle_action`, stopping early if the game ends.
+    /// Returns the command produced by the last action played.
+    pub fn play(&self, app: &mut AppState) -> Command {
+        let mut last = Command::None;
+        for &action in &self.actions {
+            last = app.handle_action(action);
+            if matches!(last, Command::GameWon | Command::GameLost) {
+                break;
+            }
+        }
+        last
+    }
+}
+
+impl From<Vec<Action>> for Macro {
+    fn from(actions: Vec<Action>) -> Self {
+        Self { actions }
+    }
+}
+
+impl FromIterator<Action> for Macro {
+    fn from_iter<I: IntoIterator<Item = Action>>(iter: I) -> Self {
+        Self {
+            actions: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Cursor;
+    use crate::board::CellState;
+    use crate::difficulty::Difficulty;
+
+    #[test]
+    fn play_feeds_actions_in_order() {
+        let mut app = AppState::new(Difficulty::Easy);
+        let m: Macro = [Action::MoveRight, Action::MoveDown, Action::ToggleFlag]
+            .into_iter()
+            .collect();
+        m.play(&mut app);
+        assert_eq!(app.cursor, Cursor::new(1, 1));
+        assert_eq!(app.board.cell_at(1, 1), CellState::Flagged);
+    }
+
+    #[test]
+    fn recording_captures_actions_but_not_controls() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.handle_action(Action::ToggleRecording);
+        app.handle_action(Action::MoveRight);
+        app.handle_action(Action::ToggleFlag);
+        app.handle_action(Action::ToggleRecording);
+        let recorded = app.last_macro.clone().expect("macro recorded");
+        assert_eq!(recorded.actions(), &[Action::MoveRight, Action::ToggleFlag]);
+
+        // Replaying from a fresh game lands in the same place.
+        app.restart();
+        app.handle_action(Action::PlayMacro);
+        assert_eq!(app.board.cell_at(1, 0), CellState::Flagged);
+    }
+}
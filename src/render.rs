@@ -0,0 +1,155 @@
+use crate::app::{AppState, Status};
+use crate::board::CellState;
+use std::io::{self, Write};
+
+/// A front end's way of showing a game: a terminal UI, plain text, a GUI, or a test double.
+///
+/// Renderers only read the [`AppState`]; input goes through
+/// [`AppState::handle_action`](crate::app::AppState::handle_action) as usual, so a new front
+/// end needs no changes to the game logic.
+pub trait Renderer {
+    /// What can go wrong while drawing.
+    type Error;
+
+    /// Show the current state of `app`.
+    ///
+    /// # Errors
+    /// Whatever the output reports, e.g. a failed write.
+    fn render(&mut self, app: &AppState) -> Result<(), Self::Error>;
+}
+
+/// Renders the board as plain text: a `status ... mines N flags N` line, then one line per
+/// row (see [`rows`]).
+#[derive(Debug)]
+pub struct TextRenderer<W> {
+    out: W,
+}
+
+impl<W: Write> TextRenderer<W> {
+    /// Render into `out`.
+    pub const fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    /// The writer, after rendering.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> Renderer for TextRenderer<W> {
+    type Error = io::Error;
+
+    fn render(&mut self, app: &AppState) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "status {} mines {} flags {}",
+            status_name(app.status),
+            app.board.mines(),
+            app.flags_placed
+        )?;
+        for row in rows(app) {
+            writeln!(self.out, "{row}")?;
+        }
+        self.out.flush()
+    }
+}
+
+/// `"playing"`, `"won"` or `"lost"`, as text front ends report it.
+pub const fn status_name(status: Status) -> &'static str {
+    match status {
+        Status::Playing => "playing",
+        Status::Win => "won",
+        Status::Lose => "lost",
+    }
+}
+
+/// The board as plain text rows.
+///
+/// `#` is hidden, `F` flagged, `.` an empty cell and digits count adjacent mines. Once the
+/// game is over, unflagged mines show as `*`. Cells cut out of a shaped board are blank.
+pub fn rows(app: &AppState) -> Vec<String> {
+    let board = &app.board;
+    (0..board.height())
+        .map(|y| {
+            (0..board.width())
+                .map(|x| match board.cell_at(x, y) {
+                    _ if !board.in_play(x, y) => ' ',
+                    CellState::Hidden if app.status != Status::Playing && board.is_mine(x, y) => {
+                        '*'
+                    }
+                    CellState::Hidden => '#',
+                    CellState::Flagged => 'F',
+                    CellState::Revealed(0) => '.',
+                    CellState::Revealed(n) => char::from(b'0' + n),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Label for board column `x` on the rulers: A-Z, then a-z, then `?` for very wide boards.
+pub fn column_label(x: usize) -> char {
+    const LABELS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    LABELS.get(x).map_or('?', |&b| char::from(b))
+}
+
+/// The cell at column `x`, row `y` as the rulers name it, like `D5`.
+pub fn cell_name(x: usize, y: usize) -> String {
+    format!("{}{}", column_label(x), y + 1)
+}
+
+/// The column and row of the cell called `name` on a board `width` columns wide, the reverse
+/// of [`cell_name`]. On boards of up to 26 columns, whose labels are all capitals, the letter
+/// may be given in either case.
+pub fn parse_cell_name(name: &str, width: usize) -> Option<(usize, usize)> {
+    let mut chars = name.chars();
+    let label = chars.next()?;
+    let label = if width <= 26 {
+        label.to_ascii_uppercase()
+    } else {
+        label
+    };
+    let x = (0..width)
+        .take_while(|&x| column_label(x) != '?')
+        .find(|&x| column_label(x) == label)?;
+    let row: usize = chars.as_str().parse().ok()?;
+    Some((x, row.checked_sub(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Action;
+    use crate::board::Board;
+    use crate::difficulty::Difficulty;
+
+    #[test]
+    fn text_renderer_prints_status_and_rows() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 2, 1);
+        app.board.place_mines_at(&[(2, 1)]);
+        app.handle_action(Action::ToggleFlag);
+        let mut renderer = TextRenderer::new(Vec::new());
+        renderer.render(&app).expect("in-memory write");
+        assert_eq!(
+            String::from_utf8(renderer.into_inner()).expect("utf-8"),
+            "status playing mines 1 flags 1\nF##\n###\n"
+        );
+    }
+
+    #[test]
+    fn cell_names_read_back() {
+        assert_eq!(parse_cell_name(&cell_name(3, 4), 9), Some((3, 4)));
+        assert_eq!(parse_cell_name("c4", 9), Some((2, 3)), "either case");
+        assert_eq!(
+            parse_cell_name("c4", 30),
+            Some((28, 3)),
+            "lower case is its own"
+        );
+        assert_eq!(parse_cell_name("J1", 9), None, "off the board");
+        assert_eq!(parse_cell_name("?1", 60), None);
+        assert_eq!(parse_cell_name("A0", 9), None);
+        assert_eq!(parse_cell_name("A", 9), None);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::app::{Action, AppState, Cursor, Status};
+use crate::code::BoardCode;
+use crate::error::{Error, Result};
+
+/// One timed move of a recorded game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Move {
+    /// Seconds since the game started (when the board code opened its first cell).
+    pub at: f64,
+    /// [`Action::Reveal`], [`Action::ToggleFlag`] or [`Action::Chord`].
+    pub action: Action,
+    /// Target column.
+    pub x: usize,
+    /// Target row.
+    pub y: usize,
+}
+
+impl Move {
+    /// Parse a move written like a headless command: `reveal X Y`, `flag X Y` or `chord X Y`.
+    ///
+    /// # Errors
+    /// Fails on any other command or malformed coordinates.
+    pub fn parse(at: f64, text: &str) -> Result<Self> {
+        let invalid = || Error::Parse(format!("'{text}' is not a move (like 'reveal 3 4')"));
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let [command, x, y] = words[..] else {
+            return Err(invalid());
+        };
+        let action = match command.to_ascii_lowercase().as_str() {
+            "reveal" | "r" => Action::Reveal,
+            "flag" | "f" => Action::ToggleFlag,
+            "chord" | "c" => Action::Chord,
+            _ => return Err(invalid()),
+        };
+        let (Ok(x), Ok(y)) = (x.parse(), y.parse()) else {
+            return Err(invalid());
+        };
+        Ok(Self { at, action, x, y })
+    }
+}
+
+/// Replay `moves` on the game named by `code` and return the winning time in seconds: the
+/// time of the last move.
+///
+/// This proves the moves clear that exact board; the times themselves are the player's
+/// word, checked only for being in order.
+///
+/// # Errors
+/// Fails if a move is off the board, out of order, comes after the game ended or hits a
+/// mine, or if the moves leave safe cells hidden.
+pub fn verify(code: BoardCode, moves: &[Move]) -> Result<f64> {
+    let mut app = AppState::new(code.difficulty);
+    // Every move names its cell, so a repeated reveal must not turn into a chord.
+    app.double_tap_timeout = None;
+    app.apply_code(code);
+    let mut time = 0.0;
+    for (n, m) in moves.iter().enumerate().map(|(i, m)| (i + 1, m)) {
+        let broken = |message: &str| Err(Error::InvalidReplay(format!("move {n}: {message}")));
+        if app.status != Status::Playing {
+            return broken("the game is already over");
+        }
+        if !m.at.is_finite() || m.at < time {
+            return broken("times must not go backwards");
+        }
+        if m.x >= app.board.width() || m.y >= app.board.height() {
+            return broken("off the board");
+        }
+        if !matches!(
+            m.action,
+            Action::Reveal | Action::ToggleFlag | Action::Chord
+        ) {
+            return broken("only reveals, flags and chords can be replayed");
+        }
+        time = m.at;
+        app.cursor = Cursor::new(m.x, m.y);
+        app.handle_action(m.action);
+        if app.status == Status::Lose {
+            return broken("hits a mine");
+        }
+    }
+    if app.status == Status::Win {
+        Ok(time)
+    } else {
+        Err(Error::InvalidReplay(
+            "the moves leave safe cells hidden".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::CellState;
+    use crate::difficulty::Difficulty;
+
+    /// Reveals of every safe cell still hidden, one per second, playing them on the way.
+    fn clearing_moves(code: BoardCode) -> Vec<Move> {
+        let mut app = AppState::new(code.difficulty);
+        app.apply_code(code);
+        let (width, height) = (app.board.width(), app.board.height());
+        let mut moves = Vec::new();
+        for (x, y) in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+            if app.board.is_mine(x, y) || app.board.cell_at(x, y) != CellState::Hidden {
+                continue;
+            }
+            let at = f64::from(u32::try_from(moves.len()).unwrap_or(u32::MAX));
+            moves.push(Move::parse(at, &format!("reveal {x} {y}")).expect("valid move"));
+            app.cursor = Cursor::new(x, y);
+            app.handle_action(Action::Reveal);
+        }
+        moves
+    }
+
+    #[test]
+    fn winning_moves_verify_with_the_last_time() {
+        let code: BoardCode = "MS-easy-Friday".parse().expect("valid code");
+        let moves = clearing_moves(code);
+        let last = moves.last().map_or(0.0, |m| m.at);
+        assert_eq!(verify(code, &moves).ok(), Some(last));
+        assert!(verify(code, &moves[..moves.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn bad_replays_are_rejected() {
+        let code = BoardCode {
+            difficulty: Difficulty::Easy,
+            seed: 1,
+        };
+        let mut app = AppState::new(code.difficulty);
+        app.apply_code(code);
+        let (mx, my) = (0..81)
+            .map(|i| (i % 9, i / 9))
+            .find(|&(x, y)| app.board.is_mine(x, y))
+            .expect("the board has mines");
+        let mine = Move::parse(1.0, &format!("reveal {mx} {my}")).expect("valid move");
+        assert!(verify(code, &[mine]).is_err());
+        let late = Move::parse(0.5, "flag 0 0").expect("valid move");
+        assert!(verify(code, &[Move { at: 2.0, ..late }, late]).is_err());
+        assert!(Move::parse(0.0, "reveal 1").is_err());
+        assert!(Move::parse(0.0, "undo 1 1").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use minesweeper::campaign::Campaign;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// The default best-run file: `campaign.json` in the user data directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("minesweeper").join("campaign.json"))
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Saved {
+    best_depth: usize,
+}
+
+/// The deepest campaign run, kept in a JSON file so `--campaign` can show the depth to beat.
+pub struct BestRun {
+    path: PathBuf,
+    /// Best depth as last read or written.
+    saved: usize,
+}
+
+impl BestRun {
+    /// Read the best depth kept at `path` into `campaign`. A missing or unreadable file
+    /// counts as no run yet.
+    pub fn load(path: PathBuf, campaign: &mut Campaign) -> Self {
+        let saved: Saved = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        campaign.best = campaign.best.max(saved.best_depth);
+        Self {
+            path,
+            saved: saved.best_depth,
+        }
+    }
+
+    /// Write the file if `campaign` went deeper than ever since the last call. Call after
+    /// every change to the game.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be written.
+    pub fn observe(&mut self, campaign: &Campaign) -> io::Result<()> {
+        if campaign.best <= self.saved {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let saved = Saved {
+            best_depth: campaign.best,
+        };
+        fs::write(&self.path, serde_json::to_string_pretty(&saved)?)?;
+        self.saved = campaign.best;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_best_depth_survives_a_restart() {
+        let path =
+            std::env::temp_dir().join(format!("minesweeper-campaign-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut campaign = Campaign::default();
+        let mut best = BestRun::load(path.clone(), &mut campaign);
+        best.observe(&campaign).expect("nothing to write");
+        assert!(!path.exists());
+
+        campaign.finished(true);
+        best.observe(&campaign).expect("best run written");
+        let mut next = Campaign::default();
+        BestRun::load(path.clone(), &mut next);
+        let _ = fs::remove_file(&path);
+        assert_eq!(next.best, 1);
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+/// Points per cell opened by a reveal.
+const REVEAL_POINTS: u64 = 10;
+/// Points per cell opened by a chord, which takes more care than a single reveal.
+const CHORD_POINTS: u64 = 15;
+/// Points for a flag placed on a mine.
+const FLAG_POINTS: u64 = 20;
+/// Correct moves in a row that raise the multiplier by one.
+const STREAK_STEP: u32 = 5;
+/// The highest multiplier a streak reaches.
+const MAX_MULTIPLIER: u64 = 5;
+/// Points for a win per 3BV cleared per second.
+const SPEED_POINTS: u64 = 1000;
+
+/// Points earned in a game in scoring mode ([`AppState::score`]), for players who want a
+/// measure beyond the timer.
+///
+/// Reveals and chords earn points per cell opened and flags on mines earn a flat amount, all
+/// times a multiplier that grows with every few correct moves in a row. A flag on a safe cell,
+/// taking a flag back, a hint or an undo ends the streak. A win adds a bonus for speed: the
+/// board's 3BV per second.
+///
+/// [`AppState::score`]: crate::app::AppState::score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Score {
+    /// Points so far.
+    pub points: u64,
+    /// Correct moves since the streak last ended.
+    pub streak: u32,
+}
+
+impl Score {
+    /// What the next correct move's points are multiplied by.
+    pub fn multiplier(&self) -> u64 {
+        (1 + u64::from(self.streak / STREAK_STEP)).min(MAX_MULTIPLIER)
+    }
+
+    /// Credit a safe reveal or chord that opened `opened` cells.
+    pub fn opened(&mut self, opened: usize, chord: bool) {
+        let per_cell = if chord { CHORD_POINTS } else { REVEAL_POINTS };
+        let cells = u64::try_from(opened).unwrap_or(u64::MAX);
+        self.earn(per_cell.saturating_mul(cells));
+    }
+
+    /// Credit a flag placed on a mine.
+    pub fn flagged_mine(&mut self) {
+        self.earn(FLAG_POINTS);
+    }
+
+    /// End the streak after a wrong flag, a flag taken back, a hint or an undo.
+    pub const fn break_streak(&mut self) {
+        self.streak = 0;
+    }
+
+    /// Add the speed bonus for clearing a board of `bbbv` 3BV in `time`.
+    pub fn won(&mut self, bbbv: usize, time: Duration) {
+        // Anything under a second counts as a second.
+        let millis = time.as_millis().max(1000);
+        let bonus = u128::from(SPEED_POINTS) * (bbbv as u128) * 1000 / millis;
+        self.points = self
+            .points
+            .saturating_add(u64::try_from(bonus).unwrap_or(u64::MAX));
+    }
+
+    fn earn(&mut self, points: u64) {
+        self.points = self
+            .points
+            .saturating_add(points.saturating_mul(self.multiplier()));
+        self.streak = self.streak.saturating_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaks_multiply_points() {
+        let mut score = Score::default();
+        for _ in 0..STREAK_STEP {
+            score.opened(1, false);
+        }
+        assert_eq!((score.points, score.multiplier()), (50, 2));
+        score.opened(2, true);
+        score.flagged_mine();
+        assert_eq!(score.points, 50 + 60 + 40);
+        score.break_streak();
+        assert_eq!(score.multiplier(), 1);
+        score.streak = u32::MAX;
+        assert_eq!(score.multiplier(), MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn faster_wins_earn_more() {
+        let mut slow = Score::default();
+        slow.won(20, Duration::from_secs(40));
+        let mut fast = Score::default();
+        fast.won(20, Duration::from_millis(10));
+        assert_eq!((slow.points, fast.points), (500, 20_000));
+    }
+}
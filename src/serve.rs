@@ -0,0 +1,441 @@
+use minesweeper::code::BoardCode;
+use minesweeper::difficulty::Difficulty;
+use minesweeper::error::{self, Error};
+use minesweeper::replay::{self, Move};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Port `serve` listens on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// Largest request body accepted; a long game is a few thousand moves.
+const MAX_BODY: usize = 1 << 20;
+
+/// Largest request line and headers accepted, together.
+const MAX_HEAD: u64 = 8 << 10;
+
+/// How long a client may take to send its whole request, so a stalled or trickling one cannot
+/// block the server.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest player name accepted.
+const MAX_NAME: usize = 32;
+
+/// Most scores returned by one listing.
+const MAX_LISTED: usize = 100;
+
+/// A verified result, as stored and listed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    /// Player name.
+    pub name: String,
+    /// The board code the game was played from.
+    pub code: String,
+    /// `easy`, `medium`, `hard` or `WxHxM` for a custom size.
+    pub difficulty: String,
+    /// Winning time in seconds.
+    pub time: f64,
+    /// Moves the game took.
+    pub moves: usize,
+    /// When the score was accepted, in seconds since the Unix epoch.
+    pub submitted: u64,
+}
+
+/// What players `POST` to `/scores`.
+#[derive(Debug, Deserialize)]
+struct Submission {
+    name: String,
+    code: String,
+    moves: Vec<TimedMove>,
+}
+
+/// One move of a submission: `{"at": 1.5, "move": "reveal 3 4"}`.
+#[derive(Debug, Deserialize)]
+struct TimedMove {
+    at: f64,
+    #[serde(rename = "move")]
+    text: String,
+}
+
+/// An HTTP request, reduced to what the API needs.
+#[derive(Debug, Default)]
+pub struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// The method, like `GET`.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The path, without the query.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Best times for a group of players, kept in a JSON Lines file.
+///
+/// `POST /scores` replays the submitted moves on the board the code names and stores the
+/// time only if they win it; `GET /scores` lists each player's best time per difficulty,
+/// fastest first, optionally narrowed with `?difficulty=hard` or `?code=MS-hard-9xQk2`.
+pub struct Leaderboard {
+    path: Option<PathBuf>,
+    scores: Vec<Score>,
+}
+
+impl Leaderboard {
+    /// Load the scores kept at `path` (none yet if it does not exist); without a path, scores
+    /// are kept in memory only.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read or has a line that is not a score.
+    pub fn open(path: Option<PathBuf>) -> error::Result<Self> {
+        let mut scores = Vec::new();
+        if let Some(path) = &path
+            && path.exists()
+        {
+            let text = fs::read_to_string(path).map_err(|error| Error::LoadIo {
+                path: path.clone(),
+                error,
+            })?;
+            for (n, line) in text.lines().enumerate() {
+                if !line.trim().is_empty() {
+                    scores.push(serde_json::from_str(line).map_err(|e| {
+                        Error::Parse(format!(
+                            "line {} of {} is not a score: {e}",
+                            n + 1,
+                            path.display()
+                        ))
+                    })?);
+                }
+            }
+        }
+        Ok(Self { path, scores })
+    }
+
+    /// Answer one request with a status code and a JSON body.
+    pub fn handle(&mut self, request: &Request) -> (u16, Value) {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/scores") => (200, json!({ "scores": self.best(&request.query) })),
+            ("POST", "/scores") => match self.submit(&request.body) {
+                Ok(score) => (201, json!(score)),
+                Err(e) => (422, json!({ "error": e })),
+            },
+            (_, "/scores") => (405, json!({ "error": "use GET or POST" })),
+            _ => (404, json!({ "error": "not found; try /scores" })),
+        }
+    }
+
+    /// Verify a submission and keep its score.
+    fn submit(&mut self, body: &[u8]) -> Result<Score, String> {
+        let submission: Submission =
+            serde_json::from_slice(body).map_err(|e| format!("bad submission: {e}"))?;
+        let name = submission.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME {
+            return Err(format!("names must be 1 to {MAX_NAME} characters"));
+        }
+        let code: BoardCode = submission.code.parse().map_err(|e| format!("{e}"))?;
+        let moves = submission
+            .moves
+            .iter()
+            .map(|m| Move::parse(m.at, &m.text))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("{e}"))?;
+        let time = replay::verify(code, &moves).map_err(|e| format!("not a win: {e}"))?;
+        let score = Score {
+            name: name.to_string(),
+            code: code.to_string(),
+            difficulty: label(code.difficulty),
+            time,
+            moves: moves.len(),
+            submitted: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        };
+        self.append(&score)
+            .map_err(|e| format!("cannot save the score: {e}"))?;
+        self.scores.push(score.clone());
+        Ok(score)
+    }
+
+    fn append(&self, score: &Score) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(score)?)
+    }
+
+    /// Each player's best score per difficulty among those matching `query`, fastest first.
+    fn best(&self, query: &[(String, String)]) -> Vec<&Score> {
+        let matches = |score: &Score| {
+            query.iter().all(|(key, value)| match key.as_str() {
+                "difficulty" => score.difficulty.eq_ignore_ascii_case(value),
+                "code" => score.code == *value,
+                _ => true,
+            })
+        };
+        let mut best: Vec<&Score> = Vec::new();
+        for score in self.scores.iter().filter(|score| matches(score)) {
+            match best
+                .iter_mut()
+                .find(|b| b.name == score.name && b.difficulty == score.difficulty)
+            {
+                Some(b) if score.time < b.time => *b = score,
+                Some(_) => {}
+                None => best.push(score),
+            }
+        }
+        best.sort_by(|a, b| a.time.total_cmp(&b.time));
+        best.truncate(MAX_LISTED);
+        best
+    }
+}
+
+/// How a difficulty is listed: its name, or `WxHxM` for a custom size.
+fn label(difficulty: Difficulty) -> String {
+    match difficulty {
+        Difficulty::Custom {
+            width,
+            height,
+            mines,
+        } => format!("{width}x{height}x{mines}"),
+        preset => preset.name().to_string(),
+    }
+}
+
+/// The default scores file: `leaderboard.jsonl` in the user data directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("minesweeper").join("leaderboard.jsonl"))
+}
+
+/// Serve the leaderboard kept at `path` on `port`, one request at a time, until killed.
+///
+/// # Errors
+/// Fails if the scores cannot be loaded or the port cannot be bound.
+pub fn run(port: u16, path: Option<PathBuf>) -> error::Result<()> {
+    let mut leaderboard = Leaderboard::open(path)?;
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .map_err(|e| Error::Network(format!("cannot listen on port {port}: {e}")))?;
+    eprintln!("Leaderboard listening on http://0.0.0.0:{port}/scores");
+    for stream in listener.incoming().flatten() {
+        // One bad client must not stop the server.
+        let _ = serve_one(&mut leaderboard, stream);
+    }
+    Ok(())
+}
+
+fn serve_one(leaderboard: &mut Leaderboard, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let until = Deadline {
+        stream: &stream,
+        at: Instant::now() + TIMEOUT,
+    };
+    let (status, body) = match read_request(BufReader::new(until)) {
+        Ok(request) => leaderboard.handle(&request),
+        Err(e) => (400, json!({ "error": e })),
+    };
+    write_response(&mut stream, status, &body)
+}
+
+/// Reads a stream until a deadline, however slowly the other end sends.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    at: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.at.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+/// Read a request line, headers and a `Content-Length` body. The line and headers are limited
+/// to [`MAX_HEAD`] bytes and the body to [`MAX_BODY`].
+///
+/// Query values are taken as written (no percent-decoding); board codes and difficulty names
+/// never need it.
+pub fn read_request(mut reader: impl BufRead) -> Result<Request, String> {
+    let mut head = (&mut reader).take(MAX_HEAD);
+    let mut read_line = |line: &mut String| {
+        let read = head.read_line(line).map_err(|e| e.to_string())?;
+        if head.limit() == 0 && !line.ends_with('\n') {
+            return Err(format!("request headers are limited to {MAX_HEAD} bytes"));
+        }
+        Ok(read)
+    };
+    let mut line = String::new();
+    read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err("malformed request line".to_string());
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        body: Vec::new(),
+    };
+    let mut length = 0;
+    loop {
+        line.clear();
+        if read_line(&mut line)? == 0 {
+            return Err("the request ended inside the headers".to_string());
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value
+                .trim()
+                .parse()
+                .map_err(|_| "bad Content-Length".to_string())?;
+        }
+    }
+    if length > MAX_BODY {
+        return Err(format!("bodies are limited to {MAX_BODY} bytes"));
+    }
+    request.body = vec![0; length];
+    reader
+        .read_exact(&mut request.body)
+        .map_err(|e| e.to_string())?;
+    Ok(request)
+}
+
+/// Write `body` as a JSON response with `status`, readable from any web page.
+pub fn write_response(out: &mut impl Write, status: u16, body: &Value) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Unprocessable Entity",
+    };
+    let body = body.to_string();
+    write!(
+        out,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\n\
+         Access-Control-Allow-Origin: *\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::{Action, AppState, Cursor};
+    use minesweeper::board::CellState;
+
+    /// A submission that clears the board `code` names, one reveal per `step` seconds.
+    fn winning_submission(name: &str, code: &str, step: f64) -> String {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.apply_code(code.parse().expect("valid code"));
+        let mut moves = Vec::new();
+        for i in 0..app.board.width() * app.board.height() {
+            let (x, y) = (i % app.board.width(), i / app.board.width());
+            if !app.board.is_mine(x, y) && app.board.cell_at(x, y) == CellState::Hidden {
+                let at = step * f64::from(u32::try_from(moves.len() + 1).unwrap_or(0));
+                moves.push(json!({ "at": at, "move": format!("reveal {x} {y}") }));
+                app.cursor = Cursor::new(x, y);
+                app.handle_action(Action::Reveal);
+            }
+        }
+        json!({ "name": name, "code": code, "moves": moves }).to_string()
+    }
+
+    fn request(text: &str) -> Request {
+        read_request(text.as_bytes()).expect("valid request")
+    }
+
+    fn post(body: &str) -> Request {
+        request(&format!(
+            "POST /scores HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        ))
+    }
+
+    #[test]
+    fn verified_wins_are_listed_best_first() {
+        let mut board = Leaderboard::open(None).expect("in memory");
+        for (name, step) in [("ann", 2.0), ("bob", 1.0), ("ann", 1.5)] {
+            let (status, _) = board.handle(&post(&winning_submission(name, "MS-easy-a", step)));
+            assert_eq!(status, 201);
+        }
+        let (status, body) = board.handle(&request("GET /scores?difficulty=easy HTTP/1.1\r\n\r\n"));
+        assert_eq!(status, 200);
+        let names: Vec<_> = body["scores"]
+            .as_array()
+            .expect("a list")
+            .iter()
+            .map(|score| score["name"].as_str().unwrap_or_default())
+            .collect();
+        assert_eq!(names, ["bob", "ann"]);
+        assert_eq!(body["scores"][0]["code"], "MS-easy-a");
+        let (_, body) = board.handle(&request("GET /scores?difficulty=hard HTTP/1.1\r\n\r\n"));
+        assert_eq!(body["scores"], json!([]));
+    }
+
+    #[test]
+    fn unverified_or_malformed_submissions_are_refused() {
+        let mut board = Leaderboard::open(None).expect("in memory");
+        let partial = json!({ "name": "eve", "code": "MS-easy-a", "moves": [] }).to_string();
+        assert_eq!(board.handle(&post(&partial)).0, 422);
+        assert_eq!(board.handle(&post("{}")).0, 422);
+        let anonymous = winning_submission(" ", "MS-easy-a", 1.0);
+        assert_eq!(board.handle(&post(&anonymous)).0, 422);
+        assert_eq!(
+            board.handle(&request("DELETE /scores HTTP/1.1\r\n\r\n")).0,
+            405
+        );
+        assert_eq!(board.handle(&request("GET / HTTP/1.1\r\n\r\n")).0, 404);
+        assert!(read_request("GET /scores HTTP/1.1\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn oversized_boards_and_requests_are_refused() {
+        let mut board = Leaderboard::open(None).expect("in memory");
+        let huge = json!({ "name": "eve", "code": "MS-100000x100000x1-a", "moves": [] });
+        let (status, body) = board.handle(&post(&huge.to_string()));
+        assert_eq!(status, 422);
+        assert!(
+            body["error"]
+                .as_str()
+                .is_some_and(|e| e.contains("too large"))
+        );
+
+        let endless = format!("GET /scores HTTP/1.1\r\nX-Padding: {}", "a".repeat(1 << 20));
+        let error = read_request(endless.as_bytes()).expect_err("too long");
+        assert!(error.contains("limited"), "{error}");
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(1 << 20));
+        assert!(read_request(long_line.as_bytes()).is_err());
+    }
+}
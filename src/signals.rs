@@ -0,0 +1,67 @@
+use crate::input::{InputAction, InputEvent};
+use signal_hook::consts::{SIGCONT, SIGTSTP};
+use signal_hook::low_level;
+use std::io;
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Forward signals to the main loop as input, from a task on the running runtime:
+///
+/// - SIGTERM, SIGHUP (the terminal went away) and SIGINT quit, so a game killed from outside
+///   leaves through the normal exit path: the terminal is restored and anything saved on quit
+///   is saved, instead of the default handler ending the process on the spot.
+/// - SIGTSTP (e.g. `kill -TSTP`) suspends like Ctrl+Z, and SIGCONT resumes, retaking the
+///   terminal and redrawing in full after whatever happened to the screen meanwhile.
+///
+/// # Errors
+/// Fails if the signal handlers cannot be installed.
+pub fn spawn_forwarder(tx: UnboundedSender<InputEvent>) -> io::Result<()> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut stop = signal(SignalKind::from_raw(SIGTSTP))?;
+    let mut resume = signal(SignalKind::from_raw(SIGCONT))?;
+    tokio::spawn(async move {
+        loop {
+            let action = tokio::select! {
+                Some(()) = terminate.recv() => InputAction::Quit,
+                Some(()) = hangup.recv() => InputAction::Quit,
+                Some(()) = interrupt.recv() => InputAction::Quit,
+                Some(()) = stop.recv() => InputAction::Suspend,
+                Some(()) = resume.recv() => InputAction::Resume,
+                else => break,
+            };
+            if tx.send(InputEvent::Action(action)).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Stop the process as the default SIGTSTP handler would, returning once it is continued.
+pub fn stop() {
+    let _ = low_level::emulate_default_handler(SIGTSTP);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use signal_hook::consts::SIGTERM;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn signals_become_input() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        spawn_forwarder(tx).expect("handlers installed");
+        for (signal, action) in [(SIGTERM, InputAction::Quit), (SIGCONT, InputAction::Resume)] {
+            low_level::raise(signal).expect("signal raised");
+            assert_eq!(
+                timeout(Duration::from_secs(5), rx.recv()).await,
+                Ok(Some(InputEvent::Action(action)))
+            );
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use clap::Args;
+use minesweeper::board::{Board, CellState, Layout};
+use minesweeper::generator::{self, Spec};
+use minesweeper::solver;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng, rng};
+use std::fmt;
+
+/// Options for `minesweeper simulate`. Size, mines, seed and layout come from the global options.
+#[derive(Debug, Clone, Args)]
+pub struct SimulateArgs {
+    /// Number of games the solver plays.
+    #[arg(short, long, default_value_t = 1000)]
+    pub games: usize,
+
+    /// Play only boards that can be cleared from the center without guessing, as
+    /// `generate --no-guess` makes them.
+    #[arg(long)]
+    pub no_guess: bool,
+}
+
+/// How one game played by the solver went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Outcome {
+    won: bool,
+    /// Moves made with nothing certain, whether or not they came off.
+    guesses: usize,
+}
+
+/// Totals over the games played, as `simulate` reports them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Summary {
+    size: (usize, usize, usize),
+    games: usize,
+    wins: usize,
+    bbbv: usize,
+    guesses: usize,
+    /// Games that took at least one guess.
+    guessed: usize,
+}
+
+impl Summary {
+    fn add(&mut self, board: &Board, outcome: Outcome) {
+        self.games += 1;
+        self.wins += usize::from(outcome.won);
+        self.bbbv += board.bbbv();
+        self.guesses += outcome.guesses;
+        self.guessed += usize::from(outcome.guesses > 0);
+    }
+}
+
+impl fmt::Display for Summary {
+    #[allow(clippy::cast_precision_loss)] // game and cell counts are far below 2^52
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height, mines) = self.size;
+        let games = self.games.max(1) as f64;
+        let share = |n: usize| 100.0 * n as f64 / games;
+        writeln!(
+            f,
+            "{} games on {width}x{height} with {mines} mines",
+            self.games
+        )?;
+        writeln!(f, "Won:         {} ({:.1}%)", self.wins, share(self.wins))?;
+        writeln!(f, "Average 3BV: {:.1}", self.bbbv as f64 / games)?;
+        write!(
+            f,
+            "Guesses:     {:.2} per game; {} games ({:.1}%) needed one",
+            self.guesses as f64 / games,
+            self.guessed,
+            share(self.guessed)
+        )
+    }
+}
+
+/// Let the solver play `args.games` random boards of `size` (width, height, mines) and print
+/// how it fared: the win rate, the average 3BV and how often it had to guess.
+///
+/// # Errors
+/// Fails if the size cannot be played, or no no-guess board can be found for it.
+pub fn run(
+    args: &SimulateArgs,
+    size: (usize, usize, usize),
+    seed: Option<u64>,
+    layout: Layout,
+) -> anyhow::Result<()> {
+    let (width, height, mines) = size;
+    let start = (width / 2, height / 2);
+    let mut seeds = StdRng::seed_from_u64(seed.unwrap_or_else(|| rng().random()));
+    let mut summary = Summary {
+        size,
+        ..Summary::default()
+    };
+    for _ in 0..args.games {
+        let board = generator::generate(&Spec {
+            width,
+            height,
+            mines,
+            start,
+            seed: Some(seeds.random()),
+            no_guess: args.no_guess,
+            min_rating: None,
+            max_rating: None,
+            layout,
+        })?;
+        let outcome = play(board.clone(), start);
+        summary.add(&board, outcome);
+    }
+    println!("{summary}");
+    Ok(())
+}
+
+/// Play `board` as the solver would: reveal `start`, then every cell [`solver::deduce`] proves
+/// safe, and when there is none the [best guess](solver::best_guess), until the board is
+/// cleared or a mine goes off.
+fn play(mut board: Board, start: (usize, usize)) -> Outcome {
+    let mut guesses = 0;
+    let mut safe = board.reveal(start.0, start.1);
+    while safe && !board.is_win() {
+        let deduced = solver::deduce(&board).safe;
+        if deduced.is_empty() {
+            let cell = match solver::best_guess(&board) {
+                // Proved safe by the count of mines left, so not a guess.
+                Some(guess) if guess.mine_chance < 1e-9 => Some((guess.x, guess.y)),
+                guess => {
+                    guesses += 1;
+                    guess.map(|guess| (guess.x, guess.y))
+                }
+            };
+            let Some((x, y)) = cell.or_else(|| first_hidden(&board)) else {
+                break;
+            };
+            safe = board.reveal(x, y);
+        } else {
+            for (x, y) in deduced {
+                board.reveal(x, y);
+            }
+        }
+    }
+    Outcome {
+        won: safe && board.is_win(),
+        guesses,
+    }
+}
+
+/// The first hidden cell in row-major order, for when the board is too tangled to weigh.
+fn first_hidden(board: &Board) -> Option<(usize, usize)> {
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .find(|&(x, y)| board.in_play(x, y) && board.cell_at(x, y) == CellState::Hidden)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(width: usize, height: usize, mines: &[(usize, usize)]) -> Board {
+        let mut board = Board::new(width, height, mines.len());
+        board.place_mines_at(mines);
+        board
+    }
+
+    #[test]
+    fn the_solver_guesses_only_when_stuck() {
+        assert_eq!(
+            play(board(3, 1, &[(2, 0)]), (0, 0)),
+            Outcome {
+                won: true,
+                guesses: 0
+            }
+        );
+        // The mine is at (0,0) or (0,1); the guess goes to the first, which is safe.
+        assert_eq!(
+            play(board(3, 2, &[(0, 1)]), (2, 0)),
+            Outcome {
+                won: true,
+                guesses: 1
+            }
+        );
+        assert_eq!(
+            play(board(3, 2, &[(0, 0)]), (2, 0)),
+            Outcome {
+                won: false,
+                guesses: 1
+            }
+        );
+    }
+
+    #[test]
+    fn the_summary_adds_games_up() {
+        let mut summary = Summary {
+            size: (3, 2, 1),
+            ..Summary::default()
+        };
+        let won = board(3, 2, &[(0, 1)]);
+        summary.add(&won, play(won.clone(), (2, 0)));
+        let lost = board(3, 2, &[(0, 0)]);
+        summary.add(&lost, play(lost.clone(), (2, 0)));
+        assert_eq!((summary.wins, summary.guessed, summary.bbbv), (1, 2, 4));
+        assert_eq!(
+            summary.to_string(),
+            "2 games on 3x2 with 1 mines\n\
+             Won:         1 (50.0%)\n\
+             Average 3BV: 2.0\n\
+             Guesses:     1.00 per game; 2 games (100.0%) needed one"
+        );
+    }
+}
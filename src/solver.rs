@@ -0,0 +1,651 @@
+use crate::board::{Board, CellState};
+use crate::error::{Error, Result};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// What the solver concluded about a single hidden cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deduction {
+    /// The cell can be revealed safely.
+    Safe(usize, usize),
+    /// The cell is certainly a mine.
+    Mine(usize, usize),
+}
+
+/// Cells the solver could determine from the visible board alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deductions {
+    /// Cells that are certainly safe, in row-major order.
+    pub safe: Vec<(usize, usize)>,
+    /// Cells that are certainly mines, in row-major order.
+    pub mines: Vec<(usize, usize)>,
+}
+
+impl Deductions {
+    /// Whether nothing could be determined (only guesses remain).
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+
+    /// A single deduction to suggest to the player, preferring safe cells.
+    pub fn hint(&self) -> Option<Deduction> {
+        self.safe
+            .first()
+            .map(|&(x, y)| Deduction::Safe(x, y))
+            .or_else(|| self.mines.first().map(|&(x, y)| Deduction::Mine(x, y)))
+    }
+}
+
+/// Why a game was lost, judged from the board as it stood before the losing move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loss {
+    /// The numbers proved no hidden cell safe, so any move was a guess.
+    ForcedGuess,
+    /// A safe cell could have been deduced, so the loss could have been avoided.
+    Misplay,
+}
+
+impl Loss {
+    /// Judge a loss from `before`, the board as the player saw it just before the losing
+    /// move: forced if [`deduce`] finds no safe cell on it.
+    pub fn judge(before: &Board) -> Self {
+        if deduce(before).safe.is_empty() {
+            Self::ForcedGuess
+        } else {
+            Self::Misplay
+        }
+    }
+
+    /// `"forced-guess"` or `"misplay"`, as in the game log.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::ForcedGuess => "forced-guess",
+            Self::Misplay => "misplay",
+        }
+    }
+
+    /// The loss in a few words, for the game-over message.
+    pub const fn describe(self) -> &'static str {
+        match self {
+            Self::ForcedGuess => "a forced guess",
+            Self::Misplay => "a misplay",
+        }
+    }
+}
+
+/// How hard a board is to clear, as [`rate`] judges it: the hardest step the solver needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rating {
+    /// Single-number rules clear it in a few rounds.
+    Trivial,
+    /// Single-number rules clear it.
+    Easy,
+    /// Clearing it takes the subset rule between numbers, or counting the mines left.
+    Hard,
+    /// Clearing it takes at least one guess.
+    Guessy,
+}
+
+impl Rating {
+    /// `"trivial"`, `"easy"`, `"hard"` or `"guessy"`, as in the game log.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Trivial => "trivial",
+            Self::Easy => "easy",
+            Self::Hard => "hard",
+            Self::Guessy => "guessy",
+        }
+    }
+}
+
+impl FromStr for Rating {
+    type Err = Error;
+
+    /// Parse a rating name, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "trivial" => Ok(Self::Trivial),
+            "easy" => Ok(Self::Easy),
+            "hard" => Ok(Self::Hard),
+            "guessy" => Ok(Self::Guessy),
+            _ => Err(Error::Parse(format!(
+                "unknown rating '{s}' (expected trivial, easy, hard or guessy)"
+            ))),
+        }
+    }
+}
+
+/// Rounds of deductions a [`Rating::Trivial`] board is cleared in.
+const TRIVIAL_ROUNDS: usize = 3;
+/// Largest board [`rate`] takes on, in cells; the solver would hold up the end of the game on
+/// bigger ones.
+pub const MAX_RATED_CELLS: usize = 1_000;
+
+/// Rate how hard `board` is to clear from scratch, whatever has been played on it, starting
+/// from its largest opening. `None` before the mines are placed or on boards too big to rate
+/// quickly.
+pub fn rate(board: &Board) -> Option<Rating> {
+    let start = board
+        .largest_opening()
+        .or_else(|| safe_cell(&board.covered()))?;
+    rate_from(board, start)
+}
+
+/// Rate how hard `board` is to clear from scratch when `start` is revealed first: reveal what
+/// [`deduce`] proves safe round after round, falling back on the count of mines left
+/// ([`best_guess`] finding a cell that cannot be a mine) and, when that fails too, on a guess.
+/// `None` before the mines are placed, when `start` is a mine, or on boards too big to rate
+/// quickly.
+pub fn rate_from(board: &Board, start: (usize, usize)) -> Option<Rating> {
+    if !board.mines_placed() || board.cells() > MAX_RATED_CELLS {
+        return None;
+    }
+    let mut board = board.covered();
+    if !board.reveal(start.0, start.1) {
+        return None;
+    }
+    let (mut rounds, mut hard, mut guesses) = (0, false, 0);
+    while !board.is_win() {
+        let (deductions, subsets) = deduce_noting_subsets(&board);
+        let next = if deductions.safe.is_empty() {
+            match best_guess(&board) {
+                Some(guess) if guess.mine_chance < 1e-9 => {
+                    hard = true;
+                    vec![(guess.x, guess.y)]
+                }
+                guess => {
+                    guesses += 1;
+                    // Played as if the guess came off, or on the first safe cell if it would not.
+                    let cell = guess
+                        .map(|guess| (guess.x, guess.y))
+                        .filter(|&(x, y)| !board.is_mine(x, y))
+                        .or_else(|| safe_cell(&board));
+                    vec![cell?]
+                }
+            }
+        } else {
+            rounds += 1;
+            hard |= subsets;
+            deductions.safe
+        };
+        for (x, y) in next {
+            board.reveal(x, y);
+        }
+    }
+    Some(if guesses > 0 {
+        Rating::Guessy
+    } else if hard {
+        Rating::Hard
+    } else if rounds <= TRIVIAL_ROUNDS {
+        Rating::Trivial
+    } else {
+        Rating::Easy
+    })
+}
+
+/// The first hidden cell in row-major order that is not a mine.
+fn safe_cell(board: &Board) -> Option<(usize, usize)> {
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .find(|&(x, y)| {
+            board.in_play(x, y) && board.cell_at(x, y) == CellState::Hidden && !board.is_mine(x, y)
+        })
+}
+
+/// A hidden cell to try when nothing can be deduced, and how likely it is to hide a mine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guess {
+    /// Column of the cell.
+    pub x: usize,
+    /// Row of the cell.
+    pub y: usize,
+    /// Chance that the cell is a mine, from 0 to 1.
+    pub mine_chance: f64,
+}
+
+/// Search steps [`best_guess`] may take before giving up on a tangled board edge.
+const GUESS_STEPS: usize = 1_000_000;
+
+/// A revealed number and the undetermined cells around it: exactly `mines` of `cells` are mines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constraint {
+    cells: BTreeSet<usize>,
+    mines: usize,
+}
+
+/// Deduce safe cells and mines using only what the player can see.
+///
+/// Flags are ignored, since the player may have placed them wrongly. The solver applies
+/// single-number rules (all remaining neighbors are safe / all are mines) and the subset
+/// rule between pairs of numbers, repeating until nothing new is found.
+pub fn deduce(board: &Board) -> Deductions {
+    deduce_noting_subsets(board).0
+}
+
+/// [`deduce`], and whether the subset rule found any of it.
+fn deduce_noting_subsets(board: &Board) -> (Deductions, bool) {
+    let width = board.width();
+    let mut mines: BTreeSet<usize> = BTreeSet::new();
+    let mut safe: BTreeSet<usize> = BTreeSet::new();
+    let mut subsets = false;
+
+    loop {
+        let constraints = constraints(board, &mines, &safe);
+        let mut progress = false;
+
+        for c in &constraints {
+            if c.mines == 0 {
+                progress |= extend(&mut safe, &c.cells);
+            } else if c.mines == c.cells.len() {
+                progress |= extend(&mut mines, &c.cells);
+            }
+        }
+
+        if !progress {
+            for a in &constraints {
+                for b in &constraints {
+                    if a.cells.len() >= b.cells.len() || !a.cells.is_subset(&b.cells) {
+                        continue;
+                    }
+                    let rest: BTreeSet<usize> = b.cells.difference(&a.cells).copied().collect();
+                    let rest_mines = b.mines.saturating_sub(a.mines);
+                    if rest_mines == 0 {
+                        progress |= extend(&mut safe, &rest);
+                    } else if rest_mines == rest.len() {
+                        progress |= extend(&mut mines, &rest);
+                    }
+                }
+            }
+            subsets |= progress;
+        }
+
+        if !progress {
+            break;
+        }
+    }
+
+    let coords = |i: usize| (i % width, i / width);
+    let deductions = Deductions {
+        safe: safe.into_iter().map(coords).collect(),
+        mines: mines.into_iter().map(coords).collect(),
+    };
+    (deductions, subsets)
+}
+
+/// Build one constraint per revealed number that still touches undetermined cells.
+fn constraints(board: &Board, mines: &BTreeSet<usize>, safe: &BTreeSet<usize>) -> Vec<Constraint> {
+    let width = board.width();
+    let mut out = Vec::new();
+    for y in 0..board.height() {
+        for x in 0..width {
+            let CellState::Revealed(n) = board.cell_at(x, y) else {
+                continue;
+            };
+            let mut cells = BTreeSet::new();
+            let mut known_mines = 0;
+            for (nx, ny) in board.neighbors(x, y) {
+                let i = ny * width + nx;
+                if matches!(board.cell_at(nx, ny), CellState::Revealed(_)) || safe.contains(&i) {
+                    continue;
+                }
+                if mines.contains(&i) {
+                    known_mines += 1;
+                } else {
+                    cells.insert(i);
+                }
+            }
+            if !cells.is_empty() {
+                out.push(Constraint {
+                    cells,
+                    mines: usize::from(n).saturating_sub(known_mines),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// The hidden cell least likely to be a mine, for when [`deduce`] finds nothing certain.
+///
+/// Every arrangement of mines around the revealed numbers is counted, each weighted by the
+/// ways to place the remaining mines among the hidden cells no number touches. Flags are
+/// ignored, as in [`deduce`]. Ties go to the first cell in row-major order. `None` if no cell
+/// is hidden or the revealed edge is too tangled to count in reasonable time.
+pub fn best_guess(board: &Board) -> Option<Guess> {
+    let width = board.width();
+    mine_chances(board)?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, chance)| chance.map(|chance| (i, chance)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, mine_chance)| Guess {
+            x: i % width,
+            y: i / width,
+            mine_chance,
+        })
+}
+
+/// The chance of a mine for every cell in row-major order, `None` for revealed cells.
+fn mine_chances(board: &Board) -> Option<Vec<Option<f64>>> {
+    let width = board.width();
+    let total = width * board.height();
+    let constraints = constraints(board, &BTreeSet::new(), &BTreeSet::new());
+    let mut steps = GUESS_STEPS;
+    let groups = components(&constraints)
+        .into_iter()
+        .map(|group| Group::count(&group, &mut steps))
+        .collect::<Option<Vec<_>>>()?;
+    let hidden: Vec<bool> = (0..total)
+        .map(|i| (i % width, i / width))
+        .map(|(x, y)| board.in_play(x, y) && !matches!(board.cell_at(x, y), CellState::Revealed(_)))
+        .collect();
+    let edge = groups.iter().map(|group| group.cells.len()).sum::<usize>();
+    let interior = hidden.iter().filter(|&&h| h).count() - edge;
+    let mines = board.mines();
+
+    // Relative weight of the arrangements that put `k` mines on the edge.
+    let weights: Vec<f64> = {
+        let logs: Vec<Option<f64>> = (0..=edge)
+            .map(|k| {
+                let rest = mines.checked_sub(k).filter(|&rest| rest <= interior)?;
+                Some(ln_choose(interior, rest))
+            })
+            .collect();
+        let top = logs
+            .iter()
+            .flatten()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        logs.iter()
+            .map(|log| log.map_or(0.0, |log| (log - top).exp()))
+            .collect()
+    };
+    let all = groups
+        .iter()
+        .fold(vec![1.0], |ways, group| convolve(&ways, &group.ways()));
+    let weigh = |ways: &[f64], offset: usize| -> f64 {
+        ways.iter()
+            .enumerate()
+            .map(|(k, w)| w * weights.get(k + offset).copied().unwrap_or(0.0))
+            .sum()
+    };
+    let norm = weigh(&all, 0);
+    if norm <= 0.0 || !norm.is_finite() {
+        return None;
+    }
+
+    let mut chances: Vec<Option<f64>> = vec![None; total];
+    if interior > 0 {
+        let expected: f64 = all
+            .iter()
+            .enumerate()
+            .map(|(k, w)| {
+                let rest = mines.saturating_sub(k).min(interior);
+                w * weights.get(k).copied().unwrap_or(0.0) * ratio(rest, interior)
+            })
+            .sum();
+        let chance = expected / norm;
+        for (i, _) in hidden.iter().enumerate().filter(|&(_, &h)| h) {
+            chances[i] = Some(chance);
+        }
+    }
+    for (j, group) in groups.iter().enumerate() {
+        let others = groups
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != j)
+            .fold(vec![1.0], |ways, (_, other)| convolve(&ways, &other.ways()));
+        for (c, &cell) in group.cells.iter().enumerate() {
+            let weight: f64 = group
+                .by_mines
+                .iter()
+                .enumerate()
+                .map(|(k, (_, per_cell))| per_cell[c] * weigh(&others, k))
+                .sum();
+            chances[cell] = Some(weight / norm);
+        }
+    }
+    Some(chances)
+}
+
+/// One connected part of the revealed edge: cells sharing numbers, directly or through others.
+struct Group {
+    /// The cells, as board indices.
+    cells: Vec<usize>,
+    /// For each mine count `k`, the arrangements with `k` mines and, per cell, how many of
+    /// them mine it.
+    by_mines: Vec<(f64, Vec<f64>)>,
+}
+
+impl Group {
+    /// Count every arrangement of `constraints`' cells that satisfies them all, spending at
+    /// most `steps` search steps (shared between groups).
+    fn count(constraints: &[&Constraint], steps: &mut usize) -> Option<Self> {
+        let mut cells: Vec<usize> = Vec::new();
+        for constraint in constraints {
+            for &cell in &constraint.cells {
+                if !cells.contains(&cell) {
+                    cells.push(cell);
+                }
+            }
+        }
+        let mut search = Search {
+            of_cell: cells
+                .iter()
+                .map(|cell| {
+                    (0..constraints.len())
+                        .filter(|&c| constraints[c].cells.contains(cell))
+                        .collect()
+                })
+                .collect(),
+            need: constraints.iter().map(|c| c.mines).collect(),
+            placed: vec![0; constraints.len()],
+            open: constraints.iter().map(|c| c.cells.len()).collect(),
+            mined: vec![false; cells.len()],
+            by_mines: vec![(0.0, vec![0.0; cells.len()]); cells.len() + 1],
+            steps,
+        };
+        search.run(0)?;
+        let by_mines = search.by_mines;
+        Some(Self { cells, by_mines })
+    }
+
+    /// Arrangements per mine count.
+    fn ways(&self) -> Vec<f64> {
+        self.by_mines.iter().map(|&(ways, _)| ways).collect()
+    }
+}
+
+/// Backtracking over one group's cells, mine or not, pruned by the numbers around them.
+struct Search<'a> {
+    /// Constraints each cell belongs to.
+    of_cell: Vec<Vec<usize>>,
+    /// Mines each constraint needs.
+    need: Vec<usize>,
+    /// Mines placed so far in each constraint.
+    placed: Vec<usize>,
+    /// Cells of each constraint not yet decided.
+    open: Vec<usize>,
+    /// The arrangement being built.
+    mined: Vec<bool>,
+    by_mines: Vec<(f64, Vec<f64>)>,
+    steps: &'a mut usize,
+}
+
+impl Search<'_> {
+    fn run(&mut self, cell: usize) -> Option<()> {
+        *self.steps = self.steps.checked_sub(1)?;
+        if cell == self.mined.len() {
+            let count = self.mined.iter().filter(|&&m| m).count();
+            let (ways, per_cell) = &mut self.by_mines[count];
+            *ways += 1.0;
+            for (tally, _) in per_cell.iter_mut().zip(&self.mined).filter(|&(_, &m)| m) {
+                *tally += 1.0;
+            }
+            return Some(());
+        }
+        for mine in [false, true] {
+            let fits = self.of_cell[cell].iter().all(|&c| {
+                let placed = self.placed[c] + usize::from(mine);
+                placed <= self.need[c] && placed + self.open[c] > self.need[c]
+            });
+            if !fits {
+                continue;
+            }
+            for &c in &self.of_cell[cell] {
+                self.placed[c] += usize::from(mine);
+                self.open[c] -= 1;
+            }
+            self.mined[cell] = mine;
+            let done = self.run(cell + 1);
+            for &c in &self.of_cell[cell] {
+                self.placed[c] -= usize::from(mine);
+                self.open[c] += 1;
+            }
+            done?;
+        }
+        self.mined[cell] = false;
+        Some(())
+    }
+}
+
+/// Split constraints into groups that share no cells.
+fn components(constraints: &[Constraint]) -> Vec<Vec<&Constraint>> {
+    let mut groups: Vec<Vec<&Constraint>> = Vec::new();
+    for constraint in constraints {
+        let (touching, rest): (Vec<_>, Vec<_>) = groups.into_iter().partition(|group| {
+            group
+                .iter()
+                .any(|other| !other.cells.is_disjoint(&constraint.cells))
+        });
+        let mut merged: Vec<&Constraint> = touching.into_iter().flatten().collect();
+        merged.push(constraint);
+        groups = rest;
+        groups.push(merged);
+    }
+    groups
+}
+
+/// The ways to pick from two groups together, by total mine count.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            out[i + j] += x * y;
+        }
+    }
+    out
+}
+
+/// The natural log of `n` choose `k`.
+#[allow(clippy::cast_precision_loss)] // board sizes are far below 2^52
+fn ln_choose(n: usize, k: usize) -> f64 {
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+/// `part / whole` as a fraction.
+#[allow(clippy::cast_precision_loss)] // board sizes are far below 2^52
+fn ratio(part: usize, whole: usize) -> f64 {
+    part as f64 / whole as f64
+}
+
+fn extend(set: &mut BTreeSet<usize>, cells: &BTreeSet<usize>) -> bool {
+    let before = set.len();
+    set.extend(cells.iter().copied());
+    set.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_number_rules() {
+        // Mine in the corner of a 3x3; revealing (0,0) floods up to the 1s around it.
+        let mut b = Board::new(3, 3, 1);
+        b.place_mines_at(&[(2, 2)]);
+        b.reveal(0, 0);
+        let d = deduce(&b);
+        assert_eq!(d.mines, vec![(2, 2)]);
+        assert!(d.safe.is_empty());
+        assert_eq!(d.hint(), Some(Deduction::Mine(2, 2)));
+    }
+
+    #[test]
+    fn subset_rule_finds_safe_cell() {
+        // Mine at (0,1) of a 3x2 board; (0,0) and (1,0) both show 1. The 1 at (0,0) only
+        // touches (0,1)/(1,1), so the extra cells around (1,0) must be safe.
+        let mut b = Board::new(3, 2, 1);
+        b.place_mines_at(&[(0, 1)]);
+        b.reveal(0, 0);
+        b.reveal(1, 0);
+        let d = deduce(&b);
+        assert_eq!(d.safe, vec![(2, 0), (2, 1)]);
+        assert!(d.mines.is_empty());
+    }
+
+    #[test]
+    fn nothing_to_deduce_before_first_reveal() {
+        let b = Board::new(4, 4, 3);
+        assert!(deduce(&b).is_empty());
+    }
+
+    #[test]
+    fn boards_are_rated_by_the_hardest_step() {
+        let rated = |width, height, mines: &[(usize, usize)]| {
+            let mut b = Board::new(width, height, mines.len());
+            b.place_mines_at(mines);
+            rate(&b)
+        };
+        assert_eq!(rate(&Board::new(3, 1, 1)), None, "no mines yet");
+        assert_eq!(rated(3, 1, &[(2, 0)]), Some(Rating::Trivial));
+        assert_eq!(
+            rated(5, 4, &[(0, 2), (2, 2), (3, 2), (4, 2)]),
+            Some(Rating::Easy)
+        );
+        assert_eq!(
+            rated(5, 4, &[(0, 0), (3, 2), (4, 2), (1, 3)]),
+            Some(Rating::Hard)
+        );
+        // The mine is at (0,0) or (0,1) with nothing to tell which.
+        assert_eq!(rated(3, 2, &[(0, 1)]), Some(Rating::Guessy));
+
+        let mut played = Board::new(3, 2, 1);
+        played.place_mines_at(&[(0, 1)]);
+        played.reveal(0, 0);
+        played.toggle_flag(0, 1);
+        assert_eq!(rate(&played), Some(Rating::Guessy), "rated from scratch");
+    }
+
+    #[test]
+    fn best_guess_counts_the_mines_left() {
+        // The 1 at (1,0) holds the only mine, so the cells beyond it are safe even though no
+        // number touches them.
+        let mut b = Board::new(6, 1, 1);
+        b.place_mines_at(&[(0, 0)]);
+        b.reveal(1, 0);
+        assert!(deduce(&b).is_empty());
+        let guess = best_guess(&b).expect("hidden cells");
+        assert_eq!((guess.x, guess.y), (3, 0));
+        assert!(guess.mine_chance.abs() < 1e-9);
+        let chances = mine_chances(&b).expect("small board");
+        assert!((chances[0].expect("hidden") - 0.5).abs() < 1e-9);
+        assert_eq!(chances[1], None);
+    }
+
+    #[test]
+    fn mine_chances_add_up_to_the_mines() {
+        let mut b = Board::new(9, 9, 10).with_seed(11);
+        b.reveal(4, 4);
+        let chances = mine_chances(&b).expect("small board");
+        let total: f64 = chances.iter().flatten().sum();
+        assert!((total - 10.0).abs() < 1e-6, "{total}");
+        let d = deduce(&b);
+        for &(x, y) in &d.safe {
+            assert!(chances[y * 9 + x].expect("hidden") < 1e-9);
+        }
+        for &(x, y) in &d.mines {
+            assert!(chances[y * 9 + x].expect("hidden") > 1.0 - 1e-9);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::config;
+use minesweeper::app::Status;
+use minesweeper::events::Event as GameEvent;
+use std::io::{self, Write};
+
+/// Something that happened in the game worth a sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Cells were revealed.
+    Reveal,
+    /// A flag was placed or removed.
+    Flag,
+    /// A mine went off.
+    Explosion,
+    /// The board was cleared.
+    Win,
+}
+
+impl Event {
+    /// The sound for a game event, if it makes one. Undo and new games make none.
+    const fn of(event: &GameEvent) -> Option<Self> {
+        match event {
+            GameEvent::CellsRevealed { .. } => Some(Self::Reveal),
+            GameEvent::FlagToggled { .. } => Some(Self::Flag),
+            GameEvent::GameEnded {
+                status: Status::Win,
+                ..
+            } => Some(Self::Win),
+            GameEvent::GameEnded { .. } => Some(Self::Explosion),
+            GameEvent::Undone | GameEvent::NewGame => None,
+        }
+    }
+}
+
+/// Plays a sound for each game event switched on in the `[sound]` settings: the terminal
+/// bell, or with the `audio` feature a short synthesized effect per event.
+pub struct Sounds {
+    settings: config::Sound,
+    #[cfg(feature = "audio")]
+    audio: Option<audio::Player>,
+}
+
+impl Sounds {
+    /// Sounds for the events `settings` switches on. With the `audio` feature this opens the
+    /// default output device, falling back to the bell if there is none; call it before the
+    /// terminal is taken over, as audio backends may print to stderr.
+    pub fn new(settings: config::Sound) -> Self {
+        Self {
+            settings,
+            #[cfg(feature = "audio")]
+            audio: audio::Player::open(),
+        }
+    }
+
+    /// Play a sound for the game `events` taken together; the bell is written to `out`.
+    ///
+    /// # Errors
+    /// Fails if writing the bell fails.
+    pub fn observe(&self, events: &[GameEvent], out: &mut impl Write) -> io::Result<()> {
+        match Self::event(events) {
+            Some(event) if self.settings.plays(event) => {
+                #[cfg(feature = "audio")]
+                if let Some(player) = &self.audio {
+                    player.play(event);
+                    return Ok(());
+                }
+                write!(out, "\x07")?;
+                out.flush()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The most important sound among `events`: the end of a game drowns out the move that
+    /// ended it, and a reveal a flag.
+    fn event(events: &[GameEvent]) -> Option<Event> {
+        events
+            .iter()
+            .filter_map(Event::of)
+            .max_by_key(|event| match event {
+                Event::Flag => 0,
+                Event::Reveal => 1,
+                Event::Explosion | Event::Win => 2,
+            })
+    }
+}
+
+/// Synthesized effects through the default audio device (`audio` feature).
+#[cfg(feature = "audio")]
+mod audio {
+    use super::Event;
+    use rodio::source::{SineWave, Source, SquareWave};
+    use rodio::{OutputStream, OutputStreamBuilder};
+    use std::time::Duration;
+
+    /// An open audio output.
+    pub struct Player {
+        stream: OutputStream,
+    }
+
+    impl Player {
+        /// Open the default output device, if there is one.
+        pub fn open() -> Option<Self> {
+            let mut stream = OutputStreamBuilder::open_default_stream().ok()?;
+            stream.log_on_drop(false);
+            Some(Self { stream })
+        }
+
+        /// Start the effect for `event` without waiting for it to finish.
+        pub fn play(&self, event: Event) {
+            let mixer = self.stream.mixer();
+            let ms = Duration::from_millis;
+            match event {
+                Event::Reveal => mixer.add(SineWave::new(880.0).take_duration(ms(40)).amplify(0.2)),
+                Event::Flag => mixer.add(SineWave::new(660.0).take_duration(ms(60)).amplify(0.2)),
+                Event::Explosion => mixer.add(
+                    SquareWave::new(70.0)
+                        .take_duration(ms(450))
+                        .fade_out(ms(450))
+                        .amplify(0.3),
+                ),
+                Event::Win => {
+                    // A rising C major arpeggio.
+                    for (i, freq) in [523.25, 659.25, 783.99, 1046.5].into_iter().enumerate() {
+                        let start = ms(120 * u64::try_from(i).unwrap_or_default());
+                        mixer.add(
+                            SineWave::new(freq)
+                                .take_duration(ms(150))
+                                .amplify(0.2)
+                                .delay(start),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::{Action, AppState, Cursor};
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+
+    #[test]
+    fn events_follow_the_game() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(1, 0)]);
+        assert_eq!(Sounds::event(&app.events.drain()), None);
+        app.handle_action(Action::Reveal);
+        assert_eq!(Sounds::event(&app.events.drain()), Some(Event::Reveal));
+        app.cursor = Cursor::new(2, 0);
+        app.handle_action(Action::ToggleFlag);
+        assert_eq!(Sounds::event(&app.events.drain()), Some(Event::Flag));
+        assert_eq!(Sounds::event(&app.events.drain()), None);
+        app.handle_action(Action::ToggleFlag);
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        assert_eq!(
+            Sounds::event(&app.events.drain()),
+            Some(Event::Explosion),
+            "over the flag before it"
+        );
+        app.handle_action(Action::Undo);
+        assert_eq!(Sounds::event(&app.events.drain()), None);
+    }
+
+    #[test]
+    fn only_switched_on_events_ring_the_bell() {
+        let sounds = Sounds::new(config::Sound::default());
+        #[cfg(feature = "audio")]
+        let sounds = Sounds {
+            audio: None,
+            ..sounds
+        };
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(1, 0)]);
+        let mut out = Vec::new();
+        app.handle_action(Action::Reveal);
+        sounds
+            .observe(&app.events.drain(), &mut out)
+            .expect("in memory");
+        assert!(out.is_empty(), "reveals are silent by default");
+        app.cursor = Cursor::new(1, 0);
+        app.handle_action(Action::Reveal);
+        sounds
+            .observe(&app.events.drain(), &mut out)
+            .expect("in memory");
+        assert_eq!(out, b"\x07");
+    }
+}
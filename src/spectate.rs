@@ -0,0 +1,199 @@
+use minesweeper::app::AppState;
+use minesweeper::error::{self, Error};
+use minesweeper::render::{Renderer, rows, status_name};
+use serde_json::{Value, json};
+use std::convert::Infallible;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// How long a send may block before a slow spectator is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// A connected spectator, and whether it has had the full board yet.
+struct Client {
+    socket: WebSocket<TcpStream>,
+    synced: bool,
+}
+
+/// Broadcasts the game to WebSocket spectators.
+///
+/// Each new spectator first gets a `board` message with the whole board; after that, every
+/// change is sent as a `delta` listing only the cells that changed. Both carry the status,
+/// the timer and the remaining-mines count. Cells use the headless notation: `#` hidden,
+/// `F` flagged, `.` empty, digits, and `*` for mines once the game is over.
+pub struct Server {
+    clients: Arc<Mutex<Vec<Client>>>,
+    /// The board rows and header last broadcast.
+    last: Option<(Vec<String>, Value)>,
+}
+
+impl Server {
+    /// Listen for spectators on `port` on all interfaces.
+    ///
+    /// # Errors
+    /// Fails if the port cannot be bound.
+    pub fn listen(port: u16) -> error::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| {
+            Error::Network(format!("cannot listen for spectators on port {port}: {e}"))
+        })?;
+        Ok(Self::new(listener))
+    }
+
+    /// Accept spectators from `listener` on a background thread.
+    pub fn new(listener: TcpListener) -> Self {
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&clients);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                // The handshake blocks, so a stalled client cannot hold up the game.
+                if let Ok(socket) = tungstenite::accept(stream) {
+                    accepted
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(Client {
+                            socket,
+                            synced: false,
+                        });
+                }
+            }
+        });
+        Self {
+            clients,
+            last: None,
+        }
+    }
+}
+
+impl Renderer for Server {
+    type Error = Infallible;
+
+    /// Send spectators whatever changed since the last call. Cheap when nothing did; a
+    /// spectator that cannot keep up is dropped rather than reported.
+    fn render(&mut self, app: &AppState) -> Result<(), Infallible> {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        let rows = rows(app);
+        let header = json!({
+            "status": status_name(app.status),
+            "time": app.elapsed_secs(),
+            "mines_left": app.board.mines().saturating_sub(app.flags_placed),
+        });
+        let changed = self.last.as_ref() != Some(&(rows.clone(), header.clone()));
+        if !changed && clients.iter().all(|c| c.synced) {
+            return Ok(());
+        }
+
+        let full = frame("board", &header, |f| {
+            f.insert("width".into(), json!(app.board.width()));
+            f.insert("height".into(), json!(app.board.height()));
+            f.insert("rows".into(), json!(rows));
+        });
+        let delta = match &self.last {
+            Some((old, _)) if changed && old.len() == rows.len() => {
+                Some(frame("delta", &header, |f| {
+                    f.insert("cells".into(), json!(diff(old, &rows)));
+                }))
+            }
+            // A new board size (restart at another difficulty) needs everything again.
+            Some(_) if changed => {
+                clients.iter_mut().for_each(|c| c.synced = false);
+                None
+            }
+            _ => None,
+        };
+        clients.retain_mut(|client| {
+            let message = match (&delta, client.synced) {
+                (_, false) => &full,
+                (Some(delta), true) => delta,
+                (None, true) => return true,
+            };
+            client.synced = true;
+            client
+                .socket
+                .send(Message::text(message.to_string()))
+                .is_ok()
+        });
+        self.last = Some((rows, header));
+        Ok(())
+    }
+}
+
+/// A message of `kind` with the shared header fields plus whatever `fill` adds.
+fn frame(
+    kind: &str,
+    header: &Value,
+    fill: impl FnOnce(&mut serde_json::Map<String, Value>),
+) -> Value {
+    let mut fields = header.as_object().cloned().unwrap_or_default();
+    fields.insert("type".into(), json!(kind));
+    fill(&mut fields);
+    Value::Object(fields)
+}
+
+/// Cells that differ between two boards of the same size, as `[x, y, "c"]`.
+fn diff(old: &[String], new: &[String]) -> Vec<(usize, usize, String)> {
+    old.iter()
+        .zip(new)
+        .enumerate()
+        .flat_map(|(y, (a, b))| {
+            a.chars()
+                .zip(b.chars())
+                .enumerate()
+                .filter(|(_, (a, b))| a != b)
+                .map(move |(x, (_, b))| (x, y, b.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::Action;
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+
+    #[test]
+    fn diff_lists_changed_cells() {
+        let old = ["##".to_string(), "##".to_string()];
+        let new = ["#1".to_string(), "F#".to_string()];
+        assert_eq!(
+            diff(&old, &new),
+            [(1, 0, "1".to_string()), (0, 1, "F".to_string())]
+        );
+    }
+
+    #[test]
+    fn spectators_get_the_board_then_deltas() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 3, 1);
+        app.board.place_mines_at(&[(2, 2)]);
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("address");
+        let mut server = Server::new(listener);
+        let stream = TcpStream::connect(addr).expect("connect");
+        let (mut socket, _) =
+            tungstenite::client(format!("ws://{addr}"), stream).expect("handshake");
+        // Wait for the accept thread to register the spectator.
+        while server.clients.lock().expect("lock").is_empty() {
+            thread::yield_now();
+        }
+
+        server.render(&app).expect("infallible");
+        app.handle_action(Action::ToggleFlag);
+        server.render(&app).expect("infallible");
+        let mut next = || -> Value {
+            let text = socket.read().expect("message").into_text().expect("text");
+            serde_json::from_str(&text).expect("json")
+        };
+        let board = next();
+        assert_eq!(board["type"], "board");
+        assert_eq!(board["rows"][0], "###");
+        let delta = next();
+        assert_eq!(delta["type"], "delta");
+        assert_eq!(delta["cells"], json!([[0, 0, "F"]]));
+        assert_eq!(delta["mines_left"], 0);
+    }
+}
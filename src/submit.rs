@@ -0,0 +1,307 @@
+use crate::config;
+use minesweeper::app::{AppState, Status};
+use minesweeper::events::Event;
+use minesweeper::movelog::Move;
+use serde_json::{Value, json};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait on the server at each step of a request.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Wait before the first retry, doubled for each one after up to [`MAX_BACKOFF`].
+const BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+
+/// Where wins are sent: an `http://host[:port]/path` URL taken apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Endpoint {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl Endpoint {
+    fn parse(url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("'{url}' is not an http:// URL (https is not supported)"))?;
+        let (authority, path) = match rest.split_once('/') {
+            Some((authority, path)) => (authority, format!("/{path}")),
+            None => (rest, "/".to_string()),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse().map_err(|_| format!("bad port in '{url}'"))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("no host in '{url}'"));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path,
+        })
+    }
+}
+
+/// Sends wins on board codes to a leaderboard server (`[submit]` settings), for it to verify
+/// by replaying the moves, as `serve` does. Only games started from a code can be verified,
+/// so other wins are not sent.
+///
+/// Each win is sent from a thread of its own, retried while the server cannot be reached or
+/// fails, and what came of it is handed back as a notice for the game-over screen.
+pub struct Submitter {
+    settings: config::Submit,
+    sender: Sender<String>,
+    results: Receiver<String>,
+}
+
+impl Submitter {
+    /// Send wins as `settings` say.
+    pub fn new(settings: config::Submit) -> Self {
+        let (sender, results) = mpsc::channel();
+        Self {
+            settings,
+            sender,
+            results,
+        }
+    }
+
+    /// Start sending the game if `events`, just taken from `app`, end it in a win worth
+    /// sending, and return a notice for the player: that it is being sent, why it cannot be,
+    /// or what came of one sent earlier.
+    pub fn observe(&mut self, app: &AppState, events: &[Event]) -> Option<String> {
+        let won = events.iter().any(|event| {
+            matches!(
+                event,
+                Event::GameEnded {
+                    status: Status::Win,
+                    ..
+                }
+            )
+        });
+        if !won || !self.settings.enabled {
+            return self.results.try_recv().ok();
+        }
+        let name = self
+            .settings
+            .name
+            .clone()
+            .unwrap_or_else(|| "anonymous".to_string());
+        let body = match submission(app, &name)? {
+            Ok(body) => body,
+            Err(reason) => return Some(format!("Not submitted: {reason}")),
+        };
+        let endpoint = match Endpoint::parse(&self.settings.url) {
+            Ok(endpoint) => endpoint,
+            Err(e) => return Some(format!("Not submitted: {e}")),
+        };
+        let (retries, sender) = (self.settings.retries, self.sender.clone());
+        thread::spawn(move || {
+            let _ = sender.send(submit(&endpoint, &body.to_string(), retries));
+        });
+        Some("Submitting the win to the leaderboard...".to_string())
+    }
+}
+
+/// What `serve` takes for the game `app` just won: the board code and every move with its
+/// time. `None` for games not started from a code; an error for a win with a move the server
+/// cannot replay.
+fn submission(app: &AppState, name: &str) -> Option<Result<Value, &'static str>> {
+    let code = app.code?;
+    let moves: Option<Vec<Value>> = app
+        .moves
+        .entries()
+        .iter()
+        .filter(|entry| !matches!(entry.action, Move::Peeked(_)))
+        .map(|entry| {
+            let command = match entry.action {
+                Move::Revealed { .. } => "reveal",
+                Move::Chorded { .. } => "chord",
+                Move::Flagged | Move::Unflagged => "flag",
+                _ => return None,
+            };
+            Some(json!({
+                "at": entry.at.as_secs_f64(),
+                "move": format!("{command} {} {}", entry.x, entry.y),
+            }))
+        })
+        .collect();
+    Some(
+        moves
+            .map(|moves| json!({ "name": name, "code": code.to_string(), "moves": moves }))
+            .ok_or("undos, opening the rest and defusals cannot be replayed"),
+    )
+}
+
+/// Send `body` to `endpoint`, trying again up to `retries` times while the server cannot be
+/// reached or fails, and say what came of it.
+fn submit(endpoint: &Endpoint, body: &str, retries: u32) -> String {
+    let mut wait = BACKOFF;
+    let mut failure = String::new();
+    for attempt in 0..=retries {
+        if attempt > 0 {
+            thread::sleep(wait);
+            wait = (wait * 2).min(MAX_BACKOFF);
+        }
+        match post(endpoint, body) {
+            Ok((201, reply)) => {
+                tracing::info!(?reply, "win submitted");
+                return match reply["time"].as_f64() {
+                    Some(time) => format!("Submitted to the leaderboard: {time:.3}s"),
+                    None => "Submitted to the leaderboard".to_string(),
+                };
+            }
+            Ok((status, reply)) if status < 500 => {
+                let reason = reply["error"].as_str().unwrap_or("no reason given");
+                tracing::warn!(status, reason, "win refused");
+                return format!("The leaderboard refused the win: {reason}");
+            }
+            Ok((status, _)) => failure = format!("the server answered {status}"),
+            Err(e) => failure = e.to_string(),
+        }
+        tracing::warn!(attempt, "cannot submit the win: {failure}");
+    }
+    format!(
+        "Not submitted after {} tries: {failure}",
+        u64::from(retries) + 1
+    )
+}
+
+/// `POST` `body` as JSON and return the status code and the JSON reply (`null` if the reply
+/// is not JSON).
+fn post(endpoint: &Endpoint, body: &str) -> io::Result<(u16, Value)> {
+    let mut stream = TcpStream::connect((endpoint.host.as_str(), endpoint.port))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        endpoint.path,
+        endpoint.host,
+        body.len()
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not an HTTP response"))?;
+    let reply = response
+        .split_once("\r\n\r\n")
+        .and_then(|(_, body)| serde_json::from_str(body).ok())
+        .unwrap_or(Value::Null);
+    Ok((status, reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::{Action, Cursor};
+    use minesweeper::board::CellState;
+    use minesweeper::difficulty::Difficulty;
+    use minesweeper::replay;
+    use std::net::TcpListener;
+
+    #[test]
+    fn endpoints_are_http_urls() {
+        assert_eq!(
+            Endpoint::parse("http://scores.lan:9000/api/scores"),
+            Ok(Endpoint {
+                host: "scores.lan".to_string(),
+                port: 9000,
+                path: "/api/scores".to_string(),
+            })
+        );
+        let endpoint = Endpoint::parse("http://localhost").expect("valid");
+        assert_eq!((endpoint.port, endpoint.path.as_str()), (80, "/"));
+        assert!(Endpoint::parse("https://scores.lan/scores").is_err());
+        assert!(Endpoint::parse("http://:80/scores").is_err());
+    }
+
+    #[test]
+    fn won_code_games_are_submitted_as_replays() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.double_tap_timeout = None;
+        app.apply_code("MS-easy-a".parse().expect("valid code"));
+        assert_eq!(submission(&app, "ann").map(|_| ()), Some(()), "code game");
+        for i in 0..app.board.width() * app.board.height() {
+            let (x, y) = (i % app.board.width(), i / app.board.width());
+            if !app.board.is_mine(x, y) && app.board.cell_at(x, y) == CellState::Hidden {
+                app.cursor = Cursor::new(x, y);
+                app.handle_action(Action::Reveal);
+            }
+        }
+        assert_eq!(app.status, Status::Win);
+        let body = submission(&app, "ann")
+            .expect("code game")
+            .expect("replayable");
+        assert_eq!(body["name"], "ann");
+        let moves: Vec<replay::Move> = body["moves"]
+            .as_array()
+            .expect("moves")
+            .iter()
+            .map(|m| {
+                replay::Move::parse(
+                    m["at"].as_f64().expect("time"),
+                    m["move"].as_str().expect("move"),
+                )
+                .expect("valid move")
+            })
+            .collect();
+        let code = body["code"]
+            .as_str()
+            .expect("code")
+            .parse()
+            .expect("valid code");
+        assert!(replay::verify(code, &moves).is_ok());
+
+        app.handle_action(Action::Undo);
+        assert!(submission(&app, "ann").expect("code game").is_err());
+        app.code = None;
+        assert!(submission(&app, "ann").is_none());
+    }
+
+    #[test]
+    fn failures_are_retried_and_refusals_are_not() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("free port");
+        let port = listener.local_addr().expect("bound").port();
+        let replies = [
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 201 Created\r\nContent-Length: 12\r\n\r\n{\"time\":7.5}",
+            "HTTP/1.1 422 Unprocessable Entity\r\nContent-Length: 21\r\n\r\n{\"error\":\"not a win\"}",
+        ];
+        let server = thread::spawn(move || {
+            for reply in replies {
+                let (mut stream, _) = listener.accept().expect("a request");
+                // Read the whole request first, so closing the connection does not reset it.
+                let mut request = Vec::new();
+                let mut chunk = [0; 256];
+                while !request.ends_with(b"\r\n\r\n{}") {
+                    let n = stream.read(&mut chunk).expect("the request");
+                    request.extend_from_slice(&chunk[..n]);
+                }
+                stream.write_all(reply.as_bytes()).expect("reply");
+            }
+        });
+        let endpoint = Endpoint::parse(&format!("http://127.0.0.1:{port}/scores")).expect("valid");
+        assert_eq!(
+            submit(&endpoint, "{}", 1),
+            "Submitted to the leaderboard: 7.500s"
+        );
+        assert_eq!(
+            submit(&endpoint, "{}", 3),
+            "The leaderboard refused the win: not a win"
+        );
+        server.join().expect("served");
+    }
+}
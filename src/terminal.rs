@@ -0,0 +1,153 @@
+use crate::cast::Output;
+use crossterm::ExecutableCommand;
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+    supports_keyboard_enhancement,
+};
+use minesweeper::error::{self, Error};
+use ratatui::prelude::CrosstermBackend;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+use std::io::{Write, stdout};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Once, OnceLock};
+
+/// Whether the terminal is currently set up for the game, so the panic hook knows whether
+/// there is anything to restore.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Whether the keyboard enhancement flags were pushed and must be popped on the way out.
+static ENHANCED: AtomicBool = AtomicBool::new(false);
+/// Whether the game is drawn inline, in the scrollback, rather than on the alternate screen.
+static INLINE: AtomicBool = AtomicBool::new(false);
+
+/// Raw mode and the alternate screen, held for as long as the game runs.
+///
+/// Dropping the guard restores the terminal, on every way out of the main loop. A panic hook
+/// does the same before the panic message is printed, so a crash never leaves the shell in
+/// raw mode or the message hidden on the alternate screen.
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    /// Enter raw mode and the alternate screen and open a ratatui terminal drawing to `output`,
+    /// leaving the terminal as it was on failure.
+    ///
+    /// # Errors
+    /// Fails if raw mode or the alternate screen cannot be entered, or the terminal size
+    /// cannot be read.
+    pub fn enter(output: Output) -> error::Result<(Self, Terminal<CrosstermBackend<Output>>)> {
+        install_panic_hook();
+        // From here on, an early return drops the guard and undoes what was done.
+        let guard = Self { _private: () };
+        resume()?;
+        let terminal = Terminal::new(CrosstermBackend::new(output)).map_err(Error::TerminalInit)?;
+        Ok((guard, terminal))
+    }
+
+    /// Like [`enter`](Self::enter), but draw in a viewport of `lines` rows below the shell
+    /// prompt instead of on the alternate screen, so the shell's history stays in view and the
+    /// last frame stays in the scrollback. The mouse is left to the terminal, for scrolling.
+    ///
+    /// # Errors
+    /// Fails if raw mode cannot be entered or the terminal size and cursor cannot be read.
+    pub fn enter_inline(
+        lines: u16,
+        output: Output,
+    ) -> error::Result<(Self, Terminal<CrosstermBackend<Output>>)> {
+        INLINE.store(true, Ordering::SeqCst);
+        install_panic_hook();
+        let guard = Self { _private: () };
+        resume()?;
+        let options = TerminalOptions {
+            viewport: Viewport::Inline(lines),
+        };
+        let terminal = Terminal::with_options(CrosstermBackend::new(output), options)
+            .map_err(Error::TerminalInit)?;
+        Ok((guard, terminal))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Give the terminal back to the shell, as on exit, while the game is suspended.
+pub fn suspend() {
+    restore();
+}
+
+/// Take the terminal over again after [`suspend`]: raw mode, the alternate screen and mouse
+/// capture (unless drawing inline), bracketed paste and, where the terminal speaks it, the
+/// kitty keyboard protocol. Doing this twice is harmless.
+///
+/// # Errors
+/// Fails if raw mode or the alternate screen cannot be entered.
+pub fn resume() -> error::Result<()> {
+    enable_raw_mode().map_err(Error::TerminalInit)?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    let mut stdout = stdout();
+    if !INLINE.load(Ordering::SeqCst) {
+        stdout
+            .execute(EnterAlternateScreen)
+            .map_err(Error::TerminalInit)?;
+        // Mouse capture is optional: without it the viewport can still follow the cursor.
+        let _ = stdout.execute(EnableMouseCapture);
+    }
+    let _ = stdout.execute(EnableBracketedPaste);
+    if keyboard_enhancement() && !ENHANCED.swap(true, Ordering::SeqCst) {
+        // Unambiguous keys: Shift+Space and Ctrl+I arrive as such rather than as plain Space
+        // and Tab, and letters always carry their Shift.
+        let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+            | KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+        let _ = stdout.execute(PushKeyboardEnhancementFlags(flags));
+    }
+    Ok(())
+}
+
+/// Whether the terminal speaks the kitty keyboard protocol. It is asked once, before the
+/// main loop starts reading the terminal, so the answer is not mistaken for a key press.
+fn keyboard_enhancement() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| supports_keyboard_enhancement().unwrap_or(false))
+}
+
+/// Leave the alternate screen and raw mode, if the game set them up; inline, move to a fresh
+/// line below the game instead.
+fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let mut stdout = stdout();
+    if ENHANCED.swap(false, Ordering::SeqCst) {
+        let _ = stdout.execute(PopKeyboardEnhancementFlags);
+    }
+    let _ = stdout.execute(DisableBracketedPaste);
+    if INLINE.load(Ordering::SeqCst) {
+        let _ = disable_raw_mode();
+        let _ = stdout.write_all(b"\r\n");
+        let _ = stdout.flush();
+    } else {
+        let _ = stdout.execute(DisableMouseCapture);
+        let _ = stdout.execute(LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+/// Restore the terminal before the default hook prints a panic.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            default(info);
+        }));
+    });
+}
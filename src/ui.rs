@@ -1,153 +1,1195 @@
-use crate::app::Status;
-use crate::board::CellState;
+use crate::coop::Partner;
+use crate::history::{self, Standing};
+use crate::race::Opponent;
+use clap::ValueEnum;
+use minesweeper::app::{AppState, Cursor, InputMode, Layer, Status};
+use minesweeper::board::{CellState, Topology};
+use minesweeper::campaign::Campaign;
+use minesweeper::defuse::Defusal;
+use minesweeper::events::Event;
+use minesweeper::hotseat::HotSeat;
+use minesweeper::movelog::status_line;
+use minesweeper::puzzle::Pack;
+use minesweeper::render::{Renderer, cell_name, column_label};
+use minesweeper::solver::{Deduction, Guess, Loss};
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Width of the stats panel, borders included.
+const STATS_WIDTH: u16 = 22;
+/// Columns the board keeps beside the stats panel when it is too wide to show whole.
+const MIN_BOARD_WIDTH: u16 = 40;
+
+/// Draw attempts per frame before a draw error ends the game.
+const DRAW_ATTEMPTS: u32 = 5;
+
+/// Pause before the first redraw after a failure; it doubles with each further failure.
+const RETRY_PAUSE: Duration = Duration::from_millis(20);
+/// Shortest time between frames unless set otherwise: about 30 a second.
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+/// Frames the debug overlay averages over, and events it lists.
+const DEBUG_FRAMES: usize = 60;
+const DEBUG_EVENTS: usize = 6;
+/// Width of the debug overlay, borders included.
+const DEBUG_WIDTH: u16 = 36;
+
+/// Color scheme for the whole screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// Colored numbers, flags and highlights.
+    #[default]
+    Classic,
+    /// No colors, only bold/reverse; for monochrome terminals or color-blind players.
+    Mono,
+}
+
+/// How the screen is drawn, independent of game state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Appearance {
+    /// Color scheme.
+    pub theme: Theme,
+    /// Draw with plain ASCII only (borders and cell glyphs), for terminals without Unicode.
+    pub ascii: bool,
+    /// Colors of the cells, when the theme has colors.
+    pub palette: Palette,
+    /// Symbols of the cells.
+    pub glyphs: Glyphs,
+}
+
+impl Appearance {
+    /// The cell symbols to draw: in ASCII mode, custom ones outside ASCII give way to the
+    /// usual ones, which have ASCII stand-ins.
+    fn cell_glyphs(self) -> Glyphs {
+        if !self.ascii {
+            return self.glyphs;
+        }
+        let usual = Glyphs::default();
+        let pick = |glyph: char, usual: char| if glyph.is_ascii() { glyph } else { usual };
+        Glyphs {
+            hidden: pick(self.glyphs.hidden, usual.hidden),
+            flag: pick(self.glyphs.flag, usual.flag),
+            zero: pick(self.glyphs.zero, usual.zero),
+            mine: pick(self.glyphs.mine, usual.mine),
+        }
+    }
+}
+
+/// Symbols for the board's cells: the usual ones, with any the config's `[glyphs]` replace.
+/// Each takes one terminal column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+    /// Hidden cells.
+    pub hidden: char,
+    /// Flags.
+    pub flag: char,
+    /// Revealed cells with no mines around them.
+    pub zero: char,
+    /// Mines left unflagged, shown once the game is over.
+    pub mine: char,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self {
+            hidden: '■',
+            flag: '⚑',
+            zero: '·',
+            mine: '*',
+        }
+    }
+}
+
+/// Colors of the board's cells: the classic ones, with any the config's `[colors]` replace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Numbers 1 to 8, in order.
+    pub numbers: [Color; 8],
+    /// Hidden cells.
+    pub hidden: Color,
+    /// Flags.
+    pub flag: Color,
+    /// Revealed cells with no mines around them.
+    pub zero: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            numbers: [
+                Color::Blue,
+                Color::Green,
+                Color::Red,
+                Color::Magenta,
+                Color::LightRed,
+                Color::Cyan,
+                Color::Yellow,
+                Color::LightMagenta,
+            ],
+            hidden: Color::DarkGray,
+            flag: Color::Red,
+            zero: Color::Gray,
+        }
+    }
+}
+
+/// The ratatui front end as a [`Renderer`]: draws the whole UI into a terminal.
+pub struct TuiRenderer<B: Backend> {
+    terminal: Terminal<B>,
+    /// Colors and glyphs.
+    pub appearance: Appearance,
+    /// The race opponent to show in the header, when racing.
+    pub opponent: Option<Opponent>,
+    /// The co-op partner whose cursor to mark, when playing together.
+    pub partner: Option<Partner>,
+    /// A line to add to the game-over message, such as where an image was saved.
+    pub notice: Option<String>,
+    /// The times to beat, for the header, and where a win ranked, for the game-over message.
+    pub standing: Standing,
+    /// Shortest time between two frames, capping the frame rate while the screen keeps
+    /// changing.
+    pub frame_interval: Duration,
+    /// What the debug overlay shows besides the game itself.
+    pub debug: DebugLog,
+    /// Rows of the viewport when drawing inline, in the shell's scrollback, rather than on
+    /// the whole alternate screen (`--inline`).
+    pub inline: Option<u16>,
+    /// When the last frame was drawn.
+    drawn_at: Option<Instant>,
+    /// Terminal size at the last [`fit`](Self::fit).
+    size: Size,
+    /// What the last frame showed that changes without input: the timer second and the
+    /// terminal size. `None` when the next frame has to be drawn regardless.
+    shown: Option<(u64, Size)>,
+}
+
+impl<B: Backend> TuiRenderer<B> {
+    /// Draw into `terminal` with the given appearance.
+    pub const fn new(terminal: Terminal<B>, appearance: Appearance) -> Self {
+        Self {
+            terminal,
+            appearance,
+            opponent: None,
+            partner: None,
+            notice: None,
+            standing: Standing {
+                best: None,
+                board_best: None,
+                place: None,
+            },
+            frame_interval: FRAME_INTERVAL,
+            debug: DebugLog {
+                frames: VecDeque::new(),
+                events: VecDeque::new(),
+            },
+            inline: None,
+            drawn_at: None,
+            size: Size::ZERO,
+            shown: None,
+        }
+    }
+
+    /// Fit the camera to the current terminal size.
+    pub fn fit(&mut self, app: &mut AppState) {
+        if let Ok(mut size) = self.terminal.size() {
+            if let Some(lines) = self.inline {
+                size.height = size.height.min(lines);
+            }
+            self.size = size;
+            let (cols, rows) = board_view_size(Rect::new(0, 0, size.width, size.height), app);
+            app.resize_view(cols, rows);
+        }
+    }
+
+    /// Fit each player's camera to their half of the screen, for split-screen versus.
+    pub fn fit_versus(&mut self, apps: &mut [AppState; 2]) {
+        if let Ok(size) = self.terminal.size() {
+            self.size = size;
+            let (halves, _) = versus_layout(Rect::new(0, 0, size.width, size.height));
+            for (app, half) in apps.iter_mut().zip(halves) {
+                let (cols, rows) = board_view_size(half, app);
+                app.resize_view(cols, rows);
+            }
+        }
+    }
+
+    /// Draw split-screen versus: each player's game on their half of the screen, the keys
+    /// below, and `result` over everything once the race is decided.
+    ///
+    /// # Errors
+    /// Fails if the backend cannot be written to.
+    pub fn render_versus(
+        &mut self,
+        apps: &[AppState; 2],
+        result: Option<&str>,
+    ) -> std::io::Result<()> {
+        let appearance = self.appearance;
+        self.terminal.draw(|f| {
+            let area = f.area();
+            let (halves, keys) = versus_layout(area);
+            for (app, half) in apps.iter().zip(halves) {
+                let layout = app_layout(half, app);
+                draw_header(f, layout[0], app, Standing::default(), None, None);
+                draw_board(f, layout[1], app, None, appearance);
+                draw_status(f, layout[2], app);
+            }
+            let legend = "Left: WASD move, E reveal, Q flag  │  Right: arrows move, Enter reveal, \
+                          Backspace flag  │  Reveal a number twice to chord  │  Esc quit";
+            let para = Paragraph::new(legend)
+                .wrap(Wrap { trim: true })
+                .style(Style::default().fg(Color::Gray))
+                .block(Block::default().borders(Borders::ALL));
+            f.render_widget(para, keys);
+            if let Some(result) = result {
+                draw_overlay(f, area, result);
+            }
+            apply_appearance(f.buffer_mut(), appearance);
+        })?;
+        self.shown = Some((apps[0].elapsed_secs(), self.size));
+        self.drawn_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Whether the screen may no longer match `app`: something was handled since the last
+    /// frame (see [`invalidate`](Self::invalidate)), the timer reached a new second or the
+    /// terminal was resized. Drawing only then keeps an idle game from using the CPU.
+    pub fn stale(&self, app: &AppState) -> bool {
+        self.shown != Some((app.elapsed_secs(), self.size))
+    }
+
+    /// How long until the next frame should be drawn: `None` while the screen is up to date,
+    /// otherwise what is left of the [`frame_interval`](Self::frame_interval) since the last
+    /// frame. Input is handled as it comes and only the drawing waits, so a lone keypress is
+    /// drawn at once while a countdown or a burst of keys is drawn at most at the capped rate.
+    pub fn frame_due(&self, app: &AppState) -> Option<Duration> {
+        self.stale(app).then(|| {
+            self.drawn_at.map_or(Duration::ZERO, |at| {
+                self.frame_interval.saturating_sub(at.elapsed())
+            })
+        })
+    }
+
+    /// Make the next frame be drawn, after input or anything else that changes the screen.
+    pub const fn invalidate(&mut self) {
+        self.shown = None;
+    }
+
+    /// Blank the screen and forget what was drawn, so the next frame is drawn in full.
+    ///
+    /// # Errors
+    /// Fails if the backend cannot be written to.
+    pub fn clear(&mut self) -> std::io::Result<()> {
+        self.shown = None;
+        self.terminal.clear()
+    }
+}
+
+/// Wait until the next frame falls due, `due` being what [`TuiRenderer::frame_due`] gave;
+/// forever while the screen is up to date.
+pub async fn next_frame(due: Option<Duration>) {
+    match due {
+        Some(due) => tokio::time::sleep(due).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Draw a frame with `draw`, riding out momentary backend failures: after a failure, wait
+/// (a little longer each time) and draw again, passing `true` so the caller can clear the
+/// screen first. Gives up with the last error after [`DRAW_ATTEMPTS`] attempts.
+///
+/// # Errors
+/// The last draw error, if every attempt failed.
+pub fn retry_draw<E>(mut draw: impl FnMut(bool) -> Result<(), E>) -> Result<(), E> {
+    let mut result = draw(false);
+    let mut pause = RETRY_PAUSE;
+    for _ in 1..DRAW_ATTEMPTS {
+        if result.is_ok() {
+            break;
+        }
+        thread::sleep(pause);
+        pause *= 2;
+        result = draw(true);
+    }
+    result
+}
+
+impl<B: Backend> Drop for TuiRenderer<B> {
+    /// Leave the cursor on the last row of an inline viewport, so the shell carries on below
+    /// the final frame rather than over it.
+    fn drop(&mut self) {
+        if self.inline.is_some() {
+            let bottom = self.terminal.get_frame().area().bottom();
+            let _ = self
+                .terminal
+                .set_cursor_position((0, bottom.saturating_sub(1)));
+            let _ = self.terminal.show_cursor();
+        }
+    }
+}
+
+impl<B: Backend> Renderer for TuiRenderer<B> {
+    type Error = std::io::Error;
+
+    fn render(&mut self, app: &AppState) -> std::io::Result<()> {
+        let Self {
+            appearance,
+            opponent,
+            partner,
+            ref notice,
+            standing,
+            ref debug,
+            ..
+        } = *self;
+        let started = Instant::now();
+        self.terminal.draw(|f| {
+            draw_app(
+                f,
+                app,
+                appearance,
+                opponent.as_ref(),
+                partner.as_ref(),
+                notice.as_deref(),
+                standing,
+            );
+            if app.panels.debug {
+                draw_debug(f, app, debug);
+            }
+        })?;
+        self.debug.frame(started.elapsed());
+        self.shown = Some((app.elapsed_secs(), self.size));
+        self.drawn_at = Some(Instant::now());
+        Ok(())
+    }
+}
 
 /// Draw the entire app UI composed of header, board, optional overlay, and footer.
 ///
-/// Parameters:
-/// - mines_total: total mines for the board
-/// - flags: current number of placed flags
-/// - elapsed_secs: elapsed seconds since first reveal
-/// - width/height: board dimensions in cells
-/// - cell_at: returns the current CellState at (x, y)
-/// - cursor: optional (x, y) cursor position to highlight
-/// - status: current game status to decide on overlay text
-pub fn draw_app<FGet, FIsMine>(
+/// In a network game, `opponent` or `partner` adds how the other player is doing; `notice`
+/// is appended to the game-over message. `standing` puts the times to beat in the header and
+/// a win's place after its headline.
+pub fn draw_app(
     f: &mut Frame<'_>,
-    mines_total: usize,
-    flags: usize,
-    elapsed_secs: u64,
-    width: usize,
-    height: usize,
-    mut cell_at: FGet,
-    mut is_mine: FIsMine,
-    cursor: Option<(usize, usize)>,
-    status: Status,
-    correct_flags: usize,
-) where
-    FGet: FnMut(usize, usize) -> CellState,
-    FIsMine: FnMut(usize, usize) -> bool,
-{
+    app: &AppState,
+    appearance: Appearance,
+    opponent: Option<&Opponent>,
+    partner: Option<&Partner>,
+    notice: Option<&str>,
+    standing: Standing,
+) {
     let area = f.area();
+    let layout = app_layout(area, app);
+    let mines_total = app.board.mines();
+
+    draw_header(f, layout[0], app, standing, opponent, partner);
+    let (board_area, stats_area) = board_and_stats(layout[1], app);
+    draw_board(f, board_area, app, partner, appearance);
+    if let Some(stats_area) = stats_area {
+        draw_stats(f, stats_area, app);
+    }
+    draw_status(f, layout[2], app);
+    if layout[3].height > 0 {
+        draw_footer(f, layout[3]);
+    }
+
+    // Overlay for game end
+    let correct_flags = app.board.count_correct_flags();
+    let assisted = match (app.hints_used, app.auto_moves) {
+        (0, 0) => String::new(),
+        (1, 0) => " (1 hint used)".to_string(),
+        (n, 0) => format!(" ({n} hints used)"),
+        (0, _) => " (assisted)".to_string(),
+        (n, _) => format!(" (assisted, {n} hints used)"),
+    };
+    let keys = "Press R to restart, D to change difficulty, I to save an image, P to copy a summary, M to save the move log, or Esc to view the board";
+    let notice = notice.map(|text| format!("\n\n{text}")).unwrap_or_default();
+    let headline = match (&app.hotseat, app.status) {
+        _ if !app.game_over_shown() => None,
+        (Some(seat), status) => seat.outcome(status).map(|outcome| {
+            let [first, second] = seat.players;
+            format!(
+                "{outcome}! Cells opened: {} to {}.",
+                first.cells, second.cells
+            )
+        }),
+        (None, Status::Win) => Some(format!(
+            "You win{assisted}!{}{}",
+            campaign_note(app),
+            standing
+                .place
+                .map(|place| format!(" {}", history::place_message(place)))
+                .unwrap_or_default()
+        )),
+        (None, Status::Lose) => Some(format!(
+            "Boom! You lost{}.{}",
+            app.loss
+                .map(|loss| format!(" to {}", loss.describe()))
+                .unwrap_or_default(),
+            campaign_note(app)
+        )),
+        (None, Status::Playing) => None,
+    };
+    if let Some(headline) = headline {
+        let rating = app
+            .rating
+            .map(|rating| format!(" Board: {}.", rating.name()))
+            .unwrap_or_default();
+        let message = format!(
+            "{headline}{rating} Correctly flagged: {correct_flags}/{mines_total} mines\n\n{keys}{notice}"
+        );
+        draw_overlay(f, area, &message);
+    }
+
+    match app.layers.last() {
+        Some(Layer::SeedPrompt(text)) => draw_prompt(f, area, " Seed ", text),
+        Some(Layer::ConfirmQuit) => draw_confirm_quit(f, area),
+        Some(Layer::Handoff) => {
+            if let Some(seat) = &app.hotseat {
+                draw_handoff(f, area, seat.turn);
+            }
+        }
+        Some(&Layer::Packs(selected)) => draw_packs(f, area, &app.packs, selected),
+        Some(Layer::Defuse(defusal)) => draw_defusal(f, area, defusal),
+        Some(Layer::GameOver) | None => {}
+    }
 
-    // Vertical layout: header (3), board (auto), footer (3)
-    let layout = Layout::default()
+    apply_appearance(f.buffer_mut(), appearance);
+}
+
+/// Restyle the finished frame: drop colors for the mono theme and swap Unicode glyphs
+/// (box drawing, cell symbols) for ASCII stand-ins.
+fn apply_appearance(buf: &mut Buffer, appearance: Appearance) {
+    for cell in &mut buf.content {
+        if appearance.theme == Theme::Mono {
+            cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        }
+        if appearance.ascii
+            && let Some(ascii) = ascii_fallback(cell.symbol())
+        {
+            cell.set_symbol(ascii);
+        }
+    }
+}
+
+/// ASCII stand-in for a Unicode symbol the UI draws, if it is one.
+fn ascii_fallback(symbol: &str) -> Option<&'static str> {
+    match symbol {
+        "─" | "░" => Some("-"),
+        "│" => Some("|"),
+        "┌" | "┐" | "└" | "┘" => Some("+"),
+        "■" | "█" => Some("#"),
+        "⚑" => Some("F"),
+        "·" => Some("."),
+        "✓" | "↓" => Some("v"),
+        "●" => Some("*"),
+        "↑" => Some("^"),
+        "←" => Some("<"),
+        "→" => Some(">"),
+        _ => None,
+    }
+}
+
+/// Vertical layout: header, board (auto), status line and footer, with heights from
+/// [`chrome_heights`].
+fn app_layout(area: Rect, app: &AppState) -> std::rc::Rc<[Rect]> {
+    let [header, status, footer] = chrome_heights(area, app);
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),
+            Constraint::Length(header),
             Constraint::Min(3),
-            Constraint::Length(3),
+            Constraint::Length(status),
+            Constraint::Length(footer),
         ])
+        .split(area)
+}
+
+/// Heights of the header, status line and footer around the board. Normally 3, 1 and 3 (0
+/// with the footer hidden); when the whole board would not fit, the footer and then the
+/// header shrink to single unboxed lines, then the footer and the status line go, until it
+/// does. They come back as soon as there is room again.
+fn chrome_heights(area: Rect, app: &AppState) -> [u16; 3] {
+    let rulers = u16::from(app.panels.rulers);
+    let board = u16::try_from(app.board.height())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2 + rulers);
+    let footer = if app.panels.footer { 3 } else { 0 };
+    let compact = footer.min(1);
+    [
+        [3, 1, footer],
+        [3, 1, compact],
+        [1, 1, compact],
+        [1, 1, 0],
+        [1, 0, 0],
+    ]
+    .into_iter()
+    .find(|[header, status, footer]| header + status + footer + board <= area.height)
+    .unwrap_or([1, 0, 0])
+}
+
+/// Split-screen versus: the left and right players' halves, and the key legend below them.
+fn versus_layout(area: Rect) -> ([Rect; 2], Rect) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(6), Constraint::Length(4)])
         .split(area);
+    let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+    ([halves[0], halves[1]], rows[1])
+}
 
-    draw_header(f, layout[0], mines_total, flags, elapsed_secs);
-    draw_board(f, layout[1], width, height, &mut cell_at, &mut is_mine, cursor, status);
-    draw_footer(f, layout[2]);
+/// Rows an inline viewport `width` columns wide needs to show the whole board of `app` with
+/// all the header, status line and legend a terminal `max` rows tall would show, up to `max`.
+pub fn inline_height(app: &AppState, width: u16, max: u16) -> u16 {
+    let chrome = chrome_heights(Rect::new(0, 0, width, max), app);
+    (1..=max)
+        .find(|&height| {
+            let area = Rect::new(0, 0, width, height);
+            chrome_heights(area, app) == chrome
+                && board_view_size(area, app).1 >= app.board.height()
+        })
+        .unwrap_or(max)
+}
 
-    // Overlay for game end
-    match status {
-        Status::Win => {
-            let message = format!(
-                "You win! Correctly flagged: {}/{} mines\n\nPress R to restart or D to change difficulty",
-                correct_flags, mines_total
-            );
-            draw_overlay(f, area, &message)
-        }
-        Status::Lose => {
-            let message = format!(
-                "Boom! You lost. Correctly flagged: {}/{} mines\n\nPress R to restart or D to change difficulty",
-                correct_flags, mines_total
-            );
-            draw_overlay(f, area, &message)
-        }
-        Status::Playing => {}
+/// Number of board cells (columns, rows) that fit on screen for a terminal of the given size.
+pub fn board_view_size(area: Rect, app: &AppState) -> (usize, usize) {
+    let inner = Block::default()
+        .borders(Borders::ALL)
+        .inner(board_and_stats(app_layout(area, app)[1], app).0);
+    let (ruler_w, ruler_h) = if app.panels.rulers {
+        (row_label_width(app.board.height()), 1)
+    } else {
+        (0, 0)
+    };
+    // Each cell is two characters wide: the glyph plus a spacer.
+    (
+        usize::from(inner.width.saturating_sub(ruler_w) / 2),
+        usize::from(inner.height.saturating_sub(ruler_h)),
+    )
+}
+
+/// Split the board's area between the board and, when it is shown and the board keeps its full
+/// width (or at least [`MIN_BOARD_WIDTH`] columns), the stats panel on its right.
+fn board_and_stats(area: Rect, app: &AppState) -> (Rect, Option<Rect>) {
+    let rulers = if app.panels.rulers {
+        row_label_width(app.board.height())
+    } else {
+        0
+    };
+    let board = u16::try_from(app.board.width() * 2)
+        .unwrap_or(u16::MAX)
+        .saturating_add(rulers + 2);
+    if !app.panels.stats || area.width < board.min(MIN_BOARD_WIDTH) + STATS_WIDTH {
+        return (area, None);
     }
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(STATS_WIDTH)])
+        .split(area);
+    (columns[0], Some(columns[1]))
 }
 
-/// Draw header showing remaining mines and timer.
-pub fn draw_header(f: &mut Frame<'_>, area: Rect, mines_total: usize, flags: usize, elapsed_secs: u64) {
-    let mines_left = mines_total.saturating_sub(flags);
+/// Characters reserved for 1-based row numbers plus a separating space.
+fn row_label_width(height: usize) -> u16 {
+    u16::try_from(height.to_string().len() + 1).unwrap_or(u16::MAX)
+}
+
+/// Draw header showing remaining mines, timer, the times to beat, the current input mode and,
+/// online, how the other player is doing.
+pub fn draw_header(
+    f: &mut Frame<'_>,
+    area: Rect,
+    app: &AppState,
+    standing: Standing,
+    opponent: Option<&Opponent>,
+    partner: Option<&Partner>,
+) {
+    let mines_left = app.board.mines().saturating_sub(app.flags_placed);
+    let elapsed_secs = app.elapsed_secs();
     let (mm, ss) = (elapsed_secs / 60, elapsed_secs % 60);
-    let time_text = format!("{:02}:{:02}", mm, ss);
+    let time_text = format!("{mm:02}:{ss:02}");
 
-    let spans = vec![
+    let mut spans = vec![
+        board_span(app),
         Span::styled(
             format!(" Mines: {mines_left} "),
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
         ),
         Span::styled(
             format!(" Time: {time_text} "),
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
         ),
+        best_span(standing),
+        app.flags_left().map_or_else(
+            || Span::raw(""),
+            |left| Span::styled(format!(" Flags: {left} "), Style::default().fg(Color::Red)),
+        ),
+        app.score.map_or_else(
+            || Span::raw(""),
+            |score| {
+                Span::styled(
+                    format!(" Score: {} ×{} ", score.points, score.multiplier()),
+                    Style::default().fg(Color::Green),
+                )
+            },
+        ),
+        penalty_span(app.penalty),
+        app.campaign
+            .as_ref()
+            .map_or_else(|| Span::raw(""), campaign_span),
+        hints_span(app.hints_used),
+        guess_span(app.guess),
+        peek_span(app),
+        app.hotseat
+            .as_ref()
+            .map_or_else(|| Span::raw(""), turn_span),
+        match (app.code, app.seed) {
+            (Some(code), _) => {
+                Span::styled(format!(" Code: {code} "), Style::default().fg(Color::Gray))
+            }
+            (None, Some(seed)) => {
+                Span::styled(format!(" Seed: {seed} "), Style::default().fg(Color::Gray))
+            }
+            (None, None) => Span::raw(""),
+        },
+        if app.board.topology() == Topology::Cylinder {
+            Span::styled(" Cylinder ", Style::default().fg(Color::Gray))
+        } else {
+            Span::raw("")
+        },
+        if app.recording.is_some() {
+            Span::styled(
+                " ● REC ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::raw("")
+        },
+        match app.input_mode {
+            InputMode::Reveal => Span::styled(" Mode: Reveal ", Style::default().fg(Color::Gray)),
+            InputMode::Flag => Span::styled(
+                " Mode: Flag ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+        },
     ];
+    if let Some(opponent) = opponent {
+        spans.push(opponent_span(opponent));
+    }
+    if let Some(partner) = partner {
+        spans.push(partner_span(partner));
+    }
 
-    let para = Paragraph::new(Line::from(spans))
-        .block(Block::default().title(" Minesweeper ").borders(Borders::ALL));
+    let para = Paragraph::new(Line::from(spans));
+    // Squeezed to one line on short terminals, the header loses its box.
+    let para = if area.height >= 3 {
+        para.block(
+            Block::default()
+                .title(" Minesweeper ")
+                .borders(Borders::ALL),
+        )
+    } else {
+        para
+    };
     f.render_widget(para, area);
 }
 
+/// The difficulty and the board it deals, like "Hard 30x16/99", so the game being played is
+/// clear after cycling difficulty.
+fn board_span(app: &AppState) -> Span<'static> {
+    let name = app.difficulty.name();
+    let mut letters = name.chars();
+    let name: String = letters
+        .next()
+        .map(|first| first.to_ascii_uppercase())
+        .into_iter()
+        .chain(letters)
+        .collect();
+    Span::styled(
+        format!(
+            " {name} {}x{}/{} ",
+            app.board.width(),
+            app.board.height(),
+            app.board.mines()
+        ),
+        Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// The times to beat: the best on boards like this one and, for a seeded board played before,
+/// the best on this very board.
+fn best_span(standing: Standing) -> Span<'static> {
+    let clock = |secs: f64| {
+        // Whole seconds, rounded down like the timer.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let secs = secs as u64;
+        format!("{}:{:02}", secs / 60, secs % 60)
+    };
+    let text = match (standing.best, standing.board_best) {
+        (None, None) => return Span::raw(""),
+        (Some(best), None) => format!(" Best: {} ", clock(best)),
+        (best, Some(board)) => format!(
+            " Best: {}, this board {} ",
+            clock(best.unwrap_or(board)),
+            clock(board)
+        ),
+    };
+    Span::styled(text, Style::default().fg(Color::Cyan))
+}
+
+/// Depth, lives and hints of the campaign run.
+fn campaign_span(run: &Campaign) -> Span<'static> {
+    Span::styled(
+        format!(
+            " Depth: {} (best {})  Lives: {}  Hints: {} ",
+            run.depth, run.best, run.lives, run.hints
+        ),
+        Style::default().fg(Color::Magenta),
+    )
+}
+
+/// How the game that just ended moved the campaign run along, for the game-over message.
+fn campaign_note(app: &AppState) -> String {
+    match (&app.campaign, app.status) {
+        (Some(run), Status::Lose) if run.is_over() => {
+            format!(" Run over at depth {}.", run.depth)
+        }
+        (Some(run), Status::Lose) => match run.lives {
+            1 => " 1 life left.".to_string(),
+            lives => format!(" {lives} lives left."),
+        },
+        (Some(run), Status::Win) => run
+            .perk
+            .map(|perk| format!(" Perk: {}!", perk.describe()))
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// The time wrong flags added to the timer, if any.
+fn penalty_span(penalty: Duration) -> Span<'static> {
+    if penalty.is_zero() {
+        Span::raw("")
+    } else {
+        Span::styled(
+            format!(" Penalty: +{}s ", penalty.as_secs()),
+            Style::default().fg(Color::Red),
+        )
+    }
+}
+
+/// Hint counter, shown only once a hint has been used.
+fn hints_span(hints_used: usize) -> Span<'static> {
+    if hints_used == 0 {
+        Span::raw("")
+    } else {
+        Span::styled(
+            format!(" Hints: {hints_used} "),
+            Style::default().fg(Color::Magenta),
+        )
+    }
+}
+
+/// The last peek, while the cursor is still on the cell it counted around.
+fn peek_span(app: &AppState) -> Span<'static> {
+    match app.peek {
+        Some((cell, neighbors)) if cell == app.cursor => Span::styled(
+            format!(" {}: {neighbors} ", cell_name(cell.x, cell.y)),
+            Style::default().fg(Color::White),
+        ),
+        _ => Span::raw(""),
+    }
+}
+
+/// Whose turn it is in a hot-seat game, and the cells each player has opened.
+fn turn_span(seat: &HotSeat) -> Span<'static> {
+    let [first, second] = seat.players;
+    Span::styled(
+        format!(
+            " Turn: Player {} (P1 {} · P2 {}) ",
+            seat.turn + 1,
+            first.cells,
+            second.cells
+        ),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Where the best guess is and how likely it is to be a mine, while it is highlighted.
+fn guess_span(guess: Option<Guess>) -> Span<'static> {
+    guess.map_or_else(
+        || Span::raw(""),
+        |guess| {
+            Span::styled(
+                format!(
+                    " Best guess: {} ({:.0}% mine) ",
+                    cell_name(guess.x, guess.y),
+                    guess.mine_chance * 100.0
+                ),
+                Style::default().fg(Color::Yellow),
+            )
+        },
+    )
+}
+
+/// The race opponent's progress as a ten-segment bar, or how their game ended.
+fn opponent_span(opponent: &Opponent) -> Span<'static> {
+    let filled = usize::from(opponent.percent / 10);
+    let bar = format!("{}{}", "█".repeat(filled), "░".repeat(10 - filled));
+    let (text, color) = match opponent.finished {
+        Some((true, secs)) => (
+            format!(" Opponent: won in {:02}:{:02} ", secs / 60, secs % 60),
+            Color::Red,
+        ),
+        Some((false, _)) => (
+            format!(" Opponent: boom at {}% ", opponent.percent),
+            Color::Green,
+        ),
+        None if opponent.left => (" Opponent: left ".to_string(), Color::Gray),
+        None => (
+            format!(" Opponent: {bar} {:>3}% ", opponent.percent),
+            Color::LightBlue,
+        ),
+    };
+    Span::styled(text, Style::default().fg(color))
+}
+
+/// Where the co-op partner is, labelled like the rulers.
+fn partner_span(partner: &Partner) -> Span<'static> {
+    let text = match partner.cursor {
+        _ if partner.left => " Partner: left ".to_string(),
+        Some(Cursor { x, y }) => format!(" Partner: {} ", cell_name(x, y)),
+        None => " Partner ".to_string(),
+    };
+    Span::styled(text, Style::default().fg(Color::Magenta))
+}
+
 /// Draw the footer with key legend.
 pub fn draw_footer(f: &mut Frame<'_>, area: Rect) {
     let legend = concat!(
-        "Move: [1mArrows[0m/WASD  ",
+        "Move: [1mArrows[0m/WASD/hjkl (count: 7l)  ",
+        "Diagonal: [1my/U/b/n[0m  ",
         "Reveal: [1mEnter[0m/Space  ",
+        "Flag: [1mF[0m/Shift+Space  ",
+        "Flag+Next: [1mG[0m  ",
+        "Mode: [1mTab[0m  ",
+        "Chord: [1mC[0m  ",
+        "Open rest: [1mV[0m  ",
+        "Peek: [1mT[0m  ",
+        "Restart: [1mR[0m  ",
+        "Undo: [1mu[0m/Ctrl+Y  ",
+        "Hint: [1mH[0m  ",
+        "Difficulty: [1mD[0m/Alt+1-4  ",
+        "Legend: [1mF2[0m  ",
+        "Seed: [1mE[0m  ",
+        "Puzzles: [1mO[0m  ",
+        "Record/Play: [1mF5[0m/[1mF6[0m  ",
+        "Rulers: [1mF3[0m  ",
+        "Stats: [1mF4[0m  ",
+        "Quit: [1mQ[0m",
+    );
+
+    // Squeezed to one line on short terminals, only the main keys are listed, unboxed.
+    let compact = concat!(
+        "Reveal: [1mEnter[0m  ",
         "Flag: [1mF[0m  ",
         "Chord: [1mC[0m  ",
+        "Undo: [1mu[0m  ",
+        "Hint: [1mH[0m  ",
         "Restart: [1mR[0m  ",
-        "Difficulty: [1mD[0m  ",
+        "Legend: [1mF2[0m  ",
         "Quit: [1mQ[0m",
     );
+    let para = if area.height >= 3 {
+        Paragraph::new(legend)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL))
+    } else {
+        Paragraph::new(compact)
+    };
+    f.render_widget(para.style(Style::default().fg(Color::Gray)), area);
+}
 
-    let para = Paragraph::new(legend)
-        .wrap(Wrap { trim: true })
+/// The stats panel's lines: 3BV cleared, clicks, efficiency (3BV cleared per click), flags,
+/// 3BV per second, and the time left if the pace holds.
+fn stats_lines(app: &AppState) -> Vec<String> {
+    let (solved, total) = app.board.bbbv_progress();
+    let placed = app.first_click_done;
+    let secs = app.elapsed_secs();
+    let dash = || "-".to_string();
+    let bbbv = if placed {
+        format!("{solved}/{total}")
+    } else {
+        dash()
+    };
+    let efficiency = if placed && app.clicks > 0 {
+        format!("{}%", solved * 100 / app.clicks)
+    } else {
+        dash()
+    };
+    let rate = if placed && secs > 0 {
+        #[allow(clippy::cast_precision_loss)] // counts and seconds far below 2^52
+        let rate = solved as f64 / secs as f64;
+        format!("{rate:.2}")
+    } else {
+        dash()
+    };
+    let left = match u64::try_from(total - solved) {
+        Ok(remaining) if placed && solved > 0 && app.status == Status::Playing => {
+            let left = secs * remaining / solved as u64;
+            format!("~{}:{:02}", left / 60, left % 60)
+        }
+        _ => dash(),
+    };
+    [
+        ("3BV", bbbv),
+        ("Clicks", app.clicks.to_string()),
+        ("Efficiency", efficiency),
+        (
+            "Flags",
+            format!("{}/{}", app.flags_placed, app.board.mines()),
+        ),
+        ("3BV/s", rate),
+        ("Time left", left),
+        (
+            "Loss",
+            app.loss
+                .filter(|_| app.status == Status::Lose)
+                .map_or_else(dash, |loss| match loss {
+                    Loss::ForcedGuess => "forced".to_string(),
+                    Loss::Misplay => "misplay".to_string(),
+                }),
+        ),
+    ]
+    .into_iter()
+    .map(|(label, value)| format!("{label:<11}{value:>7}"))
+    .collect()
+}
+
+/// Draw the stats panel beside the board.
+fn draw_stats(f: &mut Frame<'_>, area: Rect, app: &AppState) {
+    let lines: Vec<Line> = stats_lines(app).into_iter().map(Line::from).collect();
+    let para = Paragraph::new(lines)
         .style(Style::default().fg(Color::Gray))
-        .block(Block::default().borders(Borders::ALL));
+        .block(Block::default().title(" Stats ").borders(Borders::ALL));
+    f.render_widget(para, area);
+}
+
+/// The latest frame times and game events, for the debug overlay (F12).
+#[derive(Debug, Clone, Default)]
+pub struct DebugLog {
+    frames: VecDeque<Duration>,
+    events: VecDeque<Event>,
+}
+
+impl DebugLog {
+    /// Note the events just taken from the game.
+    pub fn record(&mut self, events: &[Event]) {
+        for &event in events {
+            if self.events.len() == DEBUG_EVENTS {
+                self.events.pop_front();
+            }
+            self.events.push_back(event);
+        }
+    }
+
+    /// Note how long a frame took to draw.
+    fn frame(&mut self, time: Duration) {
+        if self.frames.len() == DEBUG_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(time);
+    }
+}
+
+/// The debug overlay's lines: frame times, a summary of the game state and recent events.
+fn debug_lines(app: &AppState, log: &DebugLog) -> Vec<String> {
+    let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+    let last = log.frames.back().copied().unwrap_or_default();
+    let max = log.frames.iter().max().copied().unwrap_or_default();
+    #[allow(clippy::cast_possible_truncation)] // DEBUG_FRAMES fits
+    let mean = log.frames.iter().sum::<Duration>() / (log.frames.len().max(1) as u32);
+    let board = &app.board;
+    let mut lines = vec![
+        format!(
+            "Frame {:.1} avg {:.1} max {:.1} ms",
+            ms(last),
+            ms(mean),
+            ms(max)
+        ),
+        format!(
+            "Status {:?}, {} moves",
+            app.status,
+            app.moves.entries().len()
+        ),
+        format!(
+            "Open {}/{} flags {}/{}",
+            board.revealed(),
+            board.cells() - board.mines(),
+            app.flags_placed,
+            board.mines()
+        ),
+        format!(
+            "Cursor {}, {} layers",
+            cell_name(app.cursor.x, app.cursor.y),
+            app.layers.len()
+        ),
+        format!(
+            "Seed {}",
+            board
+                .seed()
+                .map_or_else(|| "random".to_string(), |seed| seed.to_string())
+        ),
+        "Events:".to_string(),
+    ];
+    lines.extend(log.events.iter().rev().map(|event| match *event {
+        Event::CellsRevealed { x, y, opened } => format!(" {} opened {opened}", cell_name(x, y)),
+        Event::FlagToggled { x, y, flagged } => {
+            let what = if flagged { "flagged" } else { "unflagged" };
+            format!(" {} {what}", cell_name(x, y))
+        }
+        Event::GameEnded { status, time } => format!(" {status:?} at {:.1}s", time.as_secs_f64()),
+        Event::Undone => " undone".to_string(),
+        Event::NewGame => " new game".to_string(),
+    }));
+    lines
+}
+
+/// Draw the debug overlay in the top right corner.
+fn draw_debug(f: &mut Frame<'_>, app: &AppState, log: &DebugLog) {
+    let lines = debug_lines(app, log);
+    let area = f.area();
+    let width = DEBUG_WIDTH.min(area.width);
+    let height = u16::try_from(lines.len() + 2)
+        .unwrap_or(u16::MAX)
+        .min(area.height);
+    let rect = Rect::new(area.right() - width, area.y, width, height);
+    let lines: Vec<Line> = lines.into_iter().map(Line::from).collect();
+    let para = Paragraph::new(lines)
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .title(" Debug (F12) ")
+                .borders(Borders::ALL),
+        );
+    f.render_widget(Clear, rect);
+    f.render_widget(para, rect);
+}
+
+/// Draw the status line: what the last move did, like "Chord revealed 6 cells".
+fn draw_status(f: &mut Frame<'_>, area: Rect, app: &AppState) {
+    let text = status_line(app).unwrap_or_default();
+    let para = Paragraph::new(format!(" {text}")).style(Style::default().fg(Color::Gray));
     f.render_widget(para, area);
 }
 
 /// Draw the central game board as a grid of Unicode glyphs with colors per number.
-pub fn draw_board<FGet, FIsMine>(
+///
+/// Only the part of the board inside the camera viewport is drawn, in the colors and glyphs of
+/// `appearance`.
+/// In co-op, the `partner`'s cursor is marked too.
+pub fn draw_board(
     f: &mut Frame<'_>,
     area: Rect,
-    width: usize,
-    height: usize,
-    cell_at: &mut FGet,
-    is_mine: &mut FIsMine,
-    cursor: Option<(usize, usize)>,
-    status: Status,
-) where
-    FGet: FnMut(usize, usize) -> CellState,
-    FIsMine: FnMut(usize, usize) -> bool,
-{
+    app: &AppState,
+    partner: Option<&Partner>,
+    appearance: Appearance,
+) {
+    let (palette, glyphs) = (appearance.palette, appearance.cell_glyphs());
+    let board = &app.board;
+    let camera = &app.camera;
+    let game_over = matches!(app.status, Status::Win | Status::Lose);
+    let x_end = (camera.x + camera.cols).min(board.width());
+    let y_end = (camera.y + camera.rows).min(board.height());
+
     // Build content line by line. Each cell is 2-character wide for spacing.
-    let mut lines: Vec<Line> = Vec::with_capacity(height);
-    for y in 0..height {
-        let mut spans: Vec<Span> = Vec::with_capacity(width);
-        for x in 0..width {
-            let cell = cell_at(x, y);
+    let mut lines: Vec<Line> = Vec::with_capacity(camera.rows + 1);
+    let label_width = usize::from(row_label_width(board.height()));
+    let ruler_style = Style::default().fg(Color::DarkGray);
+    if app.panels.rulers {
+        let mut ruler = " ".repeat(label_width);
+        for x in camera.x..x_end {
+            ruler.push(column_label(x));
+            ruler.push(' ');
+        }
+        lines.push(Line::styled(ruler, ruler_style));
+    }
+    for y in camera.y..y_end {
+        let mut spans: Vec<Span> = Vec::with_capacity(camera.cols * 2 + 1);
+        if app.panels.rulers {
+            spans.push(Span::styled(
+                format!("{:>width$} ", y + 1, width = label_width - 1),
+                ruler_style,
+            ));
+        }
+        for x in camera.x..x_end {
+            let cell = board.cell_at(x, y);
             // If game over/won, reveal mines regardless of cell state
-            let (symbol, style) = if matches!(status, Status::Win | Status::Lose) {
-                if matches!(cell, CellState::Hidden) && is_mine(x, y) {
+            let (symbol, style) = if !board.in_play(x, y) {
+                // Cut out of a shaped board
+                (" ".to_string(), Style::default())
+            } else if game_over {
+                if matches!(cell, CellState::Hidden) && board.is_mine(x, y) {
                     // Show unflagged mines as red asterisks
-                    ("*".to_string(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
-                } else if matches!(cell, CellState::Flagged) && is_mine(x, y) {
+                    (
+                        glyphs.mine.to_string(),
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    )
+                } else if matches!(cell, CellState::Flagged) && board.is_mine(x, y) {
                     // Show correctly flagged mines as green check marks
-                    ("✓".to_string(), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
-                } else if matches!(cell, CellState::Flagged) && !is_mine(x, y) {
+                    (
+                        "✓".to_string(),
+                        Style::default()
+                            .fg(Color::Green)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else if matches!(cell, CellState::Flagged) && !board.is_mine(x, y) {
                     // Keep incorrectly flagged cells as red flags (or could use ✗)
-                    ("⚑".to_string(), Style::default().fg(Color::Red))
+                    (glyphs.flag.to_string(), Style::default().fg(palette.flag))
                 } else {
-                    cell_symbol_and_style(cell)
+                    cell_symbol_and_style(cell, appearance)
                 }
+            } else if !app.is_visible(x, y) {
+                // Fog of war: the cell is too far from the cursor to be seen
+                ("░".to_string(), Style::default().fg(Color::DarkGray))
+            } else if app.is_faded(x, y) {
+                // Memory mode: the cell stays open but its number is gone
+                (" ".to_string(), Style::default())
             } else {
-                cell_symbol_and_style(cell)
+                cell_symbol_and_style(cell, appearance)
             };
             let mut style = style;
-            if let Some((cx, cy)) = cursor {
-                if cx == x && cy == y {
-                    style = style.bg(Color::Gray).add_modifier(Modifier::REVERSED);
+            match app.hint {
+                // Underlined as well, so the hint survives the mono theme
+                Some(Deduction::Safe(hx, hy)) if (hx, hy) == (x, y) => {
+                    style = style.bg(Color::Green).add_modifier(Modifier::UNDERLINED);
                 }
+                Some(Deduction::Mine(hx, hy)) if (hx, hy) == (x, y) => {
+                    style = style.bg(Color::Red).add_modifier(Modifier::UNDERLINED);
+                }
+                _ => {}
+            }
+            if app.guess.is_some_and(|g| (g.x, g.y) == (x, y)) {
+                style = style.bg(Color::Yellow).add_modifier(Modifier::UNDERLINED);
+            }
+            if partner.is_some_and(|p| !p.left && p.cursor == Some(Cursor::new(x, y))) {
+                style = style.bg(Color::Magenta).add_modifier(Modifier::BOLD);
+            }
+            if (app.cursor.x, app.cursor.y) == (x, y) {
+                style = style.bg(Color::Gray).add_modifier(Modifier::REVERSED);
             }
             // Add a space after each glyph to improve readability
             spans.push(Span::styled(symbol, style));
@@ -156,33 +1198,84 @@ pub fn draw_board<FGet, FIsMine>(
         lines.push(Line::from(spans));
     }
 
-    let block = Block::default().borders(Borders::ALL).title(" Board ");
-    let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: false });
+    let title = if camera.is_scrollable() {
+        format!(" Board [{},{}] ", camera.x, camera.y)
+    } else {
+        " Board ".to_string()
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let para = Paragraph::new(lines).block(block);
     f.render_widget(para, area);
 }
 
-/// Map a cell to a printable unicode symbol and color style.
-fn cell_symbol_and_style(cell: CellState) -> (String, Style) {
+/// Map a cell to its glyph and color in `appearance`.
+fn cell_symbol_and_style(cell: CellState, appearance: Appearance) -> (String, Style) {
+    let (palette, glyphs) = (appearance.palette, appearance.cell_glyphs());
     match cell {
-        CellState::Hidden => ("■".to_string(), Style::default().fg(Color::DarkGray)),
-        CellState::Flagged => ("⚑".to_string(), Style::default().fg(Color::Red)),
-        CellState::Revealed(0) => ("·".to_string(), Style::default().fg(Color::Gray)),
+        CellState::Hidden => (
+            glyphs.hidden.to_string(),
+            Style::default().fg(palette.hidden),
+        ),
+        CellState::Flagged => (glyphs.flag.to_string(), Style::default().fg(palette.flag)),
+        CellState::Revealed(0) => (glyphs.zero.to_string(), Style::default().fg(palette.zero)),
         CellState::Revealed(n) => {
-            let color = match n {
-                1 => Color::Blue,
-                2 => Color::Green,
-                3 => Color::Red,
-                4 => Color::Magenta,
-                5 => Color::LightRed,
-                6 => Color::Cyan,
-                7 => Color::Yellow,
-                _ => Color::LightMagenta,
-            };
-            (format!("{n}"), Style::default().fg(color).add_modifier(Modifier::BOLD))
+            let color = palette.numbers[usize::from(n.clamp(1, 8)) - 1];
+            (
+                format!("{n}"),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )
         }
     }
 }
 
+/// Draw the puzzle pack browser: each pack's puzzles with their par and best times, solved
+/// ones ticked and the `selected` one highlighted.
+pub fn draw_packs(f: &mut Frame<'_>, area: Rect, packs: &[Pack], selected: usize) {
+    let clock = |time: Duration| format!("{}:{:02}", time.as_secs() / 60, time.as_secs() % 60);
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    let mut index = 0;
+    for pack in packs {
+        lines.push(Line::styled(
+            pack.name.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        for puzzle in &pack.puzzles {
+            let mark = if puzzle.best.is_some() { "✓" } else { " " };
+            let par = puzzle
+                .par
+                .map(|par| format!("  par {}", clock(par)))
+                .unwrap_or_default();
+            let best = puzzle
+                .best
+                .map(|best| format!("  best {}", clock(best)))
+                .unwrap_or_default();
+            let mut style = Style::default();
+            if index == selected {
+                style = style.add_modifier(Modifier::REVERSED);
+                selected_line = lines.len();
+            }
+            lines.push(Line::styled(
+                format!(" {mark} {}{par}{best}", puzzle.name),
+                style,
+            ));
+            index += 1;
+        }
+    }
+    let popup = centered_rect(60, 70, area);
+    // Keep the selected puzzle in view on long lists (two rows go to the border).
+    let rows = usize::from(popup.height.saturating_sub(2)).max(1);
+    let scroll = u16::try_from(selected_line.saturating_sub(rows - 1)).unwrap_or(u16::MAX);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Puzzles (Enter to play, Esc to close) ");
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(block).scroll((scroll, 0)),
+        popup,
+    );
+}
+
 /// Draw a centered overlay with a message.
 pub fn draw_overlay(f: &mut Frame<'_>, area: Rect, message: &str) {
     let overlay_area = centered_rect(60, 25, area);
@@ -200,6 +1293,105 @@ pub fn draw_overlay(f: &mut Frame<'_>, area: Rect, message: &str) {
     f.render_widget(para, overlay_area);
 }
 
+/// Draw a centered single-line text prompt with the typed text and a cursor.
+pub fn draw_prompt(f: &mut Frame<'_>, area: Rect, title: &str, text: &str) {
+    let prompt_area = centered_rect(60, 25, area);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let lines = vec![
+        Line::from(vec![
+            Span::raw(format!("> {text}")),
+            Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+        ]),
+        Line::raw(""),
+        Line::styled(
+            "Type or paste a number or any text. Enter: apply  Esc: cancel  Empty: random",
+            Style::default().fg(Color::Gray),
+        ),
+    ];
+    let para = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .block(block);
+
+    f.render_widget(Clear, prompt_area);
+    f.render_widget(para, prompt_area);
+}
+
+/// Draw the quit confirmation dialog.
+pub fn draw_confirm_quit(f: &mut Frame<'_>, area: Rect) {
+    let dialog_area = centered_rect(40, 20, area);
+    let block = Block::default()
+        .title(" Quit? ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+    let para = Paragraph::new("Quit Minesweeper?\n\nY/Enter: quit  N/Esc: keep playing")
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .block(block);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(para, dialog_area);
+}
+
+/// Ask the next hot-seat player (index `turn`) to take the keyboard.
+pub fn draw_handoff(f: &mut Frame<'_>, area: Rect, turn: usize) {
+    let dialog_area = centered_rect(40, 20, area);
+    let block = Block::default()
+        .title(" Next turn ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let text = format!(
+        "Player {}, your turn.\n\nPress Enter when you have the keyboard",
+        turn + 1
+    );
+    let para = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .block(block);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(para, dialog_area);
+}
+
+/// Show the arrows that defuse the mine that just went off, those already entered dimmed,
+/// and the time left.
+pub fn draw_defusal(f: &mut Frame<'_>, area: Rect, defusal: &Defusal) {
+    let dialog_area = centered_rect(40, 25, area);
+    let block = Block::default()
+        .title(" Defuse! ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+    let mut arrows = Vec::new();
+    for (i, arrow) in defusal.sequence.iter().enumerate() {
+        let style = if i < defusal.entered {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        };
+        arrows.push(Span::styled(format!(" {} ", arrow.glyph()), style));
+    }
+    let left = defusal.remaining(Instant::now()).as_secs_f64();
+    let lines = vec![
+        Line::raw("A mine went off! Press the arrows in order:"),
+        Line::raw(""),
+        Line::from(arrows),
+        Line::raw(""),
+        Line::styled(format!("{left:.1}s left"), Style::default().fg(Color::Gray)),
+    ];
+    let para = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .block(block);
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(para, dialog_area);
+}
+
 /// Helper to create a centered rect with a given percentage size.
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let vert = Layout::default()
@@ -223,3 +1415,317 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     horz[1]
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minesweeper::app::{Action, Panel};
+    use minesweeper::board::Board;
+    use minesweeper::difficulty::Difficulty;
+    use ratatui::backend::TestBackend;
+
+    fn render(appearance: Appearance) -> Buffer {
+        let app = AppState::new(Difficulty::Easy);
+        let terminal = Terminal::new(TestBackend::new(60, 24)).expect("test backend");
+        let mut renderer = TuiRenderer::new(terminal, appearance);
+        renderer.render(&app).expect("draw");
+        renderer.terminal.backend().buffer().clone()
+    }
+
+    #[test]
+    fn ascii_mode_uses_only_ascii() {
+        let buf = render(Appearance {
+            ascii: true,
+            ..Appearance::default()
+        });
+        assert!(buf.content.iter().all(|c| c.symbol().is_ascii()));
+        assert!(
+            !render(Appearance::default())
+                .content
+                .iter()
+                .all(|c| c.symbol().is_ascii())
+        );
+    }
+
+    #[test]
+    fn ascii_mode_overrides_custom_glyphs_outside_ascii() {
+        let config: crate::config::Config =
+            toml::from_str("ascii = true\n[glyphs]\nhidden = \"▒\"\nzero = \"_\"")
+                .expect("valid config");
+        let appearance = config.appearance();
+        let glyphs = appearance.cell_glyphs();
+        assert_eq!(
+            (glyphs.hidden, glyphs.zero),
+            (Glyphs::default().hidden, '_')
+        );
+        assert!(
+            render(appearance)
+                .content
+                .iter()
+                .all(|c| c.symbol().is_ascii())
+        );
+    }
+
+    #[test]
+    fn the_header_names_the_board() {
+        let header: String = render(Appearance::default())
+            .content
+            .iter()
+            .take(60 * 2)
+            .map(ratatui::buffer::Cell::symbol)
+            .collect();
+        assert!(header.contains("Easy 9x9/10"), "{header}");
+        let mut app = AppState::new(Difficulty::Easy);
+        app.difficulty = Difficulty::custom(30, 20, 120).expect("valid size");
+        app.restart();
+        assert_eq!(board_span(&app).content, " Custom 30x20/120 ");
+    }
+
+    #[test]
+    fn short_terminals_collapse_the_chrome_first() {
+        let mut app = AppState::new(Difficulty::Easy);
+        // The easy board needs 12 rows: 9 cells, the ruler and its box.
+        let at = |height| Rect::new(0, 0, 60, height);
+        assert_eq!(chrome_heights(at(24), &app), [3, 1, 3]);
+        assert_eq!(chrome_heights(at(17), &app), [3, 1, 1]);
+        assert_eq!(chrome_heights(at(14), &app), [1, 1, 0]);
+        assert_eq!(chrome_heights(at(5), &app), [1, 0, 0]);
+        assert_eq!(board_view_size(at(14), &app).1, 9);
+        app.panels.footer = false;
+        assert_eq!(chrome_heights(at(16), &app), [3, 1, 0]);
+    }
+
+    #[test]
+    fn inline_viewports_fit_the_board_and_its_chrome() {
+        let mut app = AppState::new(Difficulty::Easy);
+        assert_eq!(inline_height(&app, 60, 50), 19, "3 + 1 + 3 around 12");
+        assert_eq!(inline_height(&app, 60, 14), 14, "collapsed chrome");
+        assert_eq!(inline_height(&app, 60, 10), 10, "too short for the board");
+        app.panels.footer = false;
+        assert_eq!(inline_height(&app, 60, 50), 16);
+    }
+
+    #[test]
+    fn the_stats_panel_follows_the_game() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 3, 1);
+        app.board.place_mines_at(&[(1, 1)]);
+        assert_eq!(stats_lines(&app)[0], "3BV              -");
+        app.handle_action(Action::Reveal);
+        app.cursor = Cursor::new(2, 2);
+        app.handle_action(Action::Reveal);
+        let lines = stats_lines(&app);
+        assert_eq!(lines[0], "3BV            2/8");
+        assert_eq!(lines[2], "Efficiency    100%");
+
+        let area = Rect::new(0, 0, 60, 20);
+        assert_eq!(board_and_stats(area, &app).1, None, "hidden by default");
+        app.panels.stats = true;
+        let (board, stats) = board_and_stats(area, &app);
+        assert_eq!(
+            (board.width, stats.map(|s| s.width)),
+            (60 - STATS_WIDTH, Some(STATS_WIDTH))
+        );
+        app.board = Board::new(30, 16, 99);
+        assert_eq!(board_and_stats(area, &app).1, None, "too narrow");
+    }
+
+    #[test]
+    fn the_header_shows_the_times_to_beat() {
+        assert_eq!(best_span(Standing::default()).content, "");
+        let standing = Standing {
+            best: Some(42.9),
+            board_best: Some(65.0),
+            place: None,
+        };
+        assert_eq!(best_span(standing).content, " Best: 0:42, this board 1:05 ");
+        let standing = Standing {
+            board_best: None,
+            ..standing
+        };
+        assert_eq!(best_span(standing).content, " Best: 0:42 ");
+    }
+
+    #[test]
+    fn opponent_bar_has_ascii_fallbacks() {
+        let opponent = Opponent {
+            percent: 40,
+            ..Opponent::default()
+        };
+        let span = opponent_span(&opponent);
+        assert!(span.content.contains("████░░░░░░  40%"));
+        assert!(
+            span.content
+                .chars()
+                .all(|c| c.is_ascii() || ascii_fallback(c.encode_utf8(&mut [0; 4])).is_some())
+        );
+    }
+
+    #[test]
+    fn mono_theme_drops_colors() {
+        let buf = render(Appearance {
+            theme: Theme::Mono,
+            ..Appearance::default()
+        });
+        assert!(
+            buf.content
+                .iter()
+                .all(|c| c.fg == Color::Reset && c.bg == Color::Reset)
+        );
+    }
+
+    #[test]
+    fn draws_are_retried_after_failures() {
+        let mut calls = Vec::new();
+        let drawn = retry_draw(|recovering| {
+            calls.push(recovering);
+            if calls.len() < 3 { Err("busy") } else { Ok(()) }
+        });
+        assert_eq!(drawn, Ok(()));
+        assert_eq!(calls, [false, true, true]);
+
+        let mut attempts = 0;
+        assert_eq!(
+            retry_draw(|_| {
+                attempts += 1;
+                Err("gone")
+            }),
+            Err("gone")
+        );
+        assert_eq!(attempts, DRAW_ATTEMPTS);
+    }
+
+    #[test]
+    fn frames_are_drawn_only_after_changes() {
+        let mut app = AppState::new(Difficulty::Easy);
+        let terminal = Terminal::new(TestBackend::new(60, 24)).expect("test backend");
+        let mut renderer = TuiRenderer::new(terminal, Appearance::default());
+        renderer.fit(&mut app);
+        assert!(renderer.stale(&app), "nothing drawn yet");
+        renderer.render(&app).expect("draw");
+        assert!(!renderer.stale(&app));
+        renderer.invalidate();
+        assert!(renderer.stale(&app));
+        renderer.render(&app).expect("draw");
+        renderer.terminal.backend_mut().resize(80, 24);
+        renderer.fit(&mut app);
+        assert!(renderer.stale(&app), "resized");
+    }
+
+    #[test]
+    fn frames_are_paced_but_the_first_is_drawn_at_once() {
+        let app = AppState::new(Difficulty::Easy);
+        let terminal = Terminal::new(TestBackend::new(60, 24)).expect("test backend");
+        let mut renderer = TuiRenderer::new(terminal, Appearance::default());
+        renderer.frame_interval = Duration::from_secs(1000);
+        assert_eq!(renderer.frame_due(&app), Some(Duration::ZERO));
+        renderer.render(&app).expect("draw");
+        assert_eq!(renderer.frame_due(&app), None, "up to date");
+        renderer.invalidate();
+        let due = renderer.frame_due(&app).expect("stale");
+        assert!(due > Duration::ZERO && due <= renderer.frame_interval);
+        renderer.frame_interval = Duration::ZERO;
+        assert_eq!(renderer.frame_due(&app), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn versus_draws_both_games_side_by_side() {
+        let mut apps = [
+            AppState::new(Difficulty::Easy),
+            AppState::new(Difficulty::Easy),
+        ];
+        let terminal = Terminal::new(TestBackend::new(80, 24)).expect("test backend");
+        let mut renderer = TuiRenderer::new(terminal, Appearance::default());
+        renderer.fit_versus(&mut apps);
+        assert!(
+            !apps[0].camera.is_scrollable(),
+            "an easy board fits half the screen"
+        );
+        renderer
+            .render_versus(&apps, Some("Left player wins"))
+            .expect("draw");
+        let buf = renderer.terminal.backend().buffer();
+        let top: String = (0..80).map(|x| buf[(x, 1)].symbol().to_string()).collect();
+        assert_eq!(top.matches("Mines: 10").count(), 2);
+    }
+
+    #[test]
+    fn the_debug_overlay_lists_frames_and_events() {
+        let mut app = fixed_game();
+        app.handle_action(Action::TogglePanel(Panel::Debug));
+        let terminal = Terminal::new(TestBackend::new(80, 24)).expect("test backend");
+        let mut renderer = TuiRenderer::new(terminal, Appearance::default());
+        renderer.debug.record(&app.events.drain());
+        renderer.render(&app).expect("draw");
+        renderer.render(&app).expect("draw");
+        assert_eq!(renderer.debug.frames.len(), 2);
+        let lines = debug_lines(&app, &renderer.debug);
+        assert_eq!(lines.last().map(String::as_str), Some(" H3 opened 52"));
+        let screen = renderer.terminal.backend().to_string();
+        assert!(screen.contains("Debug (F12)"), "{screen}");
+        assert!(screen.contains("Open 52/71 flags 0/10"), "{screen}");
+    }
+
+    /// The screen `app` draws to a `width` by `height` terminal, as text for a golden
+    /// snapshot. The snapshots live in `src/snapshots`; after a deliberate change to the
+    /// layout, review and accept the new ones with `cargo insta review`.
+    fn screen(app: &mut AppState, width: u16, height: u16) -> TestBackend {
+        let terminal = Terminal::new(TestBackend::new(width, height)).expect("test backend");
+        let mut renderer = TuiRenderer::new(terminal, Appearance::default());
+        renderer.fit(app);
+        renderer.render(app).expect("draw");
+        renderer.terminal.backend().clone()
+    }
+
+    /// An easy-sized game with the mines in fixed places and the timer at 1:05.
+    fn fixed_game() -> AppState {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(9, 9, 10);
+        app.board.place_mines_at(&[
+            (0, 0),
+            (8, 0),
+            (4, 2),
+            (1, 5),
+            (7, 5),
+            (3, 7),
+            (4, 7),
+            (0, 8),
+            (6, 8),
+            (8, 8),
+        ]);
+        app.cursor = Cursor::new(7, 2);
+        app.handle_action(Action::Reveal);
+        app.start_time = Instant::now().checked_sub(Duration::from_secs(65));
+        app
+    }
+
+    #[test]
+    fn a_fresh_board_matches_its_snapshot() {
+        insta::assert_snapshot!(screen(&mut AppState::new(Difficulty::Easy), 60, 24));
+    }
+
+    #[test]
+    fn a_game_in_progress_matches_its_snapshot() {
+        let mut app = fixed_game();
+        app.cursor = Cursor::new(0, 0);
+        app.handle_action(Action::ToggleFlag);
+        insta::assert_snapshot!(screen(&mut app, 60, 24));
+    }
+
+    #[test]
+    fn the_win_overlay_matches_its_snapshot() {
+        let mut app = AppState::new(Difficulty::Easy);
+        app.board = Board::new(3, 1, 1);
+        app.board.place_mines_at(&[(2, 0)]);
+        app.handle_action(Action::Reveal);
+        app.start_time = app
+            .end_time
+            .and_then(|end| end.checked_sub(Duration::from_secs(42)));
+        assert_eq!(app.status, Status::Win);
+        insta::assert_snapshot!(screen(&mut app, 60, 24));
+    }
+
+    #[test]
+    fn a_too_small_screen_matches_its_snapshot() {
+        insta::assert_snapshot!(screen(&mut fixed_game(), 24, 8));
+    }
+}
@@ -0,0 +1,274 @@
+use crate::cast::Output;
+use crate::input::{InputAction, InputEvent};
+use crate::terminal::TerminalGuard;
+use crate::ui;
+use crate::ui::{Appearance, TuiRenderer};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use futures::StreamExt;
+use minesweeper::app::{Action, AppState, Command, Cursor, Status};
+use minesweeper::difficulty::Difficulty;
+use minesweeper::error;
+use minesweeper::generator::{self, Spec};
+use rand::random;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time;
+
+/// What a key does in split-screen versus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// An action for the left (0) or right (1) player.
+    Play(usize, Action),
+    /// Start a new race.
+    Rematch,
+    /// Leave the game.
+    Quit,
+}
+
+/// Translate a key for split-screen versus: WASD, E and Q for the left player; the arrow
+/// keys, Enter and Backspace for the right one.
+pub fn translate(event: &Event) -> Option<Input> {
+    let Event::Key(KeyEvent {
+        code,
+        modifiers,
+        kind,
+        ..
+    }) = event
+    else {
+        return None;
+    };
+    if *kind == KeyEventKind::Release {
+        return None;
+    }
+    let left = |action| Some(Input::Play(0, action));
+    let right = |action| Some(Input::Play(1, action));
+    match code {
+        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => Some(Input::Quit),
+        KeyCode::Esc => Some(Input::Quit),
+        KeyCode::F(5) => Some(Input::Rematch),
+        KeyCode::Char(c) => match c.to_ascii_lowercase() {
+            'w' => left(Action::MoveUp),
+            'a' => left(Action::MoveLeft),
+            's' => left(Action::MoveDown),
+            'd' => left(Action::MoveRight),
+            'e' => left(Action::Reveal),
+            'q' => left(Action::ToggleFlag),
+            _ => None,
+        },
+        KeyCode::Up => right(Action::MoveUp),
+        KeyCode::Left => right(Action::MoveLeft),
+        KeyCode::Down => right(Action::MoveDown),
+        KeyCode::Right => right(Action::MoveRight),
+        KeyCode::Enter => right(Action::Reveal),
+        KeyCode::Backspace => right(Action::ToggleFlag),
+        _ => None,
+    }
+}
+
+/// Two players racing copies of the same board side by side at one keyboard. Both boards
+/// open the center cell at the same moment; the first to clear theirs wins, and setting off
+/// a mine hands the win to the other player.
+pub struct Versus {
+    /// The left and right players' games.
+    pub players: [AppState; 2],
+    /// Which player won, once one has.
+    pub winner: Option<usize>,
+}
+
+impl Versus {
+    /// A race on `difficulty`'s board laid out with `seed` (random if `None`), with
+    /// `setup` applied to both games first, e.g. for settings from the config.
+    ///
+    /// # Errors
+    /// Fails if the board cannot be generated.
+    pub fn new(
+        difficulty: Difficulty,
+        seed: Option<u64>,
+        setup: impl Fn(&mut AppState),
+    ) -> error::Result<Self> {
+        let (width, height, mines) = difficulty.parameters();
+        let seed = seed.unwrap_or_else(random);
+        let start = (width / 2, height / 2);
+        let mut players = [(); 2].map(|()| {
+            let mut app = AppState::new(difficulty);
+            setup(&mut app);
+            // The legend below the boards replaces each side's own.
+            app.panels.footer = false;
+            app.seed = Some(seed);
+            app
+        });
+        let board = generator::generate(&Spec {
+            width,
+            height,
+            mines,
+            start,
+            seed: Some(seed),
+            no_guess: false,
+            min_rating: None,
+            max_rating: None,
+            layout: players[0].layout,
+        })?;
+        for app in &mut players {
+            app.board = board.clone();
+            app.cursor = Cursor::new(start.0, start.1);
+            app.handle_action(Action::Reveal);
+        }
+        Ok(Self {
+            players,
+            winner: None,
+        })
+    }
+
+    /// Apply `action` to `player`'s game, unless the race is already decided.
+    pub fn handle(&mut self, player: usize, action: Action) {
+        if self.winner.is_some() {
+            return;
+        }
+        self.players[player].handle_action(action);
+        self.decide(player);
+    }
+
+    /// Let time pass in both games, as [`AppState::tick`]; returns whether either needs
+    /// redrawing.
+    pub fn tick(&mut self) -> bool {
+        if self.winner.is_some() {
+            return false;
+        }
+        let mut redraw = false;
+        for player in 0..2 {
+            redraw |= self.players[player].tick() != Command::None;
+            self.decide(player);
+        }
+        redraw
+    }
+
+    /// Settle the race if `player`'s game just ended.
+    fn decide(&mut self, player: usize) {
+        self.winner = self.winner.or(match self.players[player].status {
+            Status::Win => Some(player),
+            Status::Lose => Some(1 - player),
+            Status::Playing => None,
+        });
+    }
+
+    /// What to tell the players once the race is decided.
+    pub fn result(&self) -> Option<String> {
+        let winner = self.winner?;
+        let side = ["Left", "Right"][winner];
+        let how = match self.players[winner].status {
+            Status::Win => {
+                let secs = self.players[winner].elapsed_secs();
+                format!("cleared the board in {}:{:02}", secs / 60, secs % 60)
+            }
+            _ => "wins: the other player set off a mine".to_string(),
+        };
+        Some(format!(
+            "{side} player {how}!\n\nPress F5 for a rematch or Esc to quit"
+        ))
+    }
+}
+
+/// Play split-screen versus on `output` until a player quits, drawing at most once per
+/// `frame_interval`. `setup` is applied to both games of every race.
+///
+/// # Errors
+/// Fails if the terminal cannot be set up, drawn to or read from, or the board cannot be
+/// generated.
+pub async fn run(
+    output: Output,
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    appearance: Appearance,
+    tick: Duration,
+    frame_interval: Duration,
+    setup: impl Fn(&mut AppState),
+) -> error::Result<()> {
+    let mut game = Versus::new(difficulty, seed, &setup)?;
+    let (guard, terminal) = TerminalGuard::enter(output)?;
+    let mut renderer = TuiRenderer::new(terminal, appearance);
+    renderer.frame_interval = frame_interval;
+    let (tx, mut signals) = mpsc::unbounded_channel();
+    #[cfg(unix)]
+    let _ = crate::signals::spawn_forwarder(tx);
+    #[cfg(not(unix))]
+    drop(tx);
+    let mut events = EventStream::new();
+    let mut ticks = time::interval(tick);
+    loop {
+        renderer.fit_versus(&mut game.players);
+        if game.tick() {
+            renderer.invalidate();
+        }
+        if renderer.frame_due(&game.players[0]) == Some(Duration::ZERO) {
+            renderer.render_versus(&game.players, game.result().as_deref())?;
+        }
+        let due = renderer.frame_due(&game.players[0]);
+        let event = tokio::select! {
+            event = events.next() => event,
+            Some(InputEvent::Action(InputAction::Quit)) = signals.recv() => break,
+            _ = ticks.tick() => continue,
+            () = ui::next_frame(due) => continue,
+        };
+        let Some(event) = event.transpose()? else {
+            break;
+        };
+        renderer.invalidate();
+        match translate(&event) {
+            Some(Input::Play(player, action)) => game.handle(player, action),
+            Some(Input::Rematch) => game = Versus::new(difficulty, seed, &setup)?,
+            Some(Input::Quit) => break,
+            None => {}
+        }
+    }
+    drop(renderer);
+    drop(guard);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_players_race_the_same_board() {
+        let mut game = Versus::new(Difficulty::Easy, Some(4), |_| {}).expect("easy board");
+        let [left, right] = &game.players;
+        assert_eq!(left.board, right.board);
+        assert_ne!(
+            left.board.cell_at(4, 4),
+            minesweeper::board::CellState::Hidden
+        );
+
+        let (x, y) = (0..9)
+            .flat_map(|y| (0..9).map(move |x| (x, y)))
+            .find(|&(x, y)| game.players[0].board.is_mine(x, y))
+            .expect("a mine");
+        game.players[1].cursor = Cursor::new(x, y);
+        game.handle(1, Action::Reveal);
+        assert_eq!(game.winner, Some(0));
+        assert!(
+            game.result()
+                .is_some_and(|text| text.starts_with("Left player wins"))
+        );
+        game.handle(0, Action::MoveUp);
+        assert_eq!(
+            game.players[0].cursor,
+            Cursor::new(4, 4),
+            "the race is over"
+        );
+    }
+
+    #[test]
+    fn each_side_has_its_own_keys() {
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        assert_eq!(
+            translate(&key(KeyCode::Char('W'))),
+            Some(Input::Play(0, Action::MoveUp))
+        );
+        assert_eq!(
+            translate(&key(KeyCode::Enter)),
+            Some(Input::Play(1, Action::Reveal))
+        );
+        assert_eq!(translate(&key(KeyCode::Char('h'))), None);
+    }
+}
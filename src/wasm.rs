@@ -0,0 +1,186 @@
+use crate::app::{Action, AppState, Cursor, Status, parse_seed};
+use crate::board::CellState;
+use crate::difficulty::Difficulty;
+use crate::render::status_name;
+use crate::solver::Deduction;
+use wasm_bindgen::prelude::*;
+
+/// Cell code for a hidden cell in [`Game::cells`].
+pub const HIDDEN: i8 = -1;
+/// Cell code for a flagged cell in [`Game::cells`].
+pub const FLAGGED: i8 = -2;
+/// Cell code for an unflagged mine, shown once the game is over.
+pub const MINE: i8 = -3;
+
+/// A game for JavaScript front ends. Coordinates are 0-based column and row; moves return
+/// the status afterwards: `"playing"`, `"won"` or `"lost"`.
+#[wasm_bindgen]
+pub struct Game {
+    app: AppState,
+}
+
+/// A hint from the solver: a cell that is certainly safe, or certainly a mine.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    /// Column.
+    pub x: usize,
+    /// Row.
+    pub y: usize,
+    /// Whether the cell is a mine rather than safe.
+    pub mine: bool,
+}
+
+#[wasm_bindgen]
+impl Game {
+    /// Start a game at `"easy"`, `"medium"` or `"hard"`.
+    ///
+    /// # Errors
+    /// Fails for an unknown difficulty name.
+    #[wasm_bindgen(constructor)]
+    pub fn new(difficulty: &str) -> Result<Self, JsError> {
+        let difficulty = difficulty.parse::<Difficulty>()?;
+        Ok(Self::with(difficulty))
+    }
+
+    /// Start a game on a custom board.
+    ///
+    /// # Errors
+    /// Fails if the board cannot be played (no cells, or no safe cell left).
+    pub fn custom(width: usize, height: usize, mines: usize) -> Result<Self, JsError> {
+        Ok(Self::with(Difficulty::custom(width, height, mines)?))
+    }
+
+    /// Use a fixed seed (a number, or any text to hash) for this and later games, and start
+    /// over with it.
+    #[wasm_bindgen(js_name = setSeed)]
+    pub fn set_seed(&mut self, seed: &str) {
+        self.app.seed = parse_seed(seed);
+        self.app.restart();
+    }
+
+    /// Reveal a cell. The first reveal places the mines and is always safe.
+    pub fn reveal(&mut self, x: usize, y: usize) -> String {
+        self.at(x, y, Action::Reveal)
+    }
+
+    /// Flag a hidden cell, or remove its flag.
+    #[wasm_bindgen(js_name = toggleFlag)]
+    pub fn toggle_flag(&mut self, x: usize, y: usize) -> String {
+        self.at(x, y, Action::ToggleFlag)
+    }
+
+    /// Reveal the neighbors of a number whose mines are all flagged.
+    pub fn chord(&mut self, x: usize, y: usize) -> String {
+        self.at(x, y, Action::Chord)
+    }
+
+    /// Take back the last move; returns whether there was one.
+    pub fn undo(&mut self) -> bool {
+        let could = self.app.can_undo();
+        self.app.handle_action(Action::Undo);
+        could
+    }
+
+    /// Start a new game at the same size.
+    pub fn restart(&mut self) {
+        self.app.restart();
+    }
+
+    /// Ask the solver for a certain cell; counts as a hint used.
+    pub fn hint(&mut self) -> Option<Hint> {
+        self.app.handle_action(Action::Hint);
+        self.app.hint.map(|deduction| match deduction {
+            Deduction::Safe(x, y) => Hint { x, y, mine: false },
+            Deduction::Mine(x, y) => Hint { x, y, mine: true },
+        })
+    }
+
+    /// Board width in cells.
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.app.board.width()
+    }
+
+    /// Board height in cells.
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.app.board.height()
+    }
+
+    /// Number of mines.
+    #[wasm_bindgen(getter)]
+    pub fn mines(&self) -> usize {
+        self.app.board.mines()
+    }
+
+    /// Number of flags placed.
+    #[wasm_bindgen(getter)]
+    pub fn flags(&self) -> usize {
+        self.app.flags_placed
+    }
+
+    /// `"playing"`, `"won"` or `"lost"`.
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> String {
+        status_name(self.app.status).to_string()
+    }
+
+    /// Seconds since the first reveal.
+    #[wasm_bindgen(getter, js_name = elapsedSecs)]
+    pub fn elapsed_secs(&self) -> u32 {
+        u32::try_from(self.app.elapsed_secs()).unwrap_or(u32::MAX)
+    }
+
+    /// Every cell, row by row (an `Int8Array` in JavaScript): 0-8 for revealed numbers,
+    /// [`HIDDEN`], [`FLAGGED`], or [`MINE`] for mines once the game is over.
+    pub fn cells(&self) -> Vec<i8> {
+        let board = &self.app.board;
+        let over = self.app.status != Status::Playing;
+        (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+            .map(|(x, y)| match board.cell_at(x, y) {
+                CellState::Hidden if over && board.is_mine(x, y) => MINE,
+                CellState::Hidden => HIDDEN,
+                CellState::Flagged => FLAGGED,
+                CellState::Revealed(n) => i8::try_from(n).unwrap_or(i8::MAX),
+            })
+            .collect()
+    }
+}
+
+impl Game {
+    fn with(difficulty: Difficulty) -> Self {
+        let mut app = AppState::new(difficulty);
+        // Chords are an explicit call here, so a repeated reveal stays a reveal.
+        app.double_tap_timeout = None;
+        Self { app }
+    }
+
+    /// Apply `action` at a cell; off-board cells are ignored.
+    fn at(&mut self, x: usize, y: usize, action: Action) -> String {
+        if x < self.app.board.width() && y < self.app.board.height() {
+            self.app.cursor = Cursor::new(x, y);
+            self.app.handle_action(action);
+        }
+        self.status()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_through_the_js_api() {
+        let mut game = Game::custom(3, 3, 1).expect("valid board");
+        game.set_seed("7");
+        assert_eq!(game.cells(), [HIDDEN; 9]);
+        let status = game.reveal(0, 0);
+        assert_ne!(status, "lost");
+        assert_ne!(game.cells()[0], HIDDEN);
+        assert_eq!(game.reveal(9, 9), status);
+        assert!(game.undo());
+        assert_eq!(game.cells(), [HIDDEN; 9]);
+    }
+}